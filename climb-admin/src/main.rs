@@ -0,0 +1,707 @@
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+use std::process::ExitCode;
+
+use chrono::{DateTime, Utc};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use climb_db::descriptions::Description;
+use climb_db::geo::GeoPoint;
+use climb_db::grade::{GradeSystem, ParsedGrade};
+use climb_db::models::User;
+use climb_db::queries::{KnownGrade, Place};
+use climb_db::repo::{
+    AreaInput, AreaRepo, ClimbInput, ClimbRepo, FormationInput, FormationRepo, GradeRepo, Parent, UserRepo,
+};
+use climb_db::users::Role;
+use climb_db::MIGRATIONS;
+use diesel::{Connection, PgConnection};
+use serde::Serialize;
+
+type Result<T, E = Box<dyn Error>> = std::result::Result<T, E>;
+
+/// Administers the climbing database: its migrations, the hierarchy of areas, formations and
+/// climbs, and its users
+#[derive(Parser, Debug)]
+#[command(version)]
+struct Cli {
+    /// PostgreSQL database to administer
+    #[arg(long, env = "DATABASE_URL", global = true)]
+    database_url: Option<String>,
+    /// Prints results as JSON, for scripts
+    #[arg(long, global = true)]
+    json: bool,
+    /// Name of the user to record as the actor of additions, moves and removals
+    #[arg(long, env = "CLIMB_ACTOR", global = true)]
+    actor: Option<String>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Runs, reverts or lists the migrations
+    Migrate {
+        #[command(subcommand)]
+        command: MigrateCommand,
+    },
+    /// Prints the areas, formations and climbs within an area
+    Tree {
+        /// Id of the area
+        area: i32,
+    },
+    /// Adds an area, formation or climb
+    Add {
+        #[command(subcommand)]
+        command: AddCommand,
+    },
+    /// Moves an area, formation or climb into an area, onto a formation or out of its parent
+    Move {
+        kind: Kind,
+        id: i32,
+        #[command(flatten)]
+        to: Destination,
+    },
+    /// Removes an area, formation or climb. Areas and formations must be empty.
+    Remove { kind: Kind, id: i32 },
+    /// Inspects the grades of the grade types
+    Grades {
+        #[command(subcommand)]
+        command: GradesCommand,
+    },
+    /// Adds users and changes their roles, e.g. to add the first admin
+    User {
+        #[command(subcommand)]
+        command: UserCommand,
+    },
+    /// Creates and revokes the API tokens of users
+    Token {
+        #[command(subcommand)]
+        command: TokenCommand,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum MigrateCommand {
+    /// Runs the migrations not yet run
+    Up,
+    /// Reverts the latest migrations
+    Down {
+        /// Number of migrations to revert
+        #[arg(long, default_value_t = 1)]
+        steps: usize,
+    },
+    /// Lists the migrations and whether each has run
+    Status,
+}
+
+#[derive(Subcommand, Debug)]
+enum AddCommand {
+    /// Adds an area
+    Area {
+        #[command(flatten)]
+        details: Details,
+        /// Area to add it into
+        #[arg(long)]
+        area: Option<i32>,
+    },
+    /// Adds a formation
+    Formation {
+        #[command(flatten)]
+        details: Details,
+        #[command(flatten)]
+        parent: ParentArgs,
+        /// Longitude of the formation, along with `--latitude`
+        #[arg(long, requires = "latitude", allow_negative_numbers = true)]
+        longitude: Option<f64>,
+        /// Latitude of the formation, along with `--longitude`
+        #[arg(long, requires = "longitude", allow_negative_numbers = true)]
+        latitude: Option<f64>,
+    },
+    /// Adds a climb
+    Climb {
+        #[command(flatten)]
+        details: Details,
+        #[command(flatten)]
+        parent: ParentArgs,
+        /// Grade as `<grade type>:<grade>`, e.g. `yds:5.10a`. May be repeated.
+        #[arg(long = "grade", value_parser = parse_grade)]
+        grades: Vec<ParsedGrade>,
+    },
+}
+
+#[derive(Args, Debug)]
+struct Details {
+    /// Name, the first becoming the primary name. May be repeated.
+    #[arg(long = "name")]
+    names: Vec<String>,
+    /// Description as `<description type>=<text>`, e.g. `brief=Domes above the meadows`. May be
+    /// repeated.
+    #[arg(long = "description", value_parser = parse_description)]
+    descriptions: Vec<Description>,
+}
+
+#[derive(Args, Debug)]
+struct ParentArgs {
+    /// Area to add it into
+    #[arg(long, conflicts_with = "formation")]
+    area: Option<i32>,
+    /// Formation to add it onto
+    #[arg(long)]
+    formation: Option<i32>,
+}
+
+#[derive(Args, Debug)]
+#[group(required = true, multiple = false)]
+struct Destination {
+    /// Area to move it into
+    #[arg(long)]
+    area: Option<i32>,
+    /// Formation to move it onto
+    #[arg(long)]
+    formation: Option<i32>,
+    /// Moves it out of its parent, to the top level
+    #[arg(long)]
+    root: bool,
+}
+
+#[derive(Subcommand, Debug)]
+enum GradesCommand {
+    /// Lists the grades known to the database, ordered by grade type then difficulty
+    List {
+        /// Lists only the grades of a grade type, e.g. `yds`
+        #[arg(long, value_parser = parse_system)]
+        system: Option<GradeSystem>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum UserCommand {
+    /// Adds a user
+    Add {
+        /// Unique name of the user, the `sub` claim of their JWTs
+        name: String,
+        /// One of reader, contributor, moderator or admin
+        #[arg(long, value_parser = parse_role, default_value = "reader")]
+        role: Role,
+    },
+    /// Changes the role of a user
+    SetRole {
+        /// Name of the user
+        name: String,
+        /// One of reader, contributor, moderator or admin
+        #[arg(value_parser = parse_role)]
+        role: Role,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum TokenCommand {
+    /// Creates an API token for a user, sent as `Authorization: Bearer <token>`. Only a hash of the
+    /// token is stored, so it is printed only once.
+    Create {
+        /// Name of the user the token identifies
+        user: String,
+        /// Label to tell the token apart from other tokens of the user
+        #[arg(long)]
+        label: Option<String>,
+        /// Time after which the token is no longer valid, e.g. `2027-01-01T00:00:00Z`
+        #[arg(long)]
+        expires_at: Option<DateTime<Utc>>,
+    },
+    /// Revokes an API token of a user
+    Revoke {
+        /// Name of the user the token identifies
+        user: String,
+        /// Id of the token
+        id: i32,
+    },
+}
+
+#[derive(ValueEnum, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum Kind {
+    Area,
+    Formation,
+    Climb,
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Kind::Area => write!(f, "area"),
+            Kind::Formation => write!(f, "formation"),
+            Kind::Climb => write!(f, "climb"),
+        }
+    }
+}
+
+fn parse_system(name: &str) -> Result<GradeSystem, String> {
+    GradeSystem::from_name(name).ok_or_else(|| {
+        let names: Vec<&str> = GradeSystem::ALL.iter().map(GradeSystem::name).collect();
+
+        format!("expected one of {}", names.join(", "))
+    })
+}
+
+fn parse_role(name: &str) -> Result<Role, String> {
+    Role::from_name(name).ok_or_else(|| {
+        let names: Vec<&str> = Role::ALL.iter().map(Role::name).collect();
+
+        format!("expected one of {}", names.join(", "))
+    })
+}
+
+fn parse_grade(grade: &str) -> Result<ParsedGrade, String> {
+    let (system, value) = grade.split_once(':').ok_or("expected `<grade type>:<grade>`, e.g. `yds:5.10a`")?;
+
+    parse_system(system)?.parse(value).map_err(|e| e.to_string())
+}
+
+fn parse_description(description: &str) -> Result<Description, String> {
+    let (key, value) = description.split_once('=').ok_or("expected `<description type>=<text>`")?;
+
+    Ok(Description {
+        key: key.to_string(),
+        value: value.to_string(),
+    })
+}
+
+/// Prints a result as JSON for scripts, or as text otherwise
+fn print<T: Serialize>(json: bool, value: &T, text: impl FnOnce(&T)) -> Result<()> {
+    if json {
+        println!("{}", serde_json::to_string_pretty(value)?);
+    } else {
+        text(value);
+    }
+
+    Ok(())
+}
+
+/// An area, formation or climb
+#[derive(Serialize, Debug)]
+struct Entity {
+    kind: Kind,
+    id: i32,
+}
+
+impl From<Place> for Entity {
+    fn from(place: Place) -> Self {
+        match place {
+            Place::Area(id) => Entity { kind: Kind::Area, id },
+            Place::Formation(id) => Entity { kind: Kind::Formation, id },
+            Place::Climb(id) => Entity { kind: Kind::Climb, id },
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+struct MigrationStatus {
+    name: String,
+    applied: bool,
+}
+
+fn migrate(conn: &mut PgConnection, command: MigrateCommand, json: bool) -> Result<()> {
+    use diesel::migration::MigrationSource;
+    use diesel::pg::Pg;
+    use diesel_migrations::MigrationHarness;
+
+    match command {
+        MigrateCommand::Up => {
+            let versions = climb_db::migrate(conn).map_err(|e| format!("Failed to run migrations: {e}"))?;
+
+            print(json, &versions, |versions| {
+                for version in versions {
+                    println!("Ran migration {version}");
+                }
+            })
+        }
+        MigrateCommand::Down { steps } => {
+            let applied = conn
+                .applied_migrations()
+                .map_err(|e| format!("Failed to get the applied migrations: {e}"))?
+                .len();
+
+            // Reverts nothing rather than some of the steps
+            if steps > applied {
+                return Err(format!("Cannot revert {steps} migrations, only {applied} have run").into());
+            }
+
+            let mut versions = Vec::new();
+
+            for _ in 0..steps {
+                let version = conn
+                    .revert_last_migration(MIGRATIONS)
+                    .map_err(|e| format!("Failed to revert a migration: {e}"))?;
+
+                versions.push(version.to_string());
+            }
+
+            print(json, &versions, |versions| {
+                for version in versions {
+                    println!("Reverted migration {version}");
+                }
+            })
+        }
+        MigrateCommand::Status => {
+            let applied: HashSet<String> = conn
+                .applied_migrations()
+                .map_err(|e| format!("Failed to get the applied migrations: {e}"))?
+                .iter()
+                .map(ToString::to_string)
+                .collect();
+
+            let statuses: Vec<MigrationStatus> = MigrationSource::<Pg>::migrations(&MIGRATIONS)
+                .map_err(|e| format!("Failed to read the migrations: {e}"))?
+                .iter()
+                .map(|migration| MigrationStatus {
+                    name: migration.name().to_string(),
+                    applied: applied.contains(&migration.name().version().to_string()),
+                })
+                .collect();
+
+            print(json, &statuses, |statuses| {
+                for status in statuses {
+                    let state = if status.applied { "applied" } else { "pending" };
+
+                    println!("{state:<8} {}", status.name);
+                }
+            })
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+struct Grade {
+    system: String,
+    value: String,
+}
+
+/// An area, formation or climb along with everything within it
+#[derive(Serialize, Debug)]
+struct Node {
+    kind: Kind,
+    id: i32,
+    /// Primary name, if any
+    name: Option<String>,
+    /// Grades of a climb
+    grades: Vec<Grade>,
+    children: Vec<Node>,
+}
+
+/// Gets the children of every place below an area, loading a level of the hierarchy at a time
+fn hierarchy(conn: &mut PgConnection, area: i32) -> Result<HashMap<Place, Vec<Place>>> {
+    let mut children: HashMap<Place, Vec<Place>> = HashMap::new();
+    let mut areas = vec![area];
+    let mut formations = Vec::new();
+
+    while !areas.is_empty() || !formations.is_empty() {
+        let mut repo = AreaRepo::new(conn);
+        let sub_areas = repo.sub_areas(&areas)?;
+        let area_formations = repo.formations(&areas)?;
+        let area_climbs = repo.climbs(&areas)?;
+
+        let mut repo = FormationRepo::new(conn);
+        let sub_formations = repo.sub_formations(&formations)?;
+        let formation_climbs = repo.climbs(&formations)?;
+
+        areas = sub_areas.iter().map(|&(_, id)| id).collect();
+        formations = area_formations
+            .iter()
+            .chain(&sub_formations)
+            .map(|&(_, id)| id)
+            .collect();
+
+        let edges = sub_areas
+            .into_iter()
+            .map(|(parent, id)| (Place::Area(parent), Place::Area(id)))
+            .chain(area_formations.into_iter().map(|(parent, id)| (Place::Area(parent), Place::Formation(id))))
+            .chain(area_climbs.into_iter().map(|(parent, id)| (Place::Area(parent), Place::Climb(id))))
+            .chain(sub_formations.into_iter().map(|(parent, id)| (Place::Formation(parent), Place::Formation(id))))
+            .chain(formation_climbs.into_iter().map(|(parent, id)| (Place::Formation(parent), Place::Climb(id))));
+
+        for (parent, child) in edges {
+            children.entry(parent).or_default().push(child);
+        }
+    }
+
+    Ok(children)
+}
+
+/// Gets an area and everything within it
+fn tree(conn: &mut PgConnection, area: i32) -> Result<Node> {
+    use climb_db::names::names;
+
+    AreaRepo::new(conn).get(area)?;
+
+    let children = hierarchy(conn, area)?;
+
+    let places: Vec<Place> = std::iter::once(Place::Area(area)).chain(children.values().flatten().copied()).collect();
+    let climb_ids: Vec<i32> = places
+        .iter()
+        .filter_map(|place| match place {
+            Place::Climb(id) => Some(*id),
+            _ => None,
+        })
+        .collect();
+
+    // The primary name, if any, comes first
+    let mut primary_names = HashMap::new();
+
+    for (place, name) in names(conn, &places)? {
+        primary_names.entry(place).or_insert(name.value);
+    }
+
+    let mut grades: HashMap<i32, Vec<Grade>> = HashMap::new();
+
+    for (climb, (system, value)) in ClimbRepo::new(conn).grades(&climb_ids)? {
+        grades.entry(climb).or_default().push(Grade { system, value });
+    }
+
+    fn node(
+        place: Place,
+        children: &HashMap<Place, Vec<Place>>,
+        names: &mut HashMap<Place, String>,
+        grades: &mut HashMap<i32, Vec<Grade>>,
+    ) -> Node {
+        let Entity { kind, id } = place.into();
+
+        Node {
+            kind,
+            id,
+            name: names.remove(&place),
+            grades: if kind == Kind::Climb { grades.remove(&id).unwrap_or_default() } else { Vec::new() },
+            children: children
+                .get(&place)
+                .into_iter()
+                .flatten()
+                .map(|&child| node(child, children, names, grades))
+                .collect(),
+        }
+    }
+
+    Ok(node(Place::Area(area), &children, &mut primary_names, &mut grades))
+}
+
+fn print_tree(node: &Node, depth: usize) {
+    let name = node.name.as_deref().unwrap_or("(unnamed)");
+    let grades: Vec<String> = node.grades.iter().map(|grade| format!("{} {}", grade.system, grade.value)).collect();
+
+    if grades.is_empty() {
+        println!("{:indent$}{} {}: {name}", "", node.kind, node.id, indent = depth * 2);
+    } else {
+        println!("{:indent$}{} {}: {name} ({})", "", node.kind, node.id, grades.join(", "), indent = depth * 2);
+    }
+
+    for child in &node.children {
+        print_tree(child, depth + 1);
+    }
+}
+
+fn add(conn: &mut PgConnection, command: AddCommand) -> Result<Entity> {
+    match command {
+        AddCommand::Area { details, area } => {
+            let id = AreaRepo::new(conn).create(AreaInput {
+                names: details.names,
+                descriptions: details.descriptions,
+                super_area_id: area,
+            })?;
+
+            Ok(Entity { kind: Kind::Area, id })
+        }
+        AddCommand::Formation {
+            details,
+            parent,
+            longitude,
+            latitude,
+        } => {
+            let location = match (longitude, latitude) {
+                (Some(longitude), Some(latitude)) => Some(GeoPoint::new(longitude, latitude)?),
+                _ => None,
+            };
+
+            let id = FormationRepo::new(conn).create(FormationInput {
+                names: details.names,
+                descriptions: details.descriptions,
+                location,
+                parent: Parent::from_ids(parent.area, parent.formation)?,
+            })?;
+
+            Ok(Entity { kind: Kind::Formation, id })
+        }
+        AddCommand::Climb { details, parent, grades } => {
+            let id = ClimbRepo::new(conn).create(ClimbInput {
+                names: details.names,
+                descriptions: details.descriptions,
+                grades,
+                parent: Parent::from_ids(parent.area, parent.formation)?,
+            })?;
+
+            Ok(Entity { kind: Kind::Climb, id })
+        }
+    }
+}
+
+fn move_to(conn: &mut PgConnection, kind: Kind, id: i32, to: Destination) -> Result<()> {
+    let parent = Parent::from_ids(to.area, to.formation)?;
+
+    match kind {
+        Kind::Area => {
+            if let Some(Parent::Formation(_)) = parent {
+                return Err("Areas can only be moved into areas".into());
+            }
+
+            AreaRepo::new(conn).move_to(id, to.area)?
+        }
+        Kind::Formation => FormationRepo::new(conn).move_to(id, parent)?,
+        Kind::Climb => ClimbRepo::new(conn).move_to(id, parent)?,
+    }
+
+    Ok(())
+}
+
+fn remove(conn: &mut PgConnection, kind: Kind, id: i32) -> Result<()> {
+    match kind {
+        Kind::Area => AreaRepo::new(conn).delete(id)?,
+        Kind::Formation => FormationRepo::new(conn).delete(id)?,
+        Kind::Climb => ClimbRepo::new(conn).delete(id)?,
+    }
+
+    Ok(())
+}
+
+fn grades(conn: &mut PgConnection, command: GradesCommand, json: bool) -> Result<()> {
+    let GradesCommand::List { system } = command;
+
+    let grades: Vec<KnownGrade> = GradeRepo::new(conn).list(system)?;
+
+    print(json, &grades, |grades| {
+        for grade in grades {
+            let difficulty = grade.difficulty.map_or("-".to_string(), |difficulty| difficulty.to_string());
+
+            println!("{:<8} {:<10} {difficulty}", grade.system, grade.value);
+        }
+    })
+}
+
+/// An API token, shown only when created
+#[derive(Serialize, Debug)]
+struct Token {
+    id: i32,
+    user: String,
+    token: Option<String>,
+}
+
+/// Gets a user by name, failing if there is none
+fn user(conn: &mut PgConnection, name: &str) -> Result<User> {
+    Ok(UserRepo::new(conn)
+        .by_name(name)?
+        .ok_or_else(|| format!("User {name} not found"))?)
+}
+
+fn users(conn: &mut PgConnection, command: UserCommand) -> Result<User> {
+    match command {
+        UserCommand::Add { name, role } => Ok(UserRepo::new(conn).create(&name, role)?),
+        UserCommand::SetRole { name, role } => {
+            let id = user(conn, &name)?.id;
+
+            Ok(UserRepo::new(conn).set_role(id, role)?)
+        }
+    }
+}
+
+fn tokens(conn: &mut PgConnection, command: TokenCommand) -> Result<Token> {
+    match command {
+        TokenCommand::Create { user: name, label, expires_at } => {
+            let user_id = user(conn, &name)?.id;
+            let (id, token) = UserRepo::new(conn).add_api_token(user_id, label.as_deref(), expires_at)?;
+
+            Ok(Token { id, user: name, token: Some(token) })
+        }
+        TokenCommand::Revoke { user: name, id } => {
+            let user_id = user(conn, &name)?.id;
+
+            UserRepo::new(conn).revoke_api_token(user_id, id)?;
+
+            Ok(Token { id, user: name, token: None })
+        }
+    }
+}
+
+/// Makes a change in a transaction, recording the user named, if any, as its actor
+fn act<T>(
+    conn: &mut PgConnection,
+    actor: Option<&str>,
+    f: impl FnOnce(&mut PgConnection) -> Result<T>,
+) -> Result<T> {
+    conn.transaction(|conn| {
+        let user = match actor {
+            Some(name) => Some(user(conn, name)?),
+            None => None,
+        };
+
+        UserRepo::new(conn).act_as(user.as_ref())?;
+
+        f(conn)
+    })
+}
+
+fn run(cli: Cli) -> Result<()> {
+    let database_url = cli
+        .database_url
+        .ok_or("No database given, set `--database-url` or `DATABASE_URL`")?;
+
+    let conn = &mut PgConnection::establish(&database_url)
+        .map_err(|e| format!("Failed to connect to the database: {e}"))?;
+
+    let json = cli.json;
+    let actor = cli.actor.as_deref();
+
+    match cli.command {
+        Command::Migrate { command } => migrate(conn, command, json),
+        Command::Tree { area } => {
+            let tree = tree(conn, area)?;
+
+            print(json, &tree, |tree| print_tree(tree, 0))
+        }
+        Command::Add { command } => {
+            let entity = act(conn, actor, |conn| add(conn, command))?;
+
+            print(json, &entity, |entity| println!("Added {} {}", entity.kind, entity.id))
+        }
+        Command::Move { kind, id, to } => {
+            act(conn, actor, |conn| move_to(conn, kind, id, to))?;
+
+            print(json, &Entity { kind, id }, |entity| println!("Moved {} {}", entity.kind, entity.id))
+        }
+        Command::Remove { kind, id } => {
+            act(conn, actor, |conn| remove(conn, kind, id))?;
+
+            print(json, &Entity { kind, id }, |entity| println!("Removed {} {}", entity.kind, entity.id))
+        }
+        Command::Grades { command } => grades(conn, command, json),
+        Command::User { command } => {
+            let user = act(conn, actor, |conn| users(conn, command))?;
+
+            print(json, &user, |user| println!("User {} {}: {}", user.id, user.name, user.role))
+        }
+        Command::Token { command } => {
+            let token = act(conn, actor, |conn| tokens(conn, command))?;
+
+            print(json, &token, |token| match &token.token {
+                Some(secret) => println!("Created API token {} of {}: {secret}", token.id, token.user),
+                None => println!("Revoked API token {} of {}", token.id, token.user),
+            })
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use std::process::Command;
+
+use async_graphql::Request;
+use climb_graphql::auth::Auth;
+use climb_graphql::build_schema;
+use climb_graphql::db::{Db, DbConfig};
+use climb_graphql::notifications::Changes;
+use common::TestDatabase;
+
+#[path = "../../climb-db/tests/common/mod.rs"]
+mod common;
+
+/// Runs climb-admin against a database, returning what it prints as JSON
+fn climb_admin(database_url: &str, args: &[&str]) -> serde_json::Value {
+    let output = Command::new(env!("CARGO_BIN_EXE_climb-admin"))
+        .args(["--json", "--database-url", database_url])
+        .args(args)
+        .output()
+        .expect("Failed to run climb-admin");
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    serde_json::from_slice(&output.stdout).expect("Failed to parse output")
+}
+
+/// An admin added to an empty database, with an API token created for them, may add users
+#[tokio::test]
+async fn first_admin() {
+    let db = TestDatabase::with_migrations("test__bootstrap__first_admin");
+    let url = db.url();
+
+    climb_admin(&url, &["user", "add", "root", "--role", "admin"]);
+
+    let token = climb_admin(&url, &["token", "create", "root", "--label", "bootstrap"]);
+    let token = token["token"].as_str().expect("No token printed");
+
+    let pool = Db::new(&url, &DbConfig::default()).expect("Failed to create pool");
+    let schema = build_schema(pool.clone(), Changes::listen(url.clone()));
+
+    let mutation = r#"mutation { addUser(name: "alice", role: CONTRIBUTOR) { name role } }"#;
+
+    // Without a user, the mutation is not allowed
+    assert!(schema.execute(mutation).await.is_err());
+
+    let user = Auth::with_jwt(None)
+        .authenticate_authorization(&pool, &format!("Bearer {token}"))
+        .await
+        .expect("Failed to authenticate");
+
+    let response = schema.execute(Request::new(mutation).data(user)).await;
+
+    assert!(response.is_ok(), "{:?}", response.errors);
+    assert_eq!(
+        response.data.into_json().expect("Failed to convert response"),
+        serde_json::json!({ "addUser": { "name": "alice", "role": "CONTRIBUTOR" } })
+    );
+}
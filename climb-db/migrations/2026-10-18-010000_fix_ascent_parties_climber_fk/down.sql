@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE ascent_parties
+    DROP CONSTRAINT ascent_parties_climber_id_fkey,
+    ADD CONSTRAINT ascent_parties_climber_id_fkey
+        FOREIGN KEY (climber_id) REFERENCES ascents(id) ON DELETE CASCADE;
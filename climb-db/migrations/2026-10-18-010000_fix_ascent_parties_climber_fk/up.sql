@@ -0,0 +1,5 @@
+-- Your SQL goes here
+ALTER TABLE ascent_parties
+    DROP CONSTRAINT ascent_parties_climber_id_fkey,
+    ADD CONSTRAINT ascent_parties_climber_id_fkey
+        FOREIGN KEY (climber_id) REFERENCES climbers(id) ON DELETE CASCADE;
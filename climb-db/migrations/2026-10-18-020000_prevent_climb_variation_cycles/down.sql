@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP TRIGGER IF EXISTS trigger_prevent_climb_variation_cycle ON climb_variations;
+DROP FUNCTION IF EXISTS prevent_climb_variation_cycle();
@@ -0,0 +1,25 @@
+-- Your SQL goes here
+CREATE FUNCTION prevent_climb_variation_cycle() RETURNS trigger AS $$
+BEGIN
+    -- A climb may be a variation of several others, so walk every path down
+    -- from the new variation looking for the root
+    IF EXISTS (
+        WITH RECURSIVE descendants(id) AS (
+            SELECT NEW.variation_id
+            UNION
+            SELECT cv.variation_id
+            FROM climb_variations cv
+            JOIN descendants d ON cv.root_id = d.id
+        )
+        SELECT 1 FROM descendants WHERE id = NEW.root_id
+    ) THEN
+        RAISE EXCEPTION 'Cycle detected: climb cannot be a variation of its own variation';
+    END IF;
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trigger_prevent_climb_variation_cycle
+BEFORE INSERT OR UPDATE ON climb_variations
+FOR EACH ROW EXECUTE FUNCTION prevent_climb_variation_cycle();
@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX climb_belongs_to_formation_id_idx;
+DROP INDEX climb_belongs_to_area_id_idx;
+DROP INDEX formation_belongs_to_super_formation_id_idx;
+DROP INDEX formation_belongs_to_area_id_idx;
+DROP INDEX area_belongs_to_super_area_id_idx;
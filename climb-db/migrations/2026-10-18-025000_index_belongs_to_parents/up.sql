@@ -0,0 +1,7 @@
+-- Your SQL goes here
+-- Pages of the children of a parent are read in id order from these
+CREATE INDEX area_belongs_to_super_area_id_idx ON area_belongs_to (super_area_id, area_id);
+CREATE INDEX formation_belongs_to_area_id_idx ON formation_belongs_to (area_id, formation_id);
+CREATE INDEX formation_belongs_to_super_formation_id_idx ON formation_belongs_to (super_formation_id, formation_id);
+CREATE INDEX climb_belongs_to_area_id_idx ON climb_belongs_to (area_id, climb_id);
+CREATE INDEX climb_belongs_to_formation_id_idx ON climb_belongs_to (formation_id, climb_id);
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX formations_location_geography_idx;
+
+DROP INDEX formations_location_idx;
@@ -0,0 +1,4 @@
+-- Your SQL goes here
+CREATE INDEX formations_location_idx ON formations USING GIST (location);
+
+CREATE INDEX formations_location_geography_idx ON formations USING GIST ((location::geography));
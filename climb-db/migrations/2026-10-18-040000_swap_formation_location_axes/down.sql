@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+UPDATE formations
+	SET location = ST_SetSRID(ST_FlipCoordinates(location), 4326)
+	WHERE location IS NOT NULL;
@@ -0,0 +1,5 @@
+-- Your SQL goes here
+-- Locations were written with latitude as x and longitude as y
+UPDATE formations
+	SET location = ST_SetSRID(ST_FlipCoordinates(location), 4326)
+	WHERE location IS NOT NULL;
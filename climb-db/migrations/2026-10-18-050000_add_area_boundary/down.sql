@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX areas_boundary_idx;
+
+ALTER TABLE areas
+	DROP COLUMN boundary;
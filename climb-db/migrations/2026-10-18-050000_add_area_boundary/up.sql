@@ -0,0 +1,6 @@
+-- Your SQL goes here
+ALTER TABLE areas
+	ADD COLUMN boundary geometry(MULTIPOLYGON, 4326)
+	CONSTRAINT areas_boundary_valid CHECK (ST_IsValid(boundary));
+
+CREATE INDEX areas_boundary_idx ON areas USING GIST (boundary);
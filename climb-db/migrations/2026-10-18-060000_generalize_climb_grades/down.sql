@@ -0,0 +1,24 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE grade_sort_key_version;
+
+CREATE TABLE climb_vermin_grades (
+    climb_id INTEGER NOT NULL REFERENCES climbs(id) ON DELETE cascade,
+    value INTEGER NOT NULL CHECK (value >= 0),
+    PRIMARY KEY (climb_id, value)
+);
+
+INSERT INTO climb_vermin_grades (climb_id, value)
+	SELECT cg.climb_id, substring(g.value FROM 2)::INTEGER
+	FROM climb_grades cg
+	JOIN grades g ON g.id = cg.grade_id
+	JOIN grade_types gt ON gt.id = g.grade_type_id AND gt.name = 'vermin'
+	WHERE g.value ~ '^V[0-9]+$';
+
+DROP TABLE climb_grades;
+
+ALTER TABLE grades
+	DROP COLUMN sort_key;
+
+-- Grades of other grade types cascade
+DELETE FROM grade_types
+	WHERE name IN ('yds', 'french', 'uiaa', 'ewbank', 'font');
@@ -0,0 +1,57 @@
+-- Your SQL goes here
+INSERT INTO grade_types (name) VALUES
+('yds'),
+('french'),
+('uiaa'),
+('ewbank'),
+('font');
+
+-- Orders grades within their grade type
+ALTER TABLE grades
+	ADD COLUMN sort_key INTEGER;
+
+UPDATE grades
+	SET sort_key = CASE
+		WHEN value ~ '^V[0-9]+$' THEN substring(value FROM 2)::INTEGER
+		ELSE 0
+	END
+	WHERE grade_type_id = (SELECT id FROM grade_types WHERE name = 'vermin');
+
+UPDATE grades
+	SET sort_key = 0
+	WHERE sort_key IS NULL;
+
+ALTER TABLE grades
+	ALTER COLUMN sort_key SET NOT NULL;
+
+CREATE INDEX grades_sort_key_idx ON grades (grade_type_id, sort_key);
+
+CREATE TABLE climb_grades (
+	climb_id INTEGER NOT NULL REFERENCES climbs(id) ON DELETE CASCADE,
+	grade_id INTEGER NOT NULL REFERENCES grades(id) ON DELETE CASCADE,
+	PRIMARY KEY (climb_id, grade_id)
+);
+
+CREATE INDEX climb_grades_grade_id_idx ON climb_grades (grade_id);
+
+INSERT INTO grades (grade_type_id, value, sort_key)
+	SELECT DISTINCT gt.id, 'V' || cvg.value, cvg.value
+	FROM climb_vermin_grades cvg, grade_types gt
+	WHERE gt.name = 'vermin'
+	ON CONFLICT (grade_type_id, value) DO NOTHING;
+
+INSERT INTO climb_grades (climb_id, grade_id)
+	SELECT cvg.climb_id, g.id
+	FROM climb_vermin_grades cvg
+	JOIN grades g ON g.value = 'V' || cvg.value
+	JOIN grade_types gt ON gt.id = g.grade_type_id AND gt.name = 'vermin';
+
+DROP TABLE climb_vermin_grades;
+
+-- Version of the sort keys the grades are stored with, see `grade::SORT_KEY_VERSION`. Grades are
+-- re-keyed after the migrations run, which stores the current version.
+CREATE TABLE grade_sort_key_version (
+	version INTEGER PRIMARY KEY
+);
+
+INSERT INTO grade_sort_key_version (version) VALUES (0);
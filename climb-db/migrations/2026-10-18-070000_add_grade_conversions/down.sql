@@ -0,0 +1,15 @@
+-- This file should undo anything in `up.sql`
+DROP TRIGGER trigger_refresh_climb_difficulty_on_conversion_delete ON grade_conversions;
+DROP TRIGGER trigger_refresh_climb_difficulty_on_conversion_update ON grade_conversions;
+DROP TRIGGER trigger_refresh_climb_difficulty_on_conversion_insert ON grade_conversions;
+DROP FUNCTION refresh_climb_difficulty_on_conversion();
+DROP TRIGGER trigger_refresh_climb_difficulty_on_grade ON climb_grades;
+DROP FUNCTION refresh_climb_difficulty_on_grade();
+DROP FUNCTION refresh_climb_difficulty(INTEGER);
+
+ALTER TABLE climbs
+	DROP COLUMN difficulty;
+
+DROP FUNCTION grade_difficulty(INTEGER, INTEGER);
+DROP TABLE grade_conversions;
+DROP TABLE pending_grade_conversions;
@@ -0,0 +1,308 @@
+-- Your SQL goes here
+-- Places grades on a common scale of difficulty, so grades can be converted between grade types
+-- and climbs graded in different grade types can be compared. The scale follows Ewbank, with
+-- bouldering grades placed by their common route equivalents.
+CREATE TABLE grade_conversions (
+	grade_id INTEGER PRIMARY KEY REFERENCES grades(id) ON DELETE CASCADE,
+	difficulty DOUBLE PRECISION NOT NULL
+);
+
+-- Default conversions, by grade value, not yet applied. Grades are only ever keyed by the grade
+-- parsers, so `climb_db::migrate` applies these through them after running migrations.
+CREATE TABLE pending_grade_conversions (
+	grade_type_id INTEGER NOT NULL REFERENCES grade_types(id) ON DELETE CASCADE,
+	value VARCHAR(50) NOT NULL,
+	difficulty DOUBLE PRECISION NOT NULL,
+	PRIMARY KEY (grade_type_id, value)
+);
+
+CREATE TEMPORARY TABLE default_grade_conversions (
+	grade_type VARCHAR(100) NOT NULL,
+	value VARCHAR(50) NOT NULL,
+	difficulty DOUBLE PRECISION NOT NULL
+);
+
+INSERT INTO default_grade_conversions (grade_type, value, difficulty) VALUES
+('vermin', 'V0', 19),
+('vermin', 'V1', 22),
+('vermin', 'V2', 23.5),
+('vermin', 'V3', 25),
+('vermin', 'V4', 26.5),
+('vermin', 'V5', 28),
+('vermin', 'V6', 29),
+('vermin', 'V7', 30),
+('vermin', 'V8', 31.5),
+('vermin', 'V9', 33),
+('vermin', 'V10', 34),
+('vermin', 'V11', 35),
+('vermin', 'V12', 36),
+('vermin', 'V13', 37),
+('vermin', 'V14', 38),
+('vermin', 'V15', 39),
+('vermin', 'V16', 40),
+('vermin', 'V17', 41),
+('yds', '5.3', 10),
+('yds', '5.4', 11),
+('yds', '5.5', 12),
+('yds', '5.6', 14),
+('yds', '5.7', 15),
+('yds', '5.8', 16),
+('yds', '5.9', 17),
+('yds', '5.10a', 18),
+('yds', '5.10b', 19),
+('yds', '5.10c', 20),
+('yds', '5.10d', 21),
+('yds', '5.11a', 22),
+('yds', '5.11b', 23),
+('yds', '5.11c', 24),
+('yds', '5.11d', 25),
+('yds', '5.12a', 26),
+('yds', '5.12b', 27),
+('yds', '5.12c', 28),
+('yds', '5.12d', 29),
+('yds', '5.13a', 30),
+('yds', '5.13b', 31),
+('yds', '5.13c', 32),
+('yds', '5.13d', 33),
+('yds', '5.14a', 34),
+('yds', '5.14b', 35),
+('yds', '5.14c', 36),
+('yds', '5.14d', 37),
+('yds', '5.15a', 38),
+('yds', '5.15b', 39),
+('yds', '5.15c', 40),
+('yds', '5.15d', 41),
+('french', '3', 12),
+('french', '3+', 13),
+('french', '4', 14),
+('french', '4+', 15),
+('french', '5a', 16),
+('french', '5b', 17),
+('french', '5c', 18),
+('french', '6a', 19),
+('french', '6a+', 20),
+('french', '6b', 21),
+('french', '6b+', 22),
+('french', '6c', 23),
+('french', '6c+', 24),
+('french', '7a', 25),
+('french', '7a+', 26),
+('french', '7b', 27),
+('french', '7b+', 28),
+('french', '7c', 29),
+('french', '7c+', 30),
+('french', '8a', 31),
+('french', '8a+', 32),
+('french', '8b', 33),
+('french', '8b+', 34),
+('french', '8c', 35),
+('french', '8c+', 36),
+('french', '9a', 37),
+('french', '9a+', 38),
+('french', '9b', 39),
+('french', '9b+', 40),
+('french', '9c', 41),
+('uiaa', 'III', 10),
+('uiaa', 'III+', 11),
+('uiaa', 'IV', 12),
+('uiaa', 'IV+', 13),
+('uiaa', 'V', 14),
+('uiaa', 'V+', 15),
+('uiaa', 'VI-', 16),
+('uiaa', 'VI', 17),
+('uiaa', 'VI+', 18),
+('uiaa', 'VII-', 19),
+('uiaa', 'VII', 20),
+('uiaa', 'VII+', 21),
+('uiaa', 'VIII-', 22),
+('uiaa', 'VIII', 23),
+('uiaa', 'VIII+', 24),
+('uiaa', 'IX-', 25),
+('uiaa', 'IX', 26),
+('uiaa', 'IX+', 27),
+('uiaa', 'X-', 28),
+('uiaa', 'X', 29),
+('uiaa', 'X+', 30),
+('uiaa', 'XI-', 31),
+('uiaa', 'XI', 32),
+('uiaa', 'XI+', 33),
+('uiaa', 'XII-', 34),
+('uiaa', 'XII', 35),
+('uiaa', 'XII+', 36),
+('ewbank', '1', 1),
+('ewbank', '2', 2),
+('ewbank', '3', 3),
+('ewbank', '4', 4),
+('ewbank', '5', 5),
+('ewbank', '6', 6),
+('ewbank', '7', 7),
+('ewbank', '8', 8),
+('ewbank', '9', 9),
+('ewbank', '10', 10),
+('ewbank', '11', 11),
+('ewbank', '12', 12),
+('ewbank', '13', 13),
+('ewbank', '14', 14),
+('ewbank', '15', 15),
+('ewbank', '16', 16),
+('ewbank', '17', 17),
+('ewbank', '18', 18),
+('ewbank', '19', 19),
+('ewbank', '20', 20),
+('ewbank', '21', 21),
+('ewbank', '22', 22),
+('ewbank', '23', 23),
+('ewbank', '24', 24),
+('ewbank', '25', 25),
+('ewbank', '26', 26),
+('ewbank', '27', 27),
+('ewbank', '28', 28),
+('ewbank', '29', 29),
+('ewbank', '30', 30),
+('ewbank', '31', 31),
+('ewbank', '32', 32),
+('ewbank', '33', 33),
+('ewbank', '34', 34),
+('ewbank', '35', 35),
+('ewbank', '36', 36),
+('ewbank', '37', 37),
+('ewbank', '38', 38),
+('ewbank', '39', 39),
+('font', '4', 19),
+('font', '5', 22),
+('font', '5+', 23.5),
+('font', '6A', 25),
+('font', '6A+', 25.75),
+('font', '6B', 26.5),
+('font', '6B+', 27.25),
+('font', '6C', 28),
+('font', '6C+', 28.5),
+('font', '7A', 29),
+('font', '7A+', 30),
+('font', '7B', 31.5),
+('font', '7B+', 32.25),
+('font', '7C', 33),
+('font', '7C+', 34),
+('font', '8A', 35),
+('font', '8A+', 36),
+('font', '8B', 37),
+('font', '8B+', 38),
+('font', '8C', 39),
+('font', '8C+', 40),
+('font', '9A', 41);
+
+INSERT INTO pending_grade_conversions (grade_type_id, value, difficulty)
+	SELECT gt.id, d.value, d.difficulty
+	FROM default_grade_conversions d
+	JOIN grade_types gt ON gt.name = d.grade_type;
+
+DROP TABLE default_grade_conversions;
+
+-- Gets the difficulty of a grade, interpolating between the nearest conversions of its grade type
+CREATE FUNCTION grade_difficulty(grade_type INTEGER, key INTEGER)
+RETURNS DOUBLE PRECISION AS $$
+	WITH conversions AS (
+		SELECT g.sort_key, gc.difficulty
+		FROM grade_conversions gc
+		JOIN grades g ON g.id = gc.grade_id
+		WHERE g.grade_type_id = grade_type
+	),
+	lower AS (
+		SELECT * FROM conversions WHERE sort_key <= key ORDER BY sort_key DESC LIMIT 1
+	),
+	upper AS (
+		SELECT * FROM conversions WHERE sort_key >= key ORDER BY sort_key LIMIT 1
+	)
+	SELECT CASE
+		WHEN l.sort_key IS NULL THEN u.difficulty
+		WHEN u.sort_key IS NULL OR u.sort_key = l.sort_key THEN l.difficulty
+		ELSE l.difficulty
+			+ (u.difficulty - l.difficulty) * (key - l.sort_key) / (u.sort_key - l.sort_key)
+	END
+	FROM (SELECT 1) one
+	LEFT JOIN lower l ON TRUE
+	LEFT JOIN upper u ON TRUE;
+$$ LANGUAGE sql STABLE;
+
+-- The mean difficulty of the grades of a climb
+ALTER TABLE climbs
+	ADD COLUMN difficulty DOUBLE PRECISION;
+
+CREATE INDEX climbs_difficulty_idx ON climbs (difficulty);
+
+CREATE FUNCTION refresh_climb_difficulty(climb INTEGER)
+RETURNS VOID AS $$
+	UPDATE climbs
+	SET difficulty = (
+		SELECT avg(grade_difficulty(g.grade_type_id, g.sort_key))
+		FROM climb_grades cg
+		JOIN grades g ON g.id = cg.grade_id
+		WHERE cg.climb_id = climb
+	)
+	WHERE id = climb;
+$$ LANGUAGE sql;
+
+CREATE FUNCTION refresh_climb_difficulty_on_grade()
+RETURNS TRIGGER AS $$
+BEGIN
+	IF TG_OP = 'DELETE' THEN
+		PERFORM refresh_climb_difficulty(OLD.climb_id);
+	ELSE
+		PERFORM refresh_climb_difficulty(NEW.climb_id);
+	END IF;
+	RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trigger_refresh_climb_difficulty_on_grade
+AFTER INSERT OR DELETE ON climb_grades
+FOR EACH ROW
+EXECUTE FUNCTION refresh_climb_difficulty_on_grade();
+
+-- Interpolation spans every conversion of a grade type, so a changed conversion can move the
+-- difficulty of any grade of its type, but of no other
+CREATE FUNCTION refresh_climb_difficulty_on_conversion()
+RETURNS TRIGGER AS $$
+DECLARE
+	changed INTEGER[];
+BEGIN
+	IF TG_OP = 'INSERT' THEN
+		changed := ARRAY(SELECT grade_id FROM new_conversions);
+	ELSIF TG_OP = 'DELETE' THEN
+		changed := ARRAY(SELECT grade_id FROM old_conversions);
+	ELSE
+		changed := ARRAY(SELECT grade_id FROM new_conversions UNION SELECT grade_id FROM old_conversions);
+	END IF;
+
+	PERFORM refresh_climb_difficulty(climb_id)
+	FROM (
+		SELECT DISTINCT cg.climb_id
+		FROM climb_grades cg
+		JOIN grades g ON g.id = cg.grade_id
+		WHERE g.grade_type_id IN (SELECT grade_type_id FROM grades WHERE id = ANY(changed))
+	) affected;
+
+	RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Transition tables are only allowed on triggers of a single event
+CREATE TRIGGER trigger_refresh_climb_difficulty_on_conversion_insert
+AFTER INSERT ON grade_conversions
+REFERENCING NEW TABLE AS new_conversions
+FOR EACH STATEMENT
+EXECUTE FUNCTION refresh_climb_difficulty_on_conversion();
+
+CREATE TRIGGER trigger_refresh_climb_difficulty_on_conversion_update
+AFTER UPDATE ON grade_conversions
+REFERENCING OLD TABLE AS old_conversions NEW TABLE AS new_conversions
+FOR EACH STATEMENT
+EXECUTE FUNCTION refresh_climb_difficulty_on_conversion();
+
+CREATE TRIGGER trigger_refresh_climb_difficulty_on_conversion_delete
+AFTER DELETE ON grade_conversions
+REFERENCING OLD TABLE AS old_conversions
+FOR EACH STATEMENT
+EXECUTE FUNCTION refresh_climb_difficulty_on_conversion();
+
+SELECT refresh_climb_difficulty(id) FROM climbs;
@@ -0,0 +1,13 @@
+-- This file should undo anything in `up.sql`
+DROP TRIGGER trigger_refresh_climb_consensus_grades_on_conversion ON grade_conversions;
+DROP FUNCTION refresh_climb_consensus_grades_on_conversion();
+DROP TRIGGER trigger_refresh_climb_consensus_grades_on_ascent ON ascents;
+DROP FUNCTION refresh_climb_consensus_grades_on_ascent();
+DROP TRIGGER trigger_refresh_climb_consensus_grades_on_ascent_grade ON ascent_grades;
+DROP FUNCTION refresh_climb_consensus_grades_on_ascent_grade();
+DROP FUNCTION refresh_climb_consensus_grades(INTEGER);
+
+DROP INDEX ascents_climb_id_idx;
+DROP INDEX ascent_grades_grade_id_idx;
+
+DROP TABLE climb_consensus_grades;
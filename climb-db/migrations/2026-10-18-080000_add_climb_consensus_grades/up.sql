@@ -0,0 +1,109 @@
+-- Your SQL goes here
+-- The consensus of the grades proposed by the ascents of a climb, per grade type
+CREATE TABLE climb_consensus_grades (
+	climb_id INTEGER NOT NULL REFERENCES climbs(id) ON DELETE CASCADE,
+	grade_type_id INTEGER NOT NULL REFERENCES grade_types(id) ON DELETE CASCADE,
+	-- The lower median when there is an even number of votes
+	median_grade_id INTEGER NOT NULL REFERENCES grades(id) ON DELETE CASCADE,
+	-- The easiest of the most voted grades
+	mode_grade_id INTEGER NOT NULL REFERENCES grades(id) ON DELETE CASCADE,
+	votes INTEGER NOT NULL,
+	-- Difference in difficulty between the hardest and easiest votes
+	spread DOUBLE PRECISION,
+	PRIMARY KEY (climb_id, grade_type_id)
+);
+
+CREATE INDEX ascent_grades_grade_id_idx ON ascent_grades (grade_id);
+CREATE INDEX ascents_climb_id_idx ON ascents (climb_id);
+
+CREATE FUNCTION refresh_climb_consensus_grades(climb INTEGER)
+RETURNS VOID AS $$
+	DELETE FROM climb_consensus_grades WHERE climb_id = climb;
+
+	WITH votes AS (
+		SELECT g.grade_type_id, g.id AS grade_id, g.sort_key
+		FROM ascents a
+		JOIN ascent_grades ag ON ag.ascent_id = a.id
+		JOIN grades g ON g.id = ag.grade_id
+		WHERE a.climb_id = climb
+	),
+	tally AS (
+		SELECT grade_type_id, grade_id, sort_key, count(*) AS votes
+		FROM votes
+		GROUP BY grade_type_id, grade_id, sort_key
+	)
+	INSERT INTO climb_consensus_grades (climb_id, grade_type_id, median_grade_id, mode_grade_id, votes, spread)
+	SELECT climb,
+		v.grade_type_id,
+		(array_agg(v.grade_id ORDER BY v.sort_key))[(count(*) + 1) / 2],
+		(
+			SELECT t.grade_id
+			FROM tally t
+			WHERE t.grade_type_id = v.grade_type_id
+			ORDER BY t.votes DESC, t.sort_key
+			LIMIT 1
+		),
+		count(*),
+		max(grade_difficulty(v.grade_type_id, v.sort_key)) - min(grade_difficulty(v.grade_type_id, v.sort_key))
+	FROM votes v
+	GROUP BY v.grade_type_id;
+$$ LANGUAGE sql;
+
+CREATE FUNCTION refresh_climb_consensus_grades_on_ascent_grade()
+RETURNS TRIGGER AS $$
+DECLARE
+	climb INTEGER;
+BEGIN
+	IF TG_OP = 'DELETE' THEN
+		SELECT climb_id INTO climb FROM ascents WHERE id = OLD.ascent_id;
+	ELSE
+		SELECT climb_id INTO climb FROM ascents WHERE id = NEW.ascent_id;
+	END IF;
+
+	-- Grades removed along with their ascent are handled by the trigger on ascents
+	IF climb IS NOT NULL THEN
+		PERFORM refresh_climb_consensus_grades(climb);
+	END IF;
+
+	RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trigger_refresh_climb_consensus_grades_on_ascent_grade
+AFTER INSERT OR DELETE ON ascent_grades
+FOR EACH ROW
+EXECUTE FUNCTION refresh_climb_consensus_grades_on_ascent_grade();
+
+CREATE FUNCTION refresh_climb_consensus_grades_on_ascent()
+RETURNS TRIGGER AS $$
+BEGIN
+	PERFORM refresh_climb_consensus_grades(OLD.climb_id);
+
+	IF TG_OP = 'UPDATE' THEN
+		PERFORM refresh_climb_consensus_grades(NEW.climb_id);
+	END IF;
+
+	RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trigger_refresh_climb_consensus_grades_on_ascent
+AFTER DELETE OR UPDATE OF climb_id ON ascents
+FOR EACH ROW
+EXECUTE FUNCTION refresh_climb_consensus_grades_on_ascent();
+
+-- Spreads are measured in difficulty
+CREATE FUNCTION refresh_climb_consensus_grades_on_conversion()
+RETURNS TRIGGER AS $$
+BEGIN
+	PERFORM refresh_climb_consensus_grades(climb_id) FROM (SELECT DISTINCT climb_id FROM climb_consensus_grades) c;
+	RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trigger_refresh_climb_consensus_grades_on_conversion
+AFTER INSERT OR UPDATE OR DELETE ON grade_conversions
+FOR EACH STATEMENT
+EXECUTE FUNCTION refresh_climb_consensus_grades_on_conversion();
+
+SELECT refresh_climb_consensus_grades(climb_id) FROM (SELECT DISTINCT climb_id FROM ascents) a;
@@ -0,0 +1,8 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX climb_descriptions_value_search_idx;
+
+DROP FUNCTION search_fold(TEXT);
+
+DROP TEXT SEARCH CONFIGURATION climb_search;
+
+-- The extensions are left installed, other objects may depend on them
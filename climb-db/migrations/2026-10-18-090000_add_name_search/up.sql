@@ -0,0 +1,17 @@
+-- Your SQL goes here
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+CREATE EXTENSION IF NOT EXISTS unaccent;
+
+-- Like `simple`, but folding accents, so "Tuolumne" matches "Tuólumne"
+CREATE TEXT SEARCH CONFIGURATION climb_search (COPY = simple);
+
+ALTER TEXT SEARCH CONFIGURATION climb_search
+	ALTER MAPPING FOR hword, hword_part, word WITH unaccent, simple;
+
+-- `unaccent` is only stable, so this wrapper is declared immutable to allow indexing it
+CREATE FUNCTION search_fold(value TEXT)
+RETURNS TEXT AS $$
+	SELECT lower(public.unaccent('public.unaccent', value));
+$$ LANGUAGE sql IMMUTABLE PARALLEL SAFE STRICT;
+
+CREATE INDEX climb_descriptions_value_search_idx ON climb_descriptions USING GIN (to_tsvector('climb_search', value));
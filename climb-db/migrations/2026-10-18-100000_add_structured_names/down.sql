@@ -0,0 +1,42 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE areas
+	ADD COLUMN names TEXT[];
+
+UPDATE areas t
+SET names = COALESCE(
+	(SELECT array_agg(value ORDER BY kind <> 'primary', ordering, id) FROM area_names WHERE area_id = t.id),
+	'{}'
+);
+
+ALTER TABLE areas
+	ALTER COLUMN names SET NOT NULL;
+
+DROP TABLE area_names;
+
+ALTER TABLE formations
+	ADD COLUMN names TEXT[];
+
+UPDATE formations t
+SET names = COALESCE(
+	(SELECT array_agg(value ORDER BY kind <> 'primary', ordering, id) FROM formation_names WHERE formation_id = t.id),
+	'{}'
+);
+
+ALTER TABLE formations
+	ALTER COLUMN names SET NOT NULL;
+
+DROP TABLE formation_names;
+
+ALTER TABLE climbs
+	ADD COLUMN names TEXT[];
+
+UPDATE climbs t
+SET names = COALESCE(
+	(SELECT array_agg(value ORDER BY kind <> 'primary', ordering, id) FROM climb_names WHERE climb_id = t.id),
+	'{}'
+);
+
+ALTER TABLE climbs
+	ALTER COLUMN names SET NOT NULL;
+
+DROP TABLE climb_names;
@@ -0,0 +1,109 @@
+-- Your SQL goes here
+-- Names of areas, formations and climbs, replacing their `names` arrays. A thing has at most one
+-- primary name, its canonical name, and any number of aliases, historic names and translations.
+-- Names are listed primary first, then by `ordering`.
+
+CREATE TABLE area_names (
+	id SERIAL PRIMARY KEY,
+	area_id INTEGER NOT NULL REFERENCES areas(id) ON DELETE CASCADE,
+	value TEXT NOT NULL,
+	-- BCP 47 language tag, e.g. `en` or `ja-Latn`
+	language VARCHAR(35),
+	kind VARCHAR(20) NOT NULL DEFAULT 'alias',
+	ordering INTEGER NOT NULL DEFAULT 0,
+	CONSTRAINT area_names_kind_valid CHECK (kind IN ('primary', 'alias', 'historic', 'translation')),
+	CONSTRAINT area_names_language_valid CHECK (language ~ '^[A-Za-z]{2,3}(-[A-Za-z0-9]{1,8})*$'),
+	CONSTRAINT area_names_translation_language CHECK (kind <> 'translation' OR language IS NOT NULL)
+);
+
+CREATE UNIQUE INDEX area_names_primary_idx ON area_names (area_id) WHERE kind = 'primary';
+CREATE UNIQUE INDEX area_names_value_idx ON area_names (area_id, value, COALESCE(language, ''));
+CREATE INDEX area_names_search_idx ON area_names USING GIN (to_tsvector('climb_search', value));
+CREATE INDEX area_names_trgm_idx ON area_names USING GIN (search_fold(value) gin_trgm_ops);
+
+-- The first name becomes the primary name, and repeated names are dropped
+INSERT INTO area_names (area_id, value, kind, ordering)
+	SELECT id,
+		value,
+		CASE WHEN row_number() OVER (PARTITION BY id ORDER BY position) = 1 THEN 'primary' ELSE 'alias' END,
+		row_number() OVER (PARTITION BY id ORDER BY position) - 1
+	FROM (
+		SELECT DISTINCT ON (t.id, n.value) t.id, n.value, n.position
+		FROM areas t
+		CROSS JOIN LATERAL unnest(t.names) WITH ORDINALITY AS n(value, position)
+		WHERE n.value IS NOT NULL
+		ORDER BY t.id, n.value, n.position
+	) names;
+
+ALTER TABLE areas
+	DROP COLUMN names;
+
+CREATE TABLE formation_names (
+	id SERIAL PRIMARY KEY,
+	formation_id INTEGER NOT NULL REFERENCES formations(id) ON DELETE CASCADE,
+	value TEXT NOT NULL,
+	-- BCP 47 language tag, e.g. `en` or `ja-Latn`
+	language VARCHAR(35),
+	kind VARCHAR(20) NOT NULL DEFAULT 'alias',
+	ordering INTEGER NOT NULL DEFAULT 0,
+	CONSTRAINT formation_names_kind_valid CHECK (kind IN ('primary', 'alias', 'historic', 'translation')),
+	CONSTRAINT formation_names_language_valid CHECK (language ~ '^[A-Za-z]{2,3}(-[A-Za-z0-9]{1,8})*$'),
+	CONSTRAINT formation_names_translation_language CHECK (kind <> 'translation' OR language IS NOT NULL)
+);
+
+CREATE UNIQUE INDEX formation_names_primary_idx ON formation_names (formation_id) WHERE kind = 'primary';
+CREATE UNIQUE INDEX formation_names_value_idx ON formation_names (formation_id, value, COALESCE(language, ''));
+CREATE INDEX formation_names_search_idx ON formation_names USING GIN (to_tsvector('climb_search', value));
+CREATE INDEX formation_names_trgm_idx ON formation_names USING GIN (search_fold(value) gin_trgm_ops);
+
+-- The first name becomes the primary name, and repeated names are dropped
+INSERT INTO formation_names (formation_id, value, kind, ordering)
+	SELECT id,
+		value,
+		CASE WHEN row_number() OVER (PARTITION BY id ORDER BY position) = 1 THEN 'primary' ELSE 'alias' END,
+		row_number() OVER (PARTITION BY id ORDER BY position) - 1
+	FROM (
+		SELECT DISTINCT ON (t.id, n.value) t.id, n.value, n.position
+		FROM formations t
+		CROSS JOIN LATERAL unnest(t.names) WITH ORDINALITY AS n(value, position)
+		WHERE n.value IS NOT NULL
+		ORDER BY t.id, n.value, n.position
+	) names;
+
+ALTER TABLE formations
+	DROP COLUMN names;
+
+CREATE TABLE climb_names (
+	id SERIAL PRIMARY KEY,
+	climb_id INTEGER NOT NULL REFERENCES climbs(id) ON DELETE CASCADE,
+	value TEXT NOT NULL,
+	-- BCP 47 language tag, e.g. `en` or `ja-Latn`
+	language VARCHAR(35),
+	kind VARCHAR(20) NOT NULL DEFAULT 'alias',
+	ordering INTEGER NOT NULL DEFAULT 0,
+	CONSTRAINT climb_names_kind_valid CHECK (kind IN ('primary', 'alias', 'historic', 'translation')),
+	CONSTRAINT climb_names_language_valid CHECK (language ~ '^[A-Za-z]{2,3}(-[A-Za-z0-9]{1,8})*$'),
+	CONSTRAINT climb_names_translation_language CHECK (kind <> 'translation' OR language IS NOT NULL)
+);
+
+CREATE UNIQUE INDEX climb_names_primary_idx ON climb_names (climb_id) WHERE kind = 'primary';
+CREATE UNIQUE INDEX climb_names_value_idx ON climb_names (climb_id, value, COALESCE(language, ''));
+CREATE INDEX climb_names_search_idx ON climb_names USING GIN (to_tsvector('climb_search', value));
+CREATE INDEX climb_names_trgm_idx ON climb_names USING GIN (search_fold(value) gin_trgm_ops);
+
+-- The first name becomes the primary name, and repeated names are dropped
+INSERT INTO climb_names (climb_id, value, kind, ordering)
+	SELECT id,
+		value,
+		CASE WHEN row_number() OVER (PARTITION BY id ORDER BY position) = 1 THEN 'primary' ELSE 'alias' END,
+		row_number() OVER (PARTITION BY id ORDER BY position) - 1
+	FROM (
+		SELECT DISTINCT ON (t.id, n.value) t.id, n.value, n.position
+		FROM climbs t
+		CROSS JOIN LATERAL unnest(t.names) WITH ORDINALITY AS n(value, position)
+		WHERE n.value IS NOT NULL
+		ORDER BY t.id, n.value, n.position
+	) names;
+
+ALTER TABLE climbs
+	DROP COLUMN names;
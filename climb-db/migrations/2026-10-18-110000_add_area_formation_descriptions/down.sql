@@ -0,0 +1,24 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE formation_descriptions;
+DROP TABLE area_descriptions;
+
+ALTER TABLE climb_descriptions
+	RENAME CONSTRAINT climb_descriptions_description_type_id_fkey TO climb_descriptions_climb_description_type_id_fkey;
+
+ALTER TABLE climb_descriptions
+	RENAME COLUMN description_type_id TO climb_description_type_id;
+
+ALTER TABLE description_types
+	DROP CONSTRAINT description_types_name_valid;
+
+ALTER TABLE description_types
+	RENAME CONSTRAINT description_types_name_key TO climb_description_types_name_key;
+
+ALTER TABLE description_types
+	RENAME CONSTRAINT description_types_pkey TO climb_description_types_pkey;
+
+ALTER SEQUENCE description_types_id_seq
+	RENAME TO climb_description_types_id_seq;
+
+ALTER TABLE description_types
+	RENAME TO climb_description_types;
@@ -0,0 +1,39 @@
+-- Your SQL goes here
+-- Description types are shared by areas, formations and climbs
+ALTER TABLE climb_description_types
+	RENAME TO description_types;
+
+ALTER SEQUENCE climb_description_types_id_seq
+	RENAME TO description_types_id_seq;
+
+ALTER TABLE description_types
+	RENAME CONSTRAINT climb_description_types_pkey TO description_types_pkey;
+
+ALTER TABLE description_types
+	RENAME CONSTRAINT climb_description_types_name_key TO description_types_name_key;
+
+ALTER TABLE description_types
+	ADD CONSTRAINT description_types_name_valid CHECK (btrim(name) <> '');
+
+ALTER TABLE climb_descriptions
+	RENAME COLUMN climb_description_type_id TO description_type_id;
+
+ALTER TABLE climb_descriptions
+	RENAME CONSTRAINT climb_descriptions_climb_description_type_id_fkey TO climb_descriptions_description_type_id_fkey;
+
+CREATE TABLE area_descriptions (
+	area_id INTEGER NOT NULL REFERENCES areas(id) ON DELETE CASCADE,
+	description_type_id INTEGER NOT NULL REFERENCES description_types(id) ON DELETE CASCADE,
+	value TEXT NOT NULL,
+	PRIMARY KEY (area_id, description_type_id)
+);
+
+CREATE TABLE formation_descriptions (
+	formation_id INTEGER NOT NULL REFERENCES formations(id) ON DELETE CASCADE,
+	description_type_id INTEGER NOT NULL REFERENCES description_types(id) ON DELETE CASCADE,
+	value TEXT NOT NULL,
+	PRIMARY KEY (formation_id, description_type_id)
+);
+
+CREATE INDEX area_descriptions_value_search_idx ON area_descriptions USING GIN (to_tsvector('climb_search', value));
+CREATE INDEX formation_descriptions_value_search_idx ON formation_descriptions USING GIN (to_tsvector('climb_search', value));
@@ -0,0 +1,23 @@
+-- This file should undo anything in `up.sql`
+DROP FUNCTION revert_change(BIGINT);
+DROP FUNCTION revert_row_change(audit_log);
+DROP FUNCTION audit_row_match(JSONB);
+
+DROP TRIGGER climb_variations_audit ON climb_variations;
+DROP TRIGGER climb_grades_audit ON climb_grades;
+DROP TRIGGER climb_belongs_to_audit ON climb_belongs_to;
+DROP TRIGGER climb_descriptions_audit ON climb_descriptions;
+DROP TRIGGER climb_names_audit ON climb_names;
+DROP TRIGGER climbs_audit ON climbs;
+DROP TRIGGER formation_belongs_to_audit ON formation_belongs_to;
+DROP TRIGGER formation_descriptions_audit ON formation_descriptions;
+DROP TRIGGER formation_names_audit ON formation_names;
+DROP TRIGGER formations_audit ON formations;
+DROP TRIGGER area_belongs_to_audit ON area_belongs_to;
+DROP TRIGGER area_descriptions_audit ON area_descriptions;
+DROP TRIGGER area_names_audit ON area_names;
+DROP TRIGGER areas_audit ON areas;
+
+DROP FUNCTION record_change();
+
+DROP TABLE audit_log;
@@ -0,0 +1,220 @@
+-- Your SQL goes here
+CREATE TABLE audit_log (
+	id BIGSERIAL PRIMARY KEY,
+	changed_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+	-- Whoever made the change, set per transaction with `SET LOCAL climb.actor`
+	actor TEXT,
+	transaction_id BIGINT NOT NULL DEFAULT txid_current(),
+	table_name TEXT NOT NULL,
+	operation VARCHAR(6) NOT NULL,
+	-- The area, formation or climb the changed row is part of
+	entity_kind VARCHAR(20) NOT NULL,
+	entity_id INTEGER NOT NULL,
+	before JSONB,
+	after JSONB,
+	-- The change which this change reverted
+	reverts BIGINT REFERENCES audit_log(id) ON DELETE SET NULL,
+	CONSTRAINT audit_log_operation_valid CHECK (operation IN ('INSERT', 'UPDATE', 'DELETE'))
+);
+
+CREATE INDEX audit_log_entity_idx ON audit_log (entity_kind, entity_id, id);
+CREATE INDEX audit_log_transaction_id_idx ON audit_log (transaction_id);
+
+-- Records a change of a row. Arguments are the kind of entity the row is part of, the column
+-- holding the id of that entity, and any derived columns which are not recorded.
+CREATE FUNCTION record_change()
+RETURNS TRIGGER AS $$
+DECLARE
+	ignored TEXT[] := TG_ARGV[2:];
+	before JSONB;
+	after JSONB;
+	geometry_column TEXT;
+	geometry JSONB;
+BEGIN
+	IF TG_OP <> 'INSERT' THEN
+		before := to_jsonb(OLD) - ignored;
+	END IF;
+
+	IF TG_OP <> 'DELETE' THEN
+		after := to_jsonb(NEW) - ignored;
+	END IF;
+
+	-- Geometries are recorded as EWKT, which casts back to a geometry when reverting
+	FOR geometry_column IN
+		SELECT attname
+		FROM pg_attribute
+		WHERE attrelid = TG_RELID AND atttypid = to_regtype('geometry') AND NOT attisdropped
+	LOOP
+		IF before IS NOT NULL THEN
+			EXECUTE format('SELECT to_jsonb(ST_AsEWKT(($1).%I))', geometry_column) INTO geometry USING OLD;
+			before := jsonb_set(before, ARRAY[geometry_column], COALESCE(geometry, 'null'));
+		END IF;
+
+		IF after IS NOT NULL THEN
+			EXECUTE format('SELECT to_jsonb(ST_AsEWKT(($1).%I))', geometry_column) INTO geometry USING NEW;
+			after := jsonb_set(after, ARRAY[geometry_column], COALESCE(geometry, 'null'));
+		END IF;
+	END LOOP;
+
+	IF before = after THEN
+		RETURN NULL;
+	END IF;
+
+	INSERT INTO audit_log (actor, table_name, operation, entity_kind, entity_id, before, after, reverts)
+	VALUES (
+		NULLIF(current_setting('climb.actor', true), ''),
+		TG_TABLE_NAME,
+		TG_OP,
+		TG_ARGV[0],
+		(COALESCE(after, before) ->> TG_ARGV[1])::INTEGER,
+		before,
+		after,
+		NULLIF(current_setting('climb.reverting', true), '')::BIGINT
+	);
+
+	RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER areas_audit AFTER INSERT OR UPDATE OR DELETE ON areas
+FOR EACH ROW EXECUTE FUNCTION record_change('area', 'id');
+
+CREATE TRIGGER area_names_audit AFTER INSERT OR UPDATE OR DELETE ON area_names
+FOR EACH ROW EXECUTE FUNCTION record_change('area', 'area_id');
+
+CREATE TRIGGER area_descriptions_audit AFTER INSERT OR UPDATE OR DELETE ON area_descriptions
+FOR EACH ROW EXECUTE FUNCTION record_change('area', 'area_id');
+
+CREATE TRIGGER area_belongs_to_audit AFTER INSERT OR UPDATE OR DELETE ON area_belongs_to
+FOR EACH ROW EXECUTE FUNCTION record_change('area', 'area_id');
+
+CREATE TRIGGER formations_audit AFTER INSERT OR UPDATE OR DELETE ON formations
+FOR EACH ROW EXECUTE FUNCTION record_change('formation', 'id');
+
+CREATE TRIGGER formation_names_audit AFTER INSERT OR UPDATE OR DELETE ON formation_names
+FOR EACH ROW EXECUTE FUNCTION record_change('formation', 'formation_id');
+
+CREATE TRIGGER formation_descriptions_audit AFTER INSERT OR UPDATE OR DELETE ON formation_descriptions
+FOR EACH ROW EXECUTE FUNCTION record_change('formation', 'formation_id');
+
+CREATE TRIGGER formation_belongs_to_audit AFTER INSERT OR UPDATE OR DELETE ON formation_belongs_to
+FOR EACH ROW EXECUTE FUNCTION record_change('formation', 'formation_id');
+
+CREATE TRIGGER climbs_audit AFTER INSERT OR UPDATE OR DELETE ON climbs
+FOR EACH ROW EXECUTE FUNCTION record_change('climb', 'id', 'difficulty');
+
+CREATE TRIGGER climb_names_audit AFTER INSERT OR UPDATE OR DELETE ON climb_names
+FOR EACH ROW EXECUTE FUNCTION record_change('climb', 'climb_id');
+
+CREATE TRIGGER climb_descriptions_audit AFTER INSERT OR UPDATE OR DELETE ON climb_descriptions
+FOR EACH ROW EXECUTE FUNCTION record_change('climb', 'climb_id');
+
+CREATE TRIGGER climb_belongs_to_audit AFTER INSERT OR UPDATE OR DELETE ON climb_belongs_to
+FOR EACH ROW EXECUTE FUNCTION record_change('climb', 'climb_id');
+
+CREATE TRIGGER climb_grades_audit AFTER INSERT OR UPDATE OR DELETE ON climb_grades
+FOR EACH ROW EXECUTE FUNCTION record_change('climb', 'climb_id');
+
+CREATE TRIGGER climb_variations_audit AFTER INSERT OR UPDATE OR DELETE ON climb_variations
+FOR EACH ROW EXECUTE FUNCTION record_change('climb', 'root_id');
+
+-- Matches the rows `r` of a table having every value of a recorded row `j`
+CREATE FUNCTION audit_row_match(recorded JSONB)
+RETURNS TEXT AS $$
+	SELECT string_agg(format('r.%1$I IS NOT DISTINCT FROM j.%1$I', key), ' AND ')
+	FROM jsonb_object_keys(recorded) AS key;
+$$ LANGUAGE sql IMMUTABLE STRICT;
+
+-- Reverts a recorded change of a row, failing if the row has changed since
+CREATE FUNCTION revert_row_change(change audit_log)
+RETURNS VOID AS $$
+DECLARE
+	columns TEXT;
+	affected INTEGER;
+BEGIN
+	IF change.operation = 'INSERT' THEN
+		EXECUTE format(
+			'DELETE FROM %1$I r USING jsonb_populate_record(NULL::%1$I, $1) j WHERE %2$s',
+			change.table_name,
+			audit_row_match(change.after)
+		) USING change.after;
+	ELSIF change.operation = 'UPDATE' THEN
+		SELECT string_agg(format('%I', key), ', ') INTO columns FROM jsonb_object_keys(change.before) AS key;
+
+		EXECUTE format(
+			'UPDATE %1$I r SET (%2$s) = (SELECT %2$s FROM jsonb_populate_record(NULL::%1$I, $2))
+			FROM jsonb_populate_record(NULL::%1$I, $1) j
+			WHERE %3$s',
+			change.table_name,
+			columns,
+			audit_row_match(change.after)
+		) USING change.after, change.before;
+	ELSE
+		SELECT string_agg(format('%I', key), ', ') INTO columns FROM jsonb_object_keys(change.before) AS key;
+
+		EXECUTE format(
+			'INSERT INTO %1$I (%2$s) SELECT %2$s FROM jsonb_populate_record(NULL::%1$I, $1)
+			ON CONFLICT DO NOTHING',
+			change.table_name,
+			columns
+		) USING change.before;
+	END IF;
+
+	GET DIAGNOSTICS affected = ROW_COUNT;
+
+	IF affected = 0 THEN
+		RAISE EXCEPTION 'Revert conflict: % row of change % has changed since', change.table_name, change.id;
+	END IF;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Reverts every change made in the transaction of a change, returning the number of changes
+-- reverted. Changes are reverted newest first, except that a change whose revert violates a
+-- foreign key, e.g. a row deleted by a cascade before its parent, is retried after the others.
+CREATE FUNCTION revert_change(change_id BIGINT)
+RETURNS INTEGER AS $$
+DECLARE
+	pending BIGINT[];
+	postponed BIGINT[];
+	pending_id BIGINT;
+	change audit_log;
+	reverted INTEGER;
+BEGIN
+	pending := ARRAY(
+		SELECT id
+		FROM audit_log
+		WHERE transaction_id = (SELECT transaction_id FROM audit_log WHERE id = change_id)
+		ORDER BY id DESC
+	);
+
+	reverted := cardinality(pending);
+
+	PERFORM set_config('climb.reverting', change_id::TEXT, true);
+
+	WHILE cardinality(pending) > 0 LOOP
+		postponed := '{}';
+
+		FOREACH pending_id IN ARRAY pending LOOP
+			SELECT * INTO change FROM audit_log WHERE id = pending_id;
+
+			BEGIN
+				PERFORM revert_row_change(change);
+			EXCEPTION WHEN foreign_key_violation THEN
+				postponed := postponed || pending_id;
+			END;
+		END LOOP;
+
+		-- Without progress, fail with the violation of the first change
+		IF cardinality(postponed) = cardinality(pending) THEN
+			SELECT * INTO change FROM audit_log WHERE id = postponed[1];
+			PERFORM revert_row_change(change);
+		END IF;
+
+		pending := postponed;
+	END LOOP;
+
+	PERFORM set_config('climb.reverting', '', true);
+
+	RETURN reverted;
+END;
+$$ LANGUAGE plpgsql;
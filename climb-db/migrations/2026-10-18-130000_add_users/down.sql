@@ -0,0 +1,11 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE ascents DROP COLUMN created_by;
+ALTER TABLE climbers DROP COLUMN created_by;
+ALTER TABLE climbs DROP COLUMN created_by;
+ALTER TABLE formations DROP COLUMN created_by;
+ALTER TABLE areas DROP COLUMN created_by;
+
+DROP FUNCTION acting_user_id();
+
+DROP TABLE api_tokens;
+DROP TABLE users;
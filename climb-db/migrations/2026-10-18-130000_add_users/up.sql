@@ -0,0 +1,43 @@
+-- Your SQL goes here
+CREATE TABLE users (
+	id SERIAL PRIMARY KEY,
+	name TEXT NOT NULL UNIQUE,
+	role VARCHAR(20) NOT NULL DEFAULT 'reader',
+	created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+	CONSTRAINT users_name_valid CHECK (btrim(name) <> ''),
+	CONSTRAINT users_role_valid CHECK (role IN ('reader', 'contributor', 'moderator', 'admin'))
+);
+
+-- Only the SHA-256 hash of a token is stored, the token itself is shown once when created
+CREATE TABLE api_tokens (
+	id SERIAL PRIMARY KEY,
+	user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+	token_hash BYTEA NOT NULL UNIQUE,
+	label TEXT,
+	created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+	expires_at TIMESTAMPTZ,
+	last_used_at TIMESTAMPTZ
+);
+
+CREATE INDEX api_tokens_user_id_idx ON api_tokens (user_id);
+
+-- The user acting in the current transaction, set with `set_config('climb.user_id', ..., true)`
+CREATE FUNCTION acting_user_id()
+RETURNS INTEGER AS $$
+	SELECT NULLIF(current_setting('climb.user_id', true), '')::INTEGER;
+$$ LANGUAGE sql STABLE;
+
+ALTER TABLE areas
+	ADD COLUMN created_by INTEGER DEFAULT acting_user_id() REFERENCES users(id) ON DELETE SET NULL;
+
+ALTER TABLE formations
+	ADD COLUMN created_by INTEGER DEFAULT acting_user_id() REFERENCES users(id) ON DELETE SET NULL;
+
+ALTER TABLE climbs
+	ADD COLUMN created_by INTEGER DEFAULT acting_user_id() REFERENCES users(id) ON DELETE SET NULL;
+
+ALTER TABLE climbers
+	ADD COLUMN created_by INTEGER DEFAULT acting_user_id() REFERENCES users(id) ON DELETE SET NULL;
+
+ALTER TABLE ascents
+	ADD COLUMN created_by INTEGER DEFAULT acting_user_id() REFERENCES users(id) ON DELETE SET NULL;
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP TRIGGER audit_log_notify ON audit_log;
+DROP FUNCTION notify_change();
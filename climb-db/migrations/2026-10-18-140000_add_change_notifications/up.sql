@@ -0,0 +1,17 @@
+-- Your SQL goes here
+-- Notifies listeners of the `climb_changes` channel of each recorded change once committed. The
+-- payload is `<change id> <transaction id> <entity kind> <entity id> <table> <operation>`.
+CREATE FUNCTION notify_change()
+RETURNS TRIGGER AS $$
+BEGIN
+	PERFORM pg_notify(
+		'climb_changes',
+		concat_ws(' ', NEW.id, NEW.transaction_id, NEW.entity_kind, NEW.entity_id, NEW.table_name, NEW.operation)
+	);
+
+	RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER audit_log_notify AFTER INSERT ON audit_log
+FOR EACH ROW EXECUTE FUNCTION notify_change();
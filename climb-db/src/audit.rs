@@ -0,0 +1,94 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Integer, Nullable, Text, Timestamptz};
+use diesel::PgConnection;
+
+use crate::queries::Place;
+
+/// A recorded insert, update or delete of a row of an area, formation or climb
+#[derive(Debug, Clone, PartialEq, QueryableByName)]
+pub struct Change {
+    #[diesel(sql_type = BigInt)]
+    pub id: i64,
+    /// The start of the transaction which made the change
+    #[diesel(sql_type = Timestamptz)]
+    pub changed_at: DateTime<Utc>,
+    #[diesel(sql_type = Nullable<Text>)]
+    pub actor: Option<String>,
+    /// Changes made together share a transaction and are reverted together
+    #[diesel(sql_type = BigInt)]
+    pub transaction_id: i64,
+    #[diesel(sql_type = Text)]
+    pub table_name: String,
+    /// One of `INSERT`, `UPDATE` or `DELETE`
+    #[diesel(sql_type = Text)]
+    pub operation: String,
+    /// The row before the change as a JSON object, unless inserted
+    #[diesel(sql_type = Nullable<Text>)]
+    pub before: Option<String>,
+    /// The row after the change as a JSON object, unless deleted
+    #[diesel(sql_type = Nullable<Text>)]
+    pub after: Option<String>,
+    /// The change this change reverted, if any
+    #[diesel(sql_type = Nullable<BigInt>)]
+    pub reverts: Option<i64>,
+}
+
+const CHANGE_COLUMNS: &str =
+    "id, changed_at, actor, transaction_id, table_name, operation, before::text, after::text, reverts";
+
+/// Records the actor of the changes made in the rest of the current transaction
+pub fn set_actor(conn: &mut PgConnection, actor: &str) -> QueryResult<()> {
+    diesel::sql_query("SELECT set_config('climb.actor', $1, true)")
+        .bind::<Text, _>(actor)
+        .execute(conn)?;
+
+    Ok(())
+}
+
+/// Gets a change
+pub fn change(conn: &mut PgConnection, id: i64) -> QueryResult<Change> {
+    diesel::sql_query(format!("SELECT {CHANGE_COLUMNS} FROM audit_log WHERE id = $1"))
+        .bind::<BigInt, _>(id)
+        .get_result(conn)
+}
+
+/// Gets the latest changes of a place, newest first, including changes of its names,
+/// descriptions, grades and links
+pub fn history(conn: &mut PgConnection, place: Place, limit: i64) -> QueryResult<Vec<Change>> {
+    diesel::sql_query(format!(
+        "SELECT {CHANGE_COLUMNS}
+        FROM audit_log
+        WHERE entity_kind = $1 AND entity_id = $2
+        ORDER BY id DESC
+        LIMIT $3"
+    ))
+    .bind::<Text, _>(place.kind())
+    .bind::<Integer, _>(place.id())
+    .bind::<BigInt, _>(limit)
+    .load(conn)
+}
+
+/// Reverts a change along with every other change of its transaction, returning the changes
+/// made by reverting.
+///
+/// Fails with `NotFound` if the change does not exist, or with a "Revert conflict" error if a
+/// changed row has changed again since.
+pub fn revert_change(conn: &mut PgConnection, id: i64) -> QueryResult<Vec<Change>> {
+    conn.transaction(|conn| {
+        change(conn, id)?;
+
+        diesel::sql_query("SELECT revert_change($1)")
+            .bind::<BigInt, _>(id)
+            .execute(conn)?;
+
+        diesel::sql_query(format!(
+            "SELECT {CHANGE_COLUMNS}
+            FROM audit_log
+            WHERE reverts = $1 AND transaction_id = txid_current()
+            ORDER BY id"
+        ))
+        .bind::<BigInt, _>(id)
+        .load(conn)
+    })
+}
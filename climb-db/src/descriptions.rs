@@ -0,0 +1,136 @@
+use diesel::prelude::*;
+use diesel::sql_types::{Array, Integer, Text};
+use diesel::PgConnection;
+
+use crate::queries::{find_place, Place};
+
+/// A description of an area, formation or climb
+#[derive(Debug, Clone, PartialEq, QueryableByName)]
+pub struct Description {
+    /// Name of the description type
+    #[diesel(sql_type = Text)]
+    pub key: String,
+    #[diesel(sql_type = Text)]
+    pub value: String,
+}
+
+/// The descriptions table of a place and its column referencing the place
+fn descriptions_table(place: Place) -> (&'static str, &'static str) {
+    match place {
+        Place::Area(_) => ("area_descriptions", "area_id"),
+        Place::Formation(_) => ("formation_descriptions", "formation_id"),
+        Place::Climb(_) => ("climb_descriptions", "climb_id"),
+    }
+}
+
+/// Gets the names of every description type, alphabetically
+pub fn description_types(conn: &mut PgConnection) -> QueryResult<Vec<String>> {
+    use crate::schema::description_types;
+
+    description_types::table
+        .select(description_types::name)
+        .order(description_types::name)
+        .load(conn)
+}
+
+/// Adds a description type, returning its id. Adding an existing type is an error.
+pub fn add_description_type(conn: &mut PgConnection, name: &str) -> QueryResult<i32> {
+    use crate::models::NewDescriptionType;
+    use crate::schema::description_types;
+
+    diesel::insert_into(description_types::table)
+        .values(NewDescriptionType { name: name.to_string() })
+        .returning(description_types::id)
+        .get_result(conn)
+}
+
+/// Sets a description of a place, replacing its description of the same type if any.
+///
+/// Fails with `NotFound` if the place or description type does not exist.
+pub fn set_description(conn: &mut PgConnection, place: Place, key: &str, value: &str) -> QueryResult<()> {
+    use crate::schema::description_types;
+
+    let (table, column) = descriptions_table(place);
+
+    conn.transaction(|conn| {
+        find_place(conn, place)?;
+
+        let type_id = description_types::table
+            .filter(description_types::name.eq(key))
+            .select(description_types::id)
+            .first::<i32>(conn)?;
+
+        diesel::sql_query(format!(
+            "INSERT INTO {table} ({column}, description_type_id, value)
+            VALUES ($1, $2, $3)
+            ON CONFLICT ({column}, description_type_id) DO UPDATE SET value = excluded.value"
+        ))
+        .bind::<Integer, _>(place.id())
+        .bind::<Integer, _>(type_id)
+        .bind::<Text, _>(value)
+        .execute(conn)?;
+
+        Ok(())
+    })
+}
+
+/// Removes a description of a place, returning whether it had one.
+///
+/// Fails with `NotFound` if the place does not exist.
+pub fn remove_description(conn: &mut PgConnection, place: Place, key: &str) -> QueryResult<bool> {
+    let (table, column) = descriptions_table(place);
+
+    conn.transaction(|conn| {
+        find_place(conn, place)?;
+
+        let removed = diesel::sql_query(format!(
+            "DELETE FROM {table} d
+            USING description_types t
+            WHERE d.{column} = $1 AND d.description_type_id = t.id AND t.name = $2"
+        ))
+        .bind::<Integer, _>(place.id())
+        .bind::<Text, _>(key)
+        .execute(conn)?;
+
+        Ok(removed > 0)
+    })
+}
+
+#[derive(QueryableByName)]
+struct DescriptionRow {
+    #[diesel(sql_type = Text)]
+    owner_kind: String,
+    #[diesel(sql_type = Integer)]
+    owner_id: i32,
+    #[diesel(embed)]
+    description: Description,
+}
+
+/// Gets the descriptions of places, as `(place, description)` pairs ordered by type name
+pub fn descriptions(conn: &mut PgConnection, places: &[Place]) -> QueryResult<Vec<(Place, Description)>> {
+    let kinds: Vec<&str> = places.iter().map(Place::kind).collect();
+    let ids: Vec<i32> = places.iter().map(Place::id).collect();
+
+    let rows = diesel::sql_query(
+        "WITH all_descriptions(owner_kind, owner_id, description_type_id, value) AS (
+            SELECT 'area', area_id, description_type_id, value FROM area_descriptions
+            UNION ALL
+            SELECT 'formation', formation_id, description_type_id, value FROM formation_descriptions
+            UNION ALL
+            SELECT 'climb', climb_id, description_type_id, value FROM climb_descriptions
+        )
+        SELECT d.owner_kind, d.owner_id, t.name AS key, d.value
+        FROM unnest($1, $2) AS p(kind, id)
+        JOIN all_descriptions d ON d.owner_kind = p.kind AND d.owner_id = p.id
+        JOIN description_types t ON t.id = d.description_type_id
+        ORDER BY d.owner_kind, d.owner_id, t.name",
+    )
+    .bind::<Array<Text>, _>(kinds)
+    .bind::<Array<Integer>, _>(ids)
+    .load::<DescriptionRow>(conn)?;
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|row| Some((Place::from_row(&row.owner_kind, row.owner_id)?, row.description)))
+        .collect())
+}
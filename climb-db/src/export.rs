@@ -0,0 +1,157 @@
+use diesel::prelude::*;
+use diesel::sql_types::{Integer, Text};
+use diesel::PgConnection;
+
+use crate::schema::areas;
+
+#[derive(QueryableByName)]
+struct GeoJsonRow {
+    #[diesel(sql_type = Text)]
+    geojson: String,
+}
+
+/// Exports an area, its sub areas and all of their formations as a GeoJSON `FeatureCollection`.
+///
+/// Areas with a boundary become `MultiPolygon` features and formations with a location become
+/// `Point` features. Each feature has the properties
+///
+/// - `kind`, either `area` or `formation`
+/// - `id`
+/// - `names`, the primary name first
+/// - `climbCount`, the number of climbs within the area or formation, including those of its sub
+///   areas and sub formations
+/// - `grades`, the lowest and highest grade of those climbs per grade type, e.g.
+///   `{ "vermin": { "min": "V3", "max": "V7" } }`
+///
+/// Features are ordered areas first, then by id.
+pub fn area_feature_collection(conn: &mut PgConnection, id: i32) -> QueryResult<String> {
+    // Fail on missing areas rather than export an empty collection
+    areas::table.find(id).select(areas::id).first::<i32>(conn)?;
+
+    // `UNION` discards duplicate rows, so the recursion terminates even if a cycle slipped past
+    // the `prevent_*_cycle` triggers
+    diesel::sql_query(
+        "WITH RECURSIVE subtree(id) AS (
+            SELECT $1
+            UNION
+            SELECT abt.area_id
+            FROM area_belongs_to abt
+            JOIN subtree s ON abt.super_area_id = s.id
+        ),
+        area_closure(ancestor, descendant) AS (
+            SELECT id, id FROM subtree
+            UNION
+            SELECT ac.ancestor, abt.area_id
+            FROM area_closure ac
+            JOIN area_belongs_to abt ON abt.super_area_id = ac.descendant
+        ),
+        formation_subtree(id) AS (
+            SELECT fbt.formation_id
+            FROM formation_belongs_to fbt
+            JOIN subtree s ON fbt.area_id = s.id
+            UNION
+            SELECT fbt.formation_id
+            FROM formation_belongs_to fbt
+            JOIN formation_subtree fs ON fbt.super_formation_id = fs.id
+        ),
+        formation_closure(ancestor, descendant) AS (
+            SELECT id, id FROM formation_subtree
+            UNION
+            SELECT fc.ancestor, fbt.formation_id
+            FROM formation_closure fc
+            JOIN formation_belongs_to fbt ON fbt.super_formation_id = fc.descendant
+        ),
+        formation_climbs(formation_id, climb_id) AS (
+            SELECT fc.ancestor, cbt.climb_id
+            FROM formation_closure fc
+            JOIN climb_belongs_to cbt ON cbt.formation_id = fc.descendant
+        ),
+        area_climbs(area_id, climb_id) AS (
+            SELECT ac.ancestor, cbt.climb_id
+            FROM area_closure ac
+            JOIN climb_belongs_to cbt ON cbt.area_id = ac.descendant
+            UNION
+            SELECT ac.ancestor, fc.climb_id
+            FROM area_closure ac
+            JOIN formation_belongs_to fbt ON fbt.area_id = ac.descendant
+            JOIN formation_climbs fc ON fc.formation_id = fbt.formation_id
+        ),
+        area_grades(area_id, grades) AS (
+            SELECT r.area_id, json_object_agg(r.name, json_build_object('min', r.min, 'max', r.max))
+            FROM (
+                SELECT ac.area_id,
+                    gt.name,
+                    (array_agg(g.value ORDER BY g.sort_key))[1] AS min,
+                    (array_agg(g.value ORDER BY g.sort_key DESC))[1] AS max
+                FROM area_climbs ac
+                JOIN climb_grades cg ON cg.climb_id = ac.climb_id
+                JOIN grades g ON g.id = cg.grade_id
+                JOIN grade_types gt ON gt.id = g.grade_type_id
+                GROUP BY ac.area_id, gt.name
+            ) r
+            GROUP BY r.area_id
+        ),
+        formation_grades(formation_id, grades) AS (
+            SELECT r.formation_id, json_object_agg(r.name, json_build_object('min', r.min, 'max', r.max))
+            FROM (
+                SELECT fc.formation_id,
+                    gt.name,
+                    (array_agg(g.value ORDER BY g.sort_key))[1] AS min,
+                    (array_agg(g.value ORDER BY g.sort_key DESC))[1] AS max
+                FROM formation_climbs fc
+                JOIN climb_grades cg ON cg.climb_id = fc.climb_id
+                JOIN grades g ON g.id = cg.grade_id
+                JOIN grade_types gt ON gt.id = g.grade_type_id
+                GROUP BY fc.formation_id, gt.name
+            ) r
+            GROUP BY r.formation_id
+        ),
+        features(feature, kind_order, id) AS (
+            SELECT json_build_object(
+                'type', 'Feature',
+                'geometry', ST_AsGeoJSON(a.boundary)::json,
+                'properties', json_build_object(
+                    'kind', 'area',
+                    'id', a.id,
+                    'names', (
+                        SELECT COALESCE(json_agg(value ORDER BY kind <> 'primary', ordering, id), '[]'::json)
+                        FROM area_names
+                        WHERE area_id = a.id
+                    ),
+                    'climbCount', (SELECT count(DISTINCT climb_id) FROM area_climbs WHERE area_id = a.id),
+                    'grades', COALESCE((SELECT grades FROM area_grades WHERE area_id = a.id), '{}'::json)
+                )
+            ), 0, a.id
+            FROM subtree s
+            JOIN areas a ON a.id = s.id
+            WHERE a.boundary IS NOT NULL
+            UNION ALL
+            SELECT json_build_object(
+                'type', 'Feature',
+                'geometry', ST_AsGeoJSON(f.location)::json,
+                'properties', json_build_object(
+                    'kind', 'formation',
+                    'id', f.id,
+                    'names', (
+                        SELECT COALESCE(json_agg(value ORDER BY kind <> 'primary', ordering, id), '[]'::json)
+                        FROM formation_names
+                        WHERE formation_id = f.id
+                    ),
+                    'climbCount', (SELECT count(DISTINCT climb_id) FROM formation_climbs WHERE formation_id = f.id),
+                    'grades', COALESCE((SELECT grades FROM formation_grades WHERE formation_id = f.id), '{}'::json)
+                )
+            ), 1, f.id
+            FROM formation_subtree fs
+            JOIN formations f ON f.id = fs.id
+            WHERE f.location IS NOT NULL
+        )
+        SELECT json_build_object(
+            'type', 'FeatureCollection',
+            'features', COALESCE(json_agg(feature ORDER BY kind_order, id), '[]'::json)
+        )::text AS geojson
+        FROM features",
+    )
+    .bind::<Integer, _>(id)
+    .get_result::<GeoJsonRow>(conn)
+    .map(|row| row.geojson)
+}
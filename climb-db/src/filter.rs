@@ -0,0 +1,337 @@
+use diesel::dsl::{exists, not, sql};
+use diesel::pg::Pg;
+use diesel::prelude::*;
+use diesel::sql_types::{Bool, Integer, Nullable, Text};
+
+use crate::grade::{GradeSystem, ParsedGrade};
+use crate::repo::Page;
+use crate::schema::climbs;
+
+/// Grades of one grade type within an inclusive range, unbounded where `None`
+#[derive(Debug, Clone)]
+pub struct GradeRange {
+    pub system: GradeSystem,
+    pub min: Option<ParsedGrade>,
+    pub max: Option<ParsedGrade>,
+}
+
+/// Criteria climbs must all meet, ignored where `None`
+#[derive(Debug, Clone, Default)]
+pub struct ClimbFilter {
+    /// Climbs with a grade in any of the ranges, ignored when empty
+    pub grades: Vec<GradeRange>,
+    /// Climbs directly in an area
+    pub area_id: Option<i32>,
+    /// Climbs directly on a formation
+    pub formation_id: Option<i32>,
+    /// Climbs anywhere within an area, including its sub areas and their formations
+    pub within_area_id: Option<i32>,
+    /// Climbs anywhere on a formation, including its sub formations
+    pub within_formation_id: Option<i32>,
+    /// Climbs on a formation, or sub formation of one, with a location
+    pub has_location: Option<bool>,
+    pub has_ascents: Option<bool>,
+    pub has_description: Option<bool>,
+    /// Climbs with any name containing the text, ignoring case
+    pub name_contains: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ClimbOrderField {
+    #[default]
+    Id,
+    /// The primary name of the climb, or its first name if it has none, ignoring case
+    Name,
+    /// The normalised difficulty of the climb
+    Difficulty,
+}
+
+/// Order of climbs, ties being broken by ascending id. Climbs without a name or difficulty come
+/// last in either direction.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ClimbOrder {
+    pub field: ClimbOrderField,
+    pub descending: bool,
+}
+
+type ClimbPredicate = Box<dyn BoxableExpression<climbs::table, Pg, SqlType = Bool>>;
+
+/// Escapes `LIKE` wildcards of a text so it is matched literally
+fn escape_like(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+}
+
+fn grade_range_predicate(range: &GradeRange) -> ClimbPredicate {
+    use crate::schema::{climb_grades, grade_types, grades};
+
+    let mut query = climb_grades::table
+        .inner_join(grades::table.inner_join(grade_types::table))
+        .filter(grade_types::name.eq(range.system.name()))
+        .select(climb_grades::climb_id)
+        .into_boxed();
+
+    if let Some(min) = &range.min {
+        query = query.filter(grades::sort_key.ge(min.sort_key));
+    }
+
+    if let Some(max) = &range.max {
+        query = query.filter(grades::sort_key.le(max.sort_key));
+    }
+
+    Box::new(climbs::id.eq_any(query))
+}
+
+/// Climbs within an area, its sub areas, their formations and the sub formations of those
+fn within_area_predicate(id: i32) -> ClimbPredicate {
+    Box::new(
+        sql::<Bool>(
+            "climbs.id IN (
+                WITH RECURSIVE area_subtree(id) AS (
+                    SELECT ",
+        )
+        .bind::<Integer, _>(id)
+        .sql(
+            "
+                    UNION
+                    SELECT abt.area_id
+                    FROM area_belongs_to abt
+                    JOIN area_subtree s ON abt.super_area_id = s.id
+                ),
+                formation_subtree(id) AS (
+                    SELECT fbt.formation_id
+                    FROM formation_belongs_to fbt
+                    JOIN area_subtree s ON fbt.area_id = s.id
+                    UNION
+                    SELECT fbt.formation_id
+                    FROM formation_belongs_to fbt
+                    JOIN formation_subtree fs ON fbt.super_formation_id = fs.id
+                )
+                SELECT cbt.climb_id
+                FROM climb_belongs_to cbt
+                WHERE cbt.area_id IN (SELECT id FROM area_subtree)
+                    OR cbt.formation_id IN (SELECT id FROM formation_subtree)
+            )",
+        ),
+    )
+}
+
+/// Climbs on a formation or its sub formations
+fn within_formation_predicate(id: i32) -> ClimbPredicate {
+    Box::new(
+        sql::<Bool>(
+            "climbs.id IN (
+                WITH RECURSIVE formation_subtree(id) AS (
+                    SELECT ",
+        )
+        .bind::<Integer, _>(id)
+        .sql(
+            "
+                    UNION
+                    SELECT fbt.formation_id
+                    FROM formation_belongs_to fbt
+                    JOIN formation_subtree fs ON fbt.super_formation_id = fs.id
+                )
+                SELECT cbt.climb_id
+                FROM climb_belongs_to cbt
+                WHERE cbt.formation_id IN (SELECT id FROM formation_subtree)
+            )",
+        ),
+    )
+}
+
+/// Climbs on a formation, or sub formation of one, with a location
+fn located_predicate() -> ClimbPredicate {
+    Box::new(sql::<Bool>(
+        "climbs.id IN (
+            WITH RECURSIVE climb_formations(climb_id, formation_id) AS (
+                SELECT climb_id, formation_id
+                FROM climb_belongs_to
+                WHERE formation_id IS NOT NULL
+                UNION
+                SELECT cf.climb_id, fbt.super_formation_id
+                FROM climb_formations cf
+                JOIN formation_belongs_to fbt ON fbt.formation_id = cf.formation_id
+                WHERE fbt.super_formation_id IS NOT NULL
+            )
+            SELECT cf.climb_id
+            FROM climb_formations cf
+            JOIN formations f ON f.id = cf.formation_id
+            WHERE f.location IS NOT NULL
+        )",
+    ))
+}
+
+/// SQL of the value climbs of a table or alias are ordered by, or `None` when ordered by id alone
+fn order_key(field: ClimbOrderField, climbs: &str) -> Option<String> {
+    match field {
+        ClimbOrderField::Id => None,
+        ClimbOrderField::Name => Some(format!(
+            "lower((
+                SELECT value
+                FROM climb_names
+                WHERE climb_id = {climbs}.id
+                ORDER BY kind <> 'primary', ordering, id
+                LIMIT 1
+            ))"
+        )),
+        ClimbOrderField::Difficulty => Some(format!("{climbs}.difficulty")),
+    }
+}
+
+/// SQL of whether the climb of `later` comes after the climb of `earlier` in an order
+fn follows(order: ClimbOrder, later: &str, earlier: &str) -> String {
+    let op = if order.descending { "<" } else { ">" };
+
+    match (order_key(order.field, later), order_key(order.field, earlier)) {
+        (Some(later_key), Some(earlier_key)) => format!(
+            "(({later_key}) IS NULL) > (({earlier_key}) IS NULL)
+            OR ((({later_key}) IS NULL) = (({earlier_key}) IS NULL) AND (
+                ({later_key}) {op} ({earlier_key})
+                OR (({later_key}) IS NOT DISTINCT FROM ({earlier_key}) AND {later}.id > {earlier}.id)
+            ))"
+        ),
+        _ => format!("{later}.id {op} {earlier}.id"),
+    }
+}
+
+/// Climbs coming after, or before, the climb of a cursor in an order
+fn keyset_predicate(order: ClimbOrder, cursor: i32, after: bool) -> ClimbPredicate {
+    let condition = if after { follows(order, "climbs", "bound") } else { follows(order, "bound", "climbs") };
+
+    Box::new(
+        sql::<Bool>("EXISTS (SELECT 1 FROM climbs bound WHERE bound.id = ")
+            .bind::<Integer, _>(cursor)
+            .sql(&format!(" AND ({condition}))")),
+    )
+}
+
+/// Compiles a filter into an unordered query of climb ids
+fn filtered_query<'a>(filter: &ClimbFilter) -> climbs::BoxedQuery<'a, Pg, Integer> {
+    use crate::schema::{ascents, climb_belongs_to, climb_descriptions};
+
+    let mut query = climbs::table.select(climbs::id).into_boxed();
+
+    if let Some(first) = filter.grades.first() {
+        let predicate = filter.grades[1..]
+            .iter()
+            .fold(grade_range_predicate(first), |predicate, range| {
+                Box::new(predicate.or(grade_range_predicate(range)))
+            });
+
+        query = query.filter(predicate);
+    }
+
+    if let Some(id) = filter.area_id {
+        query = query.filter(
+            climbs::id.eq_any(
+                climb_belongs_to::table
+                    .filter(climb_belongs_to::area_id.eq(id))
+                    .select(climb_belongs_to::climb_id),
+            ),
+        );
+    }
+
+    if let Some(id) = filter.formation_id {
+        query = query.filter(
+            climbs::id.eq_any(
+                climb_belongs_to::table
+                    .filter(climb_belongs_to::formation_id.eq(id))
+                    .select(climb_belongs_to::climb_id),
+            ),
+        );
+    }
+
+    if let Some(id) = filter.within_area_id {
+        query = query.filter(within_area_predicate(id));
+    }
+
+    if let Some(id) = filter.within_formation_id {
+        query = query.filter(within_formation_predicate(id));
+    }
+
+    match filter.has_location {
+        Some(true) => query = query.filter(located_predicate()),
+        Some(false) => query = query.filter(not(located_predicate())),
+        None => {}
+    }
+
+    let ascended = exists(ascents::table.filter(ascents::climb_id.eq(climbs::id)));
+
+    match filter.has_ascents {
+        Some(true) => query = query.filter(ascended),
+        Some(false) => query = query.filter(not(ascended)),
+        None => {}
+    }
+
+    let described = exists(climb_descriptions::table.filter(climb_descriptions::climb_id.eq(climbs::id)));
+
+    match filter.has_description {
+        Some(true) => query = query.filter(described),
+        Some(false) => query = query.filter(not(described)),
+        None => {}
+    }
+
+    if let Some(text) = &filter.name_contains {
+        query = query.filter(
+            sql::<Bool>("EXISTS (SELECT 1 FROM climb_names WHERE climb_id = climbs.id AND value ILIKE ")
+                .bind::<Text, _>(format!("%{}%", escape_like(text)))
+                .sql(")"),
+        );
+    }
+
+    query
+}
+
+/// Orders a query of climb ids, in reverse when `reversed`
+fn ordered_query<'a>(
+    query: climbs::BoxedQuery<'a, Pg, Integer>,
+    order: ClimbOrder,
+    reversed: bool,
+) -> climbs::BoxedQuery<'a, Pg, Integer> {
+    let descending = order.descending != reversed;
+
+    let query = match (order_key(order.field, "climbs"), descending) {
+        (None, false) => return query.order(climbs::id.asc()),
+        (None, true) => return query.order(climbs::id.desc()),
+        // Climbs without a key come last, so first in reverse
+        (Some(key), false) if reversed => query.order(sql::<Nullable<Text>>(&key).asc().nulls_first()),
+        (Some(key), true) if reversed => query.order(sql::<Nullable<Text>>(&key).desc().nulls_first()),
+        (Some(key), false) => query.order(sql::<Nullable<Text>>(&key).asc().nulls_last()),
+        (Some(key), true) => query.order(sql::<Nullable<Text>>(&key).desc().nulls_last()),
+    };
+
+    // Ties are broken by ascending id
+    if reversed {
+        query.then_order_by(climbs::id.desc())
+    } else {
+        query.then_order_by(climbs::id.asc())
+    }
+}
+
+/// Compiles a filter and order into a query of climb ids
+pub fn climbs_query<'a>(filter: &ClimbFilter, order: ClimbOrder) -> climbs::BoxedQuery<'a, Pg, Integer> {
+    ordered_query(filtered_query(filter), order, false)
+}
+
+/// Compiles a filter and order into a query of a page of climb ids, bounded by the climbs of its
+/// cursors rather than their ids, as described by `Page`
+pub(crate) fn climbs_page_query<'a>(
+    filter: &ClimbFilter,
+    order: ClimbOrder,
+    page: Page,
+) -> climbs::BoxedQuery<'a, Pg, Integer> {
+    let mut query = filtered_query(filter);
+
+    if let Some(after) = page.after {
+        query = query.filter(keyset_predicate(order, after, true));
+    }
+
+    if let Some(before) = page.before {
+        query = query.filter(keyset_predicate(order, before, false));
+    }
+
+    match (page.first, page.last) {
+        (_, Some(last)) => ordered_query(query, order, true).limit(last as i64 + 1),
+        (first, None) => ordered_query(query, order, false).limit(first.unwrap_or(Page::DEFAULT_SIZE) as i64 + 1),
+    }
+}
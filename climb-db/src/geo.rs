@@ -0,0 +1,107 @@
+use std::fmt;
+
+use diesel::deserialize::{self, FromSql, FromSqlRow};
+use diesel::expression::AsExpression;
+use diesel::pg::{Pg, PgValue};
+use diesel::serialize::{self, Output, ToSql};
+use postgis_diesel::sql_types::Geometry;
+use postgis_diesel::types::Point;
+
+/// Spatial reference system of every stored geometry, WGS 84
+pub const SRID: u32 = 4326;
+
+/// A location on the earth in WGS 84 degrees.
+///
+/// This is the only way locations are written to or read from PostGIS, so they are always stored
+/// in the EPSG:4326 axis order used by PostGIS, `x` being the longitude and `y` the latitude.
+#[derive(Debug, Clone, Copy, PartialEq, AsExpression, FromSqlRow)]
+#[diesel(sql_type = Geometry)]
+pub struct GeoPoint {
+    longitude: f64,
+    latitude: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GeoPointError {
+    /// The longitude is not within [-180, 180]
+    LongitudeOutOfRange(f64),
+    /// The latitude is not within [-90, 90]
+    LatitudeOutOfRange(f64),
+    /// The stored geometry is not in the WGS 84 spatial reference system
+    UnexpectedSrid(u32),
+}
+
+impl fmt::Display for GeoPointError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GeoPointError::LongitudeOutOfRange(longitude) => {
+                write!(f, "Longitude {longitude} is not within [-180, 180]")
+            }
+            GeoPointError::LatitudeOutOfRange(latitude) => {
+                write!(f, "Latitude {latitude} is not within [-90, 90]")
+            }
+            GeoPointError::UnexpectedSrid(srid) => {
+                write!(f, "Expected SRID {SRID}, found {srid}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for GeoPointError {}
+
+impl GeoPoint {
+    pub fn new(longitude: f64, latitude: f64) -> Result<Self, GeoPointError> {
+        if !(-180.0..=180.0).contains(&longitude) {
+            return Err(GeoPointError::LongitudeOutOfRange(longitude));
+        }
+
+        if !(-90.0..=90.0).contains(&latitude) {
+            return Err(GeoPointError::LatitudeOutOfRange(latitude));
+        }
+
+        Ok(GeoPoint { longitude, latitude })
+    }
+
+    pub fn longitude(&self) -> f64 {
+        self.longitude
+    }
+
+    pub fn latitude(&self) -> f64 {
+        self.latitude
+    }
+}
+
+impl From<GeoPoint> for Point {
+    fn from(point: GeoPoint) -> Self {
+        Point {
+            x: point.longitude,
+            y: point.latitude,
+            srid: Some(SRID),
+        }
+    }
+}
+
+impl TryFrom<Point> for GeoPoint {
+    type Error = GeoPointError;
+
+    fn try_from(point: Point) -> Result<Self, Self::Error> {
+        match point.srid {
+            Some(SRID) | None => GeoPoint::new(point.x, point.y),
+            Some(srid) => Err(GeoPointError::UnexpectedSrid(srid)),
+        }
+    }
+}
+
+impl ToSql<Geometry, Pg> for GeoPoint {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        let point = Point::from(*self);
+        <Point as ToSql<Geometry, Pg>>::to_sql(&point, &mut out.reborrow())
+    }
+}
+
+impl FromSql<Geometry, Pg> for GeoPoint {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        let point = <Point as FromSql<Geometry, Pg>>::from_sql(bytes)?;
+        Ok(GeoPoint::try_from(point)?)
+    }
+}
@@ -0,0 +1,102 @@
+use std::fmt;
+use std::str::FromStr;
+
+use climbing_grades::{ewbank, font, french, uiaa, verm, yds};
+
+/// A system of grading the difficulty of climbs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GradeSystem {
+    /// Hueco, e.g. `V5`
+    Vermin,
+    /// Yosemite Decimal System, e.g. `5.10a`
+    Yds,
+    /// French sport, e.g. `6b+`
+    French,
+    /// UIAA, e.g. `VI+`
+    Uiaa,
+    /// Australian, e.g. `21`
+    Ewbank,
+    /// Fontainebleau, e.g. `6B+`
+    Font,
+}
+
+impl GradeSystem {
+    pub const ALL: [GradeSystem; 6] = [
+        GradeSystem::Vermin,
+        GradeSystem::Yds,
+        GradeSystem::French,
+        GradeSystem::Uiaa,
+        GradeSystem::Ewbank,
+        GradeSystem::Font,
+    ];
+
+    /// Name of the system in the `grade_types` table
+    pub fn name(&self) -> &'static str {
+        match self {
+            GradeSystem::Vermin => "vermin",
+            GradeSystem::Yds => "yds",
+            GradeSystem::French => "french",
+            GradeSystem::Uiaa => "uiaa",
+            GradeSystem::Ewbank => "ewbank",
+            GradeSystem::Font => "font",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        GradeSystem::ALL.into_iter().find(|system| system.name() == name)
+    }
+
+    /// Parses a grade of this system
+    pub fn parse(&self, value: &str) -> Result<ParsedGrade, GradeError> {
+        let value = value.trim();
+
+        let parsed = match self {
+            GradeSystem::Vermin => parse_with(value, |grade: &verm::Grade| i32::from(grade.value())),
+            GradeSystem::Yds => parse_with(value, |grade: &yds::Grade| i32::from(grade.value())),
+            GradeSystem::French => parse_with(value, |grade: &french::Grade| i32::from(grade.value())),
+            GradeSystem::Uiaa => parse_with(value, |grade: &uiaa::Grade| i32::from(grade.value())),
+            GradeSystem::Ewbank => parse_with(value, |grade: &ewbank::Grade| i32::from(grade.value())),
+            GradeSystem::Font => parse_with(value, |grade: &font::Grade| i32::from(grade.value())),
+        };
+
+        parsed
+            .map(|(value, sort_key)| ParsedGrade { system: *self, value, sort_key })
+            .ok_or_else(|| GradeError { system: *self, value: value.to_string() })
+    }
+}
+
+/// A grade in its canonical form.
+///
+/// Grades of the same system are ordered by `sort_key`, which is meaningless across systems.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedGrade {
+    pub system: GradeSystem,
+    pub value: String,
+    pub sort_key: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GradeError {
+    pub system: GradeSystem,
+    pub value: String,
+}
+
+impl fmt::Display for GradeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Failed to parse {} grade {:?}", self.system.name(), self.value)
+    }
+}
+
+impl std::error::Error for GradeError {}
+
+/// Version of the sort keys grades parse to, stored along with the grades keyed by it. Increase
+/// it whenever the keys change, e.g. by updating `climbing_grades`, so `migrate` re-keys stored
+/// grades.
+pub const SORT_KEY_VERSION: i32 = 1;
+
+/// Parses a grade with the parser of its system, returning its canonical form and its sort key,
+/// the value the parser orders it by
+fn parse_with<G: FromStr + fmt::Display>(value: &str, sort_key: impl Fn(&G) -> i32) -> Option<(String, i32)> {
+    let grade = G::from_str(value).ok()?;
+    Some((grade.to_string(), sort_key(&grade)))
+}
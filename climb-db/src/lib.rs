@@ -1,5 +1,35 @@
-use diesel_migrations::{EmbeddedMigrations,embed_migrations};
+use std::error::Error;
 
+use diesel::PgConnection;
+use diesel_migrations::{EmbeddedMigrations,embed_migrations, MigrationHarness};
+
+pub mod audit;
+pub mod descriptions;
+pub mod export;
+pub mod filter;
+pub mod geo;
+pub mod grade;
 pub mod models;
+pub mod names;
+pub mod notify;
+pub mod queries;
+pub mod repo;
 pub mod schema;
+pub mod search;
+pub mod users;
 pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations");
+
+/// Runs the migrations not yet run, returning their versions.
+///
+/// If any ran, or the grade sort keys changed since the grades were keyed, this then applies the
+/// grade conversions left pending and re-keys the stored grades.
+pub fn migrate(conn: &mut PgConnection) -> Result<Vec<String>, Box<dyn Error + Send + Sync>> {
+    let versions: Vec<String> = conn.run_pending_migrations(MIGRATIONS)?.iter().map(ToString::to_string).collect();
+
+    if !versions.is_empty() || queries::grade_sort_key_version(conn)? != grade::SORT_KEY_VERSION {
+        queries::apply_pending_grade_conversions(conn)?;
+        queries::rekey_grades(conn)?;
+    }
+
+    Ok(versions)
+}
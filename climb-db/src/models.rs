@@ -2,23 +2,17 @@ use chrono::NaiveDate;
 use std::ops::Bound;
 
 use diesel::prelude::*;
-use postgis_diesel::types::*;
+use crate::geo::GeoPoint;
 
-#[derive(Queryable, Selectable)]
+#[derive(Queryable, Selectable, Clone)]
 #[diesel(table_name = crate::schema::areas)]
 #[diesel(check_for_backend(diesel::pg::Pg))]
 pub struct Area {
     pub id: i32,
-    pub names: Vec<Option<String>>,
-}
-
-#[derive(Insertable, Default)]
-#[diesel(table_name = crate::schema::areas)]
-pub struct NewArea {
-    pub names: Vec<Option<String>>,
+    pub created_by: Option<i32>,
 }
 
-#[derive(Queryable)]
+#[derive(Queryable, Selectable)]
 #[diesel(table_name = crate::schema::ascent_grades)]
 #[diesel(primary_key(ascent_id, grade_id))]
 #[diesel(check_for_backend(diesel::pg::Pg))]
@@ -35,7 +29,7 @@ pub struct NewAscentGrade {
     pub grade_id: i32,
 }
 
-#[derive(Queryable)]
+#[derive(Queryable, Selectable)]
 #[diesel(table_name = crate::schema::ascent_parties)]
 #[diesel(primary_key(ascent_id, climber_id))]
 #[diesel(check_for_backend(diesel::pg::Pg))]
@@ -52,13 +46,14 @@ pub struct NewAscentParty {
     pub climber_id: i32,
 }
 
-#[derive(Queryable)]
+#[derive(Queryable, Selectable, Clone)]
 #[diesel(table_name = crate::schema::ascents)]
 #[diesel(check_for_backend(diesel::pg::Pg))]
 pub struct Ascent {
     pub id: i32,
     pub climb_id: i32,
     pub ascent_date: Option<(Bound<NaiveDate>, Bound<NaiveDate>)>,
+    pub created_by: Option<i32>,
 }
 
 #[derive(Insertable)]
@@ -69,13 +64,14 @@ pub struct NewAscent {
     pub ascent_date: Option<(Bound<NaiveDate>, Bound<NaiveDate>)>,
 }
 
-#[derive(Queryable, Selectable)]
+#[derive(Queryable, Selectable, Clone)]
 #[diesel(table_name = crate::schema::climbers)]
 #[diesel(check_for_backend(diesel::pg::Pg))]
 pub struct Climber {
     pub id: i32,
     pub first_name: String,
     pub last_name: String,
+    pub created_by: Option<i32>,
 }
 
 #[derive(Insertable)]
@@ -86,39 +82,32 @@ pub struct NewClimber {
     pub last_name: String,
 }
 
-#[derive(Queryable, Selectable)]
+#[derive(Queryable, Selectable, Clone)]
 #[diesel(table_name = crate::schema::climbs)]
 #[diesel(check_for_backend(diesel::pg::Pg))]
 pub struct Climb {
     pub id: i32,
-    pub names: Vec<Option<String>>,
+    pub difficulty: Option<f64>,
+    pub created_by: Option<i32>,
 }
 
-#[derive(Insertable, Default)]
-#[diesel(table_name = crate::schema::climbs)]
-#[diesel(check_for_backend(diesel::pg::Pg))]
-pub struct NewClimb {
-    pub names: Vec<Option<String>>,
-}
-
-#[derive(Queryable, Selectable)]
+#[derive(Queryable, Selectable, Clone)]
 #[diesel(table_name = crate::schema::formations)]
 #[diesel(check_for_backend(diesel::pg::Pg))]
 pub struct Formation {
     pub id: i32,
-    pub names: Vec<Option<String>>,
-    pub location: Option<Point>,
+    pub location: Option<GeoPoint>,
+    pub created_by: Option<i32>,
 }
 
 #[derive(Insertable, Default)]
 #[diesel(table_name = crate::schema::formations)]
 #[diesel(check_for_backend(diesel::pg::Pg))]
 pub struct NewFormation {
-    pub names: Vec<Option<String>>,
-    pub location: Option<Point>,
+    pub location: Option<GeoPoint>,
 }
 
-#[derive(Queryable, Selectable)]
+#[derive(Queryable, Selectable, Clone)]
 #[diesel(table_name = crate::schema::climb_belongs_to)]
 #[diesel(check_for_backend(diesel::pg::Pg))]
 pub struct ClimbBelongsTo {
@@ -136,7 +125,7 @@ pub struct NewClimbBelongsTo {
     pub formation_id: Option<i32>,
 }
 
-#[derive(Queryable, Selectable)]
+#[derive(Queryable, Selectable, Clone)]
 #[diesel(table_name = crate::schema::formation_belongs_to)]
 #[diesel(check_for_backend(diesel::pg::Pg))]
 pub struct FormationBelongsTo {
@@ -192,6 +181,7 @@ pub struct Grade {
     pub id: i32,
     pub grade_type_id: i32,
     pub value: String,
+    pub sort_key: i32,
 }
 
 #[derive(Insertable)]
@@ -200,14 +190,40 @@ pub struct Grade {
 pub struct NewGrade {
     pub grade_type_id: i32,
     pub value: String,
+    pub sort_key: i32,
+}
+
+#[derive(Queryable, Selectable)]
+#[diesel(table_name = crate::schema::grade_conversions)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct GradeConversion {
+    pub grade_id: i32,
+    pub difficulty: f64,
 }
 
 #[derive(Insertable)]
-#[diesel(table_name = crate::schema::climb_vermin_grades)]
+#[diesel(table_name = crate::schema::grade_conversions)]
 #[diesel(check_for_backend(diesel::pg::Pg))]
-pub struct NewClimbVerminGrade {
+pub struct NewGradeConversion {
+    pub grade_id: i32,
+    pub difficulty: f64,
+}
+
+#[derive(Queryable, Selectable)]
+#[diesel(table_name = crate::schema::climb_grades)]
+#[diesel(primary_key(climb_id, grade_id))]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct ClimbGrade {
+    pub climb_id: i32,
+    pub grade_id: i32,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = crate::schema::climb_grades)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct NewClimbGrade {
     pub climb_id: i32,
-    pub value: i32,
+    pub grade_id: i32,
 }
 
 #[derive(Insertable)]
@@ -227,16 +243,16 @@ pub struct ClimbVariation {
 }
 
 #[derive(Insertable)]
-#[diesel(table_name = crate::schema::climb_description_types)]
+#[diesel(table_name = crate::schema::description_types)]
 #[diesel(check_for_backend(diesel::pg::Pg))]
-pub struct NewClimbDescriptionType {
+pub struct NewDescriptionType {
     pub name: String,
 }
 
 #[derive(Queryable, Selectable)]
-#[diesel(table_name = crate::schema::climb_description_types)]
+#[diesel(table_name = crate::schema::description_types)]
 #[diesel(check_for_backend(diesel::pg::Pg))]
-pub struct ClimbDescriptionType {
+pub struct DescriptionType {
     pub id: i32,
     pub name: String,
 }
@@ -246,7 +262,7 @@ pub struct ClimbDescriptionType {
 #[diesel(check_for_backend(diesel::pg::Pg))]
 pub struct NewClimbDescription {
     pub climb_id: i32,
-    pub climb_description_type_id: i32,
+    pub description_type_id: i32,
     pub value: String,
 }
 
@@ -255,6 +271,60 @@ pub struct NewClimbDescription {
 #[diesel(check_for_backend(diesel::pg::Pg))]
 pub struct ClimbDescription {
     pub climb_id: i32,
-    pub climb_description_type_id: i32,
+    pub description_type_id: i32,
     pub value: String,
 }
+
+#[derive(Insertable)]
+#[diesel(table_name = crate::schema::area_descriptions)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct NewAreaDescription {
+    pub area_id: i32,
+    pub description_type_id: i32,
+    pub value: String,
+}
+
+#[derive(Queryable, Selectable)]
+#[diesel(table_name = crate::schema::area_descriptions)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct AreaDescription {
+    pub area_id: i32,
+    pub description_type_id: i32,
+    pub value: String,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = crate::schema::formation_descriptions)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct NewFormationDescription {
+    pub formation_id: i32,
+    pub description_type_id: i32,
+    pub value: String,
+}
+
+#[derive(Queryable, Selectable)]
+#[diesel(table_name = crate::schema::formation_descriptions)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct FormationDescription {
+    pub formation_id: i32,
+    pub description_type_id: i32,
+    pub value: String,
+}
+
+#[derive(Queryable, QueryableByName, Selectable, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[diesel(table_name = crate::schema::users)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct User {
+    pub id: i32,
+    pub name: String,
+    pub role: String,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = crate::schema::users)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct NewUser {
+    pub name: String,
+    pub role: String,
+}
@@ -0,0 +1,177 @@
+use diesel::prelude::*;
+use diesel::sql_types::{Array, Integer, Nullable, Text};
+use diesel::PgConnection;
+
+use crate::queries::{find_place, Place};
+
+/// Kinds of names
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NameKind {
+    /// The canonical name, of which a place has at most one
+    Primary,
+    /// Another name in use
+    Alias,
+    /// A name no longer in use
+    Historic,
+    /// A name in another language, which must be given
+    Translation,
+}
+
+impl NameKind {
+    pub const ALL: [NameKind; 4] = [NameKind::Primary, NameKind::Alias, NameKind::Historic, NameKind::Translation];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            NameKind::Primary => "primary",
+            NameKind::Alias => "alias",
+            NameKind::Historic => "historic",
+            NameKind::Translation => "translation",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        NameKind::ALL.into_iter().find(|kind| kind.name() == name)
+    }
+}
+
+/// A name of an area, formation or climb
+#[derive(Debug, Clone, PartialEq, QueryableByName)]
+pub struct Name {
+    #[diesel(sql_type = Integer)]
+    pub id: i32,
+    #[diesel(sql_type = Text)]
+    pub value: String,
+    /// BCP 47 language tag, e.g. `en` or `ja-Latn`
+    #[diesel(sql_type = Nullable<Text>)]
+    pub language: Option<String>,
+    /// One of the `NameKind` names
+    #[diesel(sql_type = Text)]
+    pub kind: String,
+    #[diesel(sql_type = Integer)]
+    pub ordering: i32,
+}
+
+/// The names table of a place and its column referencing the place
+fn names_table(place: Place) -> (&'static str, &'static str) {
+    match place {
+        Place::Area(_) => ("area_names", "area_id"),
+        Place::Formation(_) => ("formation_names", "formation_id"),
+        Place::Climb(_) => ("climb_names", "climb_id"),
+    }
+}
+
+/// Adds a name to a place after its other names, returning the name.
+///
+/// Without a kind the name becomes the primary name if the place has none, otherwise an alias.
+/// A new primary name demotes the current primary name, if any, to an alias. Adding a name the
+/// place already has in the same language is an error.
+pub fn add_name(
+    conn: &mut PgConnection,
+    place: Place,
+    value: &str,
+    kind: Option<NameKind>,
+    language: Option<&str>,
+) -> QueryResult<Name> {
+    let (table, column) = names_table(place);
+
+    conn.transaction(|conn| {
+        find_place(conn, place)?;
+
+        if kind == Some(NameKind::Primary) {
+            diesel::sql_query(format!(
+                "UPDATE {table} SET kind = 'alias' WHERE {column} = $1 AND kind = 'primary'"
+            ))
+            .bind::<Integer, _>(place.id())
+            .execute(conn)?;
+        }
+
+        diesel::sql_query(format!(
+            "INSERT INTO {table} ({column}, value, language, kind, ordering)
+            SELECT $1,
+                $2,
+                $3,
+                COALESCE($4, CASE WHEN bool_or(kind = 'primary') THEN 'alias' ELSE 'primary' END),
+                COALESCE(max(ordering) + 1, 0)
+            FROM {table}
+            WHERE {column} = $1
+            RETURNING id, value, language, kind, ordering"
+        ))
+        .bind::<Integer, _>(place.id())
+        .bind::<Text, _>(value)
+        .bind::<Nullable<Text>, _>(language)
+        .bind::<Nullable<Text>, _>(kind.map(|kind| kind.name()))
+        .get_result(conn)
+    })
+}
+
+/// Removes every name of a place with a value, in any language, returning the number removed.
+///
+/// Removing the primary name promotes the first remaining alias, if any, in its place.
+pub fn remove_name(conn: &mut PgConnection, place: Place, value: &str) -> QueryResult<usize> {
+    let (table, column) = names_table(place);
+
+    conn.transaction(|conn| {
+        find_place(conn, place)?;
+
+        let removed = diesel::sql_query(format!("DELETE FROM {table} WHERE {column} = $1 AND value = $2"))
+            .bind::<Integer, _>(place.id())
+            .bind::<Text, _>(value)
+            .execute(conn)?;
+
+        diesel::sql_query(format!(
+            "UPDATE {table}
+            SET kind = 'primary'
+            WHERE id = (
+                SELECT id
+                FROM {table}
+                WHERE {column} = $1 AND kind = 'alias'
+                ORDER BY ordering, id
+                LIMIT 1
+            )
+            AND NOT EXISTS (SELECT 1 FROM {table} WHERE {column} = $1 AND kind = 'primary')"
+        ))
+        .bind::<Integer, _>(place.id())
+        .execute(conn)?;
+
+        Ok(removed)
+    })
+}
+
+#[derive(QueryableByName)]
+struct NameRow {
+    #[diesel(sql_type = Text)]
+    owner_kind: String,
+    #[diesel(sql_type = Integer)]
+    owner_id: i32,
+    #[diesel(embed)]
+    name: Name,
+}
+
+/// Gets the names of places, as `(place, name)` pairs, the primary name first and then by
+/// ordering
+pub fn names(conn: &mut PgConnection, places: &[Place]) -> QueryResult<Vec<(Place, Name)>> {
+    let kinds: Vec<&str> = places.iter().map(Place::kind).collect();
+    let ids: Vec<i32> = places.iter().map(Place::id).collect();
+
+    let rows = diesel::sql_query(
+        "WITH all_names(owner_kind, owner_id, id, value, language, kind, ordering) AS (
+            SELECT 'area', area_id, id, value, language, kind, ordering FROM area_names
+            UNION ALL
+            SELECT 'formation', formation_id, id, value, language, kind, ordering FROM formation_names
+            UNION ALL
+            SELECT 'climb', climb_id, id, value, language, kind, ordering FROM climb_names
+        )
+        SELECT n.*
+        FROM unnest($1, $2) AS p(kind, id)
+        JOIN all_names n ON n.owner_kind = p.kind AND n.owner_id = p.id
+        ORDER BY n.owner_kind, n.owner_id, n.kind <> 'primary', n.ordering, n.id",
+    )
+    .bind::<Array<Text>, _>(kinds)
+    .bind::<Array<Integer>, _>(ids)
+    .load::<NameRow>(conn)?;
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|row| Some((Place::from_row(&row.owner_kind, row.owner_id)?, row.name)))
+        .collect())
+}
@@ -0,0 +1,61 @@
+use diesel::prelude::*;
+use diesel::PgConnection;
+
+use crate::queries::Place;
+
+/// Channel on which each change recorded in the audit log is notified once committed
+pub const CHANGES_CHANNEL: &str = "climb_changes";
+
+/// A notification of a committed change of an area, formation or climb
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChangeNotification {
+    /// Id of the change in the audit log
+    pub change_id: i64,
+    pub transaction_id: i64,
+    /// The area, formation or climb the changed row is part of
+    pub place: Place,
+    pub table_name: String,
+    /// One of `INSERT`, `UPDATE` or `DELETE`
+    pub operation: String,
+}
+
+impl ChangeNotification {
+    /// Parses the payload of a notification, as sent by the `notify_change` trigger
+    fn parse(payload: &str) -> Option<Self> {
+        let mut fields = payload.split(' ');
+
+        let change_id = fields.next()?.parse().ok()?;
+        let transaction_id = fields.next()?.parse().ok()?;
+        let kind = fields.next()?;
+        let id = fields.next()?.parse().ok()?;
+
+        Some(ChangeNotification {
+            change_id,
+            transaction_id,
+            place: Place::from_row(kind, id)?,
+            table_name: fields.next()?.to_string(),
+            operation: fields.next()?.to_string(),
+        })
+    }
+}
+
+/// Starts listening for changes on a connection
+pub fn listen(conn: &mut PgConnection) -> QueryResult<()> {
+    diesel::sql_query(format!("LISTEN {CHANGES_CHANNEL}")).execute(conn)?;
+
+    Ok(())
+}
+
+/// Gets the changes notified to a listening connection since last called, without waiting for
+/// any. Fails if the connection is lost.
+pub fn notifications(conn: &mut PgConnection) -> QueryResult<Vec<ChangeNotification>> {
+    conn.notifications_iter()
+        .filter_map(|notification| match notification {
+            Ok(notification) if notification.channel == CHANGES_CHANNEL => {
+                ChangeNotification::parse(&notification.payload).map(Ok)
+            }
+            Ok(_) => None,
+            Err(e) => Some(Err(e)),
+        })
+        .collect()
+}
@@ -0,0 +1,734 @@
+use diesel::prelude::*;
+use diesel::sql_types::{Array, BigInt, Double, Integer, Nullable, Text};
+use diesel::PgConnection;
+use postgis_diesel::types::{MultiPolygon, Point};
+
+use crate::geo::GeoPoint;
+use crate::grade::{GradeSystem, ParsedGrade};
+use crate::names::{add_name, remove_name};
+use crate::schema::areas;
+
+/// Appends a name to an area, as its primary name if it has none, returning the id of the area
+pub fn add_area_name(conn: &mut PgConnection, id: i32, name: &str) -> QueryResult<i32> {
+    add_name(conn, Place::Area(id), name, None, None).map(|_| id)
+}
+
+/// Removes every occurrence of a name from an area, returning the id of the area
+pub fn remove_area_name(conn: &mut PgConnection, id: i32, name: &str) -> QueryResult<i32> {
+    remove_name(conn, Place::Area(id), name).map(|_| id)
+}
+
+/// Appends a name to a climb, as its primary name if it has none, returning the id of the climb
+pub fn add_climb_name(conn: &mut PgConnection, id: i32, name: &str) -> QueryResult<i32> {
+    add_name(conn, Place::Climb(id), name, None, None).map(|_| id)
+}
+
+/// Removes every occurrence of a name from a climb, returning the id of the climb
+pub fn remove_climb_name(conn: &mut PgConnection, id: i32, name: &str) -> QueryResult<i32> {
+    remove_name(conn, Place::Climb(id), name).map(|_| id)
+}
+
+/// Appends a name to a formation, as its primary name if it has none, returning the id of the
+/// formation
+pub fn add_formation_name(conn: &mut PgConnection, id: i32, name: &str) -> QueryResult<i32> {
+    add_name(conn, Place::Formation(id), name, None, None).map(|_| id)
+}
+
+/// Removes every occurrence of a name from a formation, returning the id of the formation
+pub fn remove_formation_name(conn: &mut PgConnection, id: i32, name: &str) -> QueryResult<i32> {
+    remove_name(conn, Place::Formation(id), name).map(|_| id)
+}
+
+#[derive(QueryableByName)]
+struct IdRow {
+    #[diesel(sql_type = Integer)]
+    id: i32,
+}
+
+/// A formation along with its distance, in meters, from a point
+#[derive(Debug, PartialEq, QueryableByName)]
+pub struct FormationDistance {
+    #[diesel(sql_type = Integer)]
+    pub id: i32,
+    #[diesel(sql_type = Double)]
+    pub distance: f64,
+}
+
+/// Gets the ids of formations located within a bounding box, ordered by id.
+///
+/// A box whose south west corner is east of its north east corner crosses the antimeridian, and
+/// is searched as the two boxes either side of it.
+pub fn formations_within(
+    conn: &mut PgConnection,
+    south_west: &GeoPoint,
+    north_east: &GeoPoint,
+) -> QueryResult<Vec<i32>> {
+    let (west, east) = (south_west.longitude(), north_east.longitude());
+
+    // Longitude ranges to search, a box not crossing the antimeridian being searched twice over
+    let [(west_a, east_a), (west_b, east_b)] =
+        if west > east { [(west, 180.0), (-180.0, east)] } else { [(west, east), (west, east)] };
+
+    // `&&` only compares bounding boxes, which is exact for points
+    diesel::sql_query(
+        "SELECT id
+        FROM formations
+        WHERE location && ST_MakeEnvelope($3, $1, $4, $2, 4326)
+            OR location && ST_MakeEnvelope($5, $1, $6, $2, 4326)
+        ORDER BY id",
+    )
+    .bind::<Double, _>(south_west.latitude())
+    .bind::<Double, _>(north_east.latitude())
+    .bind::<Double, _>(west_a)
+    .bind::<Double, _>(east_a)
+    .bind::<Double, _>(west_b)
+    .bind::<Double, _>(east_b)
+    .load::<IdRow>(conn)
+    .map(|rows| rows.into_iter().map(|row| row.id).collect())
+}
+
+/// Gets formations located within a radius, in meters, of a point, nearest first
+pub fn formations_near(
+    conn: &mut PgConnection,
+    point: &GeoPoint,
+    radius: f64,
+) -> QueryResult<Vec<FormationDistance>> {
+    // Distances are measured on the spheroid by casting to geography, which the
+    // `formations_location_geography_idx` index covers
+    diesel::sql_query(
+        "WITH origin(point) AS (
+            SELECT ST_SetSRID(ST_MakePoint($1, $2), 4326)::geography
+        )
+        SELECT f.id, ST_Distance(f.location::geography, o.point) AS distance
+        FROM formations f, origin o
+        WHERE ST_DWithin(f.location::geography, o.point, $3)
+        ORDER BY distance, f.id",
+    )
+    .bind::<Double, _>(point.longitude())
+    .bind::<Double, _>(point.latitude())
+    .bind::<Double, _>(radius)
+    .load(conn)
+}
+
+/// Gets at most `limit` formations nearest to a point, nearest first
+pub fn nearest_formations(
+    conn: &mut PgConnection,
+    point: &GeoPoint,
+    limit: i64,
+) -> QueryResult<Vec<FormationDistance>> {
+    // `<->` on geography is answered by the `formations_location_geography_idx` index
+    diesel::sql_query(
+        "WITH origin(point) AS (
+            SELECT ST_SetSRID(ST_MakePoint($1, $2), 4326)::geography
+        )
+        SELECT f.id, ST_Distance(f.location::geography, o.point) AS distance
+        FROM formations f, origin o
+        WHERE f.location IS NOT NULL
+        ORDER BY f.location::geography <-> o.point, f.id
+        LIMIT $3",
+    )
+    .bind::<Double, _>(point.longitude())
+    .bind::<Double, _>(point.latitude())
+    .bind::<BigInt, _>(limit)
+    .load(conn)
+}
+
+/// Sets the boundary of an area from a GeoJSON `Polygon` or `MultiPolygon`, returning the id of
+/// the area
+pub fn set_area_boundary(conn: &mut PgConnection, id: i32, geojson: &str) -> QueryResult<i32> {
+    // GeoJSON coordinates are always WGS 84 longitude/latitude
+    diesel::sql_query(
+        "UPDATE areas
+        SET boundary = ST_Multi(ST_SetSRID(ST_GeomFromGeoJSON($2), 4326))
+        WHERE id = $1
+        RETURNING id",
+    )
+    .bind::<Integer, _>(id)
+    .bind::<Text, _>(geojson)
+    .get_result::<IdRow>(conn)
+    .map(|row| row.id)
+}
+
+/// Clears the boundary of an area, returning the id of the area
+pub fn clear_area_boundary(conn: &mut PgConnection, id: i32) -> QueryResult<i32> {
+    diesel::update(areas::table.find(id))
+        .set(areas::boundary.eq(None::<MultiPolygon<Point>>))
+        .returning(areas::id)
+        .get_result(conn)
+}
+
+/// An area boundary as a GeoJSON `MultiPolygon`
+#[derive(Debug, PartialEq, QueryableByName)]
+pub struct AreaBoundary {
+    #[diesel(sql_type = Integer)]
+    pub id: i32,
+    #[diesel(sql_type = Text)]
+    pub geojson: String,
+}
+
+/// Gets the boundaries of areas which have one
+pub fn area_boundaries(conn: &mut PgConnection, ids: &[i32]) -> QueryResult<Vec<AreaBoundary>> {
+    diesel::sql_query(
+        "SELECT id, ST_AsGeoJSON(boundary) AS geojson
+        FROM areas
+        WHERE id = ANY($1) AND boundary IS NOT NULL",
+    )
+    .bind::<Array<Integer>, _>(ids)
+    .load(conn)
+}
+
+/// Gets the ids of areas whose boundary contains a point, innermost (smallest) first
+pub fn areas_containing(conn: &mut PgConnection, point: &GeoPoint) -> QueryResult<Vec<i32>> {
+    // `ST_Covers` includes points on the boundary itself, unlike `ST_Contains`
+    diesel::sql_query(
+        "SELECT id
+        FROM areas
+        WHERE ST_Covers(boundary, ST_SetSRID(ST_MakePoint($1, $2), 4326))
+        ORDER BY ST_Area(boundary), id",
+    )
+    .bind::<Double, _>(point.longitude())
+    .bind::<Double, _>(point.latitude())
+    .load::<IdRow>(conn)
+    .map(|rows| rows.into_iter().map(|row| row.id).collect())
+}
+
+/// A formation located outside of the boundary of the area it belongs to
+#[derive(Debug, PartialEq, QueryableByName)]
+pub struct FormationOutsideArea {
+    #[diesel(sql_type = Integer)]
+    pub formation_id: i32,
+    #[diesel(sql_type = Integer)]
+    pub area_id: i32,
+}
+
+/// Gets formations located outside of the boundary of their area, ordered by formation id.
+///
+/// The area of a formation on another formation is the area of its root formation. Formations
+/// without a location and areas without a boundary are never reported.
+pub fn formations_outside_areas(conn: &mut PgConnection) -> QueryResult<Vec<FormationOutsideArea>> {
+    // Walks up from every formation until reaching the area of its root formation
+    diesel::sql_query(
+        "WITH RECURSIVE formation_areas(formation_id, super_formation_id, area_id) AS (
+            SELECT formation_id, super_formation_id, area_id
+            FROM formation_belongs_to
+            UNION
+            SELECT fa.formation_id, fbt.super_formation_id, fbt.area_id
+            FROM formation_areas fa
+            JOIN formation_belongs_to fbt ON fbt.formation_id = fa.super_formation_id
+        )
+        SELECT f.id AS formation_id, a.id AS area_id
+        FROM formations f
+        JOIN formation_areas fa ON fa.formation_id = f.id
+        JOIN areas a ON a.id = fa.area_id
+        WHERE NOT ST_Covers(a.boundary, f.location)
+        ORDER BY f.id",
+    )
+    .load(conn)
+}
+
+/// Gets the id of a grade, inserting it if it does not exist yet
+pub fn upsert_grade(conn: &mut PgConnection, grade: &ParsedGrade) -> QueryResult<i32> {
+    use crate::models::NewGrade;
+    use crate::schema::{grade_types, grades};
+    use diesel::upsert::excluded;
+
+    let grade_type_id = grade_types::table
+        .filter(grade_types::name.eq(grade.system.name()))
+        .select(grade_types::id)
+        .first::<i32>(conn)?;
+
+    diesel::insert_into(grades::table)
+        .values(NewGrade {
+            grade_type_id,
+            value: grade.value.clone(),
+            sort_key: grade.sort_key,
+        })
+        .on_conflict((grades::grade_type_id, grades::value))
+        .do_update()
+        .set(grades::sort_key.eq(excluded(grades::sort_key)))
+        .returning(grades::id)
+        .get_result(conn)
+}
+
+/// Grades a climb, doing nothing if the climb already has the grade
+pub fn add_climb_grade(conn: &mut PgConnection, climb_id: i32, grade: &ParsedGrade) -> QueryResult<()> {
+    use crate::models::NewClimbGrade;
+    use crate::schema::climb_grades;
+
+    conn.transaction(|conn| {
+        let grade_id = upsert_grade(conn, grade)?;
+
+        diesel::insert_into(climb_grades::table)
+            .values(NewClimbGrade { climb_id, grade_id })
+            .on_conflict_do_nothing()
+            .execute(conn)?;
+
+        Ok(())
+    })
+}
+
+/// Removes a grade from a climb, returning the number of grades removed
+pub fn remove_climb_grade(conn: &mut PgConnection, climb_id: i32, grade: &ParsedGrade) -> QueryResult<usize> {
+    use crate::schema::{climb_grades, grade_types, grades};
+
+    let grade_ids = grades::table
+        .inner_join(grade_types::table)
+        .filter(grade_types::name.eq(grade.system.name()))
+        .filter(grades::value.eq(&grade.value))
+        .select(grades::id);
+
+    diesel::delete(
+        climb_grades::table
+            .filter(climb_grades::climb_id.eq(climb_id))
+            .filter(climb_grades::grade_id.eq_any(grade_ids)),
+    )
+    .execute(conn)
+}
+
+/// Places a grade on the common scale of difficulty, replacing its previous difficulty.
+///
+/// Climbs are given the mean difficulty of their grades, where grades without a conversion of
+/// their own are interpolated between the nearest conversions of their grade type.
+pub fn set_grade_conversion(conn: &mut PgConnection, grade: &ParsedGrade, difficulty: f64) -> QueryResult<()> {
+    use crate::models::NewGradeConversion;
+    use crate::schema::grade_conversions;
+
+    conn.transaction(|conn| {
+        let grade_id = upsert_grade(conn, grade)?;
+
+        diesel::insert_into(grade_conversions::table)
+            .values(NewGradeConversion { grade_id, difficulty })
+            .on_conflict(grade_conversions::grade_id)
+            .do_update()
+            .set(grade_conversions::difficulty.eq(difficulty))
+            .execute(conn)?;
+
+        Ok(())
+    })
+}
+
+/// Removes the conversion of a grade, returning the number of conversions removed
+pub fn remove_grade_conversion(conn: &mut PgConnection, grade: &ParsedGrade) -> QueryResult<usize> {
+    use crate::schema::{grade_conversions, grade_types, grades};
+
+    let grade_ids = grades::table
+        .inner_join(grade_types::table)
+        .filter(grade_types::name.eq(grade.system.name()))
+        .filter(grades::value.eq(&grade.value))
+        .select(grades::id);
+
+    diesel::delete(grade_conversions::table.filter(grade_conversions::grade_id.eq_any(grade_ids))).execute(conn)
+}
+
+/// A grade known to the database
+#[derive(Debug, Clone, PartialEq, Queryable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct KnownGrade {
+    /// Name of the grade type
+    pub system: String,
+    pub value: String,
+    pub sort_key: i32,
+    /// Difficulty of the grade's own conversion, if it has one
+    pub difficulty: Option<f64>,
+}
+
+/// Lists the grades known to the database, only those of a system if given, ordered by grade
+/// type then sort key
+pub fn known_grades(conn: &mut PgConnection, system: Option<GradeSystem>) -> QueryResult<Vec<KnownGrade>> {
+    use crate::schema::{grade_conversions, grade_types, grades};
+
+    let mut query = grades::table
+        .inner_join(grade_types::table)
+        .left_join(grade_conversions::table)
+        .select((grade_types::name, grades::value, grades::sort_key, grade_conversions::difficulty.nullable()))
+        .order((grade_types::id, grades::sort_key))
+        .into_boxed();
+
+    if let Some(system) = system {
+        query = query.filter(grade_types::name.eq(system.name()));
+    }
+
+    query.load(conn)
+}
+
+/// Applies conversions left pending by migrations, parsing their grades so they are stored in
+/// their canonical form with their sort key, and returning the number of conversions applied.
+///
+/// Conversions of grades that fail to parse are left pending.
+pub fn apply_pending_grade_conversions(conn: &mut PgConnection) -> QueryResult<usize> {
+    use crate::schema::{grade_types, pending_grade_conversions};
+
+    conn.transaction(|conn| {
+        let pending: Vec<(i32, String, String, f64)> = pending_grade_conversions::table
+            .inner_join(grade_types::table)
+            .select((
+                pending_grade_conversions::grade_type_id,
+                grade_types::name,
+                pending_grade_conversions::value,
+                pending_grade_conversions::difficulty,
+            ))
+            .load(conn)?;
+
+        let mut applied = 0;
+
+        for (grade_type_id, system, value, difficulty) in pending {
+            let Some(grade) = GradeSystem::from_name(&system).and_then(|system| system.parse(&value).ok()) else {
+                continue;
+            };
+
+            set_grade_conversion(conn, &grade, difficulty)?;
+            diesel::delete(pending_grade_conversions::table.find((grade_type_id, value))).execute(conn)?;
+
+            applied += 1;
+        }
+
+        Ok(applied)
+    })
+}
+
+/// Gets the version of the sort keys the grades are stored with, see `grade::SORT_KEY_VERSION`
+pub fn grade_sort_key_version(conn: &mut PgConnection) -> QueryResult<i32> {
+    use crate::schema::grade_sort_key_version;
+
+    grade_sort_key_version::table.select(grade_sort_key_version::version).first(conn)
+}
+
+/// Gives every stored grade of a known system the sort key its system parses it to, refreshing the
+/// difficulty and consensus grades of climbs with re-keyed grades, and returning the number of
+/// grades re-keyed. The grades are then stored with the current `grade::SORT_KEY_VERSION`.
+pub fn rekey_grades(conn: &mut PgConnection) -> QueryResult<usize> {
+    use crate::grade::SORT_KEY_VERSION;
+    use crate::schema::{ascent_grades, ascents, climb_grades, grade_sort_key_version, grade_types, grades};
+
+    conn.transaction(|conn| {
+        let stored: Vec<(i32, String, String, i32)> = grades::table
+            .inner_join(grade_types::table)
+            .select((grades::id, grade_types::name, grades::value, grades::sort_key))
+            .load(conn)?;
+
+        let mut rekeyed = Vec::new();
+
+        for (id, system, value, sort_key) in stored {
+            let Some(grade) = GradeSystem::from_name(&system).and_then(|system| system.parse(&value).ok()) else {
+                continue;
+            };
+
+            if grade.sort_key != sort_key {
+                diesel::update(grades::table.find(id))
+                    .set(grades::sort_key.eq(grade.sort_key))
+                    .execute(conn)?;
+
+                rekeyed.push(id);
+            }
+        }
+
+        let climb_ids: Vec<i32> = climb_grades::table
+            .filter(climb_grades::grade_id.eq_any(&rekeyed))
+            .select(climb_grades::climb_id)
+            .distinct()
+            .load(conn)?;
+
+        diesel::sql_query("SELECT refresh_climb_difficulty(id) FROM unnest($1) AS id")
+            .bind::<Array<Integer>, _>(&climb_ids)
+            .execute(conn)?;
+
+        let ascended_ids: Vec<i32> = ascents::table
+            .inner_join(ascent_grades::table)
+            .filter(ascent_grades::grade_id.eq_any(&rekeyed))
+            .select(ascents::climb_id)
+            .distinct()
+            .load(conn)?;
+
+        diesel::sql_query("SELECT refresh_climb_consensus_grades(id) FROM unnest($1) AS id")
+            .bind::<Array<Integer>, _>(&ascended_ids)
+            .execute(conn)?;
+
+        diesel::update(grade_sort_key_version::table)
+            .set(grade_sort_key_version::version.eq(SORT_KEY_VERSION))
+            .execute(conn)?;
+
+        Ok(rekeyed.len())
+    })
+}
+
+#[derive(QueryableByName)]
+struct DifficultyRow {
+    #[diesel(sql_type = Nullable<Double>)]
+    difficulty: Option<f64>,
+}
+
+/// Gets the difficulty of a grade, or `None` if its grade type has no conversions
+pub fn grade_difficulty(conn: &mut PgConnection, grade: &ParsedGrade) -> QueryResult<Option<f64>> {
+    diesel::sql_query(
+        "SELECT grade_difficulty((SELECT id FROM grade_types WHERE name = $1), $2) AS difficulty",
+    )
+    .bind::<Text, _>(grade.system.name())
+    .bind::<Integer, _>(grade.sort_key)
+    .get_result::<DifficultyRow>(conn)
+    .map(|row| row.difficulty)
+}
+
+/// A grade of a climb converted from another grade type
+#[derive(Debug, PartialEq, QueryableByName)]
+pub struct ConvertedGrade {
+    #[diesel(sql_type = Integer)]
+    pub climb_id: i32,
+    #[diesel(sql_type = Text)]
+    pub value: String,
+}
+
+/// Converts the grades of climbs into a grade type, ordered by climb then grade.
+///
+/// Each grade of another grade type becomes the conversion of `system` nearest in difficulty,
+/// preferring the easier of two equally near conversions. Grades already of `system` are not
+/// converted, nor duplicated in the result.
+pub fn converted_climb_grades(
+    conn: &mut PgConnection,
+    climb_ids: &[i32],
+    system: GradeSystem,
+) -> QueryResult<Vec<ConvertedGrade>> {
+    diesel::sql_query(
+        "WITH source(climb_id, difficulty) AS (
+            SELECT cg.climb_id, grade_difficulty(g.grade_type_id, g.sort_key)
+            FROM climb_grades cg
+            JOIN grades g ON g.id = cg.grade_id
+            JOIN grade_types gt ON gt.id = g.grade_type_id
+            WHERE cg.climb_id = ANY($1) AND gt.name <> $2
+        ),
+        target(value, sort_key, difficulty) AS (
+            SELECT g.value, g.sort_key, gc.difficulty
+            FROM grade_conversions gc
+            JOIN grades g ON g.id = gc.grade_id
+            JOIN grade_types gt ON gt.id = g.grade_type_id
+            WHERE gt.name = $2
+        )
+        SELECT climb_id, value
+        FROM (
+            SELECT DISTINCT s.climb_id, t.value, t.sort_key
+            FROM source s
+            CROSS JOIN LATERAL (
+                SELECT value, sort_key
+                FROM target
+                ORDER BY abs(target.difficulty - s.difficulty), sort_key
+                LIMIT 1
+            ) t
+            WHERE s.difficulty IS NOT NULL
+        ) converted
+        ORDER BY climb_id, sort_key",
+    )
+    .bind::<Array<Integer>, _>(climb_ids)
+    .bind::<Text, _>(system.name())
+    .load(conn)
+}
+
+/// The consensus of the grades proposed by the ascents of a climb in one grade type
+#[derive(Debug, Clone, PartialEq, QueryableByName)]
+pub struct ConsensusGrade {
+    #[diesel(sql_type = Integer)]
+    pub climb_id: i32,
+    #[diesel(sql_type = Text)]
+    pub grade_type: String,
+    /// The lower median when there is an even number of votes
+    #[diesel(sql_type = Text)]
+    pub median: String,
+    /// The easiest of the most voted grades
+    #[diesel(sql_type = Text)]
+    pub mode: String,
+    #[diesel(sql_type = Integer)]
+    pub votes: i32,
+    /// Difference in difficulty between the hardest and easiest votes, if the grade type has
+    /// conversions
+    #[diesel(sql_type = Nullable<Double>)]
+    pub spread: Option<f64>,
+}
+
+/// Gets the consensus grades of climbs, ordered by climb, then most votes, then grade type
+pub fn consensus_grades(conn: &mut PgConnection, climb_ids: &[i32]) -> QueryResult<Vec<ConsensusGrade>> {
+    diesel::sql_query(
+        "SELECT c.climb_id, gt.name AS grade_type, median.value AS median, mode.value AS mode, c.votes, c.spread
+        FROM climb_consensus_grades c
+        JOIN grade_types gt ON gt.id = c.grade_type_id
+        JOIN grades median ON median.id = c.median_grade_id
+        JOIN grades mode ON mode.id = c.mode_grade_id
+        WHERE c.climb_id = ANY($1)
+        ORDER BY c.climb_id, c.votes DESC, gt.id",
+    )
+    .bind::<Array<Integer>, _>(climb_ids)
+    .load(conn)
+}
+
+/// The number of ascents of a climb which proposed a grade
+#[derive(Debug, Clone, PartialEq, QueryableByName)]
+pub struct GradeVotes {
+    #[diesel(sql_type = Integer)]
+    pub climb_id: i32,
+    #[diesel(sql_type = Text)]
+    pub grade_type: String,
+    #[diesel(sql_type = Text)]
+    pub value: String,
+    #[diesel(sql_type = BigInt)]
+    pub votes: i64,
+}
+
+/// Gets the votes for each grade proposed by the ascents of climbs, ordered by climb, grade type
+/// and grade
+pub fn grade_votes(conn: &mut PgConnection, climb_ids: &[i32]) -> QueryResult<Vec<GradeVotes>> {
+    diesel::sql_query(
+        "SELECT a.climb_id, gt.name AS grade_type, g.value, count(*) AS votes
+        FROM ascents a
+        JOIN ascent_grades ag ON ag.ascent_id = a.id
+        JOIN grades g ON g.id = ag.grade_id
+        JOIN grade_types gt ON gt.id = g.grade_type_id
+        WHERE a.climb_id = ANY($1)
+        GROUP BY a.climb_id, gt.id, gt.name, g.value, g.sort_key
+        ORDER BY a.climb_id, gt.id, g.sort_key",
+    )
+    .bind::<Array<Integer>, _>(climb_ids)
+    .load(conn)
+}
+
+/// An area below another, `depth` levels down
+#[derive(Debug, Clone, Copy, PartialEq, QueryableByName)]
+pub struct AreaDescendant {
+    #[diesel(sql_type = Integer)]
+    pub id: i32,
+    #[diesel(sql_type = Integer)]
+    pub depth: i32,
+}
+
+/// Gets the areas below an area, at most `max_depth` levels down, ordered by depth then id
+pub fn area_descendants(
+    conn: &mut PgConnection,
+    id: i32,
+    max_depth: Option<i32>,
+) -> QueryResult<Vec<AreaDescendant>> {
+    diesel::sql_query(
+        "WITH RECURSIVE descendants(id, depth) AS (
+            SELECT area_id, 1
+            FROM area_belongs_to
+            WHERE super_area_id = $1
+            UNION
+            SELECT abt.area_id, d.depth + 1
+            FROM descendants d
+            JOIN area_belongs_to abt ON abt.super_area_id = d.id
+            WHERE $2 IS NULL OR d.depth < $2
+        )
+        SELECT id, depth
+        FROM descendants
+        ORDER BY depth, id",
+    )
+    .bind::<Integer, _>(id)
+    .bind::<Nullable<Integer>, _>(max_depth)
+    .load(conn)
+}
+
+/// Something climbs are found within, or a climb itself
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Place {
+    Area(i32),
+    Formation(i32),
+    Climb(i32),
+}
+
+impl Place {
+    pub(crate) fn kind(&self) -> &'static str {
+        match self {
+            Place::Area(_) => "area",
+            Place::Formation(_) => "formation",
+            Place::Climb(_) => "climb",
+        }
+    }
+
+    pub(crate) fn id(&self) -> i32 {
+        match self {
+            Place::Area(id) | Place::Formation(id) | Place::Climb(id) => *id,
+        }
+    }
+
+    pub(crate) fn from_row(kind: &str, id: i32) -> Option<Self> {
+        match kind {
+            "area" => Some(Place::Area(id)),
+            "formation" => Some(Place::Formation(id)),
+            "climb" => Some(Place::Climb(id)),
+            _ => None,
+        }
+    }
+}
+
+/// Fails with `NotFound` unless a place exists
+pub(crate) fn find_place(conn: &mut PgConnection, place: Place) -> QueryResult<()> {
+    use crate::schema::{climbs, formations};
+
+    match place {
+        Place::Area(id) => areas::table.find(id).select(areas::id).first::<i32>(conn)?,
+        Place::Formation(id) => formations::table.find(id).select(formations::id).first::<i32>(conn)?,
+        Place::Climb(id) => climbs::table.find(id).select(climbs::id).first::<i32>(conn)?,
+    };
+
+    Ok(())
+}
+
+#[derive(QueryableByName)]
+struct AncestorRow {
+    #[diesel(sql_type = Text)]
+    origin_kind: String,
+    #[diesel(sql_type = Integer)]
+    origin_id: i32,
+    #[diesel(sql_type = Text)]
+    kind: String,
+    #[diesel(sql_type = Integer)]
+    id: i32,
+}
+
+/// Gets the areas and formations places are within, as `(place, ancestor)` pairs, nearest
+/// ancestor first.
+///
+/// A formation is within its super formation, or its area if it has none, and likewise a climb
+/// within its formation or area.
+pub fn ancestors(conn: &mut PgConnection, places: &[Place]) -> QueryResult<Vec<(Place, Place)>> {
+    let kinds: Vec<&str> = places.iter().map(Place::kind).collect();
+    let ids: Vec<i32> = places.iter().map(Place::id).collect();
+
+    // The `prevent_*_cycle` triggers keep every chain of parents finite
+    let rows = diesel::sql_query(
+        "WITH RECURSIVE ancestry(origin_kind, origin_id, kind, id, depth) AS (
+            SELECT s.kind, s.id, s.kind, s.id, 0
+            FROM unnest($1, $2) AS s(kind, id)
+            UNION ALL
+            SELECT a.origin_kind, a.origin_id, p.kind, p.id, a.depth + 1
+            FROM ancestry a
+            -- Only the table of the kind of each place is read
+            CROSS JOIN LATERAL (
+                SELECT 'area', super_area_id
+                FROM area_belongs_to
+                WHERE a.kind = 'area' AND area_id = a.id
+                UNION ALL
+                SELECT CASE WHEN super_formation_id IS NULL THEN 'area' ELSE 'formation' END,
+                    COALESCE(super_formation_id, area_id)
+                FROM formation_belongs_to
+                WHERE a.kind = 'formation' AND formation_id = a.id
+                    AND COALESCE(super_formation_id, area_id) IS NOT NULL
+                UNION ALL
+                SELECT CASE WHEN formation_id IS NULL THEN 'area' ELSE 'formation' END,
+                    COALESCE(formation_id, area_id)
+                FROM climb_belongs_to
+                WHERE a.kind = 'climb' AND climb_id = a.id
+                    AND COALESCE(formation_id, area_id) IS NOT NULL
+            ) AS p(kind, id)
+        )
+        SELECT origin_kind, origin_id, kind, id
+        FROM ancestry
+        WHERE depth > 0
+        ORDER BY origin_kind, origin_id, depth",
+    )
+    .bind::<Array<Text>, _>(kinds)
+    .bind::<Array<Integer>, _>(ids)
+    .load::<AncestorRow>(conn)?;
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|row| {
+            Some((Place::from_row(&row.origin_kind, row.origin_id)?, Place::from_row(&row.kind, row.id)?))
+        })
+        .collect())
+}
@@ -0,0 +1,378 @@
+//! Typed operations on areas, formations, climbs, climbers and ascents, along with grades,
+//! descriptions, search, the audit log and users, for tools built on the database rather than on
+//! its tables.
+//!
+//! Each repository borrows a connection, e.g. `AreaRepo::new(conn).get(id)`, and fails with an
+//! [`Error`] telling what went wrong in terms of the entities rather than of SQL.
+
+use std::fmt;
+
+use diesel::prelude::*;
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
+use diesel::PgConnection;
+
+use crate::descriptions::Description;
+use crate::geo::GeoPointError;
+use crate::grade::GradeError;
+use crate::names::{Name, NameKind};
+use crate::queries::Place;
+
+mod area;
+mod ascent;
+mod change;
+mod climb;
+mod climber;
+mod description;
+mod formation;
+mod grade;
+mod search;
+mod user;
+
+pub use area::{AreaInput, AreaRepo};
+pub use ascent::{AscentInput, AscentRepo, AscentUpdate};
+pub use change::ChangeRepo;
+pub use climb::{ClimbInput, ClimbRepo, VariationFamilyMember};
+pub use climber::ClimberRepo;
+pub use description::DescriptionRepo;
+pub use formation::{FormationInput, FormationRepo};
+pub use grade::GradeRepo;
+pub use search::SearchRepo;
+pub use user::UserRepo;
+
+/// Errors of repository operations
+#[derive(Debug)]
+pub enum Error {
+    /// The entity, or an entity it refers to, does not exist
+    NotFound(String),
+    /// The change conflicts with existing data, e.g. removing an entity which is still referenced
+    Conflict {
+        message: String,
+        constraint: Option<String>,
+    },
+    /// The change would make an entity its own ancestor
+    CycleDetected(String),
+    /// The grade is not valid for its grade type
+    InvalidGrade(String),
+    /// The input violates a constraint, e.g. an unknown description type
+    InvalidInput {
+        message: String,
+        constraint: Option<String>,
+    },
+    /// The connection to the database was lost
+    Unavailable(String),
+    /// Any other database error
+    Database(DieselError),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    fn invalid_input(message: impl Into<String>) -> Self {
+        Error::InvalidInput {
+            message: message.into(),
+            constraint: None,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NotFound(message)
+            | Error::Conflict { message, .. }
+            | Error::CycleDetected(message)
+            | Error::InvalidGrade(message)
+            | Error::InvalidInput { message, .. }
+            | Error::Unavailable(message) => write!(f, "{message}"),
+            Error::Database(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<DieselError> for Error {
+    fn from(e: DieselError) -> Self {
+        match e {
+            DieselError::NotFound => Error::NotFound("Record not found".to_string()),
+            DieselError::DatabaseError(kind, info) => {
+                let message = info.message().to_string();
+                let constraint = info.constraint_name().map(str::to_string);
+
+                match kind {
+                    DatabaseErrorKind::UniqueViolation | DatabaseErrorKind::SerializationFailure => {
+                        Error::Conflict { message, constraint }
+                    }
+                    // Removing an entity which is still referenced, e.g. an area with climbs
+                    DatabaseErrorKind::ForeignKeyViolation if message.starts_with("update or delete") => {
+                        Error::Conflict {
+                            message: info.details().map_or(message, str::to_string),
+                            constraint,
+                        }
+                    }
+                    // Referencing an entity which does not exist
+                    DatabaseErrorKind::ForeignKeyViolation => {
+                        Error::NotFound(info.details().map_or(message, str::to_string))
+                    }
+                    DatabaseErrorKind::CheckViolation | DatabaseErrorKind::NotNullViolation => {
+                        Error::InvalidInput { message, constraint }
+                    }
+                    DatabaseErrorKind::ClosedConnection => Error::Unavailable(message),
+                    // Raised by the `prevent_*cycle` triggers of the belongs-to tables
+                    _ if message.starts_with("Cycle detected") => Error::CycleDetected(message),
+                    // Raised by `revert_change` when a row has changed since
+                    _ if message.starts_with("Revert conflict") => Error::Conflict { message, constraint },
+                    _ => Error::Database(DieselError::DatabaseError(kind, info)),
+                }
+            }
+            e => Error::Database(e),
+        }
+    }
+}
+
+impl From<GeoPointError> for Error {
+    fn from(e: GeoPointError) -> Self {
+        Error::invalid_input(e.to_string())
+    }
+}
+
+impl From<GradeError> for Error {
+    fn from(e: GradeError) -> Self {
+        Error::InvalidGrade(e.to_string())
+    }
+}
+
+/// A window of a list ordered by id, as used for cursor based pagination.
+///
+/// Lists exclude ids outside of `after` and `before`. Given `first`, a list is in ascending order
+/// with at most `first + 1` ids, and given `last`, in descending order with at most `last + 1`
+/// ids. The extra id only signals that another page exists. Given neither, a list is as given
+/// `first` of [`Page::DEFAULT_SIZE`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Page {
+    pub after: Option<i32>,
+    pub before: Option<i32>,
+    pub first: Option<usize>,
+    pub last: Option<usize>,
+}
+
+impl Page {
+    /// Size of a page given neither `first` nor `last`
+    pub const DEFAULT_SIZE: usize = 50;
+    /// Largest `first` or `last` a page may be given
+    pub const MAX_SIZE: usize = 100;
+
+    /// A page, failing with `InvalidInput` if `first` or `last` exceeds [`Page::MAX_SIZE`]
+    pub fn new(after: Option<i32>, before: Option<i32>, first: Option<usize>, last: Option<usize>) -> Result<Self> {
+        if first.into_iter().chain(last).any(|size| size > Page::MAX_SIZE) {
+            return Err(Error::invalid_input(format!("`first` and `last` must be at most {}", Page::MAX_SIZE)));
+        }
+
+        Ok(Page { after, before, first, last })
+    }
+}
+
+/// Applies a page to a boxed query of ids
+macro_rules! paginate {
+    ($query:expr, $id:expr, $page:expr) => {{
+        let page: Page = $page;
+        let mut query = $query;
+
+        if let Some(after) = page.after {
+            query = query.filter($id.gt(after));
+        }
+
+        if let Some(before) = page.before {
+            query = query.filter($id.lt(before));
+        }
+
+        match (page.first, page.last) {
+            (_, Some(last)) => query.order($id.desc()).limit(last as i64 + 1),
+            (first, None) => query.order($id).limit(first.unwrap_or($crate::repo::Page::DEFAULT_SIZE) as i64 + 1),
+        }
+    }};
+}
+
+use paginate;
+
+/// A child of a parent, as loaded by `child_pages`
+#[derive(QueryableByName)]
+struct Child {
+    #[diesel(sql_type = diesel::sql_types::Integer)]
+    parent: i32,
+    #[diesel(sql_type = diesel::sql_types::Integer)]
+    id: i32,
+}
+
+/// Applies a page to the children of each parent, as `(parent, child)` pairs grouped by parent.
+///
+/// `table` relates the `parent` and `child` columns. Each parent's page is read on its own from
+/// the `(parent, child)` index, so no more than a page of a parent's children is ever read.
+fn child_pages(
+    conn: &mut PgConnection,
+    table: &str,
+    parent: &str,
+    child: &str,
+    parents: &[i32],
+    page: Page,
+) -> Result<Vec<(i32, i32)>> {
+    use diesel::sql_types::{Array, BigInt, Integer, Nullable};
+
+    let (order, limit) = match (page.first, page.last) {
+        (_, Some(last)) => ("DESC", last),
+        (first, None) => ("ASC", first.unwrap_or(Page::DEFAULT_SIZE)),
+    };
+
+    let children = diesel::sql_query(format!(
+        "SELECT p.id AS parent, c.id
+        FROM unnest($1) AS p(id)
+        CROSS JOIN LATERAL (
+            SELECT {child} AS id
+            FROM {table}
+            WHERE {parent} = p.id
+                AND ($2 IS NULL OR {child} > $2)
+                AND ($3 IS NULL OR {child} < $3)
+            ORDER BY {child} {order}
+            LIMIT $4
+        ) c
+        ORDER BY p.id, c.id {order}"
+    ))
+    .bind::<Array<Integer>, _>(parents)
+    .bind::<Nullable<Integer>, _>(page.after)
+    .bind::<Nullable<Integer>, _>(page.before)
+    .bind::<BigInt, _>(limit as i64 + 1)
+    .load::<Child>(conn)?;
+
+    Ok(children.into_iter().map(|child| (child.parent, child.id)).collect())
+}
+
+/// What a formation or climb belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Parent {
+    Area(i32),
+    Formation(i32),
+}
+
+impl Parent {
+    /// The parent given either an area or a formation, but not both
+    pub fn from_ids(area_id: Option<i32>, formation_id: Option<i32>) -> Result<Option<Self>> {
+        match (area_id, formation_id) {
+            (Some(_), Some(_)) => Err(Error::invalid_input("Expected an area or a formation, not both")),
+            (Some(id), None) => Ok(Some(Parent::Area(id))),
+            (None, Some(id)) => Ok(Some(Parent::Formation(id))),
+            (None, None) => Ok(None),
+        }
+    }
+
+    fn area_id(self) -> Option<i32> {
+        match self {
+            Parent::Area(id) => Some(id),
+            Parent::Formation(_) => None,
+        }
+    }
+
+    fn formation_id(self) -> Option<i32> {
+        match self {
+            Parent::Formation(id) => Some(id),
+            Parent::Area(_) => None,
+        }
+    }
+}
+
+/// Fails with `NotFound` unless a place exists
+fn find(conn: &mut PgConnection, place: Place) -> Result<()> {
+    use crate::queries::find_place;
+
+    find_place(conn, place).optional()?.ok_or_else(|| not_found(place))
+}
+
+fn not_found(place: Place) -> Error {
+    Error::NotFound(format!("{} {} not found", kind_name(place), place.id()))
+}
+
+fn kind_name(place: Place) -> &'static str {
+    match place {
+        Place::Area(_) => "Area",
+        Place::Formation(_) => "Formation",
+        Place::Climb(_) => "Climb",
+    }
+}
+
+/// Gets the areas and formations places are within, as `(place, ancestor)` pairs, nearest
+/// ancestor first
+pub fn ancestors(conn: &mut PgConnection, places: &[Place]) -> Result<Vec<(Place, Place)>> {
+    use crate::queries::ancestors;
+
+    Ok(ancestors(conn, places)?)
+}
+
+/// Adds a name to a place after its other names, as [`add_name`](crate::names::add_name) does
+fn add_name(
+    conn: &mut PgConnection,
+    place: Place,
+    value: &str,
+    kind: Option<NameKind>,
+    language: Option<&str>,
+) -> Result<Name> {
+    use crate::names::add_name;
+
+    find(conn, place)?;
+
+    Ok(add_name(conn, place, value, kind, language)?)
+}
+
+/// Removes every name of a place with a value, in any language
+fn remove_name(conn: &mut PgConnection, place: Place, value: &str) -> Result<()> {
+    use crate::names::remove_name;
+
+    find(conn, place)?;
+    remove_name(conn, place, value)?;
+
+    Ok(())
+}
+
+/// Removes the description of a key from a place, failing with `NotFound` if it has none
+fn remove_description(conn: &mut PgConnection, place: Place, key: &str) -> Result<()> {
+    use crate::descriptions::remove_description;
+
+    if !remove_description(conn, place, key)? {
+        return Err(Error::NotFound(format!("{} {} has no `{key}` description", kind_name(place), place.id())));
+    }
+
+    Ok(())
+}
+
+/// Adds names to a place in order, the first becoming its primary name if it has none
+pub fn add_names(conn: &mut PgConnection, place: Place, names: &[String]) -> Result<()> {
+    use crate::names::add_name;
+
+    for name in names {
+        add_name(conn, place, name, None, None)?;
+    }
+
+    Ok(())
+}
+
+/// Sets descriptions of a place, replacing those of the same keys. Later descriptions of the same
+/// key replace earlier ones, and unknown keys are an error.
+pub fn set_descriptions(conn: &mut PgConnection, place: Place, descriptions: &[Description]) -> Result<()> {
+    use crate::descriptions::{description_types, set_description};
+
+    let keys = description_types(conn)?;
+
+    let unknown: Vec<&str> = descriptions
+        .iter()
+        .map(|description| description.key.as_str())
+        .filter(|key| !keys.iter().any(|known| known == key))
+        .collect();
+
+    if !unknown.is_empty() {
+        return Err(Error::invalid_input(format!("Unknown description types: {}", unknown.join(", "))));
+    }
+
+    for description in descriptions {
+        set_description(conn, place, &description.key, &description.value)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,297 @@
+use diesel::prelude::*;
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
+use diesel::PgConnection;
+
+use super::{
+    add_name, add_names, child_pages, find, not_found, paginate, remove_description, remove_name,
+    set_descriptions, Error, Page, Result,
+};
+use crate::descriptions::Description;
+use crate::geo::GeoPoint;
+use crate::models::{Area, NewAreaBelongsTo};
+use crate::names::{Name, NameKind};
+use crate::queries::{AreaBoundary, AreaDescendant, Place};
+use crate::schema::{area_belongs_to, areas, climb_belongs_to, formation_belongs_to};
+
+/// A new area
+#[derive(Debug, Clone, Default)]
+pub struct AreaInput {
+    /// Names in order, the first becoming the primary name
+    pub names: Vec<String>,
+    pub descriptions: Vec<Description>,
+    pub super_area_id: Option<i32>,
+}
+
+/// Areas and what they contain
+pub struct AreaRepo<'a> {
+    conn: &'a mut PgConnection,
+}
+
+impl<'a> AreaRepo<'a> {
+    pub fn new(conn: &'a mut PgConnection) -> Self {
+        AreaRepo { conn }
+    }
+
+    /// Adds an area, returning its id
+    pub fn create(&mut self, input: AreaInput) -> Result<i32> {
+        self.conn.transaction(|conn| {
+            let id = diesel::insert_into(areas::table)
+                .default_values()
+                .returning(areas::id)
+                .get_result::<i32>(conn)?;
+
+            add_names(conn, Place::Area(id), &input.names)?;
+            set_descriptions(conn, Place::Area(id), &input.descriptions)?;
+
+            if let Some(super_area_id) = input.super_area_id {
+                AreaRepo::new(conn).move_to(id, Some(super_area_id))?;
+            }
+
+            Ok(id)
+        })
+    }
+
+    pub fn get(&mut self, id: i32) -> Result<Area> {
+        areas::table
+            .find(id)
+            .select(Area::as_select())
+            .first(self.conn)
+            .optional()?
+            .ok_or_else(|| not_found(Place::Area(id)))
+    }
+
+    /// Gets the areas of ids, skipping those which do not exist
+    pub fn get_many(&mut self, ids: &[i32]) -> Result<Vec<Area>> {
+        Ok(areas::table
+            .filter(areas::id.eq_any(ids))
+            .select(Area::as_select())
+            .load(self.conn)?)
+    }
+
+    /// Lists the ids of areas, only those directly within a super area if given
+    pub fn list(&mut self, super_area_id: Option<i32>, page: Page) -> Result<Vec<i32>> {
+        let mut query = areas::table
+            .left_join(area_belongs_to::table.on(area_belongs_to::area_id.eq(areas::id)))
+            .select(areas::id)
+            .into_boxed();
+
+        if let Some(super_area_id) = super_area_id {
+            query = query.filter(area_belongs_to::super_area_id.eq(super_area_id));
+        }
+
+        Ok(paginate!(query, areas::id, page).load(self.conn)?)
+    }
+
+    /// Gets the super areas of areas, as `(area, super area)` pairs
+    pub fn super_areas(&mut self, ids: &[i32]) -> Result<Vec<(i32, i32)>> {
+        Ok(area_belongs_to::table
+            .filter(area_belongs_to::area_id.eq_any(ids))
+            .select((area_belongs_to::area_id, area_belongs_to::super_area_id))
+            .load(self.conn)?)
+    }
+
+    /// Gets the sub areas of areas, as `(area, sub area)` pairs ordered by sub area
+    pub fn sub_areas(&mut self, ids: &[i32]) -> Result<Vec<(i32, i32)>> {
+        Ok(area_belongs_to::table
+            .filter(area_belongs_to::super_area_id.eq_any(ids))
+            .select((area_belongs_to::super_area_id, area_belongs_to::area_id))
+            .order(area_belongs_to::area_id)
+            .load(self.conn)?)
+    }
+
+    /// Gets a page of the sub areas of each area, as `(area, sub area)` pairs grouped by area
+    pub fn sub_area_pages(&mut self, ids: &[i32], page: Page) -> Result<Vec<(i32, i32)>> {
+        child_pages(self.conn, "area_belongs_to", "super_area_id", "area_id", ids, page)
+    }
+
+    /// Lists the ids of the areas below an area, at most `max_depth` levels down, ordered by depth
+    /// then id. Pages are bounded by the depth and id of the areas of their cursors, failing with
+    /// `InvalidInput` for cursors of areas no longer below the area.
+    pub fn descendants(&mut self, id: i32, max_depth: Option<i32>, page: Page) -> Result<Vec<i32>> {
+        use diesel::sql_types::{BigInt, Integer, Nullable};
+
+        find(self.conn, Place::Area(id))?;
+
+        let after = page.after.map(|after| self.depth_below(id, after)).transpose()?;
+        let before = page.before.map(|before| self.depth_below(id, before)).transpose()?;
+
+        let (direction, limit) = match (page.first, page.last) {
+            (_, Some(last)) => (-1, last),
+            (first, None) => (1, first.unwrap_or(Page::DEFAULT_SIZE)),
+        };
+
+        let descendants = diesel::sql_query(
+            "WITH RECURSIVE descendants(id, depth) AS (
+                SELECT area_id, 1
+                FROM area_belongs_to
+                WHERE super_area_id = $1
+                UNION
+                SELECT abt.area_id, d.depth + 1
+                FROM descendants d
+                JOIN area_belongs_to abt ON abt.super_area_id = d.id
+                -- Areas deeper than the area of the `before` cursor are never in the page
+                WHERE ($2 IS NULL OR d.depth < $2) AND ($5 IS NULL OR d.depth < $5)
+            )
+            SELECT id, depth
+            FROM descendants
+            WHERE ($3 IS NULL OR (depth, id) > ($3, $4))
+                AND ($5 IS NULL OR (depth, id) < ($5, $6))
+            ORDER BY depth * $7, id * $7
+            LIMIT $8",
+        )
+        .bind::<Integer, _>(id)
+        .bind::<Nullable<Integer>, _>(max_depth)
+        .bind::<Nullable<Integer>, _>(after.map(|after| after.depth))
+        .bind::<Nullable<Integer>, _>(after.map(|after| after.id))
+        .bind::<Nullable<Integer>, _>(before.map(|before| before.depth))
+        .bind::<Nullable<Integer>, _>(before.map(|before| before.id))
+        .bind::<Integer, _>(direction)
+        .bind::<BigInt, _>(limit as i64 + 1)
+        .load::<AreaDescendant>(self.conn)?;
+
+        Ok(descendants.into_iter().map(|descendant| descendant.id).collect())
+    }
+
+    /// Finds how many levels below an area another area is, walking up from the other area
+    fn depth_below(&mut self, id: i32, descendant: i32) -> Result<AreaDescendant> {
+        use diesel::sql_types::Integer;
+
+        // The `prevent_area_belongs_to_cycle` trigger keeps every chain of super areas finite
+        diesel::sql_query(
+            "WITH RECURSIVE ancestors(id, depth) AS (
+                SELECT super_area_id, 1
+                FROM area_belongs_to
+                WHERE area_id = $2
+                UNION ALL
+                SELECT abt.super_area_id, a.depth + 1
+                FROM ancestors a
+                JOIN area_belongs_to abt ON abt.area_id = a.id
+                WHERE a.id <> $1
+            )
+            SELECT $2 AS id, depth
+            FROM ancestors
+            WHERE id = $1",
+        )
+        .bind::<Integer, _>(id)
+        .bind::<Integer, _>(descendant)
+        .get_result(self.conn)
+        .optional()?
+        .ok_or_else(|| Error::invalid_input(format!("Area {descendant} of the cursor is no longer below area {id}")))
+    }
+
+    /// Adds a name to an area after its other names, as its primary name if it has none unless
+    /// given a kind
+    pub fn add_name(&mut self, id: i32, name: &str, kind: Option<NameKind>, language: Option<&str>) -> Result<Name> {
+        add_name(self.conn, Place::Area(id), name, kind, language)
+    }
+
+    /// Removes every name of an area with a value, in any language
+    pub fn remove_name(&mut self, id: i32, name: &str) -> Result<()> {
+        remove_name(self.conn, Place::Area(id), name)
+    }
+
+    /// Sets a description of an area, replacing any description of the same key
+    pub fn set_description(&mut self, id: i32, description: Description) -> Result<()> {
+        set_descriptions(self.conn, Place::Area(id), &[description])
+    }
+
+    pub fn remove_description(&mut self, id: i32, key: &str) -> Result<()> {
+        remove_description(self.conn, Place::Area(id), key)
+    }
+
+    /// Gets the formations directly within areas, as `(area, formation)` pairs ordered by
+    /// formation
+    pub fn formations(&mut self, ids: &[i32]) -> Result<Vec<(i32, i32)>> {
+        Ok(formation_belongs_to::table
+            .filter(formation_belongs_to::area_id.eq_any(ids))
+            .select((formation_belongs_to::area_id.assume_not_null(), formation_belongs_to::formation_id))
+            .order(formation_belongs_to::formation_id)
+            .load(self.conn)?)
+    }
+
+    /// Gets a page of the formations directly within each area, as `(area, formation)` pairs
+    /// grouped by area
+    pub fn formation_pages(&mut self, ids: &[i32], page: Page) -> Result<Vec<(i32, i32)>> {
+        child_pages(self.conn, "formation_belongs_to", "area_id", "formation_id", ids, page)
+    }
+
+    /// Gets the climbs directly within areas, as `(area, climb)` pairs ordered by climb
+    pub fn climbs(&mut self, ids: &[i32]) -> Result<Vec<(i32, i32)>> {
+        Ok(climb_belongs_to::table
+            .filter(climb_belongs_to::area_id.eq_any(ids))
+            .select((climb_belongs_to::area_id.assume_not_null(), climb_belongs_to::climb_id))
+            .order(climb_belongs_to::climb_id)
+            .load(self.conn)?)
+    }
+
+    /// Gets a page of the climbs directly within each area, as `(area, climb)` pairs grouped by
+    /// area
+    pub fn climb_pages(&mut self, ids: &[i32], page: Page) -> Result<Vec<(i32, i32)>> {
+        child_pages(self.conn, "climb_belongs_to", "area_id", "climb_id", ids, page)
+    }
+
+    /// Sets the boundary of an area from a GeoJSON `Polygon` or `MultiPolygon`, or clears it.
+    /// GeoJSON which PostGIS cannot read is `InvalidInput`.
+    pub fn set_boundary(&mut self, id: i32, geojson: Option<&str>) -> Result<()> {
+        use crate::queries::{clear_area_boundary, set_area_boundary};
+
+        let updated = match geojson {
+            Some(geojson) => set_area_boundary(self.conn, id, geojson).optional().map_err(|e| match e {
+                // `ST_GeomFromGeoJSON` fails without a more specific SQLSTATE
+                DieselError::DatabaseError(DatabaseErrorKind::Unknown, info) => {
+                    Error::invalid_input(format!("Invalid GeoJSON: {}", info.message()))
+                }
+                e => e.into(),
+            })?,
+            None => clear_area_boundary(self.conn, id).optional()?,
+        };
+
+        updated.ok_or_else(|| not_found(Place::Area(id)))?;
+
+        Ok(())
+    }
+
+    /// Gets the boundaries of areas which have one
+    pub fn boundaries(&mut self, ids: &[i32]) -> Result<Vec<AreaBoundary>> {
+        use crate::queries::area_boundaries;
+
+        Ok(area_boundaries(self.conn, ids)?)
+    }
+
+    /// Gets the ids of areas whose boundary contains a point, innermost (smallest) first
+    pub fn containing(&mut self, point: &GeoPoint) -> Result<Vec<i32>> {
+        use crate::queries::areas_containing;
+
+        Ok(areas_containing(self.conn, point)?)
+    }
+
+    /// Moves an area into a super area, or out of its super area if none
+    pub fn move_to(&mut self, id: i32, super_area_id: Option<i32>) -> Result<()> {
+        use diesel::upsert::excluded;
+
+        find(self.conn, Place::Area(id))?;
+
+        match super_area_id {
+            Some(super_area_id) => diesel::insert_into(area_belongs_to::table)
+                .values(NewAreaBelongsTo { area_id: id, super_area_id })
+                .on_conflict(area_belongs_to::area_id)
+                .do_update()
+                .set(area_belongs_to::super_area_id.eq(excluded(area_belongs_to::super_area_id)))
+                .execute(self.conn)?,
+            None => diesel::delete(area_belongs_to::table.find(id)).execute(self.conn)?,
+        };
+
+        Ok(())
+    }
+
+    /// Removes an area. Fails with `Conflict` while anything is within it.
+    pub fn delete(&mut self, id: i32) -> Result<()> {
+        let deleted = diesel::delete(areas::table.find(id)).execute(self.conn)?;
+
+        if deleted == 0 {
+            return Err(not_found(Place::Area(id)));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,192 @@
+use std::ops::Bound;
+
+use chrono::NaiveDate;
+use diesel::prelude::*;
+use diesel::PgConnection;
+
+use super::{paginate, Error, Page, Result};
+use crate::grade::ParsedGrade;
+use crate::models::{Ascent, NewAscent, NewAscentGrade, NewAscentParty};
+use crate::schema::{ascent_grades, ascent_parties, ascents, grade_types, grades};
+
+/// A new ascent
+#[derive(Debug, Clone)]
+pub struct AscentInput {
+    pub climb_id: i32,
+    /// Days within which the ascent happened
+    pub date: Option<(Bound<NaiveDate>, Bound<NaiveDate>)>,
+    /// Climber ids of the ascent party
+    pub party: Vec<i32>,
+    /// Grade proposed by the ascent party
+    pub grade: Option<ParsedGrade>,
+}
+
+/// Changes of an ascent, leaving what is `None` unchanged
+#[derive(Debug, Clone, Default)]
+pub struct AscentUpdate {
+    /// Days within which the ascent happened, cleared if `Some(None)`
+    pub date: Option<Option<(Bound<NaiveDate>, Bound<NaiveDate>)>>,
+    /// Climber ids replacing the ascent party
+    pub party: Option<Vec<i32>>,
+    /// Grade proposed by the ascent party, cleared if `Some(None)`
+    pub grade: Option<Option<ParsedGrade>>,
+}
+
+/// Ascents, their parties and proposed grades
+pub struct AscentRepo<'a> {
+    conn: &'a mut PgConnection,
+}
+
+impl<'a> AscentRepo<'a> {
+    pub fn new(conn: &'a mut PgConnection) -> Self {
+        AscentRepo { conn }
+    }
+
+    /// Adds an ascent, returning its id
+    pub fn create(&mut self, input: AscentInput) -> Result<i32> {
+        self.conn.transaction(|conn| {
+            let id = diesel::insert_into(ascents::table)
+                .values(NewAscent {
+                    climb_id: input.climb_id,
+                    ascent_date: input.date,
+                })
+                .returning(ascents::id)
+                .get_result::<i32>(conn)?;
+
+            let mut repo = AscentRepo::new(conn);
+
+            repo.add_party(id, &input.party)?;
+
+            if let Some(grade) = &input.grade {
+                repo.add_grade(id, grade)?;
+            }
+
+            Ok(id)
+        })
+    }
+
+    /// Changes the date, party or proposed grade of an ascent
+    pub fn update(&mut self, id: i32, update: AscentUpdate) -> Result<()> {
+        self.conn.transaction(|conn| {
+            let mut repo = AscentRepo::new(conn);
+
+            repo.get(id)?;
+
+            if let Some(date) = update.date {
+                diesel::update(ascents::table.find(id))
+                    .set(ascents::ascent_date.eq(date))
+                    .execute(repo.conn)?;
+            }
+
+            if let Some(party) = update.party {
+                diesel::delete(ascent_parties::table.filter(ascent_parties::ascent_id.eq(id))).execute(repo.conn)?;
+                repo.add_party(id, &party)?;
+            }
+
+            if let Some(grade) = update.grade {
+                diesel::delete(ascent_grades::table.filter(ascent_grades::ascent_id.eq(id))).execute(repo.conn)?;
+
+                if let Some(grade) = &grade {
+                    repo.add_grade(id, grade)?;
+                }
+            }
+
+            Ok(())
+        })
+    }
+
+    pub fn get(&mut self, id: i32) -> Result<Ascent> {
+        ascents::table
+            .find(id)
+            .select(Ascent::as_select())
+            .first(self.conn)
+            .optional()?
+            .ok_or_else(|| Error::NotFound(format!("Ascent {id} not found")))
+    }
+
+    /// Gets the ascents of ids, skipping those which do not exist
+    pub fn get_many(&mut self, ids: &[i32]) -> Result<Vec<Ascent>> {
+        Ok(ascents::table
+            .filter(ascents::id.eq_any(ids))
+            .select(Ascent::as_select())
+            .load(self.conn)?)
+    }
+
+    /// Lists the ids of ascents, only those of a climb and with a climber in the party if given
+    pub fn list(&mut self, climb_id: Option<i32>, climber_id: Option<i32>, page: Page) -> Result<Vec<i32>> {
+        let mut query = ascents::table.select(ascents::id).into_boxed();
+
+        if let Some(climb_id) = climb_id {
+            query = query.filter(ascents::climb_id.eq(climb_id));
+        }
+
+        if let Some(climber_id) = climber_id {
+            query = query.filter(
+                ascents::id.eq_any(
+                    ascent_parties::table
+                        .filter(ascent_parties::climber_id.eq(climber_id))
+                        .select(ascent_parties::ascent_id),
+                ),
+            );
+        }
+
+        Ok(paginate!(query, ascents::id, page).load(self.conn)?)
+    }
+
+    /// Gets the parties of ascents, as `(ascent, climber)` pairs ordered by climber
+    pub fn parties(&mut self, ids: &[i32]) -> Result<Vec<(i32, i32)>> {
+        Ok(ascent_parties::table
+            .filter(ascent_parties::ascent_id.eq_any(ids))
+            .select((ascent_parties::ascent_id, ascent_parties::climber_id))
+            .order(ascent_parties::climber_id)
+            .load(self.conn)?)
+    }
+
+    /// Gets the grades proposed by the parties of ascents, as `(ascent, (grade type, grade))`
+    /// pairs
+    pub fn grades(&mut self, ids: &[i32]) -> Result<Vec<(i32, (String, String))>> {
+        Ok(ascent_grades::table
+            .inner_join(grades::table.inner_join(grade_types::table))
+            .filter(ascent_grades::ascent_id.eq_any(ids))
+            .select((ascent_grades::ascent_id, (grade_types::name, grades::value)))
+            .load(self.conn)?)
+    }
+
+    pub fn delete(&mut self, id: i32) -> Result<()> {
+        let deleted = diesel::delete(ascents::table.find(id)).execute(self.conn)?;
+
+        if deleted == 0 {
+            return Err(Error::NotFound(format!("Ascent {id} not found")));
+        }
+
+        Ok(())
+    }
+
+    /// Adds climbers to the party of an ascent
+    fn add_party(&mut self, id: i32, party: &[i32]) -> Result<()> {
+        let party: Vec<NewAscentParty> = party
+            .iter()
+            .map(|&climber_id| NewAscentParty { ascent_id: id, climber_id })
+            .collect();
+
+        diesel::insert_into(ascent_parties::table)
+            .values(&party)
+            .on_conflict_do_nothing()
+            .execute(self.conn)?;
+
+        Ok(())
+    }
+
+    /// Records the grade proposed by the party of an ascent
+    fn add_grade(&mut self, id: i32, grade: &ParsedGrade) -> Result<()> {
+        use crate::queries::upsert_grade;
+
+        let grade_id = upsert_grade(self.conn, grade)?;
+
+        diesel::insert_into(ascent_grades::table)
+            .values(NewAscentGrade { ascent_id: id, grade_id })
+            .execute(self.conn)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,43 @@
+use diesel::prelude::*;
+use diesel::PgConnection;
+
+use super::{Error, Result};
+use crate::audit::Change;
+use crate::queries::Place;
+
+/// Changes recorded in the audit log
+pub struct ChangeRepo<'a> {
+    conn: &'a mut PgConnection,
+}
+
+impl<'a> ChangeRepo<'a> {
+    pub fn new(conn: &'a mut PgConnection) -> Self {
+        ChangeRepo { conn }
+    }
+
+    pub fn get(&mut self, id: i64) -> Result<Change> {
+        use crate::audit::change;
+
+        change(self.conn, id).optional()?.ok_or_else(|| not_found(id))
+    }
+
+    /// Lists the latest changes of a place, newest first, including those of its names,
+    /// descriptions, grades and links
+    pub fn history(&mut self, place: Place, limit: i64) -> Result<Vec<Change>> {
+        use crate::audit::history;
+
+        Ok(history(self.conn, place, limit)?)
+    }
+
+    /// Reverts a change along with the other changes made with it, returning the changes made by
+    /// reverting. Fails with `Conflict` if a changed row has changed again since.
+    pub fn revert(&mut self, id: i64) -> Result<Vec<Change>> {
+        use crate::audit::revert_change;
+
+        revert_change(self.conn, id).optional()?.ok_or_else(|| not_found(id))
+    }
+}
+
+fn not_found(id: i64) -> Error {
+    Error::NotFound(format!("Change {id} not found"))
+}
@@ -0,0 +1,316 @@
+use diesel::prelude::*;
+use diesel::sql_types::{Bool, Integer};
+use diesel::PgConnection;
+
+use super::{
+    add_name, add_names, find, not_found, paginate, remove_description, remove_name,
+    set_descriptions, Error, Page, Parent, Result,
+};
+use crate::descriptions::Description;
+use crate::filter::{ClimbFilter, ClimbOrder};
+use crate::grade::{GradeSystem, ParsedGrade};
+use crate::models::{Climb, ClimbBelongsTo, NewClimbBelongsTo, NewClimbVariation};
+use crate::names::{Name, NameKind};
+use crate::queries::{ConsensusGrade, ConvertedGrade, GradeVotes, Place};
+use crate::schema::{ascents, climb_belongs_to, climb_grades, climb_variations, climbs, grade_types, grades};
+
+/// A new climb
+#[derive(Debug, Clone, Default)]
+pub struct ClimbInput {
+    /// Names in order, the first becoming the primary name
+    pub names: Vec<String>,
+    pub descriptions: Vec<Description>,
+    pub grades: Vec<ParsedGrade>,
+    pub parent: Option<Parent>,
+}
+
+/// A climb of a variation family
+#[derive(Debug, Clone, PartialEq, QueryableByName)]
+pub struct VariationFamilyMember {
+    #[diesel(sql_type = Integer)]
+    pub id: i32,
+    /// Whether the climb is a variation of no other climb
+    #[diesel(sql_type = Bool)]
+    pub is_root: bool,
+}
+
+/// Climbs, their grades and variations
+pub struct ClimbRepo<'a> {
+    conn: &'a mut PgConnection,
+}
+
+impl<'a> ClimbRepo<'a> {
+    pub fn new(conn: &'a mut PgConnection) -> Self {
+        ClimbRepo { conn }
+    }
+
+    /// Adds a climb, returning its id
+    pub fn create(&mut self, input: ClimbInput) -> Result<i32> {
+        self.conn.transaction(|conn| {
+            let id = diesel::insert_into(climbs::table)
+                .default_values()
+                .returning(climbs::id)
+                .get_result::<i32>(conn)?;
+
+            add_names(conn, Place::Climb(id), &input.names)?;
+            set_descriptions(conn, Place::Climb(id), &input.descriptions)?;
+
+            let mut repo = ClimbRepo::new(conn);
+
+            for grade in &input.grades {
+                repo.add_grade(id, grade)?;
+            }
+
+            if let Some(parent) = input.parent {
+                repo.move_to(id, Some(parent))?;
+            }
+
+            Ok(id)
+        })
+    }
+
+    pub fn get(&mut self, id: i32) -> Result<Climb> {
+        climbs::table
+            .find(id)
+            .select(Climb::as_select())
+            .first(self.conn)
+            .optional()?
+            .ok_or_else(|| not_found(Place::Climb(id)))
+    }
+
+    /// Gets the climbs of ids, skipping those which do not exist
+    pub fn get_many(&mut self, ids: &[i32]) -> Result<Vec<Climb>> {
+        Ok(climbs::table
+            .filter(climbs::id.eq_any(ids))
+            .select(Climb::as_select())
+            .load(self.conn)?)
+    }
+
+    /// Lists the ids of the climbs a filter matches
+    pub fn list(&mut self, filter: &ClimbFilter, page: Page) -> Result<Vec<i32>> {
+        use crate::filter::climbs_query;
+
+        let query = climbs_query(filter, ClimbOrder::default());
+
+        Ok(paginate!(query, climbs::id, page).load(self.conn)?)
+    }
+
+    /// Lists the ids of the climbs a filter matches in an order, failing with `InvalidInput` if a
+    /// cursor of the page is of a climb which no longer exists, as it no longer has a place in the
+    /// order
+    pub fn list_ordered(&mut self, filter: &ClimbFilter, order: ClimbOrder, page: Page) -> Result<Vec<i32>> {
+        use crate::filter::climbs_page_query;
+
+        if order == ClimbOrder::default() {
+            return self.list(filter, page);
+        }
+
+        for cursor in [page.after, page.before].into_iter().flatten() {
+            let exists = diesel::select(diesel::dsl::exists(climbs::table.find(cursor))).get_result::<bool>(self.conn)?;
+
+            if !exists {
+                return Err(Error::invalid_input(format!("Climb {cursor} of the cursor no longer exists")));
+            }
+        }
+
+        Ok(climbs_page_query(filter, order, page).load(self.conn)?)
+    }
+
+    /// Gets what climbs belong to, skipping climbs which belong to nothing
+    pub fn parents(&mut self, ids: &[i32]) -> Result<Vec<ClimbBelongsTo>> {
+        Ok(climb_belongs_to::table
+            .filter(climb_belongs_to::climb_id.eq_any(ids))
+            .select(ClimbBelongsTo::as_select())
+            .load(self.conn)?)
+    }
+
+    /// Moves a climb into an area or onto a formation, or out of its parent if none
+    pub fn move_to(&mut self, id: i32, parent: Option<Parent>) -> Result<()> {
+        use diesel::upsert::excluded;
+
+        find(self.conn, Place::Climb(id))?;
+
+        match parent {
+            Some(parent) => diesel::insert_into(climb_belongs_to::table)
+                .values(NewClimbBelongsTo {
+                    climb_id: id,
+                    area_id: parent.area_id(),
+                    formation_id: parent.formation_id(),
+                })
+                .on_conflict(climb_belongs_to::climb_id)
+                .do_update()
+                .set((
+                    climb_belongs_to::area_id.eq(excluded(climb_belongs_to::area_id)),
+                    climb_belongs_to::formation_id.eq(excluded(climb_belongs_to::formation_id)),
+                ))
+                .execute(self.conn)?,
+            None => diesel::delete(climb_belongs_to::table.find(id)).execute(self.conn)?,
+        };
+
+        Ok(())
+    }
+
+    /// Adds a name to a climb after its other names, as its primary name if it has none unless
+    /// given a kind
+    pub fn add_name(&mut self, id: i32, name: &str, kind: Option<NameKind>, language: Option<&str>) -> Result<Name> {
+        add_name(self.conn, Place::Climb(id), name, kind, language)
+    }
+
+    /// Removes every name of a climb with a value, in any language
+    pub fn remove_name(&mut self, id: i32, name: &str) -> Result<()> {
+        remove_name(self.conn, Place::Climb(id), name)
+    }
+
+    /// Sets a description of a climb, replacing any description of the same key
+    pub fn set_description(&mut self, id: i32, description: Description) -> Result<()> {
+        set_descriptions(self.conn, Place::Climb(id), &[description])
+    }
+
+    pub fn remove_description(&mut self, id: i32, key: &str) -> Result<()> {
+        remove_description(self.conn, Place::Climb(id), key)
+    }
+
+    /// Gets the grades of climbs, as `(climb, (grade type, grade))` pairs ordered by grade type
+    /// then difficulty
+    pub fn grades(&mut self, ids: &[i32]) -> Result<Vec<(i32, (String, String))>> {
+        Ok(climb_grades::table
+            .inner_join(grades::table.inner_join(grade_types::table))
+            .filter(climb_grades::climb_id.eq_any(ids))
+            .order((grade_types::id, grades::sort_key))
+            .select((climb_grades::climb_id, (grade_types::name, grades::value)))
+            .load(self.conn)?)
+    }
+
+    /// Grades a climb, doing nothing if the climb already has the grade
+    pub fn add_grade(&mut self, id: i32, grade: &ParsedGrade) -> Result<()> {
+        use crate::queries::add_climb_grade;
+
+        find(self.conn, Place::Climb(id))?;
+
+        Ok(add_climb_grade(self.conn, id, grade)?)
+    }
+
+    pub fn remove_grade(&mut self, id: i32, grade: &ParsedGrade) -> Result<()> {
+        use crate::queries::remove_climb_grade;
+
+        if remove_climb_grade(self.conn, id, grade)? == 0 {
+            return Err(Error::NotFound("Grade not found for the specified climb".to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Converts the grades of climbs into a grade type, ordered by climb then grade. Grades already
+    /// of the grade type are not converted.
+    pub fn converted_grades(&mut self, ids: &[i32], system: GradeSystem) -> Result<Vec<ConvertedGrade>> {
+        use crate::queries::converted_climb_grades;
+
+        Ok(converted_climb_grades(self.conn, ids, system)?)
+    }
+
+    /// Gets the consensus grades of climbs, ordered by climb, then most votes, then grade type
+    pub fn consensus_grades(&mut self, ids: &[i32]) -> Result<Vec<ConsensusGrade>> {
+        use crate::queries::consensus_grades;
+
+        Ok(consensus_grades(self.conn, ids)?)
+    }
+
+    /// Gets the votes for each grade proposed by the ascents of climbs, ordered by climb, grade
+    /// type and grade
+    pub fn grade_votes(&mut self, ids: &[i32]) -> Result<Vec<GradeVotes>> {
+        use crate::queries::grade_votes;
+
+        Ok(grade_votes(self.conn, ids)?)
+    }
+
+    /// Gets the variations of climbs, as `(root, variation)` pairs ordered by variation
+    pub fn variations(&mut self, ids: &[i32]) -> Result<Vec<(i32, i32)>> {
+        Ok(climb_variations::table
+            .filter(climb_variations::root_id.eq_any(ids))
+            .select((climb_variations::root_id, climb_variations::variation_id))
+            .order(climb_variations::variation_id)
+            .load(self.conn)?)
+    }
+
+    /// Gets the climbs climbs are variations of, as `(variation, root)` pairs ordered by root
+    pub fn variation_roots(&mut self, ids: &[i32]) -> Result<Vec<(i32, i32)>> {
+        Ok(climb_variations::table
+            .filter(climb_variations::variation_id.eq_any(ids))
+            .select((climb_variations::variation_id, climb_variations::root_id))
+            .order(climb_variations::root_id)
+            .load(self.conn)?)
+    }
+
+    /// Makes a climb a variation of another, doing nothing if it already is
+    pub fn add_variation(&mut self, root_id: i32, variation_id: i32) -> Result<()> {
+        diesel::insert_into(climb_variations::table)
+            .values(NewClimbVariation { root_id, variation_id })
+            .on_conflict_do_nothing()
+            .execute(self.conn)?;
+
+        Ok(())
+    }
+
+    pub fn remove_variation(&mut self, root_id: i32, variation_id: i32) -> Result<()> {
+        let removed = diesel::delete(climb_variations::table.find((root_id, variation_id))).execute(self.conn)?;
+
+        if removed == 0 {
+            return Err(Error::NotFound("Variation not found for the specified climb".to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Gets every climb in the variation family of a climb, that is, the roots it is a
+    /// (transitive) variation of and all of their transitive variations, by id
+    pub fn variation_family(&mut self, id: i32) -> Result<Vec<VariationFamilyMember>> {
+        find(self.conn, Place::Climb(id))?;
+
+        // `UNION` discards duplicate rows, so the recursion terminates even if a cycle slipped
+        // past the `prevent_climb_variation_cycle` trigger
+        Ok(diesel::sql_query(
+            "WITH RECURSIVE ancestors(id) AS (
+                SELECT $1
+                UNION
+                SELECT cv.root_id
+                FROM climb_variations cv
+                JOIN ancestors a ON cv.variation_id = a.id
+            ),
+            family(id) AS (
+                SELECT a.id
+                FROM ancestors a
+                WHERE NOT EXISTS (SELECT 1 FROM climb_variations cv WHERE cv.variation_id = a.id)
+                UNION
+                SELECT cv.variation_id
+                FROM climb_variations cv
+                JOIN family f ON cv.root_id = f.id
+            )
+            SELECT f.id,
+                NOT EXISTS (SELECT 1 FROM climb_variations cv WHERE cv.variation_id = f.id) AS is_root
+            FROM family f
+            ORDER BY f.id",
+        )
+        .bind::<Integer, _>(id)
+        .load(self.conn)?)
+    }
+
+    /// Gets the ascents of climbs, as `(climb, ascent)` pairs ordered by ascent
+    pub fn ascents(&mut self, ids: &[i32]) -> Result<Vec<(i32, i32)>> {
+        Ok(ascents::table
+            .filter(ascents::climb_id.eq_any(ids))
+            .select((ascents::climb_id, ascents::id))
+            .order(ascents::id)
+            .load(self.conn)?)
+    }
+
+    /// Removes a climb along with its ascents, names, descriptions, grades and variations
+    pub fn delete(&mut self, id: i32) -> Result<()> {
+        let deleted = diesel::delete(climbs::table.find(id)).execute(self.conn)?;
+
+        if deleted == 0 {
+            return Err(not_found(Place::Climb(id)));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,84 @@
+use diesel::prelude::*;
+use diesel::PgConnection;
+
+use super::{paginate, Error, Page, Result};
+use crate::models::{Climber, NewClimber};
+use crate::schema::{ascent_parties, climbers};
+
+/// Climbers and the ascents they were part of
+pub struct ClimberRepo<'a> {
+    conn: &'a mut PgConnection,
+}
+
+impl<'a> ClimberRepo<'a> {
+    pub fn new(conn: &'a mut PgConnection) -> Self {
+        ClimberRepo { conn }
+    }
+
+    /// Adds a climber, returning its id
+    pub fn create(&mut self, first_name: &str, last_name: &str) -> Result<i32> {
+        Ok(diesel::insert_into(climbers::table)
+            .values(NewClimber {
+                first_name: first_name.to_string(),
+                last_name: last_name.to_string(),
+            })
+            .returning(climbers::id)
+            .get_result(self.conn)?)
+    }
+
+    pub fn get(&mut self, id: i32) -> Result<Climber> {
+        climbers::table
+            .find(id)
+            .select(Climber::as_select())
+            .first(self.conn)
+            .optional()?
+            .ok_or_else(|| Error::NotFound(format!("Climber {id} not found")))
+    }
+
+    /// Gets the climbers of ids, skipping those which do not exist
+    pub fn get_many(&mut self, ids: &[i32]) -> Result<Vec<Climber>> {
+        Ok(climbers::table
+            .filter(climbers::id.eq_any(ids))
+            .select(Climber::as_select())
+            .load(self.conn)?)
+    }
+
+    /// Lists the ids of climbers
+    pub fn list(&mut self, page: Page) -> Result<Vec<i32>> {
+        let query = climbers::table.select(climbers::id).into_boxed();
+
+        Ok(paginate!(query, climbers::id, page).load(self.conn)?)
+    }
+
+    /// Renames a climber
+    pub fn update(&mut self, id: i32, first_name: &str, last_name: &str) -> Result<()> {
+        let updated = diesel::update(climbers::table.find(id))
+            .set((climbers::first_name.eq(first_name), climbers::last_name.eq(last_name)))
+            .execute(self.conn)?;
+
+        if updated == 0 {
+            return Err(Error::NotFound(format!("Climber {id} not found")));
+        }
+
+        Ok(())
+    }
+
+    /// Gets the ascents of climbers, as `(climber, ascent)` pairs ordered by ascent
+    pub fn ascents(&mut self, ids: &[i32]) -> Result<Vec<(i32, i32)>> {
+        Ok(ascent_parties::table
+            .filter(ascent_parties::climber_id.eq_any(ids))
+            .select((ascent_parties::climber_id, ascent_parties::ascent_id))
+            .order(ascent_parties::ascent_id)
+            .load(self.conn)?)
+    }
+    /// Removes a climber, along with them from the parties of their ascents
+    pub fn delete(&mut self, id: i32) -> Result<()> {
+        let deleted = diesel::delete(climbers::table.find(id)).execute(self.conn)?;
+
+        if deleted == 0 {
+            return Err(Error::NotFound(format!("Climber {id} not found")));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,39 @@
+use diesel::PgConnection;
+
+use super::Result;
+use crate::descriptions::Description;
+use crate::queries::Place;
+
+/// Types of description, and the descriptions of areas, formations and climbs
+pub struct DescriptionRepo<'a> {
+    conn: &'a mut PgConnection,
+}
+
+impl<'a> DescriptionRepo<'a> {
+    pub fn new(conn: &'a mut PgConnection) -> Self {
+        DescriptionRepo { conn }
+    }
+
+    /// Lists the keys of every description type, alphabetically
+    pub fn types(&mut self) -> Result<Vec<String>> {
+        use crate::descriptions::description_types;
+
+        Ok(description_types(self.conn)?)
+    }
+
+    /// Adds a type of description, failing with `Conflict` if its key is taken
+    pub fn add_type(&mut self, key: &str) -> Result<()> {
+        use crate::descriptions::add_description_type;
+
+        add_description_type(self.conn, key)?;
+
+        Ok(())
+    }
+
+    /// Gets the descriptions of places, as `(place, description)` pairs
+    pub fn get_many(&mut self, places: &[Place]) -> Result<Vec<(Place, Description)>> {
+        use crate::descriptions::descriptions;
+
+        Ok(descriptions(self.conn, places)?)
+    }
+}
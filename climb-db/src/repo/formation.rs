@@ -0,0 +1,224 @@
+use diesel::prelude::*;
+use diesel::PgConnection;
+
+use super::{
+    add_name, add_names, child_pages, find, not_found, paginate, remove_description, remove_name,
+    set_descriptions, Page, Parent, Result,
+};
+use crate::descriptions::Description;
+use crate::geo::GeoPoint;
+use crate::models::{Formation, FormationBelongsTo, NewFormation, NewFormationBelongsTo};
+use crate::names::{Name, NameKind};
+use crate::queries::{FormationDistance, FormationOutsideArea, Place};
+use crate::schema::{climb_belongs_to, formation_belongs_to, formations};
+
+/// A new formation
+#[derive(Debug, Clone, Default)]
+pub struct FormationInput {
+    /// Names in order, the first becoming the primary name
+    pub names: Vec<String>,
+    pub descriptions: Vec<Description>,
+    pub location: Option<GeoPoint>,
+    pub parent: Option<Parent>,
+}
+
+/// Formations and the climbs on them
+pub struct FormationRepo<'a> {
+    conn: &'a mut PgConnection,
+}
+
+impl<'a> FormationRepo<'a> {
+    pub fn new(conn: &'a mut PgConnection) -> Self {
+        FormationRepo { conn }
+    }
+
+    /// Adds a formation, returning its id
+    pub fn create(&mut self, input: FormationInput) -> Result<i32> {
+        self.conn.transaction(|conn| {
+            let id = diesel::insert_into(formations::table)
+                .values(NewFormation { location: input.location })
+                .returning(formations::id)
+                .get_result::<i32>(conn)?;
+
+            add_names(conn, Place::Formation(id), &input.names)?;
+            set_descriptions(conn, Place::Formation(id), &input.descriptions)?;
+
+            if let Some(parent) = input.parent {
+                FormationRepo::new(conn).move_to(id, Some(parent))?;
+            }
+
+            Ok(id)
+        })
+    }
+
+    pub fn get(&mut self, id: i32) -> Result<Formation> {
+        formations::table
+            .find(id)
+            .select(Formation::as_select())
+            .first(self.conn)
+            .optional()?
+            .ok_or_else(|| not_found(Place::Formation(id)))
+    }
+
+    /// Gets the formations of ids, skipping those which do not exist
+    pub fn get_many(&mut self, ids: &[i32]) -> Result<Vec<Formation>> {
+        Ok(formations::table
+            .filter(formations::id.eq_any(ids))
+            .select(Formation::as_select())
+            .load(self.conn)?)
+    }
+
+    /// Lists the ids of formations, only those directly within a parent if given
+    pub fn list(&mut self, parent: Option<Parent>, page: Page) -> Result<Vec<i32>> {
+        let mut query = formations::table
+            .left_join(formation_belongs_to::table.on(formation_belongs_to::formation_id.eq(formations::id)))
+            .select(formations::id)
+            .into_boxed();
+
+        match parent {
+            Some(Parent::Area(id)) => query = query.filter(formation_belongs_to::area_id.eq(id)),
+            Some(Parent::Formation(id)) => query = query.filter(formation_belongs_to::super_formation_id.eq(id)),
+            None => {}
+        }
+
+        Ok(paginate!(query, formations::id, page).load(self.conn)?)
+    }
+
+    /// Gets what formations belong to, skipping formations which belong to nothing
+    pub fn parents(&mut self, ids: &[i32]) -> Result<Vec<FormationBelongsTo>> {
+        Ok(formation_belongs_to::table
+            .filter(formation_belongs_to::formation_id.eq_any(ids))
+            .select(FormationBelongsTo::as_select())
+            .load(self.conn)?)
+    }
+
+    /// Gets the sub formations of formations, as `(formation, sub formation)` pairs ordered by
+    /// sub formation
+    pub fn sub_formations(&mut self, ids: &[i32]) -> Result<Vec<(i32, i32)>> {
+        Ok(formation_belongs_to::table
+            .filter(formation_belongs_to::super_formation_id.eq_any(ids))
+            .select((formation_belongs_to::super_formation_id.assume_not_null(), formation_belongs_to::formation_id))
+            .order(formation_belongs_to::formation_id)
+            .load(self.conn)?)
+    }
+
+    /// Gets a page of the sub formations of each formation, as `(formation, sub formation)` pairs
+    /// grouped by formation
+    pub fn sub_formation_pages(&mut self, ids: &[i32], page: Page) -> Result<Vec<(i32, i32)>> {
+        child_pages(self.conn, "formation_belongs_to", "super_formation_id", "formation_id", ids, page)
+    }
+
+    /// Gets the climbs directly on formations, as `(formation, climb)` pairs ordered by climb
+    pub fn climbs(&mut self, ids: &[i32]) -> Result<Vec<(i32, i32)>> {
+        Ok(climb_belongs_to::table
+            .filter(climb_belongs_to::formation_id.eq_any(ids))
+            .select((climb_belongs_to::formation_id.assume_not_null(), climb_belongs_to::climb_id))
+            .order(climb_belongs_to::climb_id)
+            .load(self.conn)?)
+    }
+
+    /// Gets a page of the climbs directly on each formation, as `(formation, climb)` pairs grouped
+    /// by formation
+    pub fn climb_pages(&mut self, ids: &[i32], page: Page) -> Result<Vec<(i32, i32)>> {
+        child_pages(self.conn, "climb_belongs_to", "formation_id", "climb_id", ids, page)
+    }
+
+    /// Adds a name to a formation after its other names, as its primary name if it has none unless
+    /// given a kind
+    pub fn add_name(&mut self, id: i32, name: &str, kind: Option<NameKind>, language: Option<&str>) -> Result<Name> {
+        add_name(self.conn, Place::Formation(id), name, kind, language)
+    }
+
+    /// Removes every name of a formation with a value, in any language
+    pub fn remove_name(&mut self, id: i32, name: &str) -> Result<()> {
+        remove_name(self.conn, Place::Formation(id), name)
+    }
+
+    /// Sets a description of a formation, replacing any description of the same key
+    pub fn set_description(&mut self, id: i32, description: Description) -> Result<()> {
+        set_descriptions(self.conn, Place::Formation(id), &[description])
+    }
+
+    pub fn remove_description(&mut self, id: i32, key: &str) -> Result<()> {
+        remove_description(self.conn, Place::Formation(id), key)
+    }
+
+    /// Sets or clears the location of a formation
+    pub fn set_location(&mut self, id: i32, location: Option<GeoPoint>) -> Result<()> {
+        let updated = diesel::update(formations::table.find(id))
+            .set(formations::location.eq(location))
+            .execute(self.conn)?;
+
+        if updated == 0 {
+            return Err(not_found(Place::Formation(id)));
+        }
+
+        Ok(())
+    }
+
+    /// Gets the ids of formations located within a bounding box, ordered by id. A box whose south
+    /// west corner is east of its north east corner crosses the antimeridian.
+    pub fn within(&mut self, south_west: &GeoPoint, north_east: &GeoPoint) -> Result<Vec<i32>> {
+        use crate::queries::formations_within;
+
+        Ok(formations_within(self.conn, south_west, north_east)?)
+    }
+
+    /// Gets formations located within a radius, in meters, of a point, nearest first
+    pub fn near(&mut self, point: &GeoPoint, radius: f64) -> Result<Vec<FormationDistance>> {
+        use crate::queries::formations_near;
+
+        Ok(formations_near(self.conn, point, radius)?)
+    }
+
+    /// Gets at most `limit` formations nearest to a point, nearest first
+    pub fn nearest(&mut self, point: &GeoPoint, limit: i64) -> Result<Vec<FormationDistance>> {
+        use crate::queries::nearest_formations;
+
+        Ok(nearest_formations(self.conn, point, limit)?)
+    }
+
+    /// Gets formations located outside of the boundary of their area, ordered by formation id
+    pub fn outside_areas(&mut self) -> Result<Vec<FormationOutsideArea>> {
+        use crate::queries::formations_outside_areas;
+
+        Ok(formations_outside_areas(self.conn)?)
+    }
+
+    /// Moves a formation into an area or onto a super formation, or out of its parent if none
+    pub fn move_to(&mut self, id: i32, parent: Option<Parent>) -> Result<()> {
+        use diesel::upsert::excluded;
+
+        find(self.conn, Place::Formation(id))?;
+
+        match parent {
+            Some(parent) => diesel::insert_into(formation_belongs_to::table)
+                .values(NewFormationBelongsTo {
+                    formation_id: id,
+                    area_id: parent.area_id(),
+                    super_formation_id: parent.formation_id(),
+                })
+                .on_conflict(formation_belongs_to::formation_id)
+                .do_update()
+                .set((
+                    formation_belongs_to::area_id.eq(excluded(formation_belongs_to::area_id)),
+                    formation_belongs_to::super_formation_id.eq(excluded(formation_belongs_to::super_formation_id)),
+                ))
+                .execute(self.conn)?,
+            None => diesel::delete(formation_belongs_to::table.find(id)).execute(self.conn)?,
+        };
+
+        Ok(())
+    }
+
+    /// Removes a formation. Fails with `Conflict` while anything is on it.
+    pub fn delete(&mut self, id: i32) -> Result<()> {
+        let deleted = diesel::delete(formations::table.find(id)).execute(self.conn)?;
+
+        if deleted == 0 {
+            return Err(not_found(Place::Formation(id)));
+        }
+
+        Ok(())
+    }
+}
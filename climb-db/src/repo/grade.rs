@@ -0,0 +1,45 @@
+use diesel::PgConnection;
+
+use super::{Error, Result};
+use crate::grade::{GradeSystem, ParsedGrade};
+use crate::queries::KnownGrade;
+
+/// Grades and their conversions to the common scale of difficulty
+pub struct GradeRepo<'a> {
+    conn: &'a mut PgConnection,
+}
+
+impl<'a> GradeRepo<'a> {
+    pub fn new(conn: &'a mut PgConnection) -> Self {
+        GradeRepo { conn }
+    }
+
+    /// Lists the grades known to the database, only those of a system if given, ordered by grade
+    /// type then sort key
+    pub fn list(&mut self, system: Option<GradeSystem>) -> Result<Vec<KnownGrade>> {
+        use crate::queries::known_grades;
+
+        Ok(known_grades(self.conn, system)?)
+    }
+
+    /// Places a grade on the common scale of difficulty, replacing its previous difficulty
+    pub fn set_conversion(&mut self, grade: &ParsedGrade, difficulty: f64) -> Result<()> {
+        use crate::queries::set_grade_conversion;
+
+        if !difficulty.is_finite() {
+            return Err(Error::invalid_input("Difficulty must be a finite number"));
+        }
+
+        Ok(set_grade_conversion(self.conn, grade, difficulty)?)
+    }
+
+    pub fn remove_conversion(&mut self, grade: &ParsedGrade) -> Result<()> {
+        use crate::queries::remove_grade_conversion;
+
+        if remove_grade_conversion(self.conn, grade)? == 0 {
+            return Err(Error::NotFound(format!("Conversion of grade {} not found", grade.value)));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,35 @@
+use diesel::PgConnection;
+
+use super::{Error, Result};
+use crate::search::{SearchHit, SearchKind};
+
+/// Searches of the names and descriptions of areas, formations and climbs
+pub struct SearchRepo<'a> {
+    conn: &'a mut PgConnection,
+}
+
+impl<'a> SearchRepo<'a> {
+    /// Most results a search may be given
+    pub const MAX_LIMIT: i64 = 100;
+
+    pub fn new(conn: &'a mut PgConnection) -> Self {
+        SearchRepo { conn }
+    }
+
+    /// Searches for places of kinds, best matches first, as [`search`](crate::search::search)
+    /// does. Fails with `InvalidInput` given an empty query or a limit outside of
+    /// `1..=MAX_LIMIT`.
+    pub fn search(&mut self, query: &str, kinds: &[SearchKind], limit: i64) -> Result<Vec<SearchHit>> {
+        use crate::search::search;
+
+        if query.trim().is_empty() {
+            return Err(Error::invalid_input("Query must not be empty"));
+        }
+
+        if !(1..=Self::MAX_LIMIT).contains(&limit) {
+            return Err(Error::invalid_input(format!("Limit must be within [1, {}]", Self::MAX_LIMIT)));
+        }
+
+        Ok(search(self.conn, query, kinds, limit)?)
+    }
+}
@@ -0,0 +1,85 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel::PgConnection;
+
+use super::{Error, Result};
+use crate::models::User;
+use crate::users::Role;
+
+/// Users, their roles and API tokens
+pub struct UserRepo<'a> {
+    conn: &'a mut PgConnection,
+}
+
+impl<'a> UserRepo<'a> {
+    pub fn new(conn: &'a mut PgConnection) -> Self {
+        UserRepo { conn }
+    }
+
+    /// Adds a user, failing with `Conflict` if the name is taken
+    pub fn create(&mut self, name: &str, role: Role) -> Result<User> {
+        use crate::users::add_user;
+
+        Ok(add_user(self.conn, name, role)?)
+    }
+
+    /// Gets the users of ids, skipping those which do not exist
+    pub fn get_many(&mut self, ids: &[i32]) -> Result<Vec<User>> {
+        use crate::users::users;
+
+        Ok(users(self.conn, ids)?)
+    }
+
+    pub fn by_name(&mut self, name: &str) -> Result<Option<User>> {
+        use crate::users::user_by_name;
+
+        Ok(user_by_name(self.conn, name).optional()?)
+    }
+
+    /// Gets the user an unexpired API token belongs to, if any, noting the use of the token
+    pub fn by_api_token(&mut self, token: &str) -> Result<Option<User>> {
+        use crate::users::user_by_api_token;
+
+        Ok(user_by_api_token(self.conn, token)?)
+    }
+
+    /// Changes the role of a user, returning the user
+    pub fn set_role(&mut self, id: i32, role: Role) -> Result<User> {
+        use crate::users::set_role;
+
+        set_role(self.conn, id, role)
+            .optional()?
+            .ok_or_else(|| Error::NotFound(format!("User {id} not found")))
+    }
+
+    /// Adds an API token for a user, returning its id and the token, which cannot be shown again
+    pub fn add_api_token(
+        &mut self,
+        user_id: i32,
+        label: Option<&str>,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<(i32, String)> {
+        use crate::users::add_api_token;
+
+        Ok(add_api_token(self.conn, user_id, label, expires_at)?)
+    }
+
+    /// Revokes an API token of a user, failing with `NotFound` unless the user has the token
+    pub fn revoke_api_token(&mut self, user_id: i32, token_id: i32) -> Result<()> {
+        use crate::users::revoke_api_token;
+
+        if !revoke_api_token(self.conn, user_id, token_id)? {
+            return Err(Error::NotFound(format!("API token {token_id} not found")));
+        }
+
+        Ok(())
+    }
+
+    /// Records a user as the actor of the changes made in the rest of the current transaction, as
+    /// [`act_as`](crate::users::act_as) does
+    pub fn act_as(&mut self, user: Option<&User>) -> Result<()> {
+        use crate::users::act_as;
+
+        Ok(act_as(self.conn, user)?)
+    }
+}
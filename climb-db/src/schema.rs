@@ -1,5 +1,20 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    use postgis_diesel::sql_types::*;
+    use diesel::sql_types::*;
+
+    api_tokens (id) {
+        id -> Int4,
+        user_id -> Int4,
+        token_hash -> Bytea,
+        label -> Nullable<Text>,
+        created_at -> Timestamptz,
+        expires_at -> Nullable<Timestamptz>,
+        last_used_at -> Nullable<Timestamptz>,
+    }
+}
+
 diesel::table! {
     use postgis_diesel::sql_types::*;
     use diesel::sql_types::*;
@@ -10,13 +25,41 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    use postgis_diesel::sql_types::*;
+    use diesel::sql_types::*;
+
+    area_descriptions (area_id, description_type_id) {
+        area_id -> Int4,
+        description_type_id -> Int4,
+        value -> Text,
+    }
+}
+
+diesel::table! {
+    use postgis_diesel::sql_types::*;
+    use diesel::sql_types::*;
+
+    area_names (id) {
+        id -> Int4,
+        area_id -> Int4,
+        value -> Text,
+        #[max_length = 35]
+        language -> Nullable<Varchar>,
+        #[max_length = 20]
+        kind -> Varchar,
+        ordering -> Int4,
+    }
+}
+
 diesel::table! {
     use postgis_diesel::sql_types::*;
     use diesel::sql_types::*;
 
     areas (id) {
         id -> Int4,
-        names -> Array<Nullable<Text>>,
+        boundary -> Nullable<Geometry>,
+        created_by -> Nullable<Int4>,
     }
 }
 
@@ -48,6 +91,28 @@ diesel::table! {
         id -> Int4,
         climb_id -> Int4,
         ascent_date -> Nullable<Daterange>,
+        created_by -> Nullable<Int4>,
+    }
+}
+
+diesel::table! {
+    use postgis_diesel::sql_types::*;
+    use diesel::sql_types::*;
+
+    audit_log (id) {
+        id -> Int8,
+        changed_at -> Timestamptz,
+        actor -> Nullable<Text>,
+        transaction_id -> Int8,
+        table_name -> Text,
+        #[max_length = 6]
+        operation -> Varchar,
+        #[max_length = 20]
+        entity_kind -> Varchar,
+        entity_id -> Int4,
+        before -> Nullable<Jsonb>,
+        after -> Nullable<Jsonb>,
+        reverts -> Nullable<Int8>,
     }
 }
 
@@ -66,10 +131,13 @@ diesel::table! {
     use postgis_diesel::sql_types::*;
     use diesel::sql_types::*;
 
-    climb_description_types (id) {
-        id -> Int4,
-        #[max_length = 100]
-        name -> Varchar,
+    climb_consensus_grades (climb_id, grade_type_id) {
+        climb_id -> Int4,
+        grade_type_id -> Int4,
+        median_grade_id -> Int4,
+        mode_grade_id -> Int4,
+        votes -> Int4,
+        spread -> Nullable<Float8>,
     }
 }
 
@@ -77,9 +145,9 @@ diesel::table! {
     use postgis_diesel::sql_types::*;
     use diesel::sql_types::*;
 
-    climb_descriptions (climb_id, climb_description_type_id) {
+    climb_descriptions (climb_id, description_type_id) {
         climb_id -> Int4,
-        climb_description_type_id -> Int4,
+        description_type_id -> Int4,
         value -> Text,
     }
 }
@@ -98,9 +166,25 @@ diesel::table! {
     use postgis_diesel::sql_types::*;
     use diesel::sql_types::*;
 
-    climb_vermin_grades (climb_id, value) {
+    climb_grades (climb_id, grade_id) {
+        climb_id -> Int4,
+        grade_id -> Int4,
+    }
+}
+
+diesel::table! {
+    use postgis_diesel::sql_types::*;
+    use diesel::sql_types::*;
+
+    climb_names (id) {
+        id -> Int4,
         climb_id -> Int4,
-        value -> Int4,
+        value -> Text,
+        #[max_length = 35]
+        language -> Nullable<Varchar>,
+        #[max_length = 20]
+        kind -> Varchar,
+        ordering -> Int4,
     }
 }
 
@@ -114,6 +198,7 @@ diesel::table! {
         first_name -> Varchar,
         #[max_length = 100]
         last_name -> Varchar,
+        created_by -> Nullable<Int4>,
     }
 }
 
@@ -123,7 +208,19 @@ diesel::table! {
 
     climbs (id) {
         id -> Int4,
-        names -> Array<Nullable<Text>>,
+        difficulty -> Nullable<Float8>,
+        created_by -> Nullable<Int4>,
+    }
+}
+
+diesel::table! {
+    use postgis_diesel::sql_types::*;
+    use diesel::sql_types::*;
+
+    description_types (id) {
+        id -> Int4,
+        #[max_length = 100]
+        name -> Varchar,
     }
 }
 
@@ -138,14 +235,60 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    use postgis_diesel::sql_types::*;
+    use diesel::sql_types::*;
+
+    formation_descriptions (formation_id, description_type_id) {
+        formation_id -> Int4,
+        description_type_id -> Int4,
+        value -> Text,
+    }
+}
+
+diesel::table! {
+    use postgis_diesel::sql_types::*;
+    use diesel::sql_types::*;
+
+    formation_names (id) {
+        id -> Int4,
+        formation_id -> Int4,
+        value -> Text,
+        #[max_length = 35]
+        language -> Nullable<Varchar>,
+        #[max_length = 20]
+        kind -> Varchar,
+        ordering -> Int4,
+    }
+}
+
 diesel::table! {
     use postgis_diesel::sql_types::*;
     use diesel::sql_types::*;
 
     formations (id) {
         id -> Int4,
-        names -> Array<Nullable<Text>>,
         location -> Nullable<Geometry>,
+        created_by -> Nullable<Int4>,
+    }
+}
+
+diesel::table! {
+    use postgis_diesel::sql_types::*;
+    use diesel::sql_types::*;
+
+    grade_conversions (grade_id) {
+        grade_id -> Int4,
+        difficulty -> Float8,
+    }
+}
+
+diesel::table! {
+    use postgis_diesel::sql_types::*;
+    use diesel::sql_types::*;
+
+    grade_sort_key_version (version) {
+        version -> Int4,
     }
 }
 
@@ -169,6 +312,19 @@ diesel::table! {
         grade_type_id -> Int4,
         #[max_length = 50]
         value -> Varchar,
+        sort_key -> Int4,
+    }
+}
+
+diesel::table! {
+    use postgis_diesel::sql_types::*;
+    use diesel::sql_types::*;
+
+    pending_grade_conversions (grade_type_id, value) {
+        grade_type_id -> Int4,
+        #[max_length = 50]
+        value -> Varchar,
+        difficulty -> Float8,
     }
 }
 
@@ -188,34 +344,79 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    use postgis_diesel::sql_types::*;
+    use diesel::sql_types::*;
+
+    users (id) {
+        id -> Int4,
+        name -> Text,
+        #[max_length = 20]
+        role -> Varchar,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::joinable!(api_tokens -> users (user_id));
+diesel::joinable!(area_descriptions -> areas (area_id));
+diesel::joinable!(area_descriptions -> description_types (description_type_id));
+diesel::joinable!(area_names -> areas (area_id));
+diesel::joinable!(areas -> users (created_by));
 diesel::joinable!(ascent_grades -> ascents (ascent_id));
 diesel::joinable!(ascent_grades -> grades (grade_id));
+diesel::joinable!(ascent_parties -> ascents (ascent_id));
+diesel::joinable!(ascent_parties -> climbers (climber_id));
 diesel::joinable!(ascents -> climbs (climb_id));
+diesel::joinable!(ascents -> users (created_by));
 diesel::joinable!(climb_belongs_to -> areas (area_id));
 diesel::joinable!(climb_belongs_to -> climbs (climb_id));
 diesel::joinable!(climb_belongs_to -> formations (formation_id));
-diesel::joinable!(climb_descriptions -> climb_description_types (climb_description_type_id));
+diesel::joinable!(climb_consensus_grades -> climbs (climb_id));
+diesel::joinable!(climb_consensus_grades -> grade_types (grade_type_id));
+diesel::joinable!(climb_descriptions -> description_types (description_type_id));
 diesel::joinable!(climb_descriptions -> climbs (climb_id));
-diesel::joinable!(climb_vermin_grades -> climbs (climb_id));
+diesel::joinable!(climb_grades -> climbs (climb_id));
+diesel::joinable!(climb_grades -> grades (grade_id));
+diesel::joinable!(climb_names -> climbs (climb_id));
+diesel::joinable!(climbers -> users (created_by));
+diesel::joinable!(climbs -> users (created_by));
 diesel::joinable!(formation_belongs_to -> areas (area_id));
+diesel::joinable!(formation_descriptions -> description_types (description_type_id));
+diesel::joinable!(formation_descriptions -> formations (formation_id));
+diesel::joinable!(formation_names -> formations (formation_id));
+diesel::joinable!(formations -> users (created_by));
+diesel::joinable!(grade_conversions -> grades (grade_id));
 diesel::joinable!(grades -> grade_types (grade_type_id));
+diesel::joinable!(pending_grade_conversions -> grade_types (grade_type_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    api_tokens,
     area_belongs_to,
+    area_descriptions,
+    area_names,
     areas,
     ascent_grades,
     ascent_parties,
     ascents,
+    audit_log,
     climb_belongs_to,
-    climb_description_types,
+    climb_consensus_grades,
     climb_descriptions,
+    climb_grades,
+    climb_names,
     climb_variations,
-    climb_vermin_grades,
     climbers,
     climbs,
+    description_types,
     formation_belongs_to,
+    formation_descriptions,
+    formation_names,
     formations,
+    grade_conversions,
+    grade_sort_key_version,
     grade_types,
     grades,
+    pending_grade_conversions,
     spatial_ref_sys,
+    users,
 );
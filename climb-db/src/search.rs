@@ -0,0 +1,201 @@
+use diesel::prelude::*;
+use diesel::sql_types::{Array, BigInt, Double, Integer, Text};
+use diesel::PgConnection;
+
+/// Kinds of things which are searched
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SearchKind {
+    Area,
+    Formation,
+    Climb,
+}
+
+impl SearchKind {
+    pub const ALL: [SearchKind; 3] = [SearchKind::Area, SearchKind::Formation, SearchKind::Climb];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SearchKind::Area => "area",
+            SearchKind::Formation => "formation",
+            SearchKind::Climb => "climb",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        SearchKind::ALL.into_iter().find(|kind| kind.name() == name)
+    }
+}
+
+/// A search result
+#[derive(Debug, Clone, PartialEq, QueryableByName)]
+pub struct SearchHit {
+    /// One of the `SearchKind` names
+    #[diesel(sql_type = Text)]
+    pub kind: String,
+    #[diesel(sql_type = Integer)]
+    pub id: i32,
+    /// The best matching name, or the primary name if only a description matched
+    #[diesel(sql_type = Text)]
+    pub name: String,
+    /// The matched name, or an excerpt of the matched description, as HTML escaped text with
+    /// matched words wrapped in `<mark>` tags
+    #[diesel(sql_type = Text)]
+    pub highlight: String,
+    #[diesel(sql_type = Double)]
+    pub rank: f64,
+}
+
+/// Searches the names and descriptions of areas, formations and climbs, best matches first.
+///
+/// Words are matched by full-text search, and names are also matched by trigram similarity to
+/// tolerate typos. Both ignore case and accents.
+pub fn search(
+    conn: &mut PgConnection,
+    query: &str,
+    kinds: &[SearchKind],
+    limit: i64,
+) -> QueryResult<Vec<SearchHit>> {
+    let kinds: Vec<&str> = kinds.iter().map(SearchKind::name).collect();
+
+    // Each table is filtered on the expressions of its full-text and trigram indexes, so the
+    // indexes are used, and only the matches are ranked and highlighted.
+    //
+    // Matches are delimited by control characters, stripped from the text beforehand, so the text
+    // can be escaped before they become `<mark>` tags.
+    //
+    // Names are ranked by the better of their full-text rank and similarity, which are both within
+    // [0, 1], and each thing by its best matching name. Description matches are ranked below equal
+    // name matches.
+    diesel::sql_query(
+        "WITH named(kind, id, name) AS (
+            SELECT 'area', area_id, value
+            FROM area_names
+            WHERE 'area' = ANY($2)
+                AND (to_tsvector('climb_search', value) @@ websearch_to_tsquery('climb_search', $1)
+                    OR search_fold($1) <% search_fold(value))
+            UNION ALL
+            SELECT 'formation', formation_id, value
+            FROM formation_names
+            WHERE 'formation' = ANY($2)
+                AND (to_tsvector('climb_search', value) @@ websearch_to_tsquery('climb_search', $1)
+                    OR search_fold($1) <% search_fold(value))
+            UNION ALL
+            SELECT 'climb', climb_id, value
+            FROM climb_names
+            WHERE 'climb' = ANY($2)
+                AND (to_tsvector('climb_search', value) @@ websearch_to_tsquery('climb_search', $1)
+                    OR search_fold($1) <% search_fold(value))
+        ),
+        described(kind, id, name, value) AS (
+            SELECT 'area',
+                d.area_id,
+                (
+                    SELECT value
+                    FROM area_names
+                    WHERE area_id = d.area_id
+                    ORDER BY kind <> 'primary', ordering, id
+                    LIMIT 1
+                ),
+                d.value
+            FROM area_descriptions d
+            WHERE 'area' = ANY($2)
+                AND to_tsvector('climb_search', d.value) @@ websearch_to_tsquery('climb_search', $1)
+            UNION ALL
+            SELECT 'formation',
+                d.formation_id,
+                (
+                    SELECT value
+                    FROM formation_names
+                    WHERE formation_id = d.formation_id
+                    ORDER BY kind <> 'primary', ordering, id
+                    LIMIT 1
+                ),
+                d.value
+            FROM formation_descriptions d
+            WHERE 'formation' = ANY($2)
+                AND to_tsvector('climb_search', d.value) @@ websearch_to_tsquery('climb_search', $1)
+            UNION ALL
+            SELECT 'climb',
+                d.climb_id,
+                (
+                    SELECT value
+                    FROM climb_names
+                    WHERE climb_id = d.climb_id
+                    ORDER BY kind <> 'primary', ordering, id
+                    LIMIT 1
+                ),
+                d.value
+            FROM climb_descriptions d
+            WHERE 'climb' = ANY($2)
+                AND to_tsvector('climb_search', d.value) @@ websearch_to_tsquery('climb_search', $1)
+        ),
+        q(tsquery, folded, name_options, description_options) AS (
+            SELECT websearch_to_tsquery('climb_search', $1),
+                search_fold($1),
+                'StartSel=' || chr(1) || ', StopSel=' || chr(2) || ', HighlightAll=true',
+                'StartSel=' || chr(1) || ', StopSel=' || chr(2) || ', MaxFragments=1'
+        ),
+        name_hits(kind, id, name, highlight, rank) AS (
+            SELECT n.kind,
+                n.id,
+                n.name,
+                ts_headline('climb_search', translate(n.name, chr(1) || chr(2), ''), q.tsquery, q.name_options),
+                greatest(
+                    ts_rank(to_tsvector('climb_search', n.name), q.tsquery, 1),
+                    word_similarity(q.folded, search_fold(n.name))
+                )
+            FROM named n
+            CROSS JOIN q
+        ),
+        description_hits(kind, id, name, highlight, rank) AS (
+            SELECT d.kind,
+                d.id,
+                COALESCE(d.name, ''),
+                ts_headline('climb_search', translate(d.value, chr(1) || chr(2), ''), q.tsquery, q.description_options),
+                ts_rank(to_tsvector('climb_search', d.value), q.tsquery, 1) / 2
+            FROM described d
+            CROSS JOIN q
+        )
+        SELECT kind, id, name, highlight, rank::double precision AS rank
+        FROM (
+            SELECT DISTINCT ON (kind, id) *
+            FROM (
+                SELECT * FROM name_hits
+                UNION ALL
+                SELECT * FROM description_hits
+            ) hits
+            ORDER BY kind, id, rank DESC
+        ) best
+        ORDER BY rank DESC, kind, id
+        LIMIT $3",
+    )
+    .bind::<Text, _>(query)
+    .bind::<Array<Text>, _>(kinds)
+    .bind::<BigInt, _>(limit)
+    .load::<SearchHit>(conn)
+    .map(|hits| {
+        hits.into_iter()
+            .map(|hit| SearchHit { highlight: escape_highlight(&hit.highlight), ..hit })
+            .collect()
+    })
+}
+
+/// Escapes a headline as HTML, turning the control characters delimiting matches into `<mark>` tags
+fn escape_highlight(headline: &str) -> String {
+    let mut escaped = String::with_capacity(headline.len());
+
+    for c in headline.chars() {
+        match c {
+            '\u{1}' => escaped.push_str("<mark>"),
+            '\u{2}' => escaped.push_str("</mark>"),
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
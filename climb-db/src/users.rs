@@ -0,0 +1,166 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel::sql_types::{Integer, Nullable, Text, Timestamptz};
+use diesel::PgConnection;
+
+use crate::models::User;
+
+/// Roles of users, each allowed everything the roles before it are
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Role {
+    /// May read, but not change anything
+    Reader,
+    /// May add and edit areas, formations, climbs and ascents
+    Contributor,
+    /// May also remove entities and revert changes
+    Moderator,
+    /// May also manage users
+    Admin,
+}
+
+impl Role {
+    pub const ALL: [Role; 4] = [Role::Reader, Role::Contributor, Role::Moderator, Role::Admin];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Role::Reader => "reader",
+            Role::Contributor => "contributor",
+            Role::Moderator => "moderator",
+            Role::Admin => "admin",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Role::ALL.into_iter().find(|role| role.name() == name)
+    }
+}
+
+/// Prefix of API tokens, telling them apart from other bearer tokens such as JWTs
+pub const API_TOKEN_PREFIX: &str = "climb_";
+
+/// Adds a user. Adding a user with the name of another is an error.
+pub fn add_user(conn: &mut PgConnection, name: &str, role: Role) -> QueryResult<User> {
+    use crate::models::NewUser;
+    use crate::schema::users;
+
+    diesel::insert_into(users::table)
+        .values(NewUser { name: name.to_string(), role: role.name().to_string() })
+        .returning(User::as_returning())
+        .get_result(conn)
+}
+
+/// Gets the users of ids, skipping those which do not exist
+pub fn users(conn: &mut PgConnection, ids: &[i32]) -> QueryResult<Vec<User>> {
+    use crate::schema::users;
+
+    users::table
+        .filter(users::id.eq_any(ids))
+        .select(User::as_select())
+        .load(conn)
+}
+
+/// Gets a user by name
+pub fn user_by_name(conn: &mut PgConnection, name: &str) -> QueryResult<User> {
+    use crate::schema::users;
+
+    users::table
+        .filter(users::name.eq(name))
+        .select(User::as_select())
+        .first(conn)
+}
+
+/// Changes the role of a user, returning the user
+pub fn set_role(conn: &mut PgConnection, id: i32, role: Role) -> QueryResult<User> {
+    use crate::schema::users;
+
+    diesel::update(users::table.find(id))
+        .set(users::role.eq(role.name()))
+        .returning(User::as_returning())
+        .get_result(conn)
+}
+
+/// Adds an API token for a user, returning its id and the token. Only a hash of the token is
+/// stored, so it cannot be shown again.
+pub fn add_api_token(
+    conn: &mut PgConnection,
+    user_id: i32,
+    label: Option<&str>,
+    expires_at: Option<DateTime<Utc>>,
+) -> QueryResult<(i32, String)> {
+    #[derive(QueryableByName)]
+    struct Token {
+        #[diesel(sql_type = Integer)]
+        id: i32,
+        #[diesel(sql_type = Text)]
+        token: String,
+    }
+
+    let token = diesel::sql_query(
+        "WITH generated AS (
+            SELECT $1 || replace(gen_random_uuid()::text || gen_random_uuid()::text, '-', '') AS token
+        ), added AS (
+            INSERT INTO api_tokens (user_id, token_hash, label, expires_at)
+            SELECT $2, sha256(convert_to(token, 'UTF8')), $3, $4
+            FROM generated
+            RETURNING id
+        )
+        SELECT added.id, generated.token FROM added, generated",
+    )
+    .bind::<Text, _>(API_TOKEN_PREFIX)
+    .bind::<Integer, _>(user_id)
+    .bind::<Nullable<Text>, _>(label)
+    .bind::<Nullable<Timestamptz>, _>(expires_at)
+    .get_result::<Token>(conn)?;
+
+    Ok((token.id, token.token))
+}
+
+/// Revokes an API token of a user, returning whether the user had the token
+pub fn revoke_api_token(conn: &mut PgConnection, user_id: i32, token_id: i32) -> QueryResult<bool> {
+    use crate::schema::api_tokens;
+
+    let revoked = diesel::delete(
+        api_tokens::table
+            .filter(api_tokens::id.eq(token_id))
+            .filter(api_tokens::user_id.eq(user_id)),
+    )
+    .execute(conn)?;
+
+    Ok(revoked > 0)
+}
+
+/// Gets the user an unexpired API token belongs to, if any, noting the use of the token
+pub fn user_by_api_token(conn: &mut PgConnection, token: &str) -> QueryResult<Option<User>> {
+    diesel::sql_query(
+        "WITH used AS (
+            UPDATE api_tokens
+            SET last_used_at = now()
+            WHERE token_hash = sha256(convert_to($1, 'UTF8')) AND (expires_at IS NULL OR expires_at > now())
+            RETURNING user_id
+        )
+        SELECT u.id, u.name, u.role
+        FROM users u
+        JOIN used ON used.user_id = u.id",
+    )
+    .bind::<Text, _>(token)
+    .get_result(conn)
+    .optional()
+}
+
+/// Records a user as the actor of the changes made in the current transaction, until acting as
+/// another. Outside a transaction this lasts only for the statement itself, so has no effect.
+///
+/// Entities created meanwhile are recorded as created by the user, and the audit log records the
+/// user as the actor of each change. The setting ends with the transaction, so it never carries
+/// over to the next user of a pooled connection.
+pub fn act_as(conn: &mut PgConnection, user: Option<&User>) -> QueryResult<()> {
+    diesel::sql_query(
+        "SELECT set_config('climb.user_id', COALESCE($1::text, ''), true),
+            set_config('climb.actor', COALESCE($2, ''), true)",
+    )
+    .bind::<Nullable<Integer>, _>(user.map(|user| user.id))
+    .bind::<Nullable<Text>, _>(user.map(|user| user.name.as_str()))
+    .execute(conn)?;
+
+    Ok(())
+}
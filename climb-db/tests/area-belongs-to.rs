@@ -1,4 +1,4 @@
-use climb_db::models::{Area, AreaBelongsTo, NewArea, NewAreaBelongsTo};
+use climb_db::models::{Area, AreaBelongsTo, NewAreaBelongsTo};
 use diesel::prelude::*;
 use common::TestDatabase;
 use diesel::RunQueryDsl;
@@ -16,13 +16,13 @@ pub fn cascade() {
     use climb_db::schema::areas;
 
     let area = diesel::insert_into(areas::table)
-        .values(NewArea { names: vec![Some("The Gallery".to_string())] })
+        .default_values()
         .returning(Area::as_returning())
         .get_result(conn)
         .expect("Failed to insert area");
 
     let super_area = diesel::insert_into(areas::table)
-        .values(NewArea { names: vec![Some("Old Baldy Mountain".to_string())] })
+        .default_values()
         .returning(Area::as_returning())
         .get_result(conn)
         .expect("Failed to insert area");
@@ -60,7 +60,7 @@ pub fn foreign_key() {
     use climb_db::schema::areas;
 
     let area = diesel::insert_into(areas::table)
-        .values(NewArea { names: vec![Some("The Gallery".to_string())] })
+        .default_values()
         .returning(Area::as_returning())
         .get_result(conn)
         .expect("Failed to insert area");
@@ -97,13 +97,13 @@ pub fn restrict() {
     use climb_db::schema::areas;
 
     let area = diesel::insert_into(areas::table)
-        .values(NewArea { names: vec![Some("The Gallery".to_string())] })
+        .default_values()
         .returning(Area::as_returning())
         .get_result(conn)
         .expect("Failed to insert area");
 
     let super_area = diesel::insert_into(areas::table)
-        .values(NewArea { names: vec![Some("Old Baldy Mountain".to_string())] })
+        .default_values()
         .returning(Area::as_returning())
         .get_result(conn)
         .expect("Failed to insert area");
@@ -136,7 +136,7 @@ pub fn no_self_parent() {
     use climb_db::schema::areas;
 
     let area = diesel::insert_into(areas::table)
-        .values(NewArea { names: vec![Some("The Gallery".to_string())] })
+        .default_values()
         .returning(Area::as_returning())
         .get_result(conn)
         .expect("Failed to insert area");
@@ -163,13 +163,13 @@ pub fn no_cycles() {
     use climb_db::schema::areas;
 
     let area = diesel::insert_into(areas::table)
-        .values(NewArea { names: vec![Some("The Gallery".to_string())] })
+        .default_values()
         .returning(Area::as_returning())
         .get_result(conn)
         .expect("Failed to insert area");
 
     let super_area = diesel::insert_into(areas::table)
-        .values(NewArea { names: vec![Some("Old Baldy Mountain".to_string())] })
+        .default_values()
         .returning(Area::as_returning())
         .get_result(conn)
         .expect("Failed to insert area");
@@ -0,0 +1,203 @@
+use common::TestDatabase;
+use diesel::prelude::*;
+
+mod common;
+
+/// A square of `size` degrees, as a GeoJSON polygon, with its south west corner at a point
+fn square(longitude: f64, latitude: f64, size: f64) -> String {
+    format!(
+        r#"{{"type":"Polygon","coordinates":[[[{w},{s}],[{e},{s}],[{e},{n}],[{w},{n}],[{w},{s}]]]}}"#,
+        w = longitude,
+        s = latitude,
+        e = longitude + size,
+        n = latitude + size,
+    )
+}
+
+fn insert_area(conn: &mut PgConnection) -> i32 {
+    use climb_db::schema::areas;
+
+    diesel::insert_into(areas::table)
+        .default_values()
+        .returning(areas::id)
+        .get_result(conn)
+        .expect("Failed to insert area")
+}
+
+/// Polygons are stored, and read back, as multi polygons
+#[test]
+fn polygon() {
+    let mut db = TestDatabase::with_migrations("test__area_boundary__polygon");
+    let conn = db.connection();
+
+    let area = insert_area(conn);
+
+    use climb_db::queries::{area_boundaries, set_area_boundary};
+
+    set_area_boundary(conn, area, &square(-103.5, 43.8, 0.1)).expect("Failed to set boundary");
+
+    let result = area_boundaries(conn, &[area]).expect("Failed to get boundaries");
+
+    assert_eq!(result.len(), 1);
+    assert!(result[0].geojson.starts_with(r#"{"type":"MultiPolygon""#));
+}
+
+/// Self-intersecting boundaries are rejected
+#[test]
+fn invalid() {
+    let mut db = TestDatabase::with_migrations("test__area_boundary__invalid");
+    let conn = db.connection();
+
+    let area = insert_area(conn);
+
+    use climb_db::queries::set_area_boundary;
+
+    let bowtie = r#"{"type":"Polygon","coordinates":[[[0,0],[1,1],[1,0],[0,1],[0,0]]]}"#;
+
+    let result = set_area_boundary(conn, area, bowtie);
+
+    assert!(matches!(
+        result,
+        Err(diesel::result::Error::DatabaseError(
+            diesel::result::DatabaseErrorKind::CheckViolation,
+            _
+        ))
+    ));
+}
+
+/// Areas containing a point are found, innermost first
+#[test]
+fn containing() {
+    let mut db = TestDatabase::with_migrations("test__area_boundary__containing");
+    let conn = db.connection();
+
+    let region = insert_area(conn);
+    let crag = insert_area(conn);
+    let elsewhere = insert_area(conn);
+    let unbounded = insert_area(conn);
+
+    use climb_db::queries::set_area_boundary;
+
+    set_area_boundary(conn, region, &square(-104.0, 43.5, 1.0)).expect("Failed to set boundary");
+    set_area_boundary(conn, crag, &square(-103.5, 43.8, 0.1)).expect("Failed to set boundary");
+    set_area_boundary(conn, elsewhere, &square(-106.0, 40.0, 1.0)).expect("Failed to set boundary");
+
+    use climb_db::geo::GeoPoint;
+    use climb_db::queries::areas_containing;
+
+    let point = GeoPoint::new(-103.456774, 43.889938).unwrap();
+
+    let result = areas_containing(conn, &point).expect("Failed to get areas");
+
+    assert_eq!(result, vec![crag, region]);
+    assert!(!result.contains(&unbounded));
+}
+
+/// Formations located outside of their area's boundary are reported
+#[test]
+fn formations_outside() {
+    let mut db = TestDatabase::with_migrations("test__area_boundary__formations_outside");
+    let conn = db.connection();
+
+    let area = insert_area(conn);
+
+    use climb_db::queries::set_area_boundary;
+
+    set_area_boundary(conn, area, &square(-103.5, 43.8, 0.1)).expect("Failed to set boundary");
+
+    use climb_db::geo::GeoPoint;
+    use climb_db::models::NewFormation;
+    use climb_db::schema::formations;
+
+    let locations = [
+        Some(GeoPoint::new(-103.456774, 43.889938).unwrap()),
+        Some(GeoPoint::new(-105.293966, 40.018234).unwrap()),
+        None,
+    ];
+
+    let new_formations: Vec<NewFormation> = locations
+        .into_iter()
+        .map(|location| NewFormation { location })
+        .collect();
+
+    let ids: Vec<i32> = diesel::insert_into(formations::table)
+        .values(&new_formations)
+        .returning(formations::id)
+        .get_results(conn)
+        .expect("Failed to insert formations");
+
+    use climb_db::models::NewFormationBelongsTo;
+    use climb_db::schema::formation_belongs_to;
+
+    let belongs_to: Vec<NewFormationBelongsTo> = ids
+        .iter()
+        .map(|&formation_id| NewFormationBelongsTo {
+            formation_id,
+            area_id: Some(area),
+            super_formation_id: None,
+        })
+        .collect();
+
+    diesel::insert_into(formation_belongs_to::table)
+        .values(&belongs_to)
+        .execute(conn)
+        .expect("Failed to insert formation_belongs_to");
+
+    use climb_db::queries::{formations_outside_areas, FormationOutsideArea};
+
+    let result = formations_outside_areas(conn).expect("Failed to check formations");
+
+    assert_eq!(result, vec![FormationOutsideArea { formation_id: ids[1], area_id: area }]);
+}
+
+/// Formations on other formations are checked against the area of their root formation
+#[test]
+fn sub_formations_outside() {
+    let mut db = TestDatabase::with_migrations("test__area_boundary__sub_formations_outside");
+    let conn = db.connection();
+
+    let area = insert_area(conn);
+
+    use climb_db::queries::set_area_boundary;
+
+    set_area_boundary(conn, area, &square(-103.5, 43.8, 0.1)).expect("Failed to set boundary");
+
+    use climb_db::geo::GeoPoint;
+    use climb_db::repo::{FormationInput, FormationRepo, Parent};
+
+    let formation = FormationRepo::new(conn)
+        .create(FormationInput {
+            parent: Some(Parent::Area(area)),
+            ..Default::default()
+        })
+        .expect("Failed to create formation");
+
+    let sub_formation = FormationRepo::new(conn)
+        .create(FormationInput {
+            location: Some(GeoPoint::new(-105.293966, 40.018234).unwrap()),
+            parent: Some(Parent::Formation(formation)),
+            ..Default::default()
+        })
+        .expect("Failed to create formation");
+
+    use climb_db::queries::{formations_outside_areas, FormationOutsideArea};
+
+    let result = formations_outside_areas(conn).expect("Failed to check formations");
+
+    assert_eq!(result, vec![FormationOutsideArea { formation_id: sub_formation, area_id: area }]);
+}
+
+/// Boundaries which are not GeoJSON are invalid input
+#[test]
+fn invalid_geojson() {
+    let mut db = TestDatabase::with_migrations("test__area_boundary__invalid_geojson");
+    let conn = db.connection();
+
+    let area = insert_area(conn);
+
+    use climb_db::repo::{AreaRepo, Error};
+
+    let result = AreaRepo::new(conn).set_boundary(area, Some(r#"{"type":"Polygon","coordinates":"nowhere"}"#));
+
+    assert!(matches!(result, Err(Error::InvalidInput { .. })));
+}
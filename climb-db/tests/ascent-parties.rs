@@ -0,0 +1,110 @@
+use common::TestDatabase;
+use diesel::prelude::*;
+
+mod common;
+
+/// Tests the climber_id foreign key constraint
+#[test]
+fn climber_fk() {
+    let mut db = TestDatabase::with_migrations("test__ascent_parties__climber_fk");
+    let conn = db.connection();
+
+    use climb_db::models::Climb;
+    use climb_db::schema::climbs;
+
+    let climb = diesel::insert_into(climbs::table)
+        .default_values()
+        .returning(Climb::as_returning())
+        .get_result(conn)
+        .expect("Failed to insert climb");
+
+    use climb_db::models::{Ascent, NewAscent};
+    use climb_db::schema::ascents;
+
+    let ascent = diesel::insert_into(ascents::table)
+        .values(NewAscent {
+            climb_id: climb.id,
+            ascent_date: None,
+        })
+        .returning(Ascent::as_returning())
+        .get_result(conn)
+        .expect("Failed to insert ascent");
+
+    use climb_db::models::NewAscentParty;
+    use climb_db::schema::ascent_parties;
+
+    // The ascent id exists, but there is no climber with it
+    let result = diesel::insert_into(ascent_parties::table)
+        .values(NewAscentParty {
+            ascent_id: ascent.id,
+            climber_id: ascent.id,
+        })
+        .execute(conn);
+
+    assert!(result.is_err());
+}
+
+/// Tests the delete-cascade on climber_id
+#[test]
+fn climber_cascade() {
+    let mut db = TestDatabase::with_migrations("test__ascent_parties__climber_cascade");
+    let conn = db.connection();
+
+    use climb_db::models::Climb;
+    use climb_db::schema::climbs;
+
+    let climb = diesel::insert_into(climbs::table)
+        .default_values()
+        .returning(Climb::as_returning())
+        .get_result(conn)
+        .expect("Failed to insert climb");
+
+    use climb_db::models::{Climber, NewClimber};
+    use climb_db::schema::climbers;
+
+    let climber = diesel::insert_into(climbers::table)
+        .values(NewClimber {
+            first_name: "Daniel".to_string(),
+            last_name: "Woods".to_string(),
+        })
+        .returning(Climber::as_returning())
+        .get_result(conn)
+        .expect("Failed to insert climber");
+
+    use climb_db::models::{Ascent, NewAscent};
+    use climb_db::schema::ascents;
+
+    let ascent = diesel::insert_into(ascents::table)
+        .values(NewAscent {
+            climb_id: climb.id,
+            ascent_date: None,
+        })
+        .returning(Ascent::as_returning())
+        .get_result(conn)
+        .expect("Failed to insert ascent");
+
+    use climb_db::models::{AscentParty, NewAscentParty};
+    use climb_db::schema::ascent_parties;
+
+    let party = diesel::insert_into(ascent_parties::table)
+        .values(NewAscentParty {
+            ascent_id: ascent.id,
+            climber_id: climber.id,
+        })
+        .returning(AscentParty::as_returning())
+        .get_result(conn)
+        .expect("Failed to insert ascent party");
+
+    diesel::delete(climbers::table)
+        .filter(climbers::id.eq(climber.id))
+        .execute(conn)
+        .expect("Failed to delete climber");
+
+    let result = ascent_parties::table
+        .find((party.ascent_id, party.climber_id))
+        .first::<AscentParty>(conn)
+        .optional()
+        .expect("Failed");
+
+    assert!(result.is_none());
+}
@@ -0,0 +1,177 @@
+use common::TestDatabase;
+use diesel::prelude::*;
+
+mod common;
+
+use climb_db::queries::Place;
+
+fn insert_area(conn: &mut PgConnection) -> i32 {
+    use climb_db::schema::areas;
+
+    diesel::insert_into(areas::table)
+        .default_values()
+        .returning(areas::id)
+        .get_result(conn)
+        .expect("Failed to insert area")
+}
+
+/// Names of a place
+fn values(conn: &mut PgConnection, place: Place) -> Vec<String> {
+    use climb_db::names::names;
+
+    names(conn, &[place])
+        .expect("Failed to get names")
+        .into_iter()
+        .map(|(_, name)| name.value)
+        .collect()
+}
+
+/// Changes of a place as `(table, operation)` pairs, newest first
+fn operations(conn: &mut PgConnection, place: Place) -> Vec<(String, String)> {
+    use climb_db::audit::history;
+
+    history(conn, place, 50)
+        .expect("Failed to get history")
+        .into_iter()
+        .map(|change| (change.table_name, change.operation))
+        .collect()
+}
+
+/// Changes of a place and its names are recorded along with their actor
+#[test]
+fn history() {
+    let mut db = TestDatabase::with_migrations("test__audit__history");
+    let conn = db.connection();
+
+    use climb_db::audit::{history, set_actor};
+    use climb_db::names::add_name;
+
+    let area = conn
+        .transaction(|conn| {
+            set_actor(conn, "alice")?;
+
+            let area = Place::Area(insert_area(conn));
+            add_name(conn, area, "Dome", None, None)?;
+
+            QueryResult::Ok(area)
+        })
+        .expect("Failed to add area");
+
+    diesel::sql_query("UPDATE area_names SET value = 'Big Dome'")
+        .execute(conn)
+        .expect("Failed to rename area");
+
+    assert_eq!(operations(conn, area), vec![
+        ("area_names".to_string(), "UPDATE".to_string()),
+        ("area_names".to_string(), "INSERT".to_string()),
+        ("areas".to_string(), "INSERT".to_string()),
+    ]);
+
+    let changes = history(conn, area, 50).expect("Failed to get history");
+    let actors: Vec<_> = changes.iter().map(|change| change.actor.as_deref()).collect();
+
+    assert_eq!(actors, vec![None, Some("alice"), Some("alice")]);
+    assert_eq!(changes[1].transaction_id, changes[2].transaction_id);
+
+    let before: serde_json::Value = serde_json::from_str(changes[0].before.as_deref().unwrap()).unwrap();
+    let after: serde_json::Value = serde_json::from_str(changes[0].after.as_deref().unwrap()).unwrap();
+
+    assert_eq!(before["value"], "Dome");
+    assert_eq!(after["value"], "Big Dome");
+}
+
+/// Updates which change only derived columns are not recorded
+#[test]
+fn derived() {
+    let mut db = TestDatabase::with_migrations("test__audit__derived");
+    let conn = db.connection();
+
+    use climb_db::schema::climbs;
+
+    let climb = diesel::insert_into(climbs::table)
+        .default_values()
+        .returning(climbs::id)
+        .get_result(conn)
+        .expect("Failed to insert climb");
+
+    diesel::update(climbs::table.find(climb))
+        .set(climbs::difficulty.eq(Some(12.0)))
+        .execute(conn)
+        .expect("Failed to update climb");
+
+    assert_eq!(operations(conn, Place::Climb(climb)), vec![("climbs".to_string(), "INSERT".to_string())]);
+}
+
+/// Reverting a change reverts every change of its transaction, recording the reverting changes
+#[test]
+fn revert() {
+    let mut db = TestDatabase::with_migrations("test__audit__revert");
+    let conn = db.connection();
+
+    use climb_db::audit::{history, revert_change};
+    use climb_db::names::add_name;
+
+    let area = Place::Area(insert_area(conn));
+
+    add_name(conn, area, "Dome", None, None).expect("Failed to add name");
+    add_name(conn, area, "Big Dome", Some(climb_db::names::NameKind::Primary), None).expect("Failed to add name");
+
+    assert_eq!(values(conn, area), vec!["Big Dome".to_string(), "Dome".to_string()]);
+
+    let latest = history(conn, area, 1).expect("Failed to get history").remove(0);
+    let reverting = revert_change(conn, latest.id).expect("Failed to revert change");
+
+    assert_eq!(values(conn, area), vec!["Dome".to_string()]);
+    assert_eq!(reverting.len(), 2);
+    assert!(reverting.iter().all(|change| change.reverts == Some(latest.id)));
+}
+
+/// Reverting the removal of an area restores the rows removed along with it
+#[test]
+fn revert_cascade() {
+    let mut db = TestDatabase::with_migrations("test__audit__revert_cascade");
+    let conn = db.connection();
+
+    use climb_db::audit::{history, revert_change};
+    use climb_db::names::add_name;
+    use climb_db::schema::areas;
+
+    let area_id = insert_area(conn);
+    let area = Place::Area(area_id);
+
+    add_name(conn, area, "Dome", None, None).expect("Failed to add name");
+
+    diesel::delete(areas::table.find(area_id))
+        .execute(conn)
+        .expect("Failed to remove area");
+
+    let latest = history(conn, area, 1).expect("Failed to get history").remove(0);
+
+    revert_change(conn, latest.id).expect("Failed to revert change");
+
+    assert_eq!(values(conn, area), vec!["Dome".to_string()]);
+}
+
+/// Changes of rows which have changed since cannot be reverted, nor can missing changes
+#[test]
+fn revert_conflict() {
+    let mut db = TestDatabase::with_migrations("test__audit__revert_conflict");
+    let conn = db.connection();
+
+    use climb_db::audit::{history, revert_change};
+    use climb_db::names::add_name;
+
+    let area = Place::Area(insert_area(conn));
+
+    add_name(conn, area, "Dome", None, None).expect("Failed to add name");
+
+    let added = history(conn, area, 1).expect("Failed to get history").remove(0);
+
+    diesel::sql_query("UPDATE area_names SET value = 'Big Dome'")
+        .execute(conn)
+        .expect("Failed to rename area");
+
+    assert!(revert_change(conn, added.id).is_err());
+    assert_eq!(values(conn, area), vec!["Big Dome".to_string()]);
+    assert_eq!(revert_change(conn, added.id + 100), Err(diesel::result::Error::NotFound));
+}
@@ -1,4 +1,4 @@
-use climb_db::models::{NewClimb, Climb, NewArea, Area, NewFormation, Formation, NewClimbBelongsTo, ClimbBelongsTo};
+use climb_db::models::{Climb, Area, NewFormation, Formation, NewClimbBelongsTo, ClimbBelongsTo};
 use diesel::prelude::*;
 use common::TestDatabase;
 use diesel::RunQueryDsl;
@@ -15,7 +15,7 @@ pub fn cascade() {
     use climb_db::schema::climbs;
 
     let climb = diesel::insert_into(climbs::table)
-        .values(NewClimb { names: vec![Some("The Cheat".to_string())] })
+        .default_values()
         .returning(Climb::as_returning())
         .get_result(conn)
         .expect("Failed to insert climb");
@@ -23,10 +23,7 @@ pub fn cascade() {
     use climb_db::schema::formations;
 
     let formation = diesel::insert_into(formations::table)
-        .values(NewFormation {
-            names: vec![Some("North Nostril Cave".to_string())],
-            ..Default::default()
-        })
+        .values(NewFormation::default())
         .returning(Formation::as_returning())
         .get_result(conn)
         .expect("Failed to insert formation");
@@ -67,7 +64,7 @@ pub fn foreign_key() {
     use climb_db::schema::climbs;
 
     let climb = diesel::insert_into(climbs::table)
-        .values(NewClimb { names: vec![Some("The Cheat".to_string())] })
+        .default_values()
         .returning(Climb::as_returning())
         .get_result(conn)
         .expect("Failed to insert climb");
@@ -106,7 +103,7 @@ pub fn one_parent() {
     use climb_db::schema::climbs;
 
     let climb = diesel::insert_into(climbs::table)
-        .values(NewClimb { names: vec![Some("The Cheat".to_string())] })
+        .default_values()
         .returning(Climb::as_returning())
         .get_result(conn)
         .expect("Failed to insert climb");
@@ -114,10 +111,7 @@ pub fn one_parent() {
     use climb_db::schema::formations;
 
     let formation = diesel::insert_into(formations::table)
-        .values(NewFormation {
-            names: vec![Some("North Nostril Cave".to_string())],
-            ..Default::default()
-        })
+        .values(NewFormation::default())
         .returning(Formation::as_returning())
         .get_result(conn)
         .expect("Failed to insert formation");
@@ -125,7 +119,7 @@ pub fn one_parent() {
     use climb_db::schema::areas;
 
     let area = diesel::insert_into(areas::table)
-        .values(NewArea { names: vec![Some("North Nostril Cave".to_string())] })
+        .default_values()
         .returning(Area::as_returning())
         .get_result(conn)
         .expect("Failed to insert area");
@@ -165,7 +159,7 @@ pub fn restrict() {
     use climb_db::schema::climbs;
 
     let climb = diesel::insert_into(climbs::table)
-        .values(NewClimb { names: vec![Some("The Cheat".to_string())] })
+        .default_values()
         .returning(Climb::as_returning())
         .get_result(conn)
         .expect("Failed to insert climb");
@@ -173,10 +167,7 @@ pub fn restrict() {
     use climb_db::schema::formations;
 
     let formation = diesel::insert_into(formations::table)
-        .values(NewFormation {
-            names: vec![Some("North Nostril Cave".to_string())],
-            ..Default::default()
-        })
+        .values(NewFormation::default())
         .returning(Formation::as_returning())
         .get_result(conn)
         .expect("Failed to insert formation");
@@ -9,16 +9,16 @@ fn climb_fk() {
     let mut db = TestDatabase::with_migrations("test__climb_descriptions__climb_fk");
     let conn = db.connection();
 
-    use climb_db::schema::climb_description_types;
-    use climb_db::models::NewClimbDescriptionType;
-    use climb_db::models::ClimbDescriptionType;
+    use climb_db::schema::description_types;
+    use climb_db::models::NewDescriptionType;
+    use climb_db::models::DescriptionType;
 
-    let brief_type = diesel::insert_into(climb_description_types::table)
-        .values(NewClimbDescriptionType { name: "brief".to_string() })
-        .on_conflict(climb_description_types::name)
+    let brief_type = diesel::insert_into(description_types::table)
+        .values(NewDescriptionType { name: "brief".to_string() })
+        .on_conflict(description_types::name)
         .do_update()
-        .set(climb_description_types::name.eq(climb_description_types::name))
-        .returning(ClimbDescriptionType::as_returning())
+        .set(description_types::name.eq(description_types::name))
+        .returning(DescriptionType::as_returning())
         .get_result(conn)
         .expect("Failed to upsert brief type");
 
@@ -27,7 +27,7 @@ fn climb_fk() {
 
     let result = diesel::insert_into(climb_descriptions::table)
         .values(NewClimbDescription {
-            climb_description_type_id: brief_type.id,
+            description_type_id: brief_type.id,
             climb_id: 10,
             value: "SDS and follow incuts to a cruxy sequence to gain the lip".to_string(),
         })
@@ -36,19 +36,17 @@ fn climb_fk() {
     assert!(result.is_err());
 }
 
-/// Tests the climb_description_type_id foreign key constraint
+/// Tests the description_type_id foreign key constraint
 #[test]
 fn climb_description_type_fk() {
     let mut db = TestDatabase::with_migrations("test__climb_descriptions__climb_description_type_fk");
     let conn = db.connection();
 
-    use climb_db::models::{Climb, NewClimb};
+    use climb_db::models::Climb;
     use climb_db::schema::climbs;
 
     let climb = diesel::insert_into(climbs::table)
-        .values(NewClimb {
-            names: vec![Some("The Cheat".to_string())],
-        })
+        .default_values()
         .returning(Climb::as_returning())
         .get_result(conn)
         .expect("Failed to insert climb");
@@ -58,7 +56,7 @@ fn climb_description_type_fk() {
 
     let result = diesel::insert_into(climb_descriptions::table)
         .values(NewClimbDescription {
-            climb_description_type_id: 10,
+            description_type_id: 10,
             climb_id: climb.id,
             value: "SDS and follow incuts to a cruxy sequence to gain the lip".to_string(),
         })
@@ -73,27 +71,25 @@ fn climb_cascade() {
     let mut db = TestDatabase::with_migrations("test__climb_descriptions__climb_cascade");
     let conn = db.connection();
 
-    use climb_db::models::{Climb, NewClimb};
+    use climb_db::models::Climb;
     use climb_db::schema::climbs;
 
     let climb = diesel::insert_into(climbs::table)
-        .values(NewClimb {
-            names: vec![Some("The Cheat".to_string())],
-        })
+        .default_values()
         .returning(Climb::as_returning())
         .get_result(conn)
         .expect("Failed to insert climb");
 
-    use climb_db::schema::climb_description_types;
-    use climb_db::models::NewClimbDescriptionType;
-    use climb_db::models::ClimbDescriptionType;
+    use climb_db::schema::description_types;
+    use climb_db::models::NewDescriptionType;
+    use climb_db::models::DescriptionType;
 
-    let brief_type = diesel::insert_into(climb_description_types::table)
-        .values(NewClimbDescriptionType { name: "brief".to_string() })
-        .on_conflict(climb_description_types::name)
+    let brief_type = diesel::insert_into(description_types::table)
+        .values(NewDescriptionType { name: "brief".to_string() })
+        .on_conflict(description_types::name)
         .do_update()
-        .set(climb_description_types::name.eq(climb_description_types::name))
-        .returning(ClimbDescriptionType::as_returning())
+        .set(description_types::name.eq(description_types::name))
+        .returning(DescriptionType::as_returning())
         .get_result(conn)
         .expect("Failed to upsert brief type");
 
@@ -102,7 +98,7 @@ fn climb_cascade() {
 
     let description = diesel::insert_into(climb_descriptions::table)
         .values(NewClimbDescription {
-            climb_description_type_id: brief_type.id,
+            description_type_id: brief_type.id,
             climb_id: climb.id,
             value: "SDS and follow incuts to a cruxy sequence to gain the lip".to_string(),
         })
@@ -116,7 +112,7 @@ fn climb_cascade() {
         .expect("Failed to delete climb");
 
     let result = climb_descriptions::table
-        .find((description.climb_id, description.climb_description_type_id))
+        .find((description.climb_id, description.description_type_id))
         .first::<ClimbDescription>(conn)
         .optional()
         .expect("Failed");
@@ -124,33 +120,31 @@ fn climb_cascade() {
     assert!(result.is_none());
 }
 
-/// Tests the delete-cascade on climb_description_type_id
+/// Tests the delete-cascade on description_type_id
 #[test]
 fn climb_description_type_cascade() {
     let mut db = TestDatabase::with_migrations("test__climb_descriptions__climb_description_type_cascade");
     let conn = db.connection();
 
-    use climb_db::models::{Climb, NewClimb};
+    use climb_db::models::Climb;
     use climb_db::schema::climbs;
 
     let climb = diesel::insert_into(climbs::table)
-        .values(NewClimb {
-            names: vec![Some("The Cheat".to_string())],
-        })
+        .default_values()
         .returning(Climb::as_returning())
         .get_result(conn)
         .expect("Failed to insert climb");
 
-    use climb_db::schema::climb_description_types;
-    use climb_db::models::NewClimbDescriptionType;
-    use climb_db::models::ClimbDescriptionType;
+    use climb_db::schema::description_types;
+    use climb_db::models::NewDescriptionType;
+    use climb_db::models::DescriptionType;
 
-    let brief_type = diesel::insert_into(climb_description_types::table)
-        .values(NewClimbDescriptionType { name: "brief".to_string() })
-        .on_conflict(climb_description_types::name)
+    let brief_type = diesel::insert_into(description_types::table)
+        .values(NewDescriptionType { name: "brief".to_string() })
+        .on_conflict(description_types::name)
         .do_update()
-        .set(climb_description_types::name.eq(climb_description_types::name))
-        .returning(ClimbDescriptionType::as_returning())
+        .set(description_types::name.eq(description_types::name))
+        .returning(DescriptionType::as_returning())
         .get_result(conn)
         .expect("Failed to upsert brief type");
 
@@ -159,7 +153,7 @@ fn climb_description_type_cascade() {
 
     let description = diesel::insert_into(climb_descriptions::table)
         .values(NewClimbDescription {
-            climb_description_type_id: brief_type.id,
+            description_type_id: brief_type.id,
             climb_id: climb.id,
             value: "SDS and follow incuts to a cruxy sequence to gain the lip".to_string(),
         })
@@ -167,13 +161,13 @@ fn climb_description_type_cascade() {
         .get_result(conn)
         .expect("Failed to add climb description");
 
-    diesel::delete(climb_description_types::table)
-        .filter(climb_description_types::id.eq(description.climb_description_type_id))
+    diesel::delete(description_types::table)
+        .filter(description_types::id.eq(description.description_type_id))
         .execute(conn)
         .expect("Failed to delete description type");
 
     let result = climb_descriptions::table
-        .find((description.climb_id, description.climb_description_type_id))
+        .find((description.climb_id, description.description_type_id))
         .first::<ClimbDescription>(conn)
         .optional()
         .expect("Failed");
@@ -193,12 +187,12 @@ fn initial_description_types() {
         "hist".to_string(),
     ];
 
-    use climb_db::models::ClimbDescriptionType;
-    use climb_db::schema::climb_description_types;
+    use climb_db::models::DescriptionType;
+    use climb_db::schema::description_types;
 
     // Create a query to select all rows
-    let desc_types = climb_description_types::table
-        .load::<ClimbDescriptionType>(conn)
+    let desc_types = description_types::table
+        .load::<DescriptionType>(conn)
         .expect("Error loading rows");
 
     let mut expected_desc_names: Vec<String> = default_desc_type_names.to_vec();
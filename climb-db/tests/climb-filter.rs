@@ -0,0 +1,229 @@
+use common::{insert_climb, TestDatabase};
+use diesel::prelude::*;
+
+mod common;
+
+fn add_grade(conn: &mut PgConnection, climb: i32, system: climb_db::grade::GradeSystem, value: &str) {
+    use climb_db::queries::add_climb_grade;
+
+    add_climb_grade(conn, climb, &system.parse(value).unwrap()).expect("Failed to add grade");
+}
+
+fn load(conn: &mut PgConnection, filter: &climb_db::filter::ClimbFilter, order: climb_db::filter::ClimbOrder) -> Vec<i32> {
+    use climb_db::filter::climbs_query;
+
+    climbs_query(filter, order).load(conn).expect("Failed to filter climbs")
+}
+
+/// Climbs match if they have a grade in any of the ranges
+#[test]
+fn grades() {
+    let mut db = TestDatabase::with_migrations("test__climb_filter__grades");
+    let conn = db.connection();
+
+    use climb_db::grade::GradeSystem;
+
+    let v3 = insert_climb(conn, &["V3"]);
+    let v5 = insert_climb(conn, &["V5"]);
+    let v7 = insert_climb(conn, &["V7"]);
+    let font = insert_climb(conn, &["6B"]);
+
+    add_grade(conn, v3, GradeSystem::Vermin, "V3");
+    add_grade(conn, v5, GradeSystem::Vermin, "V5");
+    add_grade(conn, v7, GradeSystem::Vermin, "V7");
+    add_grade(conn, font, GradeSystem::Font, "6B");
+
+    use climb_db::filter::{ClimbFilter, GradeRange};
+
+    let vermin = GradeRange {
+        system: GradeSystem::Vermin,
+        min: Some(GradeSystem::Vermin.parse("V4").unwrap()),
+        max: Some(GradeSystem::Vermin.parse("V7").unwrap()),
+    };
+
+    let filter = ClimbFilter { grades: vec![vermin.clone()], ..Default::default() };
+
+    assert_eq!(load(conn, &filter, Default::default()), vec![v5, v7]);
+
+    let font_range = GradeRange {
+        system: GradeSystem::Font,
+        min: None,
+        max: Some(GradeSystem::Font.parse("6C").unwrap()),
+    };
+
+    let filter = ClimbFilter { grades: vec![vermin, font_range], ..Default::default() };
+
+    assert_eq!(load(conn, &filter, Default::default()), vec![v5, v7, font]);
+}
+
+/// Climbs within an area include those of its sub areas and of their formations
+#[test]
+fn within() {
+    let mut db = TestDatabase::with_migrations("test__climb_filter__within");
+    let conn = db.connection();
+
+    use climb_db::schema::areas;
+
+    let areas: Vec<i32> = (0..3)
+        .map(|_| diesel::insert_into(areas::table).default_values().returning(areas::id).get_result(conn))
+        .collect::<QueryResult<_>>()
+        .expect("Failed to insert areas");
+
+    let (region, crag, elsewhere) = (areas[0], areas[1], areas[2]);
+
+    use climb_db::models::NewAreaBelongsTo;
+    use climb_db::schema::area_belongs_to;
+
+    diesel::insert_into(area_belongs_to::table)
+        .values(NewAreaBelongsTo { area_id: crag, super_area_id: region })
+        .execute(conn)
+        .expect("Failed to insert area_belongs_to");
+
+    use climb_db::models::NewFormation;
+    use climb_db::schema::formations;
+
+    let formations: Vec<i32> = diesel::insert_into(formations::table)
+        .values(vec![
+            NewFormation { location: None },
+            NewFormation { location: None },
+        ])
+        .returning(formations::id)
+        .get_results(conn)
+        .expect("Failed to insert formations");
+
+    let (boulder, face) = (formations[0], formations[1]);
+
+    use climb_db::models::NewFormationBelongsTo;
+    use climb_db::schema::formation_belongs_to;
+
+    diesel::insert_into(formation_belongs_to::table)
+        .values(vec![
+            NewFormationBelongsTo { formation_id: boulder, area_id: Some(crag), super_formation_id: None },
+            NewFormationBelongsTo { formation_id: face, area_id: None, super_formation_id: Some(boulder) },
+        ])
+        .execute(conn)
+        .expect("Failed to insert formation_belongs_to");
+
+    let in_region = insert_climb(conn, &["In region"]);
+    let in_crag = insert_climb(conn, &["In crag"]);
+    let on_face = insert_climb(conn, &["On face"]);
+    let outside = insert_climb(conn, &["Outside"]);
+
+    use climb_db::models::NewClimbBelongsTo;
+    use climb_db::schema::climb_belongs_to;
+
+    diesel::insert_into(climb_belongs_to::table)
+        .values(vec![
+            NewClimbBelongsTo { climb_id: in_region, area_id: Some(region), formation_id: None },
+            NewClimbBelongsTo { climb_id: in_crag, area_id: Some(crag), formation_id: None },
+            NewClimbBelongsTo { climb_id: on_face, area_id: None, formation_id: Some(face) },
+            NewClimbBelongsTo { climb_id: outside, area_id: Some(elsewhere), formation_id: None },
+        ])
+        .execute(conn)
+        .expect("Failed to insert climb_belongs_to");
+
+    use climb_db::filter::ClimbFilter;
+
+    let filter = ClimbFilter { within_area_id: Some(region), ..Default::default() };
+    assert_eq!(load(conn, &filter, Default::default()), vec![in_region, in_crag, on_face]);
+
+    let filter = ClimbFilter { area_id: Some(region), ..Default::default() };
+    assert_eq!(load(conn, &filter, Default::default()), vec![in_region]);
+
+    let filter = ClimbFilter { within_formation_id: Some(boulder), ..Default::default() };
+    assert_eq!(load(conn, &filter, Default::default()), vec![on_face]);
+}
+
+/// Names are matched ignoring case, with wildcards taken literally
+#[test]
+fn name_contains() {
+    let mut db = TestDatabase::with_migrations("test__climb_filter__name_contains");
+    let conn = db.connection();
+
+    let cheat = insert_climb(conn, &["The Cheat"]);
+    let percent = insert_climb(conn, &["100% Natural"]);
+    insert_climb(conn, &["Midnight Lightning"]);
+
+    use climb_db::filter::ClimbFilter;
+
+    let filter = ClimbFilter { name_contains: Some("cheat".to_string()), ..Default::default() };
+    assert_eq!(load(conn, &filter, Default::default()), vec![cheat]);
+
+    let filter = ClimbFilter { name_contains: Some("%".to_string()), ..Default::default() };
+    assert_eq!(load(conn, &filter, Default::default()), vec![percent]);
+}
+
+/// Climbs are filtered on having ascents and descriptions
+#[test]
+fn has() {
+    let mut db = TestDatabase::with_migrations("test__climb_filter__has");
+    let conn = db.connection();
+
+    let ascended = insert_climb(conn, &["Ascended"]);
+    let described = insert_climb(conn, &["Described"]);
+
+    use climb_db::models::NewAscent;
+    use climb_db::schema::ascents;
+
+    diesel::insert_into(ascents::table)
+        .values(NewAscent { climb_id: ascended, ascent_date: None })
+        .execute(conn)
+        .expect("Failed to insert ascent");
+
+    use climb_db::models::NewClimbDescription;
+    use climb_db::schema::{description_types, climb_descriptions};
+
+    let description_type = description_types::table
+        .select(description_types::id)
+        .first::<i32>(conn)
+        .expect("Failed to get description type");
+
+    diesel::insert_into(climb_descriptions::table)
+        .values(NewClimbDescription {
+            climb_id: described,
+            description_type_id: description_type,
+            value: "SDS".to_string(),
+        })
+        .execute(conn)
+        .expect("Failed to insert description");
+
+    use climb_db::filter::ClimbFilter;
+
+    let filter = ClimbFilter { has_ascents: Some(true), ..Default::default() };
+    assert_eq!(load(conn, &filter, Default::default()), vec![ascended]);
+
+    let filter = ClimbFilter { has_ascents: Some(false), ..Default::default() };
+    assert_eq!(load(conn, &filter, Default::default()), vec![described]);
+
+    let filter = ClimbFilter { has_description: Some(true), ..Default::default() };
+    assert_eq!(load(conn, &filter, Default::default()), vec![described]);
+}
+
+/// Climbs are ordered by difficulty or name, with those lacking either last
+#[test]
+fn order() {
+    let mut db = TestDatabase::with_migrations("test__climb_filter__order");
+    let conn = db.connection();
+
+    use climb_db::grade::GradeSystem;
+
+    let hard = insert_climb(conn, &["apple"]);
+    let easy = insert_climb(conn, &["Banana"]);
+    let ungraded = insert_climb(conn, &["cherry"]);
+
+    add_grade(conn, hard, GradeSystem::Vermin, "V7");
+    add_grade(conn, easy, GradeSystem::Yds, "5.10a");
+
+    use climb_db::filter::{ClimbFilter, ClimbOrder, ClimbOrderField};
+
+    let filter = ClimbFilter::default();
+
+    let order = ClimbOrder { field: ClimbOrderField::Difficulty, descending: false };
+    assert_eq!(load(conn, &filter, order), vec![easy, hard, ungraded]);
+
+    let order = ClimbOrder { field: ClimbOrderField::Difficulty, descending: true };
+    assert_eq!(load(conn, &filter, order), vec![hard, easy, ungraded]);
+
+    let order = ClimbOrder { field: ClimbOrderField::Name, descending: true };
+    assert_eq!(load(conn, &filter, order), vec![ungraded, easy, hard]);
+}
@@ -0,0 +1,154 @@
+use common::{insert_climb, TestDatabase};
+use diesel::prelude::*;
+
+mod common;
+
+/// Climbs may be graded in several systems, and equal grades share a `grades` row
+#[test]
+fn systems() {
+    let mut db = TestDatabase::with_migrations("test__climb_grades__systems");
+    let conn = db.connection();
+
+    let first = insert_climb(conn, &[]);
+    let second = insert_climb(conn, &[]);
+
+    use climb_db::grade::GradeSystem;
+    use climb_db::queries::add_climb_grade;
+
+    let grades = [
+        GradeSystem::Yds.parse("5.10a").unwrap(),
+        GradeSystem::French.parse("6a").unwrap(),
+        GradeSystem::Ewbank.parse("18").unwrap(),
+    ];
+
+    for grade in &grades {
+        add_climb_grade(conn, first, grade).expect("Failed to add grade");
+    }
+
+    // Adding a grade twice does nothing
+    add_climb_grade(conn, first, &grades[0]).expect("Failed to add grade");
+    add_climb_grade(conn, second, &grades[0]).expect("Failed to add grade");
+
+    use climb_db::schema::{climb_grades, grade_types, grades};
+
+    let result = climb_grades::table
+        .inner_join(grades::table.inner_join(grade_types::table))
+        .filter(climb_grades::climb_id.eq(first))
+        .order(grades::sort_key)
+        .select((grade_types::name, grades::value))
+        .load::<(String, String)>(conn)
+        .expect("Failed to get grades");
+
+    assert_eq!(result, vec![
+        ("french".to_string(), "6a".to_string()),
+        ("yds".to_string(), "5.10a".to_string()),
+        ("ewbank".to_string(), "18".to_string()),
+    ]);
+
+    use diesel::dsl::count_star;
+
+    let grade_count = grades::table
+        .filter(grades::id.eq_any(climb_grades::table.select(climb_grades::grade_id)))
+        .select(count_star())
+        .first::<i64>(conn)
+        .expect("Failed to count grades");
+
+    assert_eq!(grade_count, 3);
+}
+
+/// Removing a grade removes only that grade of the climb
+#[test]
+fn remove() {
+    let mut db = TestDatabase::with_migrations("test__climb_grades__remove");
+    let conn = db.connection();
+
+    let climb = insert_climb(conn, &[]);
+
+    use climb_db::grade::GradeSystem;
+    use climb_db::queries::{add_climb_grade, remove_climb_grade};
+
+    let v5 = GradeSystem::Vermin.parse("V5").unwrap();
+    let font = GradeSystem::Font.parse("6C+").unwrap();
+
+    add_climb_grade(conn, climb, &v5).expect("Failed to add grade");
+    add_climb_grade(conn, climb, &font).expect("Failed to add grade");
+
+    assert_eq!(remove_climb_grade(conn, climb, &v5), Ok(1));
+    assert_eq!(remove_climb_grade(conn, climb, &v5), Ok(0));
+
+    use climb_db::schema::climb_grades;
+
+    let remaining = climb_grades::table
+        .filter(climb_grades::climb_id.eq(climb))
+        .count()
+        .get_result::<i64>(conn)
+        .expect("Failed to count grades");
+
+    assert_eq!(remaining, 1);
+}
+
+/// Grades of removed climbs are removed
+#[test]
+fn climb_cascade() {
+    let mut db = TestDatabase::with_migrations("test__climb_grades__climb_cascade");
+    let conn = db.connection();
+
+    let climb = insert_climb(conn, &[]);
+
+    use climb_db::grade::GradeSystem;
+    use climb_db::queries::add_climb_grade;
+
+    add_climb_grade(conn, climb, &GradeSystem::Uiaa.parse("VII").unwrap()).expect("Failed to add grade");
+
+    use climb_db::schema::{climb_grades, climbs};
+
+    diesel::delete(climbs::table.find(climb)).execute(conn).expect("Failed to delete climb");
+
+    let remaining = climb_grades::table
+        .count()
+        .get_result::<i64>(conn)
+        .expect("Failed to count grades");
+
+    assert_eq!(remaining, 0);
+}
+
+/// Migrating with nothing to run re-keys grades only once their sort key version is out of date
+#[test]
+fn migrate_rekeys_outdated() {
+    let mut db = TestDatabase::with_migrations("test__climb_grades__migrate_rekeys_outdated");
+    let conn = db.connection();
+
+    use climb_db::grade::{GradeSystem, SORT_KEY_VERSION};
+    use climb_db::queries::{add_climb_grade, grade_sort_key_version};
+    use climb_db::schema::{grade_sort_key_version, grades};
+
+    let grade = GradeSystem::Yds.parse("5.10b").expect("Failed to parse grade");
+    let id = insert_climb(conn, &[]);
+    add_climb_grade(conn, id, &grade).expect("Failed to grade climb");
+
+    let sort_key = |conn: &mut PgConnection| {
+        grades::table
+            .filter(grades::value.eq("5.10b"))
+            .select(grades::sort_key)
+            .first::<i32>(conn)
+            .expect("Failed to get sort key")
+    };
+
+    diesel::update(grades::table.filter(grades::value.eq("5.10b")))
+        .set(grades::sort_key.eq(0))
+        .execute(conn)
+        .expect("Failed to make sort key stale");
+
+    assert_eq!(climb_db::migrate(conn).expect("Failed to migrate"), Vec::<String>::new());
+    assert_eq!(sort_key(conn), 0);
+
+    diesel::update(grade_sort_key_version::table)
+        .set(grade_sort_key_version::version.eq(SORT_KEY_VERSION - 1))
+        .execute(conn)
+        .expect("Failed to make sort key version stale");
+
+    climb_db::migrate(conn).expect("Failed to migrate");
+
+    assert_eq!(sort_key(conn), grade.sort_key);
+    assert_eq!(grade_sort_key_version(conn), Ok(SORT_KEY_VERSION));
+}
@@ -9,13 +9,11 @@ fn no_self_reference() {
     let mut db = TestDatabase::with_migrations("test__climb_variation__no_self_reference");
     let conn = db.connection();
 
-    use climb_db::models::{Climb, NewClimb};
+    use climb_db::models::Climb;
     use climb_db::schema::climbs;
 
     let climb = diesel::insert_into(climbs::table)
-        .values(NewClimb {
-            names: vec![Some("The Cheat".to_string())],
-        })
+        .default_values()
         .returning(Climb::as_returning())
         .get_result(conn)
         .expect("Failed to insert climb");
@@ -39,13 +37,11 @@ fn root_fk() {
     let mut db = TestDatabase::with_migrations("test__climb_variation__root_fk");
     let conn = db.connection();
 
-    use climb_db::models::{Climb, NewClimb};
+    use climb_db::models::Climb;
     use climb_db::schema::climbs;
 
     let climb = diesel::insert_into(climbs::table)
-        .values(NewClimb {
-            names: vec![Some("The Cheat".to_string())],
-        })
+        .default_values()
         .returning(Climb::as_returning())
         .get_result(conn)
         .expect("Failed to insert climb");
@@ -69,13 +65,11 @@ fn variation_fk() {
     let mut db = TestDatabase::with_migrations("test__climb_variation__variation_fk");
     let conn = db.connection();
 
-    use climb_db::models::{Climb, NewClimb};
+    use climb_db::models::Climb;
     use climb_db::schema::climbs;
 
     let climb = diesel::insert_into(climbs::table)
-        .values(NewClimb {
-            names: vec![Some("The Cheat".to_string())],
-        })
+        .default_values()
         .returning(Climb::as_returning())
         .get_result(conn)
         .expect("Failed to insert climb");
@@ -99,21 +93,17 @@ fn root_cascade() {
     let mut db = TestDatabase::with_migrations("test__climb_variation__root_cascade");
     let conn = db.connection();
 
-    use climb_db::models::{Climb, NewClimb};
+    use climb_db::models::Climb;
     use climb_db::schema::climbs;
 
     let root = diesel::insert_into(climbs::table)
-        .values(NewClimb {
-            names: vec![Some("The Cheat".to_string())],
-        })
+        .default_values()
         .returning(Climb::as_returning())
         .get_result(conn)
         .expect("Failed to insert climb");
 
     let variation = diesel::insert_into(climbs::table)
-        .values(NewClimb {
-            names: vec![Some("The Chester".to_string())],
-        })
+        .default_values()
         .returning(Climb::as_returning())
         .get_result(conn)
         .expect("Failed to insert climb");
@@ -148,21 +138,17 @@ fn variation_cascade() {
     let mut db = TestDatabase::with_migrations("test__climb_variation__variation_cascade");
     let conn = db.connection();
 
-    use climb_db::models::{Climb, NewClimb};
+    use climb_db::models::Climb;
     use climb_db::schema::climbs;
 
     let root = diesel::insert_into(climbs::table)
-        .values(NewClimb {
-            names: vec![Some("The Cheat".to_string())],
-        })
+        .default_values()
         .returning(Climb::as_returning())
         .get_result(conn)
         .expect("Failed to insert climb");
 
     let variation = diesel::insert_into(climbs::table)
-        .values(NewClimb {
-            names: vec![Some("The Chester".to_string())],
-        })
+        .default_values()
         .returning(Climb::as_returning())
         .get_result(conn)
         .expect("Failed to insert climb");
@@ -190,3 +176,49 @@ fn variation_cascade() {
 
     assert!(result.is_err())
 }
+
+/// Tests the prevent_climb_variation_cycle trigger
+#[test]
+fn no_cycles() {
+    let mut db = TestDatabase::with_migrations("test__climb_variation__no_cycles");
+    let conn = db.connection();
+
+    use climb_db::models::Climb;
+    use climb_db::schema::climbs;
+
+    let climbs = (0..3)
+        .map(|_| {
+            diesel::insert_into(climbs::table)
+                .default_values()
+                .returning(Climb::as_returning())
+                .get_result(conn)
+                .expect("Failed to insert climb")
+        })
+        .collect::<Vec<_>>();
+
+    use climb_db::models::NewClimbVariation;
+    use climb_db::schema::climb_variations;
+
+    diesel::insert_into(climb_variations::table)
+        .values(vec![
+            NewClimbVariation {
+                root_id: climbs[0].id,
+                variation_id: climbs[1].id,
+            },
+            NewClimbVariation {
+                root_id: climbs[1].id,
+                variation_id: climbs[2].id,
+            },
+        ])
+        .execute(conn)
+        .expect("Failed to setup variations");
+
+    let result = diesel::insert_into(climb_variations::table)
+        .values(NewClimbVariation {
+            root_id: climbs[2].id,
+            variation_id: climbs[0].id,
+        })
+        .execute(conn);
+
+    assert!(result.is_err());
+}
@@ -1,5 +1,4 @@
 use diesel::{Connection, PgConnection, RunQueryDsl};
-use diesel_migrations::MigrationHarness;
 use std::env;
 
 pub struct TestDatabase {
@@ -35,13 +34,39 @@ impl TestDatabase {
     pub fn with_migrations(db_name: &str) -> Self {
         let mut db = TestDatabase::new(db_name);
         let conn = db.connection();
-        conn.run_pending_migrations(climb_db::MIGRATIONS).expect("Failed to run pending migrations");
+        climb_db::migrate(conn).expect("Failed to run pending migrations");
         db
     }
 
     pub fn connection(&mut self) -> &mut PgConnection {
         self.conn.as_mut().expect("Connection closed")
     }
+
+    /// URL of the database, for connecting to it other than through `connection`
+    #[allow(dead_code)]
+    pub fn url(&self) -> String {
+        format!("{}/{}", self.db_url, self.db_name)
+    }
+}
+
+/// Inserts a climb with names, returning its id
+#[allow(dead_code)]
+pub fn insert_climb(conn: &mut PgConnection, names: &[&str]) -> i32 {
+    use climb_db::schema::climbs;
+
+    let id = diesel::insert_into(climbs::table)
+        .default_values()
+        .returning(climbs::id)
+        .get_result(conn)
+        .expect("Failed to insert climb");
+
+    use climb_db::queries::add_climb_name;
+
+    for name in names {
+        add_climb_name(conn, id, name).expect("Failed to add name");
+    }
+
+    id
 }
 
 impl Drop for TestDatabase {
@@ -50,7 +75,8 @@ impl Drop for TestDatabase {
 
         let mut conn = PgConnection::establish(&format!("{}/postgres", self.db_url)).expect("");
 
-        diesel::sql_query(format!("DROP DATABASE {}", self.db_name))
+        // Forced, as connections opened through `url` may outlive the database
+        diesel::sql_query(format!("DROP DATABASE {} WITH (FORCE)", self.db_name))
             .execute(&mut conn)
             .expect("Failed to drop table");
     }
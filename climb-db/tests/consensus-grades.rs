@@ -0,0 +1,111 @@
+use common::{insert_climb, TestDatabase};
+use diesel::prelude::*;
+
+mod common;
+
+/// Inserts an ascent of a climb proposing a grade, returning its id
+fn insert_ascent(conn: &mut PgConnection, climb_id: i32, grade: &str) -> i32 {
+    use climb_db::models::NewAscent;
+    use climb_db::schema::ascents;
+
+    let ascent_id = diesel::insert_into(ascents::table)
+        .values(NewAscent { climb_id, ascent_date: None })
+        .returning(ascents::id)
+        .get_result(conn)
+        .expect("Failed to insert ascent");
+
+    use climb_db::grade::GradeSystem;
+    use climb_db::queries::upsert_grade;
+
+    let grade_id = upsert_grade(conn, &GradeSystem::Vermin.parse(grade).unwrap()).expect("Failed to upsert grade");
+
+    use climb_db::models::NewAscentGrade;
+    use climb_db::schema::ascent_grades;
+
+    diesel::insert_into(ascent_grades::table)
+        .values(NewAscentGrade { ascent_id, grade_id })
+        .execute(conn)
+        .expect("Failed to insert ascent grade");
+
+    ascent_id
+}
+
+/// The median, mode, votes and spread follow the proposed grades
+#[test]
+fn consensus() {
+    let mut db = TestDatabase::with_migrations("test__consensus_grades__consensus");
+    let conn = db.connection();
+
+    let climb = insert_climb(conn, &[]);
+
+    insert_ascent(conn, climb, "V4");
+    insert_ascent(conn, climb, "V5");
+    insert_ascent(conn, climb, "V5");
+    insert_ascent(conn, climb, "V7");
+
+    use climb_db::queries::{consensus_grades, ConsensusGrade};
+
+    let result = consensus_grades(conn, &[climb]).expect("Failed to get consensus grades");
+
+    // V4 is 26.5 and V7 is 30
+    assert_eq!(result, vec![ConsensusGrade {
+        climb_id: climb,
+        grade_type: "vermin".to_string(),
+        median: "V5".to_string(),
+        mode: "V5".to_string(),
+        votes: 4,
+        spread: Some(3.5),
+    }]);
+
+    use climb_db::queries::{grade_votes, GradeVotes};
+
+    let result = grade_votes(conn, &[climb]).expect("Failed to get votes");
+
+    let votes = |value: &str, votes| GradeVotes {
+        climb_id: climb,
+        grade_type: "vermin".to_string(),
+        value: value.to_string(),
+        votes,
+    };
+
+    assert_eq!(result, vec![votes("V4", 1), votes("V5", 2), votes("V7", 1)]);
+}
+
+/// Removing or moving ascents updates the consensus of their climbs
+#[test]
+fn follows_ascents() {
+    let mut db = TestDatabase::with_migrations("test__consensus_grades__follows_ascents");
+    let conn = db.connection();
+
+    let climb = insert_climb(conn, &[]);
+    let other = insert_climb(conn, &[]);
+
+    let easy = insert_ascent(conn, climb, "V2");
+    let hard = insert_ascent(conn, climb, "V6");
+
+    use climb_db::queries::consensus_grades;
+
+    let medians = |conn: &mut PgConnection| {
+        consensus_grades(conn, &[climb, other])
+            .expect("Failed to get consensus grades")
+            .into_iter()
+            .map(|consensus| (consensus.climb_id, consensus.median, consensus.votes))
+            .collect::<Vec<_>>()
+    };
+
+    // Ties take the easier grade
+    assert_eq!(medians(conn), vec![(climb, "V2".to_string(), 2)]);
+
+    use climb_db::schema::ascents;
+
+    diesel::delete(ascents::table.find(easy)).execute(conn).expect("Failed to delete ascent");
+
+    assert_eq!(medians(conn), vec![(climb, "V6".to_string(), 1)]);
+
+    diesel::update(ascents::table.find(hard))
+        .set(ascents::climb_id.eq(other))
+        .execute(conn)
+        .expect("Failed to move ascent");
+
+    assert_eq!(medians(conn), vec![(other, "V6".to_string(), 1)]);
+}
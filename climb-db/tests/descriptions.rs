@@ -0,0 +1,116 @@
+use common::TestDatabase;
+use diesel::prelude::*;
+
+mod common;
+
+use climb_db::queries::Place;
+
+fn insert_area(conn: &mut PgConnection) -> i32 {
+    use climb_db::schema::areas;
+
+    diesel::insert_into(areas::table)
+        .default_values()
+        .returning(areas::id)
+        .get_result(conn)
+        .expect("Failed to insert area")
+}
+
+fn insert_formation(conn: &mut PgConnection) -> i32 {
+    use climb_db::models::NewFormation;
+    use climb_db::schema::formations;
+
+    diesel::insert_into(formations::table)
+        .values(NewFormation::default())
+        .returning(formations::id)
+        .get_result(conn)
+        .expect("Failed to insert formation")
+}
+
+/// Descriptions of a place as `(key, value)` pairs
+fn pairs(conn: &mut PgConnection, place: Place) -> Vec<(String, String)> {
+    use climb_db::descriptions::descriptions;
+
+    descriptions(conn, &[place])
+        .expect("Failed to get descriptions")
+        .into_iter()
+        .map(|(_, description)| (description.key, description.value))
+        .collect()
+}
+
+/// Setting a description replaces the description of the same type
+#[test]
+fn upsert() {
+    let mut db = TestDatabase::with_migrations("test__descriptions__upsert");
+    let conn = db.connection();
+
+    let area = Place::Area(insert_area(conn));
+
+    use climb_db::descriptions::set_description;
+
+    set_description(conn, area, "brief", "A granite dome").expect("Failed to set description");
+    set_description(conn, area, "hist", "First climbed in 1957").expect("Failed to set description");
+    set_description(conn, area, "brief", "A granite dome above the valley").expect("Failed to set description");
+
+    assert_eq!(pairs(conn, area), vec![
+        ("brief".to_string(), "A granite dome above the valley".to_string()),
+        ("hist".to_string(), "First climbed in 1957".to_string()),
+    ]);
+}
+
+/// Unknown description types and missing places are errors
+#[test]
+fn not_found() {
+    let mut db = TestDatabase::with_migrations("test__descriptions__not_found");
+    let conn = db.connection();
+
+    let formation = Place::Formation(insert_formation(conn));
+
+    use climb_db::descriptions::set_description;
+
+    let result = set_description(conn, formation, "approach", "Follow the creek");
+
+    assert_eq!(result, Err(diesel::result::Error::NotFound));
+
+    let result = set_description(conn, Place::Climb(10), "brief", "Slab");
+
+    assert_eq!(result, Err(diesel::result::Error::NotFound));
+}
+
+/// Added description types can describe areas, formations and climbs alike
+#[test]
+fn add_type() {
+    let mut db = TestDatabase::with_migrations("test__descriptions__add_type");
+    let conn = db.connection();
+
+    use climb_db::descriptions::{add_description_type, description_types, set_description};
+
+    add_description_type(conn, "approach").expect("Failed to add description type");
+
+    assert!(add_description_type(conn, "approach").is_err());
+    assert!(add_description_type(conn, " ").is_err());
+    assert!(description_types(conn).expect("Failed to get description types").contains(&"approach".to_string()));
+
+    let formation = Place::Formation(insert_formation(conn));
+
+    set_description(conn, formation, "approach", "Follow the creek").expect("Failed to set description");
+
+    assert_eq!(pairs(conn, formation), vec![("approach".to_string(), "Follow the creek".to_string())]);
+}
+
+/// Removing a description removes only that description
+#[test]
+fn remove() {
+    let mut db = TestDatabase::with_migrations("test__descriptions__remove");
+    let conn = db.connection();
+
+    let area = Place::Area(insert_area(conn));
+
+    use climb_db::descriptions::{remove_description, set_description};
+
+    set_description(conn, area, "brief", "A granite dome").expect("Failed to set description");
+    set_description(conn, area, "hist", "First climbed in 1957").expect("Failed to set description");
+
+    assert_eq!(remove_description(conn, area, "brief"), Ok(true));
+    assert_eq!(remove_description(conn, area, "brief"), Ok(false));
+    assert_eq!(pairs(conn, area), vec![("hist".to_string(), "First climbed in 1957".to_string())]);
+}
@@ -0,0 +1,142 @@
+use common::TestDatabase;
+use diesel::prelude::*;
+
+mod common;
+
+/// Formations of sub areas and sub formations are exported with climb counts and grade ranges
+#[test]
+fn feature_collection() {
+    let mut db = TestDatabase::with_migrations("test__export__feature_collection");
+    let conn = db.connection();
+
+    use climb_db::schema::areas;
+
+    let areas: Vec<i32> = (0..2)
+        .map(|_| diesel::insert_into(areas::table).default_values().returning(areas::id).get_result(conn))
+        .collect::<QueryResult<_>>()
+        .expect("Failed to insert areas");
+
+    let (region, crag) = (areas[0], areas[1]);
+
+    use climb_db::models::NewAreaBelongsTo;
+    use climb_db::schema::area_belongs_to;
+
+    diesel::insert_into(area_belongs_to::table)
+        .values(NewAreaBelongsTo { area_id: crag, super_area_id: region })
+        .execute(conn)
+        .expect("Failed to insert area_belongs_to");
+
+    use climb_db::queries::set_area_boundary;
+
+    let boundary = r#"{"type":"Polygon","coordinates":[[[-104,43],[-103,43],[-103,44],[-104,44],[-104,43]]]}"#;
+
+    set_area_boundary(conn, region, boundary).expect("Failed to set boundary");
+
+    use climb_db::geo::GeoPoint;
+    use climb_db::models::NewFormation;
+    use climb_db::schema::formations;
+
+    let formations: Vec<i32> = diesel::insert_into(formations::table)
+        .values(vec![
+            NewFormation {
+                location: Some(GeoPoint::new(-103.456774, 43.889938).unwrap()),
+            },
+            NewFormation {
+                location: Some(GeoPoint::new(-103.456770, 43.889940).unwrap()),
+            },
+        ])
+        .returning(formations::id)
+        .get_results(conn)
+        .expect("Failed to insert formations");
+
+    let (boulder, face) = (formations[0], formations[1]);
+
+    use climb_db::queries::add_formation_name;
+
+    add_formation_name(conn, boulder, "Hydra Boulder").expect("Failed to add name");
+    add_formation_name(conn, face, "Hydra Boulder, North Face").expect("Failed to add name");
+
+    use climb_db::models::NewFormationBelongsTo;
+    use climb_db::schema::formation_belongs_to;
+
+    diesel::insert_into(formation_belongs_to::table)
+        .values(vec![
+            NewFormationBelongsTo { formation_id: boulder, area_id: Some(crag), super_formation_id: None },
+            NewFormationBelongsTo { formation_id: face, area_id: None, super_formation_id: Some(boulder) },
+        ])
+        .execute(conn)
+        .expect("Failed to insert formation_belongs_to");
+
+    use climb_db::schema::climbs;
+
+    let climbs: Vec<i32> = (0..3)
+        .map(|_| diesel::insert_into(climbs::table).default_values().returning(climbs::id).get_result(conn))
+        .collect::<QueryResult<_>>()
+        .expect("Failed to insert climbs");
+
+    use climb_db::models::NewClimbBelongsTo;
+    use climb_db::schema::climb_belongs_to;
+
+    diesel::insert_into(climb_belongs_to::table)
+        .values(vec![
+            NewClimbBelongsTo { climb_id: climbs[0], area_id: None, formation_id: Some(boulder) },
+            NewClimbBelongsTo { climb_id: climbs[1], area_id: None, formation_id: Some(face) },
+            NewClimbBelongsTo { climb_id: climbs[2], area_id: Some(region), formation_id: None },
+        ])
+        .execute(conn)
+        .expect("Failed to insert climb_belongs_to");
+
+    use climb_db::grade::GradeSystem;
+    use climb_db::queries::add_climb_grade;
+
+    let v3 = GradeSystem::Vermin.parse("V3").unwrap();
+    let v10 = GradeSystem::Vermin.parse("V10").unwrap();
+
+    add_climb_grade(conn, climbs[0], &v3).expect("Failed to add grade");
+    add_climb_grade(conn, climbs[1], &v10).expect("Failed to add grade");
+
+    use climb_db::export::area_feature_collection;
+
+    let result = area_feature_collection(conn, region).expect("Failed to export area");
+    let result: serde_json::Value = serde_json::from_str(&result).expect("Failed to parse GeoJSON");
+
+    assert_eq!(result["type"], "FeatureCollection");
+
+    let features = result["features"].as_array().expect("Missing features");
+
+    let summary: Vec<_> = features
+        .iter()
+        .map(|feature| {
+            let properties = &feature["properties"];
+            (
+                feature["geometry"]["type"].clone(),
+                properties["id"].clone(),
+                properties["climbCount"].clone(),
+                properties["grades"]["vermin"]["min"].clone(),
+                properties["grades"]["vermin"]["max"].clone(),
+            )
+        })
+        .collect();
+
+    use serde_json::json;
+
+    assert_eq!(summary, vec![
+        (json!("MultiPolygon"), json!(region), json!(3), json!("V3"), json!("V10")),
+        (json!("Point"), json!(boulder), json!(2), json!("V3"), json!("V10")),
+        (json!("Point"), json!(face), json!(1), json!("V10"), json!("V10")),
+    ]);
+    assert_eq!(features[1]["properties"]["names"], json!(["Hydra Boulder"]));
+}
+
+/// Exporting a missing area is an error
+#[test]
+fn not_found() {
+    let mut db = TestDatabase::with_migrations("test__export__not_found");
+    let conn = db.connection();
+
+    use climb_db::export::area_feature_collection;
+
+    let result = area_feature_collection(conn, 10);
+
+    assert_eq!(result, Err(diesel::result::Error::NotFound));
+}
@@ -1,4 +1,4 @@
-use climb_db::models::{NewFormation, Formation, NewArea, Area, NewFormationBelongsTo, FormationBelongsTo};
+use climb_db::models::{NewFormation, Formation, Area, NewFormationBelongsTo, FormationBelongsTo};
 use diesel::prelude::*;
 use common::TestDatabase;
 use diesel::RunQueryDsl;
@@ -16,10 +16,7 @@ pub fn cascade_area() {
     use climb_db::schema::formations;
 
     let formation = diesel::insert_into(formations::table)
-        .values(NewFormation {
-            names: vec![Some("Irie Heights Boulder".to_string())],
-            ..Default::default()
-        })
+        .values(NewFormation::default())
         .returning(Formation::as_returning())
         .get_result(conn)
         .expect("Failed to insert formation");
@@ -27,7 +24,7 @@ pub fn cascade_area() {
     use climb_db::schema::areas;
 
     let area = diesel::insert_into(areas::table)
-        .values(NewArea { names: vec![Some("Irie Heights".to_string())] })
+        .default_values()
         .returning(Area::as_returning())
         .get_result(conn)
         .expect("Failed to insert formation");
@@ -68,19 +65,13 @@ pub fn cascade_super_formation() {
     use climb_db::schema::formations;
 
     let formation = diesel::insert_into(formations::table)
-        .values(NewFormation {
-            names: vec![Some("The Diamond".to_string())],
-            ..Default::default()
-        })
+        .values(NewFormation::default())
         .returning(Formation::as_returning())
         .get_result(conn)
         .expect("Failed to insert formation");
 
     let super_formation = diesel::insert_into(formations::table)
-        .values(NewFormation {
-            names: vec![Some("Long's Peak".to_string())],
-            ..Default::default()
-        })
+        .values(NewFormation::default())
         .returning(Formation::as_returning())
         .get_result(conn)
         .expect("Failed to insert formation");
@@ -121,10 +112,7 @@ pub fn foreign_key() {
     use climb_db::schema::formations;
 
     let formation = diesel::insert_into(formations::table)
-        .values(NewFormation {
-            names: vec![Some("Irie Heights Boulder".to_string())],
-            ..Default::default()
-        })
+        .values(NewFormation::default())
         .returning(Formation::as_returning())
         .get_result(conn)
         .expect("Failed to insert formation");
@@ -163,19 +151,13 @@ pub fn one_parent() {
     use climb_db::schema::formations;
 
     let formation = diesel::insert_into(formations::table)
-        .values(NewFormation {
-            names: vec![Some("The Diamond".to_string())],
-            ..Default::default()
-        })
+        .values(NewFormation::default())
         .returning(Formation::as_returning())
         .get_result(conn)
         .expect("Failed to insert formation");
 
     let super_formation = diesel::insert_into(formations::table)
-        .values(NewFormation {
-            names: vec![Some("Long's Peak".to_string())],
-            ..Default::default()
-        })
+        .values(NewFormation::default())
         .returning(Formation::as_returning())
         .get_result(conn)
         .expect("Failed to insert formation");
@@ -183,7 +165,7 @@ pub fn one_parent() {
     use climb_db::schema::areas;
 
     let area = diesel::insert_into(areas::table)
-        .values(NewArea { names: vec![Some("Rock Mountain National Park".to_string())] })
+        .default_values()
         .returning(Area::as_returning())
         .get_result(conn)
         .expect("Failed to insert area");
@@ -223,10 +205,7 @@ pub fn restrict_area() {
     use climb_db::schema::formations;
 
     let formation = diesel::insert_into(formations::table)
-        .values(NewFormation {
-            names: vec![Some("Irie Heights Boulder".to_string())],
-            ..Default::default()
-        })
+        .values(NewFormation::default())
         .returning(Formation::as_returning())
         .get_result(conn)
         .expect("Failed to insert formation");
@@ -234,7 +213,7 @@ pub fn restrict_area() {
     use climb_db::schema::areas;
 
     let area = diesel::insert_into(areas::table)
-        .values(NewArea { names: vec![Some("Irie Heights".to_string())] })
+        .default_values()
         .returning(Area::as_returning())
         .get_result(conn)
         .expect("Failed to insert formation");
@@ -269,19 +248,13 @@ pub fn restrict_super_formation() {
     use climb_db::schema::formations;
 
     let formation = diesel::insert_into(formations::table)
-        .values(NewFormation {
-            names: vec![Some("The Diamond".to_string())],
-            ..Default::default()
-        })
+        .values(NewFormation::default())
         .returning(Formation::as_returning())
         .get_result(conn)
         .expect("Failed to insert formation");
 
     let super_formation = diesel::insert_into(formations::table)
-        .values(NewFormation {
-            names: vec![Some("Long's Peak".to_string())],
-            ..Default::default()
-        })
+        .values(NewFormation::default())
         .returning(Formation::as_returning())
         .get_result(conn)
         .expect("Failed to insert formation");
@@ -315,10 +288,7 @@ pub fn no_self_parent() {
     use climb_db::schema::formations;
 
     let formation = diesel::insert_into(formations::table)
-        .values(NewFormation {
-            names: vec![Some("The Diamond".to_string())],
-            ..Default::default()
-        })
+        .values(NewFormation::default())
         .returning(Formation::as_returning())
         .get_result(conn)
         .expect("Failed to insert formation");
@@ -346,19 +316,13 @@ pub fn no_cycles() {
     use climb_db::schema::formations;
 
     let formation = diesel::insert_into(formations::table)
-        .values(NewFormation {
-            names: vec![Some("The Diamond".to_string())],
-            ..Default::default()
-        })
+        .values(NewFormation::default())
         .returning(Formation::as_returning())
         .get_result(conn)
         .expect("Failed to insert formation");
 
     let super_formation = diesel::insert_into(formations::table)
-        .values(NewFormation {
-            names: vec![Some("Long's Peak".to_string())],
-            ..Default::default()
-        })
+        .values(NewFormation::default())
         .returning(Formation::as_returning())
         .get_result(conn)
         .expect("Failed to insert formation");
@@ -1,7 +1,7 @@
 use diesel::prelude::*;
 use common::TestDatabase;
 use diesel::RunQueryDsl;
-use postgis_diesel::types::Point;
+use climb_db::geo::GeoPoint;
 
 mod common;
 
@@ -26,8 +26,7 @@ pub fn simple() {
 
     let hydra_boulder = diesel::insert_into(formations::table)
         .values(NewFormation {
-            names: vec![Some("Hydra Boulder".to_string())],
-            location: Some(Point { x: 43.889938, y: -103.456774, srid: Some(4326) }),
+            location: Some(GeoPoint::new(-103.456774, 43.889938).unwrap()),
         })
         .returning(Formation::as_returning())
         .get_result(conn)
@@ -35,8 +34,7 @@ pub fn simple() {
 
     diesel::insert_into(formations::table)
         .values(NewFormation {
-            names: vec![Some("Irie Heights Boulder".to_string())],
-            location: Some(Point { x: 43.892805, y: -103.459500, srid: Some(4326) }),
+            location: Some(GeoPoint::new(-103.459500, 43.892805).unwrap()),
         })
         .execute(conn)
         .expect("Failed to insert formation");
@@ -57,3 +55,139 @@ pub fn simple() {
 
     assert!(southern_formations.iter().any(|f| f.id == hydra_boulder.id));
 }
+
+/// Inserts formations at locations, returning their ids
+fn insert_formations(conn: &mut PgConnection, locations: &[GeoPoint]) -> Vec<i32> {
+    use climb_db::models::NewFormation;
+    use climb_db::schema::formations;
+
+    let new_formations: Vec<NewFormation> = locations
+        .iter()
+        .map(|&location| NewFormation {
+            location: Some(location),
+        })
+        .collect();
+
+    diesel::insert_into(formations::table)
+        .values(&new_formations)
+        .returning(formations::id)
+        .get_results(conn)
+        .expect("Failed to insert formations")
+}
+
+/// Only formations within a bounding box are found
+#[test]
+pub fn within() {
+    let mut db = TestDatabase::with_migrations("test__formation_spacial__within");
+    let conn = db.connection();
+
+    let ids = insert_formations(conn, &[
+        GeoPoint::new(-103.456774, 43.889938).unwrap(),
+        GeoPoint::new(-103.459500, 43.892805).unwrap(),
+        GeoPoint::new(-105.293966, 40.018234).unwrap(),
+    ]);
+
+    use climb_db::queries::formations_within;
+
+    let south_west = GeoPoint::new(-103.5, 43.8).unwrap();
+    let north_east = GeoPoint::new(-103.4, 43.9).unwrap();
+
+    let result = formations_within(conn, &south_west, &north_east)
+        .expect("Failed to get formations");
+
+    assert_eq!(result, ids[..2]);
+}
+
+/// A bounding box whose west edge is east of its east edge crosses the antimeridian
+#[test]
+pub fn within_antimeridian() {
+    let mut db = TestDatabase::with_migrations("test__formation_spacial__within_antimeridian");
+    let conn = db.connection();
+
+    let ids = insert_formations(conn, &[
+        GeoPoint::new(179.5, -16.5).unwrap(),
+        GeoPoint::new(-179.5, -16.5).unwrap(),
+        GeoPoint::new(0.0, -16.5).unwrap(),
+    ]);
+
+    use climb_db::queries::formations_within;
+
+    let south_west = GeoPoint::new(179.0, -17.0).unwrap();
+    let north_east = GeoPoint::new(-179.0, -16.0).unwrap();
+
+    let result = formations_within(conn, &south_west, &north_east)
+        .expect("Failed to get formations");
+
+    assert_eq!(result, ids[..2]);
+}
+
+/// Only formations within a radius are found, nearest first
+#[test]
+pub fn near() {
+    let mut db = TestDatabase::with_migrations("test__formation_spacial__near");
+    let conn = db.connection();
+
+    let ids = insert_formations(conn, &[
+        GeoPoint::new(-103.459500, 43.892805).unwrap(),
+        GeoPoint::new(-103.456774, 43.889938).unwrap(),
+        GeoPoint::new(-105.293966, 40.018234).unwrap(),
+    ]);
+
+    use climb_db::queries::formations_near;
+
+    let result = formations_near(conn, &GeoPoint::new(-103.456774, 43.889938).unwrap(), 1000.0)
+        .expect("Failed to get formations");
+
+    assert_eq!(result.iter().map(|f| f.id).collect::<Vec<_>>(), vec![ids[1], ids[0]]);
+    assert_eq!(result[0].distance, 0.0);
+    // Roughly 390 meters apart
+    assert!((350.0..450.0).contains(&result[1].distance));
+}
+
+/// At most the nearest `limit` formations are found, nearest first
+#[test]
+pub fn nearest() {
+    let mut db = TestDatabase::with_migrations("test__formation_spacial__nearest");
+    let conn = db.connection();
+
+    let ids = insert_formations(conn, &[
+        GeoPoint::new(-105.293966, 40.018234).unwrap(),
+        GeoPoint::new(-103.459500, 43.892805).unwrap(),
+        GeoPoint::new(-103.456774, 43.889938).unwrap(),
+    ]);
+
+    use climb_db::queries::nearest_formations;
+
+    let result = nearest_formations(conn, &GeoPoint::new(-103.0, 44.0).unwrap(), 2)
+        .expect("Failed to get formations");
+
+    assert_eq!(result.iter().map(|f| f.id).collect::<Vec<_>>(), vec![ids[2], ids[1]]);
+    assert!(result[0].distance < result[1].distance);
+}
+
+/// Locations are stored with longitude as x and latitude as y, in WGS 84
+#[test]
+pub fn axis_order() {
+    let mut db = TestDatabase::with_migrations("test__formation_spacial__axis_order");
+    let conn = db.connection();
+
+    let ids = insert_formations(conn, &[GeoPoint::new(-103.456774, 43.889938).unwrap()]);
+
+    use climb_db::schema::formations;
+
+    diesel::define_sql_function! {
+        fn st_x(geom: Nullable<Geometry>) -> Nullable<Double>;
+    }
+
+    diesel::define_sql_function! {
+        fn st_srid(geom: Nullable<Geometry>) -> Nullable<Integer>;
+    }
+
+    let result = formations::table
+        .find(ids[0])
+        .select((st_x(formations::location), st_y(formations::location), st_srid(formations::location)))
+        .first::<(Option<f64>, Option<f64>, Option<i32>)>(conn)
+        .expect("Failed to get location");
+
+    assert_eq!(result, (Some(-103.456774), Some(43.889938), Some(4326)));
+}
@@ -0,0 +1,25 @@
+use climb_db::geo::{GeoPoint, GeoPointError};
+
+/// Coordinates outside of WGS 84 bounds are rejected
+#[test]
+fn out_of_range() {
+    assert_eq!(GeoPoint::new(-180.5, 0.0), Err(GeoPointError::LongitudeOutOfRange(-180.5)));
+    assert_eq!(GeoPoint::new(0.0, 90.5), Err(GeoPointError::LatitudeOutOfRange(90.5)));
+    assert!(GeoPoint::new(f64::NAN, 0.0).is_err());
+    assert!(GeoPoint::new(180.0, -90.0).is_ok());
+}
+
+/// Points are converted with longitude as x and latitude as y, in WGS 84
+#[test]
+fn point() {
+    use postgis_diesel::types::Point;
+
+    let point = Point::from(GeoPoint::new(-103.456774, 43.889938).unwrap());
+
+    assert_eq!(point, Point { x: -103.456774, y: 43.889938, srid: Some(4326) });
+    assert_eq!(GeoPoint::try_from(point).map(|p| p.latitude()), Ok(43.889938));
+    assert_eq!(
+        GeoPoint::try_from(Point { srid: Some(3857), ..point }),
+        Err(GeoPointError::UnexpectedSrid(3857))
+    );
+}
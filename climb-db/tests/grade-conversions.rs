@@ -0,0 +1,258 @@
+use common::{insert_climb, TestDatabase};
+use diesel::prelude::*;
+
+mod common;
+
+fn climb_difficulty(conn: &mut PgConnection, id: i32) -> Option<f64> {
+    use climb_db::schema::climbs;
+
+    climbs::table
+        .find(id)
+        .select(climbs::difficulty)
+        .first(conn)
+        .expect("Failed to get difficulty")
+}
+
+/// Default conversions are all applied, in the canonical form of their grade with its sort key
+#[test]
+fn defaults_canonical() {
+    let mut db = TestDatabase::with_migrations("test__grade_conversions__defaults_canonical");
+    let conn = db.connection();
+
+    use climb_db::schema::{grade_conversions, grade_types, grades};
+
+    let conversions = grade_conversions::table
+        .inner_join(grades::table.inner_join(grade_types::table))
+        .select((grade_types::name, grades::value, grades::sort_key))
+        .load::<(String, String, i32)>(conn)
+        .expect("Failed to get conversions");
+
+    assert!(!conversions.is_empty());
+
+    use climb_db::schema::pending_grade_conversions;
+
+    let pending = pending_grade_conversions::table
+        .count()
+        .get_result::<i64>(conn)
+        .expect("Failed to count pending conversions");
+
+    assert_eq!(pending, 0);
+
+    use climb_db::grade::GradeSystem;
+
+    for (name, value, sort_key) in conversions {
+        let system = GradeSystem::from_name(&name).expect("Unknown grade type");
+        let parsed = system.parse(&value).expect("Failed to parse conversion");
+
+        assert_eq!((parsed.value, parsed.sort_key), (value, sort_key));
+    }
+}
+
+/// Changing a conversion refreshes the difficulty of climbs graded in its grade type only
+#[test]
+fn refresh_grade_type() {
+    let mut db = TestDatabase::with_migrations("test__grade_conversions__refresh_grade_type");
+    let conn = db.connection();
+
+    use climb_db::grade::GradeSystem;
+    use climb_db::queries::{add_climb_grade, set_grade_conversion};
+    use climb_db::schema::climbs;
+
+    let boulder = insert_climb(conn, &[]);
+    add_climb_grade(conn, boulder, &GradeSystem::Vermin.parse("V5").expect("Failed to parse grade"))
+        .expect("Failed to grade climb");
+
+    let route = insert_climb(conn, &[]);
+    add_climb_grade(conn, route, &GradeSystem::Yds.parse("5.10a").expect("Failed to parse grade"))
+        .expect("Failed to grade climb");
+
+    // Marks both climbs so refreshes show
+    diesel::update(climbs::table)
+        .set(climbs::difficulty.eq(0.0))
+        .execute(conn)
+        .expect("Failed to mark climbs");
+
+    set_grade_conversion(conn, &GradeSystem::Vermin.parse("V5").expect("Failed to parse grade"), 28.5)
+        .expect("Failed to set conversion");
+
+    assert_eq!(climb_difficulty(conn, boulder), Some(28.5));
+    assert_eq!(climb_difficulty(conn, route), Some(0.0));
+}
+
+/// Grades stored with stale sort keys are re-keyed, along with the difficulty of their climbs
+#[test]
+fn rekey() {
+    let mut db = TestDatabase::with_migrations("test__grade_conversions__rekey");
+    let conn = db.connection();
+
+    use climb_db::grade::GradeSystem;
+    use climb_db::queries::{add_climb_grade, rekey_grades};
+    use climb_db::schema::{grade_types, grades};
+
+    let grade = GradeSystem::Yds.parse("5.10b").expect("Failed to parse grade");
+    let id = insert_climb(conn, &[]);
+    add_climb_grade(conn, id, &grade).expect("Failed to grade climb");
+    let difficulty = climb_difficulty(conn, id);
+
+    let stale = grades::table.filter(grades::grade_type_id.eq_any(
+        grade_types::table.filter(grade_types::name.eq("yds")).select(grade_types::id),
+    ));
+
+    diesel::update(stale.filter(grades::value.eq("5.10b")))
+        .set(grades::sort_key.eq(0))
+        .execute(conn)
+        .expect("Failed to make sort key stale");
+    diesel::sql_query(format!("SELECT refresh_climb_difficulty({id})"))
+        .execute(conn)
+        .expect("Failed to refresh difficulty");
+    assert_ne!(climb_difficulty(conn, id), difficulty);
+
+    assert_eq!(rekey_grades(conn), Ok(1));
+
+    let sort_key = stale
+        .filter(grades::value.eq("5.10b"))
+        .select(grades::sort_key)
+        .first::<i32>(conn)
+        .expect("Failed to get sort key");
+
+    assert_eq!(sort_key, grade.sort_key);
+    assert_eq!(climb_difficulty(conn, id), difficulty);
+    assert_eq!(rekey_grades(conn), Ok(0));
+}
+
+/// Grades between conversions are interpolated, and grades beyond them are clamped
+#[test]
+fn difficulty() {
+    let mut db = TestDatabase::with_migrations("test__grade_conversions__difficulty");
+    let conn = db.connection();
+
+    use climb_db::grade::GradeSystem;
+    use climb_db::queries::grade_difficulty;
+
+    let difficulty = |conn: &mut PgConnection, system: GradeSystem, value: &str| {
+        grade_difficulty(conn, &system.parse(value).unwrap()).expect("Failed to get difficulty")
+    };
+
+    assert_eq!(difficulty(conn, GradeSystem::Yds, "5.10a"), Some(18.0));
+    assert_eq!(difficulty(conn, GradeSystem::French, "6a"), Some(19.0));
+    assert_eq!(difficulty(conn, GradeSystem::Ewbank, "24"), Some(24.0));
+
+    // 5.10a/b sits halfway between 5.10a and 5.10b
+    assert_eq!(difficulty(conn, GradeSystem::Yds, "5.10a/b"), Some(18.5));
+
+    // 5.0 is easier than the easiest conversion, 5.3
+    assert_eq!(difficulty(conn, GradeSystem::Yds, "5.0"), Some(10.0));
+}
+
+/// Climbs have the mean difficulty of their grades, kept up to date as they are graded
+#[test]
+fn climb_difficulty_follows_grades() {
+    let mut db = TestDatabase::with_migrations("test__grade_conversions__climb_difficulty_follows_grades");
+    let conn = db.connection();
+
+    let climb = insert_climb(conn, &[]);
+
+    assert_eq!(climb_difficulty(conn, climb), None);
+
+    use climb_db::grade::GradeSystem;
+    use climb_db::queries::{add_climb_grade, remove_climb_grade};
+
+    let yds = GradeSystem::Yds.parse("5.10a").unwrap();
+    let french = GradeSystem::French.parse("6a").unwrap();
+
+    add_climb_grade(conn, climb, &yds).expect("Failed to add grade");
+    assert_eq!(climb_difficulty(conn, climb), Some(18.0));
+
+    add_climb_grade(conn, climb, &french).expect("Failed to add grade");
+    assert_eq!(climb_difficulty(conn, climb), Some(18.5));
+
+    remove_climb_grade(conn, climb, &yds).expect("Failed to remove grade");
+    assert_eq!(climb_difficulty(conn, climb), Some(19.0));
+
+    remove_climb_grade(conn, climb, &french).expect("Failed to remove grade");
+    assert_eq!(climb_difficulty(conn, climb), None);
+}
+
+/// Changing a conversion updates the difficulty of climbs with that grade
+#[test]
+fn climb_difficulty_follows_conversions() {
+    let mut db = TestDatabase::with_migrations("test__grade_conversions__climb_difficulty_follows_conversions");
+    let conn = db.connection();
+
+    let climb = insert_climb(conn, &[]);
+
+    use climb_db::grade::GradeSystem;
+    use climb_db::queries::{add_climb_grade, remove_grade_conversion, set_grade_conversion};
+
+    let v5 = GradeSystem::Vermin.parse("V5").unwrap();
+
+    add_climb_grade(conn, climb, &v5).expect("Failed to add grade");
+    assert_eq!(climb_difficulty(conn, climb), Some(28.0));
+
+    set_grade_conversion(conn, &v5, 27.0).expect("Failed to set conversion");
+    assert_eq!(climb_difficulty(conn, climb), Some(27.0));
+
+    // Without its own conversion, V5 sits between V4 and V6
+    assert_eq!(remove_grade_conversion(conn, &v5), Ok(1));
+    assert_eq!(climb_difficulty(conn, climb), Some(27.75));
+}
+
+/// Grades are converted into the nearest grade of another grade type
+#[test]
+fn convert() {
+    let mut db = TestDatabase::with_migrations("test__grade_conversions__convert");
+    let conn = db.connection();
+
+    let boulder = insert_climb(conn, &[]);
+    let route = insert_climb(conn, &[]);
+    let ungraded = insert_climb(conn, &[]);
+
+    use climb_db::grade::GradeSystem;
+    use climb_db::queries::add_climb_grade;
+
+    add_climb_grade(conn, boulder, &GradeSystem::Vermin.parse("V5").unwrap()).expect("Failed to add grade");
+    add_climb_grade(conn, route, &GradeSystem::Yds.parse("5.11d").unwrap()).expect("Failed to add grade");
+    add_climb_grade(conn, route, &GradeSystem::French.parse("7a").unwrap()).expect("Failed to add grade");
+
+    use climb_db::queries::{converted_climb_grades, ConvertedGrade};
+
+    let result = converted_climb_grades(conn, &[boulder, route, ungraded], GradeSystem::French)
+        .expect("Failed to convert grades");
+
+    // The French grade of the route is not converted, and equal conversions are not repeated
+    assert_eq!(result, vec![
+        ConvertedGrade { climb_id: boulder, value: "7b+".to_string() },
+        ConvertedGrade { climb_id: route, value: "7a".to_string() },
+    ]);
+
+    let result = converted_climb_grades(conn, &[boulder], GradeSystem::Font).expect("Failed to convert grades");
+
+    assert_eq!(result, vec![ConvertedGrade { climb_id: boulder, value: "6C".to_string() }]);
+}
+
+/// Known grades are listed by sort key, with the difficulty of their own conversion only
+#[test]
+fn known_grades() {
+    let mut db = TestDatabase::with_migrations("test__grade_conversions__known_grades");
+    let conn = db.connection();
+
+    let climb = insert_climb(conn, &[]);
+
+    use climb_db::grade::GradeSystem;
+    use climb_db::queries::add_climb_grade;
+
+    add_climb_grade(conn, climb, &GradeSystem::Yds.parse("5.10a/b").unwrap()).expect("Failed to add grade");
+
+    use climb_db::queries::known_grades;
+
+    let grades = known_grades(conn, Some(GradeSystem::Yds)).expect("Failed to list grades");
+
+    assert!(grades.iter().all(|grade| grade.system == "yds"));
+    assert!(grades.windows(2).all(|pair| pair[0].sort_key < pair[1].sort_key));
+
+    let position = |value: &str| grades.iter().position(|grade| grade.value == value).expect("Grade not listed");
+
+    assert_eq!(position("5.10a/b"), position("5.10a") + 1);
+    assert_eq!(grades[position("5.10a")].difficulty, Some(18.0));
+    assert_eq!(grades[position("5.10a/b")].difficulty, None);
+}
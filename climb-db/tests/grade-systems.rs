@@ -0,0 +1,110 @@
+use climb_db::grade::GradeSystem;
+
+/// Parses `values`, returning their canonical values and sort keys
+fn parse(system: GradeSystem, values: &[&str]) -> Vec<(String, i32)> {
+    values
+        .iter()
+        .map(|value| {
+            let grade = system.parse(value).expect("Failed to parse grade");
+            (grade.value, grade.sort_key)
+        })
+        .collect()
+}
+
+/// Asserts sort keys strictly increase along `values`
+fn assert_ordered(system: GradeSystem, values: &[&str]) {
+    let keys: Vec<i32> = parse(system, values).into_iter().map(|(_, key)| key).collect();
+    assert!(keys.windows(2).all(|pair| pair[0] < pair[1]), "{values:?} sorted as {keys:?}");
+}
+
+/// Every system is known by its `grade_types` name
+#[test]
+fn names() {
+    for system in GradeSystem::ALL {
+        assert_eq!(GradeSystem::from_name(system.name()), Some(system));
+    }
+
+    assert_eq!(GradeSystem::from_name("basecamp"), None);
+}
+
+#[test]
+fn yds() {
+    assert_ordered(GradeSystem::Yds, &["5.6", "5.9-", "5.9", "5.9+", "5.10a", "5.10a/b", "5.10b", "5.10", "5.10d", "5.11a", "5.15d"]);
+    assert_eq!(parse(GradeSystem::Yds, &["5.10A"])[0].0, "5.10a");
+
+    for value in ["5.9a", "5.10e", "5.10a/c", "5.16a", "6.10a", "5."] {
+        assert!(GradeSystem::Yds.parse(value).is_err(), "{value} parsed");
+    }
+}
+
+#[test]
+fn french() {
+    assert_ordered(GradeSystem::French, &["4", "5a", "5+", "6a", "6a+", "6b", "6b+", "6c", "6c+", "7a", "9c"]);
+    assert_eq!(parse(GradeSystem::French, &["6B+"])[0].0, "6b+");
+
+    for value in ["6d", "0a", "10a", "6a++"] {
+        assert!(GradeSystem::French.parse(value).is_err(), "{value} parsed");
+    }
+}
+
+#[test]
+fn font() {
+    assert_ordered(GradeSystem::Font, &["3", "5+", "6A", "6A+", "6B", "7C+", "8C"]);
+    assert_eq!(parse(GradeSystem::Font, &["7a+"])[0].0, "7A+");
+}
+
+#[test]
+fn uiaa() {
+    assert_ordered(GradeSystem::Uiaa, &["III", "VI-", "VI", "VI+", "7-", "VII", "XII"]);
+    assert_eq!(parse(GradeSystem::Uiaa, &["7+", "vi"]), parse(GradeSystem::Uiaa, &["VII+", "VI"]));
+
+    for value in ["XIII", "13", "0", "VI++"] {
+        assert!(GradeSystem::Uiaa.parse(value).is_err(), "{value} parsed");
+    }
+}
+
+#[test]
+fn ewbank() {
+    assert_ordered(GradeSystem::Ewbank, &["1", "9", "10", "21", "39"]);
+
+    for value in ["0", "40", "21+", "-1"] {
+        assert!(GradeSystem::Ewbank.parse(value).is_err(), "{value} parsed");
+    }
+}
+
+/// Every grade of every system sorts after the grades easier than it, so no two grades share a key
+#[test]
+fn keys() {
+    let numbered = |range: std::ops::RangeInclusive<i32>, suffixes: &[&str], format: &dyn Fn(i32, &str) -> String| {
+        range
+            .flat_map(|number| suffixes.iter().map(move |suffix| (number, *suffix)))
+            .map(|(number, suffix)| format(number, suffix))
+            .collect::<Vec<_>>()
+    };
+
+    let yds = [
+        numbered(0..=9, &["-", "", "+"], &|class, suffix| format!("5.{class}{suffix}")),
+        numbered(10..=15, &["a", "a/b", "-", "b", "b/c", "", "c", "c/d", "+", "d"], &|class, suffix| {
+            format!("5.{class}{suffix}")
+        }),
+    ]
+    .concat();
+    let french_suffixes = ["a", "a+", "b", "", "b+", "+", "c", "c+"];
+
+    let grades = [
+        (GradeSystem::Vermin, numbered(0..=17, &[""], &|number, _| format!("V{number}"))),
+        (GradeSystem::Yds, yds),
+        (GradeSystem::French, numbered(1..=9, &french_suffixes, &|number, suffix| format!("{number}{suffix}"))),
+        (GradeSystem::Uiaa, numbered(1..=12, &["-", "", "+"], &|number, suffix| format!("{number}{suffix}"))),
+        (GradeSystem::Ewbank, numbered(1..=39, &[""], &|number, _| number.to_string())),
+        (
+            GradeSystem::Font,
+            numbered(1..=9, &french_suffixes, &|number, suffix| format!("{number}{}", suffix.to_uppercase())),
+        ),
+    ];
+
+    for (system, values) in grades {
+        let values: Vec<&str> = values.iter().map(String::as_str).collect();
+        assert_ordered(system, &values);
+    }
+}
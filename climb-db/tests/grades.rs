@@ -33,6 +33,11 @@ fn default_grade_types() {
 
     let default_grade_names = [
         "vermin".to_string(),
+        "yds".to_string(),
+        "french".to_string(),
+        "uiaa".to_string(),
+        "ewbank".to_string(),
+        "font".to_string(),
     ];
 
     use climb_db::models::GradeType;
@@ -72,7 +77,7 @@ fn grades_cascade_on_grade_type_delete() {
     use climb_db::schema::grades;
 
     let rows_inserted = diesel::insert_into(grades::table)
-        .values(NewGrade { grade_type_id: grade_type.id, value: "V5".to_string() })
+        .values(NewGrade { grade_type_id: grade_type.id, value: "V18".to_string(), sort_key: 18 })
         .execute(conn);
 
     assert_eq!(Ok(1), rows_inserted);
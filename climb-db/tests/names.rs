@@ -0,0 +1,210 @@
+use common::{insert_climb, TestDatabase};
+use diesel::prelude::*;
+
+mod common;
+
+use climb_db::queries::Place;
+
+/// Values of the names of a place, in order
+fn values(conn: &mut PgConnection, place: Place) -> Vec<String> {
+    use climb_db::names::names;
+
+    names(conn, &[place])
+        .expect("Failed to get names")
+        .into_iter()
+        .map(|(_, name)| name.value)
+        .collect()
+}
+
+/// Names containing quotes are stored verbatim
+#[test]
+fn apostrophe() {
+    let mut db = TestDatabase::with_migrations("test__names__apostrophe");
+    let conn = db.connection();
+
+    use climb_db::models::Climb;
+    use climb_db::schema::climbs;
+
+    let climb = diesel::insert_into(climbs::table)
+        .default_values()
+        .returning(Climb::as_returning())
+        .get_result(conn)
+        .expect("Failed to insert climb");
+
+    use climb_db::queries::add_climb_name;
+
+    add_climb_name(conn, climb.id, "Jerry's Kids").expect("Failed to add name");
+
+    let names = values(conn, Place::Climb(climb.id));
+
+    assert_eq!(names, vec!["Jerry's Kids".to_string()]);
+}
+
+/// Names outside of ASCII are stored verbatim
+#[test]
+fn unicode() {
+    let mut db = TestDatabase::with_migrations("test__names__unicode");
+    let conn = db.connection();
+
+    use climb_db::models::Area;
+    use climb_db::schema::areas;
+
+    let area = diesel::insert_into(areas::table)
+        .default_values()
+        .returning(Area::as_returning())
+        .get_result(conn)
+        .expect("Failed to insert area");
+
+    use climb_db::queries::add_area_name;
+
+    let names = ["Tuólumne", "Fontainebleau – Bas Cuvier", "御岳"];
+
+    for name in names {
+        add_area_name(conn, area.id, name).expect("Failed to add name");
+    }
+
+    let result = values(conn, Place::Area(area.id));
+
+    assert_eq!(result, names.map(|name| name.to_string()).to_vec());
+}
+
+/// Names are bound as parameters, not interpolated into the query
+#[test]
+fn injection() {
+    let mut db = TestDatabase::with_migrations("test__names__injection");
+    let conn = db.connection();
+
+    use climb_db::models::{Formation, NewFormation};
+    use climb_db::schema::formations;
+
+    let formation = diesel::insert_into(formations::table)
+        .values(NewFormation::default())
+        .returning(Formation::as_returning())
+        .get_result(conn)
+        .expect("Failed to insert formation");
+
+    use climb_db::queries::add_formation_name;
+
+    let name = "x'); DELETE FROM formations; --";
+
+    add_formation_name(conn, formation.id, name).expect("Failed to add name");
+
+    let result = values(conn, Place::Formation(formation.id));
+
+    assert_eq!(result, vec![name.to_string()]);
+}
+
+/// Removing a name removes only that name
+#[test]
+fn remove() {
+    let mut db = TestDatabase::with_migrations("test__names__remove");
+    let conn = db.connection();
+
+    use climb_db::models::Climb;
+    use climb_db::schema::climbs;
+
+    let climb = diesel::insert_into(climbs::table)
+        .default_values()
+        .returning(Climb::as_returning())
+        .get_result(conn)
+        .expect("Failed to insert climb");
+
+    use climb_db::queries::{add_climb_name, remove_climb_name};
+
+    add_climb_name(conn, climb.id, "Jerry's Kids").expect("Failed to add name");
+    add_climb_name(conn, climb.id, "The Cheat").expect("Failed to add name");
+
+    remove_climb_name(conn, climb.id, "Jerry's Kids").expect("Failed to remove name");
+
+    let names = values(conn, Place::Climb(climb.id));
+
+    assert_eq!(names, vec!["The Cheat".to_string()]);
+}
+
+/// Naming a missing entity is an error
+#[test]
+fn not_found() {
+    let mut db = TestDatabase::with_migrations("test__names__not_found");
+    let conn = db.connection();
+
+    use climb_db::queries::add_area_name;
+
+    let result = add_area_name(conn, 10, "The Gallery");
+
+    assert_eq!(result, Err(diesel::result::Error::NotFound));
+}
+
+/// The first name is primary, and a new primary name demotes it to an alias
+#[test]
+fn primary() {
+    let mut db = TestDatabase::with_migrations("test__names__primary");
+    let conn = db.connection();
+
+    let climb = Place::Climb(insert_climb(conn, &[]));
+
+    use climb_db::names::{add_name, names, NameKind};
+
+    add_name(conn, climb, "Dragon's Lair", None, None).expect("Failed to add name");
+    add_name(conn, climb, "The Lair", None, None).expect("Failed to add name");
+    add_name(conn, climb, "Le Repaire", Some(NameKind::Translation), Some("fr")).expect("Failed to add name");
+    add_name(conn, climb, "Lair Arete", Some(NameKind::Primary), None).expect("Failed to add name");
+
+    let result: Vec<_> = names(conn, &[climb])
+        .expect("Failed to get names")
+        .into_iter()
+        .map(|(_, name)| (name.value, name.kind, name.language))
+        .collect();
+
+    assert_eq!(result, vec![
+        ("Lair Arete".to_string(), "primary".to_string(), None),
+        ("Dragon's Lair".to_string(), "alias".to_string(), None),
+        ("The Lair".to_string(), "alias".to_string(), None),
+        ("Le Repaire".to_string(), "translation".to_string(), Some("fr".to_string())),
+    ]);
+}
+
+/// Removing the primary name promotes the first alias
+#[test]
+fn remove_primary() {
+    let mut db = TestDatabase::with_migrations("test__names__remove_primary");
+    let conn = db.connection();
+
+    let climb = Place::Climb(insert_climb(conn, &[]));
+
+    use climb_db::names::{add_name, names, remove_name, NameKind};
+
+    add_name(conn, climb, "Dragon's Lair", None, None).expect("Failed to add name");
+    add_name(conn, climb, "The Lair", Some(NameKind::Historic), None).expect("Failed to add name");
+    add_name(conn, climb, "Lair Arete", None, None).expect("Failed to add name");
+
+    remove_name(conn, climb, "Dragon's Lair").expect("Failed to remove name");
+
+    let primary: Vec<_> = names(conn, &[climb])
+        .expect("Failed to get names")
+        .into_iter()
+        .filter(|(_, name)| name.kind == "primary")
+        .map(|(_, name)| name.value)
+        .collect();
+
+    assert_eq!(primary, vec!["Lair Arete".to_string()]);
+}
+
+/// Translations need a language, languages must be BCP 47 tags, and names are unique per language
+#[test]
+fn languages() {
+    let mut db = TestDatabase::with_migrations("test__names__languages");
+    let conn = db.connection();
+
+    let climb = Place::Climb(insert_climb(conn, &[]));
+
+    use climb_db::names::{add_name, NameKind};
+
+    add_name(conn, climb, "Midnight Lightning", None, None).expect("Failed to add name");
+
+    assert!(add_name(conn, climb, "Éclair de Minuit", Some(NameKind::Translation), None).is_err());
+    assert!(add_name(conn, climb, "Éclair de Minuit", Some(NameKind::Translation), Some("not a tag")).is_err());
+    assert!(add_name(conn, climb, "Midnight Lightning", None, None).is_err());
+
+    add_name(conn, climb, "Éclair de Minuit", Some(NameKind::Translation), Some("fr-CA")).expect("Failed to add name");
+    add_name(conn, climb, "Midnight Lightning", Some(NameKind::Alias), Some("en")).expect("Failed to add name");
+}
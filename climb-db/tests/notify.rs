@@ -0,0 +1,60 @@
+use common::TestDatabase;
+use diesel::prelude::*;
+
+mod common;
+
+use climb_db::queries::Place;
+
+/// Committed changes are notified to listening connections
+#[test]
+fn committed() {
+    let mut db = TestDatabase::with_migrations("test__notify__committed");
+    let conn = db.connection();
+
+    use climb_db::names::add_name;
+    use climb_db::notify::{listen, notifications};
+    use climb_db::schema::areas;
+
+    listen(conn).expect("Failed to listen");
+
+    let area = diesel::insert_into(areas::table)
+        .default_values()
+        .returning(areas::id)
+        .get_result(conn)
+        .expect("Failed to insert area");
+
+    add_name(conn, Place::Area(area), "Dome", None, None).expect("Failed to add name");
+
+    let changes: Vec<_> = notifications(conn)
+        .expect("Failed to get notifications")
+        .into_iter()
+        .map(|change| (change.place, change.table_name, change.operation))
+        .collect();
+
+    assert_eq!(changes, vec![
+        (Place::Area(area), "areas".to_string(), "INSERT".to_string()),
+        (Place::Area(area), "area_names".to_string(), "INSERT".to_string()),
+    ]);
+
+    assert_eq!(notifications(conn), Ok(vec![]));
+}
+
+/// Changes rolled back are not notified
+#[test]
+fn rolled_back() {
+    let mut db = TestDatabase::with_migrations("test__notify__rolled_back");
+    let conn = db.connection();
+
+    use climb_db::notify::{listen, notifications};
+    use climb_db::schema::areas;
+
+    listen(conn).expect("Failed to listen");
+
+    let _ = conn.transaction(|conn| {
+        diesel::insert_into(areas::table).default_values().execute(conn)?;
+
+        Err::<(), _>(diesel::result::Error::RollbackTransaction)
+    });
+
+    assert_eq!(notifications(conn), Ok(vec![]));
+}
@@ -0,0 +1,421 @@
+use common::TestDatabase;
+
+mod common;
+
+use climb_db::repo::{
+    AreaInput, AreaRepo, ChangeRepo, ClimbInput, ClimbRepo, Error, FormationInput, FormationRepo, Page, Parent,
+    SearchRepo, UserRepo,
+};
+
+/// Creating an area adds its names and descriptions, and moves it into its super area
+#[test]
+fn create_area() {
+    let mut db = TestDatabase::with_migrations("test__repo__create_area");
+    let conn = db.connection();
+
+    let root = AreaRepo::new(conn).create(AreaInput::default()).expect("Failed to create area");
+
+    let area = AreaRepo::new(conn)
+        .create(AreaInput {
+            names: vec!["Tuolumne".to_string(), "Tuolumne Meadows".to_string()],
+            descriptions: vec![climb_db::descriptions::Description {
+                key: "brief".to_string(),
+                value: "Domes above the meadows".to_string(),
+            }],
+            super_area_id: Some(root),
+        })
+        .expect("Failed to create area");
+
+    use climb_db::descriptions::descriptions;
+    use climb_db::names::names;
+    use climb_db::queries::Place;
+
+    let names: Vec<String> = names(conn, &[Place::Area(area)])
+        .expect("Failed to get names")
+        .into_iter()
+        .map(|(_, name)| name.value)
+        .collect();
+
+    assert_eq!(names, vec!["Tuolumne", "Tuolumne Meadows"]);
+    assert_eq!(descriptions(conn, &[Place::Area(area)]).expect("Failed to get descriptions").len(), 1);
+    assert_eq!(AreaRepo::new(conn).super_areas(&[area]).expect("Failed to get super areas"), vec![(area, root)]);
+}
+
+/// Creating is all or nothing, e.g. an unknown description type adds no area
+#[test]
+fn create_atomic() {
+    let mut db = TestDatabase::with_migrations("test__repo__create_atomic");
+    let conn = db.connection();
+
+    let result = AreaRepo::new(conn).create(AreaInput {
+        names: vec!["Tuolumne".to_string()],
+        descriptions: vec![climb_db::descriptions::Description {
+            key: "approach".to_string(),
+            value: "Park at the visitor center".to_string(),
+        }],
+        super_area_id: None,
+    });
+
+    assert!(matches!(result, Err(Error::InvalidInput { .. })));
+    assert_eq!(AreaRepo::new(conn).list(None, Page::default()).expect("Failed to list areas"), vec![]);
+}
+
+/// Getting, moving or deleting an entity which does not exist is `NotFound`
+#[test]
+fn not_found() {
+    let mut db = TestDatabase::with_migrations("test__repo__not_found");
+    let conn = db.connection();
+
+    assert!(matches!(AreaRepo::new(conn).get(1), Err(Error::NotFound(_))));
+    assert!(matches!(FormationRepo::new(conn).move_to(1, None), Err(Error::NotFound(_))));
+    assert!(matches!(ClimbRepo::new(conn).delete(1), Err(Error::NotFound(_))));
+
+    let area = AreaRepo::new(conn).create(AreaInput::default()).expect("Failed to create area");
+
+    let result = ClimbRepo::new(conn).create(ClimbInput {
+        parent: Some(Parent::Formation(area + 1)),
+        ..Default::default()
+    });
+
+    assert!(matches!(result, Err(Error::NotFound(_))));
+}
+
+/// Users, API tokens and changes which do not exist are `NotFound`, and so is a token of another
+/// user
+#[test]
+fn not_found_by_id() {
+    let mut db = TestDatabase::with_migrations("test__repo__not_found_by_id");
+    let conn = db.connection();
+
+    use climb_db::users::Role;
+
+    let mut users = UserRepo::new(conn);
+
+    let alice = users.create("alice", Role::Reader).expect("Failed to add user");
+    let bob = users.create("bob", Role::Reader).expect("Failed to add user");
+    let (token, _) = users.add_api_token(alice.id, None, None).expect("Failed to add token");
+
+    assert!(matches!(users.set_role(bob.id + 1, Role::Admin), Err(Error::NotFound(_))));
+    assert!(matches!(users.revoke_api_token(bob.id, token), Err(Error::NotFound(_))));
+    assert!(matches!(users.revoke_api_token(alice.id, token), Ok(())));
+
+    assert!(matches!(ChangeRepo::new(conn).get(i64::MAX), Err(Error::NotFound(_))));
+    assert!(matches!(ChangeRepo::new(conn).revert(i64::MAX), Err(Error::NotFound(_))));
+}
+
+/// Searching for nothing, or for more results than allowed, is `InvalidInput`
+#[test]
+fn search_invalid() {
+    let mut db = TestDatabase::with_migrations("test__repo__search_invalid");
+    let conn = db.connection();
+
+    use climb_db::search::SearchKind;
+
+    let mut repo = SearchRepo::new(conn);
+
+    assert!(matches!(repo.search(" ", &SearchKind::ALL, 10), Err(Error::InvalidInput { .. })));
+    assert!(matches!(
+        repo.search("half dome", &SearchKind::ALL, SearchRepo::MAX_LIMIT + 1),
+        Err(Error::InvalidInput { .. })
+    ));
+}
+
+/// Moving replaces the parent, whether an area or a formation
+#[test]
+fn move_to() {
+    let mut db = TestDatabase::with_migrations("test__repo__move_to");
+    let conn = db.connection();
+
+    let area = AreaRepo::new(conn).create(AreaInput::default()).expect("Failed to create area");
+    let formation = FormationRepo::new(conn)
+        .create(FormationInput {
+            parent: Some(Parent::Area(area)),
+            ..Default::default()
+        })
+        .expect("Failed to create formation");
+    let sub_formation = FormationRepo::new(conn)
+        .create(FormationInput {
+            parent: Some(Parent::Area(area)),
+            ..Default::default()
+        })
+        .expect("Failed to create formation");
+
+    let mut repo = FormationRepo::new(conn);
+
+    repo.move_to(sub_formation, Some(Parent::Formation(formation))).expect("Failed to move formation");
+
+    let parents = repo.parents(&[sub_formation]).expect("Failed to get parents");
+
+    assert_eq!(parents[0].area_id, None);
+    assert_eq!(parents[0].super_formation_id, Some(formation));
+    assert_eq!(repo.list(Some(Parent::Area(area)), Page::default()).expect("Failed to list"), vec![formation]);
+
+    repo.move_to(sub_formation, None).expect("Failed to move formation");
+
+    assert!(repo.parents(&[sub_formation]).expect("Failed to get parents").is_empty());
+}
+
+/// The variation family of a climb holds its roots and all their variations, and a climb which
+/// does not exist has no family
+#[test]
+fn variation_family() {
+    let mut db = TestDatabase::with_migrations("test__repo__variation_family");
+    let conn = db.connection();
+
+    let mut repo = ClimbRepo::new(conn);
+
+    let root = repo.create(ClimbInput::default()).expect("Failed to create climb");
+    let variation = repo.create(ClimbInput::default()).expect("Failed to create climb");
+
+    repo.add_variation(root, variation).expect("Failed to add variation");
+
+    let family: Vec<(i32, bool)> = repo
+        .variation_family(variation)
+        .expect("Failed to get variation family")
+        .into_iter()
+        .map(|member| (member.id, member.is_root))
+        .collect();
+
+    assert_eq!(family, vec![(root, true), (variation, false)]);
+    assert!(matches!(repo.variation_family(variation + 1), Err(Error::NotFound(_))));
+}
+
+/// Deleting an area which still has climbs within it is a `Conflict`
+#[test]
+fn delete_conflict() {
+    let mut db = TestDatabase::with_migrations("test__repo__delete_conflict");
+    let conn = db.connection();
+
+    let area = AreaRepo::new(conn).create(AreaInput::default()).expect("Failed to create area");
+    let climb = ClimbRepo::new(conn)
+        .create(ClimbInput {
+            parent: Some(Parent::Area(area)),
+            ..Default::default()
+        })
+        .expect("Failed to create climb");
+
+    assert!(matches!(AreaRepo::new(conn).delete(area), Err(Error::Conflict { .. })));
+
+    ClimbRepo::new(conn).delete(climb).expect("Failed to delete climb");
+    AreaRepo::new(conn).delete(area).expect("Failed to delete area");
+}
+
+/// Pages are bounded by ids, and backward pages come in descending order
+#[test]
+fn list_page() {
+    let mut db = TestDatabase::with_migrations("test__repo__list_page");
+    let conn = db.connection();
+
+    let ids: Vec<i32> = (0..5)
+        .map(|_| ClimbRepo::new(conn).create(ClimbInput::default()).expect("Failed to create climb"))
+        .collect();
+
+    let mut repo = ClimbRepo::new(conn);
+    let filter = Default::default();
+
+    let page = Page { after: Some(ids[0]), first: Some(2), ..Default::default() };
+
+    assert_eq!(repo.list(&filter, page).expect("Failed to list climbs"), vec![ids[1], ids[2], ids[3]]);
+
+    let page = Page { before: Some(ids[4]), last: Some(2), ..Default::default() };
+
+    assert_eq!(repo.list(&filter, page).expect("Failed to list climbs"), vec![ids[3], ids[2], ids[1]]);
+}
+
+/// Each parent's children are paged separately
+#[test]
+fn child_pages() {
+    let mut db = TestDatabase::with_migrations("test__repo__child_pages");
+    let conn = db.connection();
+
+    let areas: Vec<i32> = (0..2)
+        .map(|_| AreaRepo::new(conn).create(AreaInput::default()).expect("Failed to create area"))
+        .collect();
+    let climbs: Vec<i32> = (0..6)
+        .map(|i| {
+            ClimbRepo::new(conn)
+                .create(ClimbInput {
+                    parent: Some(Parent::Area(areas[i % 2])),
+                    ..Default::default()
+                })
+                .expect("Failed to create climb")
+        })
+        .collect();
+
+    let mut repo = AreaRepo::new(conn);
+
+    let page = Page { first: Some(1), ..Default::default() };
+
+    assert_eq!(
+        repo.climb_pages(&areas, page).expect("Failed to page climbs"),
+        vec![(areas[0], climbs[0]), (areas[0], climbs[2]), (areas[1], climbs[1]), (areas[1], climbs[3])]
+    );
+
+    let page = Page { before: Some(climbs[4]), last: Some(1), ..Default::default() };
+
+    assert_eq!(
+        repo.climb_pages(&areas, page).expect("Failed to page climbs"),
+        vec![(areas[0], climbs[2]), (areas[0], climbs[0]), (areas[1], climbs[3]), (areas[1], climbs[1])]
+    );
+}
+
+/// Pages of an order are bounded by where the climbs of their cursors fall in it, and cursors of
+/// deleted climbs are rejected
+#[test]
+fn list_ordered_page() {
+    let mut db = TestDatabase::with_migrations("test__repo__list_ordered_page");
+    let conn = db.connection();
+
+    use climb_db::filter::{ClimbOrder, ClimbOrderField};
+
+    // Named climbs come in order of name, then the unnamed climb
+    let ids: Vec<i32> = [vec!["C"], vec!["a"], vec!["B"], vec![]]
+        .into_iter()
+        .map(|names| {
+            let names = names.into_iter().map(String::from).collect();
+            ClimbRepo::new(conn).create(ClimbInput { names, ..Default::default() }).expect("Failed to create climb")
+        })
+        .collect();
+
+    let mut repo = ClimbRepo::new(conn);
+    let filter = Default::default();
+    let order = ClimbOrder { field: ClimbOrderField::Name, descending: false };
+
+    let page = Page { after: Some(ids[1]), first: Some(2), ..Default::default() };
+
+    assert_eq!(repo.list_ordered(&filter, order, page).expect("Failed to list climbs"), vec![ids[2], ids[0], ids[3]]);
+
+    let page = Page { before: Some(ids[3]), last: Some(2), ..Default::default() };
+
+    assert_eq!(repo.list_ordered(&filter, order, page).expect("Failed to list climbs"), vec![ids[0], ids[2], ids[1]]);
+
+    repo.delete(ids[0]).expect("Failed to delete climb");
+
+    let page = Page { after: Some(ids[0]), ..Default::default() };
+
+    assert!(matches!(repo.list_ordered(&filter, order, page), Err(Error::InvalidInput { .. })));
+}
+
+/// Pages of descendants are bounded by the depth and id of the areas of their cursors, and
+/// cursors of areas not below the area are rejected
+#[test]
+fn descendants_page() {
+    let mut db = TestDatabase::with_migrations("test__repo__descendants_page");
+    let conn = db.connection();
+
+    let mut create = |super_area_id| {
+        AreaRepo::new(conn).create(AreaInput { super_area_id, ..Default::default() }).expect("Failed to create area")
+    };
+
+    let region = create(None);
+    let crag = create(Some(region));
+    let sector = create(Some(crag));
+    let other_crag = create(Some(region));
+    let other_region = create(None);
+
+    let mut repo = AreaRepo::new(conn);
+
+    let page = Page { after: Some(crag), first: Some(1), ..Default::default() };
+
+    assert_eq!(repo.descendants(region, None, page).expect("Failed to list descendants"), vec![other_crag, sector]);
+
+    let page = Page { before: Some(sector), last: Some(1), ..Default::default() };
+
+    assert_eq!(repo.descendants(region, None, page).expect("Failed to list descendants"), vec![other_crag, crag]);
+
+    let page = Page { after: Some(other_region), ..Default::default() };
+
+    assert!(matches!(repo.descendants(region, None, page), Err(Error::InvalidInput { .. })));
+}
+
+/// Pages given neither `first` nor `last` are bounded, and larger pages than allowed are rejected
+#[test]
+fn page_size() {
+    let mut db = TestDatabase::with_migrations("test__repo__page_size");
+    let conn = db.connection();
+
+    for _ in 0..Page::DEFAULT_SIZE + 5 {
+        ClimbRepo::new(conn).create(ClimbInput::default()).expect("Failed to create climb");
+    }
+
+    let ids = ClimbRepo::new(conn).list(&Default::default(), Page::default()).expect("Failed to list climbs");
+
+    // The extra id signals another page
+    assert_eq!(ids.len(), Page::DEFAULT_SIZE + 1);
+
+    assert!(Page::new(None, None, Some(Page::MAX_SIZE), None).is_ok());
+    assert!(matches!(Page::new(None, None, Some(Page::MAX_SIZE + 1), None), Err(Error::InvalidInput { .. })));
+    assert!(matches!(Page::new(None, None, None, Some(Page::MAX_SIZE + 1)), Err(Error::InvalidInput { .. })));
+}
+
+/// Updating an ascent replaces what is given and leaves the rest unchanged
+#[test]
+fn update_ascent() {
+    let mut db = TestDatabase::with_migrations("test__repo__update_ascent");
+    let conn = db.connection();
+
+    use climb_db::grade::GradeSystem;
+    use climb_db::repo::{AscentInput, AscentRepo, AscentUpdate, ClimberRepo};
+    use std::ops::Bound;
+
+    let climb = ClimbRepo::new(conn).create(ClimbInput::default()).expect("Failed to create climb");
+    let alex = ClimberRepo::new(conn).create("Alex", "Honnold").expect("Failed to create climber");
+    let tommy = ClimberRepo::new(conn).create("Tommy", "Caldwell").expect("Failed to create climber");
+
+    let ascent = AscentRepo::new(conn)
+        .create(AscentInput {
+            climb_id: climb,
+            date: None,
+            party: vec![alex],
+            grade: Some(GradeSystem::Vermin.parse("V4").unwrap()),
+        })
+        .expect("Failed to create ascent");
+
+    let date = chrono::NaiveDate::from_ymd_opt(2017, 6, 3).unwrap();
+
+    AscentRepo::new(conn)
+        .update(
+            ascent,
+            AscentUpdate {
+                date: Some(Some((Bound::Included(date), Bound::Excluded(date.succ_opt().unwrap())))),
+                party: Some(vec![alex, tommy]),
+                ..Default::default()
+            },
+        )
+        .expect("Failed to update ascent");
+
+    let mut repo = AscentRepo::new(conn);
+
+    assert!(repo.get(ascent).expect("Failed to get ascent").ascent_date.is_some());
+    assert_eq!(repo.parties(&[ascent]).expect("Failed to get parties"), vec![(ascent, alex), (ascent, tommy)]);
+    assert_eq!(
+        repo.grades(&[ascent]).expect("Failed to get grades"),
+        vec![(ascent, ("vermin".to_string(), "V4".to_string()))]
+    );
+
+    repo.update(ascent, AscentUpdate { grade: Some(None), ..Default::default() }).expect("Failed to update ascent");
+
+    assert_eq!(repo.grades(&[ascent]).expect("Failed to get grades"), vec![]);
+    assert!(matches!(repo.update(ascent + 1, AscentUpdate::default()), Err(Error::NotFound(_))));
+}
+
+/// Renaming or deleting a climber which no longer exists is `NotFound`
+#[test]
+fn update_climber() {
+    let mut db = TestDatabase::with_migrations("test__repo__update_climber");
+    let conn = db.connection();
+
+    use climb_db::repo::ClimberRepo;
+
+    let mut repo = ClimberRepo::new(conn);
+    let climber = repo.create("Alex", "Honold").expect("Failed to create climber");
+
+    repo.update(climber, "Alex", "Honnold").expect("Failed to update climber");
+
+    assert_eq!(repo.get(climber).expect("Failed to get climber").last_name, "Honnold");
+
+    repo.delete(climber).expect("Failed to delete climber");
+
+    assert!(matches!(repo.update(climber, "Alex", "Honnold"), Err(Error::NotFound(_))));
+    assert!(matches!(repo.delete(climber), Err(Error::NotFound(_))));
+}
@@ -0,0 +1,112 @@
+use common::{insert_climb, TestDatabase};
+use diesel::prelude::*;
+
+mod common;
+
+fn insert_area(conn: &mut PgConnection, names: &[&str]) -> i32 {
+    use climb_db::schema::areas;
+
+    let id = diesel::insert_into(areas::table)
+        .default_values()
+        .returning(areas::id)
+        .get_result(conn)
+        .expect("Failed to insert area");
+
+    use climb_db::queries::add_area_name;
+
+    for name in names {
+        add_area_name(conn, id, name).expect("Failed to add name");
+    }
+
+    id
+}
+
+/// Accents are ignored, and the matched name is highlighted
+#[test]
+fn accents() {
+    let mut db = TestDatabase::with_migrations("test__search__accents");
+    let conn = db.connection();
+
+    let area = insert_area(conn, &["Tuolumne", "Tuólumne Meadows"]);
+
+    use climb_db::search::{search, SearchKind};
+
+    let result = search(conn, "tuólumne meadows", &SearchKind::ALL, 10).expect("Failed to search");
+
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].id, area);
+    assert_eq!(result[0].name, "Tuólumne Meadows");
+    assert_eq!(result[0].highlight, "<mark>Tuólumne</mark> <mark>Meadows</mark>");
+}
+
+/// Highlights are escaped, so only the `<mark>` tags are markup
+#[test]
+fn escaped() {
+    let mut db = TestDatabase::with_migrations("test__search__escaped");
+    let conn = db.connection();
+
+    insert_climb(conn, &["<script>alert('Crimp')</script> & Co"]);
+
+    use climb_db::search::{search, SearchKind};
+
+    let result = search(conn, "crimp", &SearchKind::ALL, 10).expect("Failed to search");
+
+    assert_eq!(result.len(), 1);
+    assert_eq!(
+        result[0].highlight,
+        "&lt;script&gt;alert(&#39;<mark>Crimp</mark>&#39;)&lt;/script&gt; &amp; Co"
+    );
+}
+
+/// Misspelled names are still found by similarity
+#[test]
+fn fuzzy() {
+    let mut db = TestDatabase::with_migrations("test__search__fuzzy");
+    let conn = db.connection();
+
+    let area = insert_area(conn, &["Yosemite Valley"]);
+    insert_area(conn, &["Joshua Tree"]);
+
+    use climb_db::search::{search, SearchKind};
+
+    let result = search(conn, "yosemty", &SearchKind::ALL, 10).expect("Failed to search");
+
+    assert_eq!(result.iter().map(|hit| hit.id).collect::<Vec<_>>(), vec![area]);
+}
+
+/// Descriptions are searched too, ranking below names, and kinds limit the results
+#[test]
+fn descriptions() {
+    let mut db = TestDatabase::with_migrations("test__search__descriptions");
+    let conn = db.connection();
+
+    let named = insert_climb(conn, &["Midnight Lightning"]);
+    let described = insert_climb(conn, &["The Cheat"]);
+    insert_area(conn, &["Lightning Ridge"]);
+
+    use climb_db::models::NewClimbDescription;
+    use climb_db::schema::{description_types, climb_descriptions};
+
+    let description_type = description_types::table
+        .select(description_types::id)
+        .first::<i32>(conn)
+        .expect("Failed to get description type");
+
+    diesel::insert_into(climb_descriptions::table)
+        .values(NewClimbDescription {
+            climb_id: described,
+            description_type_id: description_type,
+            value: "Follow the lightning bolt crack".to_string(),
+        })
+        .execute(conn)
+        .expect("Failed to insert description");
+
+    use climb_db::search::{search, SearchKind};
+
+    let result = search(conn, "lightning", &[SearchKind::Climb], 10).expect("Failed to search");
+
+    assert_eq!(result.iter().map(|hit| hit.id).collect::<Vec<_>>(), vec![named, described]);
+    assert!(result.iter().all(|hit| hit.kind == "climb"));
+    assert_eq!(result[1].name, "The Cheat");
+    assert!(result[1].highlight.contains("<mark>lightning</mark>"));
+}
@@ -0,0 +1,115 @@
+use common::TestDatabase;
+use diesel::prelude::*;
+
+mod common;
+
+fn insert_areas(conn: &mut PgConnection, count: usize) -> Vec<i32> {
+    use climb_db::schema::areas;
+
+    (0..count)
+        .map(|_| diesel::insert_into(areas::table).default_values().returning(areas::id).get_result(conn))
+        .collect::<QueryResult<_>>()
+        .expect("Failed to insert areas")
+}
+
+fn insert_area_belongs_to(conn: &mut PgConnection, area_id: i32, super_area_id: i32) {
+    use climb_db::models::NewAreaBelongsTo;
+    use climb_db::schema::area_belongs_to;
+
+    diesel::insert_into(area_belongs_to::table)
+        .values(NewAreaBelongsTo { area_id, super_area_id })
+        .execute(conn)
+        .expect("Failed to insert area_belongs_to");
+}
+
+/// Descendants are found level by level, down to an optional depth
+#[test]
+fn descendants() {
+    let mut db = TestDatabase::with_migrations("test__subtrees__descendants");
+    let conn = db.connection();
+
+    let areas = insert_areas(conn, 4);
+    let (region, crag, other_crag, sector) = (areas[0], areas[1], areas[2], areas[3]);
+
+    insert_area_belongs_to(conn, sector, crag);
+    insert_area_belongs_to(conn, crag, region);
+    insert_area_belongs_to(conn, other_crag, region);
+
+    use climb_db::queries::{area_descendants, AreaDescendant};
+
+    let result = area_descendants(conn, region, None).expect("Failed to get descendants");
+
+    assert_eq!(result, vec![
+        AreaDescendant { id: crag, depth: 1 },
+        AreaDescendant { id: other_crag, depth: 1 },
+        AreaDescendant { id: sector, depth: 2 },
+    ]);
+
+    let result = area_descendants(conn, region, Some(1)).expect("Failed to get descendants");
+
+    assert_eq!(result.len(), 2);
+}
+
+/// Ancestors run from a place's parent to its root area, through formations and areas
+#[test]
+fn ancestors() {
+    let mut db = TestDatabase::with_migrations("test__subtrees__ancestors");
+    let conn = db.connection();
+
+    let areas = insert_areas(conn, 2);
+    let (region, crag) = (areas[0], areas[1]);
+
+    insert_area_belongs_to(conn, crag, region);
+
+    use climb_db::models::NewFormation;
+    use climb_db::schema::formations;
+
+    let formations: Vec<i32> = diesel::insert_into(formations::table)
+        .values(vec![
+            NewFormation { location: None },
+            NewFormation { location: None },
+        ])
+        .returning(formations::id)
+        .get_results(conn)
+        .expect("Failed to insert formations");
+
+    let (boulder, face) = (formations[0], formations[1]);
+
+    use climb_db::models::NewFormationBelongsTo;
+    use climb_db::schema::formation_belongs_to;
+
+    diesel::insert_into(formation_belongs_to::table)
+        .values(vec![
+            NewFormationBelongsTo { formation_id: boulder, area_id: Some(crag), super_formation_id: None },
+            NewFormationBelongsTo { formation_id: face, area_id: None, super_formation_id: Some(boulder) },
+        ])
+        .execute(conn)
+        .expect("Failed to insert formation_belongs_to");
+
+    use climb_db::schema::climbs;
+
+    let climb = diesel::insert_into(climbs::table)
+        .default_values()
+        .returning(climbs::id)
+        .get_result(conn)
+        .expect("Failed to insert climb");
+
+    use climb_db::models::NewClimbBelongsTo;
+    use climb_db::schema::climb_belongs_to;
+
+    diesel::insert_into(climb_belongs_to::table)
+        .values(NewClimbBelongsTo { climb_id: climb, area_id: None, formation_id: Some(face) })
+        .execute(conn)
+        .expect("Failed to insert climb_belongs_to");
+
+    use climb_db::queries::{ancestors, Place};
+
+    let result = ancestors(conn, &[Place::Climb(climb), Place::Area(region)]).expect("Failed to get ancestors");
+
+    assert_eq!(result, vec![
+        (Place::Climb(climb), Place::Formation(face)),
+        (Place::Climb(climb), Place::Formation(boulder)),
+        (Place::Climb(climb), Place::Area(crag)),
+        (Place::Climb(climb), Place::Area(region)),
+    ]);
+}
@@ -0,0 +1,95 @@
+use common::TestDatabase;
+use diesel::prelude::*;
+
+mod common;
+
+use climb_db::users::Role;
+
+/// Users have unique names and roles which may change
+#[test]
+fn roles() {
+    let mut db = TestDatabase::with_migrations("test__users__roles");
+    let conn = db.connection();
+
+    use climb_db::users::{add_user, set_role, user_by_name};
+
+    let user = add_user(conn, "alice", Role::Contributor).expect("Failed to add user");
+
+    assert_eq!(user.role, "contributor");
+    assert!(add_user(conn, "alice", Role::Reader).is_err());
+    assert!(add_user(conn, " ", Role::Reader).is_err());
+
+    set_role(conn, user.id, Role::Moderator).expect("Failed to set role");
+
+    let user = user_by_name(conn, "alice").expect("Failed to get user");
+
+    assert_eq!(Role::from_name(&user.role), Some(Role::Moderator));
+    assert!(Role::Moderator > Role::Contributor);
+}
+
+/// API tokens identify their user until revoked or expired
+#[test]
+fn api_tokens() {
+    let mut db = TestDatabase::with_migrations("test__users__api_tokens");
+    let conn = db.connection();
+
+    use climb_db::users::{add_api_token, add_user, revoke_api_token, user_by_api_token, API_TOKEN_PREFIX};
+
+    let alice = add_user(conn, "alice", Role::Contributor).expect("Failed to add user");
+    let bob = add_user(conn, "bob", Role::Reader).expect("Failed to add user");
+
+    let (id, token) = add_api_token(conn, alice.id, Some("laptop"), None).expect("Failed to add token");
+
+    assert!(token.starts_with(API_TOKEN_PREFIX));
+    assert_eq!(user_by_api_token(conn, &token), Ok(Some(alice.clone())));
+    assert_eq!(user_by_api_token(conn, &format!("{token}0")), Ok(None));
+
+    let expired = chrono::Utc::now() - chrono::Duration::hours(1);
+    let (_, expired_token) = add_api_token(conn, alice.id, None, Some(expired)).expect("Failed to add token");
+
+    assert_eq!(user_by_api_token(conn, &expired_token), Ok(None));
+
+    assert_eq!(revoke_api_token(conn, bob.id, id), Ok(false));
+    assert_eq!(revoke_api_token(conn, alice.id, id), Ok(true));
+    assert_eq!(user_by_api_token(conn, &token), Ok(None));
+}
+
+/// Entities and changes are recorded as made by the acting user, until the end of the transaction
+#[test]
+fn act_as() {
+    let mut db = TestDatabase::with_migrations("test__users__act_as");
+    let conn = db.connection();
+
+    use climb_db::audit::history;
+    use climb_db::queries::Place;
+    use climb_db::schema::areas;
+    use climb_db::users::{act_as, add_user};
+
+    let alice = add_user(conn, "alice", Role::Contributor).expect("Failed to add user");
+
+    let (area, created_by) = conn
+        .transaction(|conn| {
+            act_as(conn, Some(&alice))?;
+
+            diesel::insert_into(areas::table)
+                .default_values()
+                .returning((areas::id, areas::created_by))
+                .get_result::<(i32, Option<i32>)>(conn)
+        })
+        .expect("Failed to insert area");
+
+    assert_eq!(created_by, Some(alice.id));
+
+    // Later transactions on the same connection are not made by the user
+    let created_by = diesel::insert_into(areas::table)
+        .default_values()
+        .returning(areas::created_by)
+        .get_result::<Option<i32>>(conn)
+        .expect("Failed to insert area");
+
+    assert_eq!(created_by, None);
+
+    let changes = history(conn, Place::Area(area), 1).expect("Failed to get history");
+
+    assert_eq!(changes[0].actor.as_deref(), Some("alice"));
+}
@@ -0,0 +1,156 @@
+//! Requests per second of the schema as concurrent requests increase, queried against
+//! `DATABASE_URL`, on an idle runtime and on one kept busy by other requests.
+//!
+//! Every request loads a page of areas and their sub areas and climbs, so a request waits on
+//! several batched queries. Requests beyond the pool size, `DATABASE_POOL_SIZE` or 10 by default,
+//! wait for a connection. The runtime has two workers, and when busy, four other requests keep
+//! running slow queries of 20 ms through the same [`Db`], as exports and searches of a large
+//! database would.
+//!
+//! Requests per second on one CPU against a small local database, with queries run on the async
+//! workers as before [`Db`] and on blocking threads as now, by the number of concurrent requests:
+//!
+//! | Concurrent | Idle, async workers | Idle, blocking | Busy, async workers | Busy, blocking |
+//! |-----------:|--------------------:|---------------:|--------------------:|---------------:|
+//! |          1 |                 185 |            154 |                  20 |            164 |
+//! |          8 |                 870 |            762 |                 186 |            813 |
+//! |         32 |               1,377 |          1,301 |                 448 |          1,383 |
+//! |        128 |               1,836 |          1,722 |                 627 |          1,584 |
+//!
+//! While busy, a slow query on an async worker holds up every request on that worker, so
+//! throughput drops to a third or less. On blocking threads the slow queries only take their
+//! connections, and throughput stays close to that of an idle runtime. On an idle runtime with
+//! quick queries, handing each query to a blocking thread and back costs two context switches,
+//! which with no second core to run them on costs up to 15%.
+
+use std::env;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use async_graphql::futures_util::future::join_all;
+use climb_graphql::build_schema;
+use climb_graphql::db::{Db, DbConfig};
+use climb_graphql::notifications::Changes;
+use climb_graphql::ClimbSchema;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use diesel::RunQueryDsl;
+use tokio::runtime::Runtime;
+use tokio::task::JoinHandle;
+
+const QUERY: &str = "{
+    areas(first: 20) {
+        edges {
+            node {
+                id
+                primaryName
+                subAreas { edges { node { id primaryName } } }
+                climbs { edges { node { id primaryName } } }
+            }
+        }
+    }
+}";
+
+/// Workers of the runtime
+const WORKERS: usize = 2;
+
+/// Other requests running slow queries while the runtime is busy
+const BACKGROUND_REQUESTS: usize = 4;
+
+/// The query of the other requests
+const SLOW_QUERY: &str = "SELECT pg_sleep(0.02)";
+
+/// Requests running slow queries until stopped
+struct Background {
+    stop: Arc<AtomicBool>,
+    requests: Vec<JoinHandle<()>>,
+}
+
+impl Background {
+    fn start(runtime: &Runtime, db: &Db) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let requests = (0..BACKGROUND_REQUESTS)
+            .map(|_| {
+                let db = db.clone();
+                let stop = stop.clone();
+
+                runtime.spawn(async move {
+                    while !stop.load(Ordering::Relaxed) {
+                        db.run(|conn| Ok(diesel::sql_query(SLOW_QUERY).execute(conn)?))
+                            .await
+                            .expect("Slow query failed");
+
+                        // As a request does between its queries, e.g. while sending its response
+                        tokio::task::yield_now().await;
+                    }
+                })
+            })
+            .collect();
+
+        Background { stop, requests }
+    }
+
+    fn stop(self, runtime: &Runtime) {
+        self.stop.store(true, Ordering::Relaxed);
+
+        for request in self.requests {
+            runtime.block_on(request).expect("Slow request panicked");
+        }
+    }
+}
+
+fn bench_group(c: &mut Criterion, name: &str, runtime: &Runtime, schema: &ClimbSchema) {
+    let mut group = c.benchmark_group(name);
+
+    for concurrency in [1, 8, 32, 128] {
+        group.throughput(Throughput::Elements(concurrency));
+        group.bench_with_input(BenchmarkId::from_parameter(concurrency), &concurrency, |b, &concurrency| {
+            b.to_async(runtime).iter(|| {
+                let requests = (0..concurrency).map(|_| {
+                    let schema = schema.clone();
+
+                    tokio::spawn(async move { schema.execute(QUERY).await })
+                });
+
+                async move {
+                    for response in join_all(requests).await {
+                        let response = response.expect("Request panicked");
+
+                        assert!(response.is_ok(), "{:?}", response.errors);
+                    }
+                }
+            })
+        });
+    }
+
+    group.finish();
+}
+
+fn requests_per_second(c: &mut Criterion) {
+    let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+    let default = DbConfig::default();
+    let config = DbConfig {
+        max_size: env::var("DATABASE_POOL_SIZE").map_or(default.max_size, |size| {
+            size.parse().expect("DATABASE_POOL_SIZE must be a number")
+        }),
+        ..default
+    };
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(WORKERS)
+        .enable_all()
+        .build()
+        .expect("Failed to start runtime");
+    let db = Db::new(&database_url, &config).expect("Failed to create pool.");
+    let schema = build_schema(db.clone(), Changes::listen(database_url));
+
+    bench_group(c, "idle_runtime", &runtime, &schema);
+
+    let background = Background::start(&runtime, &db);
+
+    bench_group(c, "busy_runtime", &runtime, &schema);
+
+    background.stop(&runtime);
+}
+
+criterion_group!(benches, requests_per_second);
+criterion_main!(benches);
@@ -0,0 +1,198 @@
+use std::fmt;
+use std::sync::Arc;
+
+use async_graphql::{Context, Guard};
+use axum::http::{header, HeaderMap};
+use climb_db::models::User;
+use climb_db::repo::UserRepo;
+use climb_db::users::{Role, API_TOKEN_PREFIX};
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use serde::Deserialize;
+
+use crate::db::Db;
+use crate::error::{Error, Result};
+
+/// A way of identifying the user making a request from its bearer token
+pub trait Authenticator: Send + Sync {
+    /// Gets the user a token identifies, or `None` if the token is not of this kind. Tokens of
+    /// this kind which are invalid or expired are an `Unauthenticated` error.
+    fn authenticate(&self, conn: &mut PgConnection, token: &str) -> Result<Option<User>>;
+}
+
+/// Authenticates API tokens stored in the database
+pub struct ApiTokens;
+
+impl Authenticator for ApiTokens {
+    fn authenticate(&self, conn: &mut PgConnection, token: &str) -> Result<Option<User>> {
+        if !token.starts_with(API_TOKEN_PREFIX) {
+            return Ok(None);
+        }
+
+        UserRepo::new(conn)
+            .by_api_token(token)?
+            .map(Some)
+            .ok_or_else(|| Error::Unauthenticated("Invalid or expired API token".to_string()))
+    }
+}
+
+/// Claims of a JWT
+#[derive(Deserialize)]
+struct Claims {
+    /// Name of the user
+    sub: String,
+}
+
+/// Authenticates JWTs signed with a local key. Users are identified by the `sub` claim and are
+/// added, as readers, on first use. Roles come from the database only, so a token cannot grant
+/// one.
+#[derive(Clone)]
+pub struct Jwt {
+    key: DecodingKey,
+    validation: Validation,
+}
+
+impl Jwt {
+    /// Validates HS256 tokens signed with a shared secret
+    pub fn with_secret(secret: &[u8]) -> Self {
+        Jwt {
+            key: DecodingKey::from_secret(secret),
+            validation: Validation::new(Algorithm::HS256),
+        }
+    }
+
+    /// Validates RS256 tokens signed with the private key of a PEM encoded RSA public key
+    pub fn with_rsa_pem(pem: &[u8]) -> jsonwebtoken::errors::Result<Self> {
+        Ok(Jwt {
+            key: DecodingKey::from_rsa_pem(pem)?,
+            validation: Validation::new(Algorithm::RS256),
+        })
+    }
+}
+
+// Keeps secrets out of logged settings
+impl fmt::Debug for Jwt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Jwt").field("algorithms", &self.validation.algorithms).finish_non_exhaustive()
+    }
+}
+
+impl Authenticator for Jwt {
+    fn authenticate(&self, conn: &mut PgConnection, token: &str) -> Result<Option<User>> {
+        // JWTs are three base64url segments
+        if token.split('.').count() != 3 {
+            return Ok(None);
+        }
+
+        let claims = jsonwebtoken::decode::<Claims>(token, &self.key, &self.validation)
+            .map_err(|e| Error::Unauthenticated(format!("Invalid JWT: {e}")))?
+            .claims;
+
+        let mut users = UserRepo::new(conn);
+
+        let user = match users.by_name(&claims.sub)? {
+            Some(user) => user,
+            None => users.create(&claims.sub, Role::Reader)?,
+        };
+
+        Ok(Some(user))
+    }
+}
+
+/// Identifies the users making requests
+#[derive(Clone, Default)]
+pub struct Auth {
+    authenticators: Arc<Vec<Box<dyn Authenticator>>>,
+}
+
+impl Auth {
+    pub fn new(authenticators: Vec<Box<dyn Authenticator>>) -> Self {
+        Auth {
+            authenticators: Arc::new(authenticators),
+        }
+    }
+
+    /// API tokens, along with JWTs if given
+    pub fn with_jwt(jwt: Option<Jwt>) -> Self {
+        let mut authenticators: Vec<Box<dyn Authenticator>> = vec![Box::new(ApiTokens)];
+
+        if let Some(jwt) = jwt {
+            authenticators.push(Box::new(jwt));
+        }
+
+        Auth::new(authenticators)
+    }
+
+    /// Gets the user making a request, if it has a bearer token
+    pub async fn authenticate(&self, db: &Db, headers: &HeaderMap) -> Result<Option<User>> {
+        let Some(authorization) = headers.get(header::AUTHORIZATION) else {
+            return Ok(None);
+        };
+
+        let authorization = authorization
+            .to_str()
+            .map_err(|_| Error::Unauthenticated("Expected a bearer token".to_string()))?;
+
+        self.authenticate_authorization(db, authorization).await.map(Some)
+    }
+
+    /// Gets the user an `Authorization` value, `Bearer` followed by a token, identifies
+    pub async fn authenticate_authorization(&self, db: &Db, authorization: &str) -> Result<User> {
+        let token = authorization
+            .strip_prefix("Bearer ")
+            .ok_or_else(|| Error::Unauthenticated("Expected a bearer token".to_string()))?
+            .to_string();
+
+        let authenticators = self.authenticators.clone();
+
+        db.run(move |conn| {
+            for authenticator in authenticators.iter() {
+                if let Some(user) = authenticator.authenticate(conn, &token)? {
+                    return Ok(user);
+                }
+            }
+
+            Err(Error::Unauthenticated("Unknown kind of bearer token".to_string()))
+        })
+        .await
+    }
+}
+
+/// The role of a user, users with an unknown role being readers
+pub fn role(user: &User) -> Role {
+    Role::from_name(&user.role).unwrap_or(Role::Reader)
+}
+
+/// Allows only users with at least a role
+pub struct RoleGuard(pub Role);
+
+impl Guard for RoleGuard {
+    async fn check(&self, ctx: &Context<'_>) -> async_graphql::Result<()> {
+        match ctx.data_opt::<User>() {
+            None => Err(Error::Unauthenticated("Authentication required".to_string()).into()),
+            Some(user) if role(user) >= self.0 => Ok(()),
+            Some(_) => Err(Error::Forbidden(format!("Requires the {} role", self.0.name())).into()),
+        }
+    }
+}
+
+/// Runs a function in a transaction in which changes are recorded as made by the user making the
+/// request
+pub async fn act<T, F>(ctx: &Context<'_>, f: F) -> Result<T>
+where
+    F: FnOnce(&mut PgConnection) -> Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    let db = ctx.data_unchecked::<Db>();
+    let user = ctx.data_opt::<User>().cloned();
+
+    db.run(move |conn| {
+        conn.transaction(|conn| {
+            UserRepo::new(conn).act_as(user.as_ref())?;
+
+            f(conn)
+        })
+    })
+    .await
+}
@@ -0,0 +1,210 @@
+use std::fmt;
+use std::fs;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use clap::Parser;
+use serde::Deserialize;
+
+use crate::auth::Jwt;
+use crate::db::DbConfig;
+
+/// Serves the climbing database over GraphQL
+#[derive(Parser, Debug)]
+#[command(version)]
+struct Cli {
+    /// TOML file of settings. Flags and environment variables take precedence over it.
+    #[arg(long, short, env = "CLIMB_CONFIG")]
+    config: Option<PathBuf>,
+    #[command(flatten)]
+    settings: Settings,
+}
+
+/// Settings which may each be given by a flag, an environment variable or the settings file, in
+/// that order of precedence
+#[derive(clap::Args, Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+struct Settings {
+    /// PostgreSQL database to serve
+    #[arg(long, env = "DATABASE_URL")]
+    database_url: Option<String>,
+    /// Address to listen on [default: 127.0.0.1:8000]
+    #[arg(long, env = "LISTEN_ADDR")]
+    listen: Option<SocketAddr>,
+    /// Maximum number of database connections [default: 10]
+    #[arg(long, env = "DATABASE_POOL_SIZE")]
+    pool_size: Option<u32>,
+    /// How long a request waits for a database connection, in milliseconds [default: 5000]
+    #[arg(long, env = "DATABASE_ACQUIRE_TIMEOUT_MS")]
+    acquire_timeout_ms: Option<u64>,
+    /// Origins allowed to make cross-origin requests, `*` allowing any. None by default.
+    #[arg(long = "cors-origin", env = "CORS_ORIGINS", value_delimiter = ',')]
+    cors_origins: Option<Vec<String>>,
+    /// PEM certificate chain, serving over HTTPS along with `--tls-key-file`
+    #[arg(long, env = "TLS_CERT_FILE")]
+    tls_cert_file: Option<PathBuf>,
+    /// PEM private key of the certificate
+    #[arg(long, env = "TLS_KEY_FILE")]
+    tls_key_file: Option<PathBuf>,
+    /// Whether to run pending migrations before serving [default: false]
+    #[arg(long, env = "RUN_MIGRATIONS", num_args = 0..=1, require_equals = true, default_missing_value = "true")]
+    run_migrations: Option<bool>,
+    /// Whether to serve the GraphiQL IDE [default: true]
+    #[arg(long, env = "GRAPHIQL", num_args = 0..=1, require_equals = true, default_missing_value = "true")]
+    graphiql: Option<bool>,
+    /// How long to wait for in-flight requests on shutdown, in seconds [default: 30]
+    #[arg(long, env = "SHUTDOWN_TIMEOUT_SECS")]
+    shutdown_timeout_secs: Option<u64>,
+    /// Shared secret of HS256 JWTs. Only API tokens are accepted without a JWT key.
+    #[arg(long, env = "JWT_SECRET", hide_env_values = true)]
+    jwt_secret: Option<String>,
+    /// PEM RSA public key of RS256 JWTs
+    #[arg(long, env = "JWT_PUBLIC_KEY_FILE")]
+    jwt_public_key_file: Option<PathBuf>,
+}
+
+impl Settings {
+    /// Takes each setting not given from `fallback`
+    fn or(self, fallback: Settings) -> Settings {
+        Settings {
+            database_url: self.database_url.or(fallback.database_url),
+            listen: self.listen.or(fallback.listen),
+            pool_size: self.pool_size.or(fallback.pool_size),
+            acquire_timeout_ms: self.acquire_timeout_ms.or(fallback.acquire_timeout_ms),
+            cors_origins: self.cors_origins.or(fallback.cors_origins),
+            tls_cert_file: self.tls_cert_file.or(fallback.tls_cert_file),
+            tls_key_file: self.tls_key_file.or(fallback.tls_key_file),
+            run_migrations: self.run_migrations.or(fallback.run_migrations),
+            graphiql: self.graphiql.or(fallback.graphiql),
+            shutdown_timeout_secs: self.shutdown_timeout_secs.or(fallback.shutdown_timeout_secs),
+            jwt_secret: self.jwt_secret.or(fallback.jwt_secret),
+            jwt_public_key_file: self.jwt_public_key_file.or(fallback.jwt_public_key_file),
+        }
+    }
+}
+
+/// Certificate and key to serve HTTPS with
+#[derive(Clone, Debug)]
+pub struct TlsConfig {
+    pub cert_file: PathBuf,
+    pub key_file: PathBuf,
+}
+
+/// Settings of the server
+#[derive(Clone, Debug)]
+pub struct Config {
+    pub database_url: String,
+    pub listen: SocketAddr,
+    pub db: DbConfig,
+    /// Origins allowed to make cross-origin requests, `*` allowing any
+    pub cors_origins: Vec<String>,
+    /// Serves HTTPS if set, and HTTP otherwise
+    pub tls: Option<TlsConfig>,
+    pub run_migrations: bool,
+    pub graphiql: bool,
+    /// How long to wait for in-flight requests on shutdown
+    pub shutdown_timeout: Duration,
+    /// Validates JWTs if set, and only API tokens are accepted otherwise
+    pub jwt: Option<Jwt>,
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    /// No database is given
+    MissingDatabaseUrl,
+    Read { path: PathBuf, source: std::io::Error },
+    Parse { path: PathBuf, source: toml::de::Error },
+    /// Only one of the certificate and key files is given
+    IncompleteTls,
+    /// Both a JWT secret and a JWT public key are given
+    ConflictingJwtKeys,
+    InvalidJwtPublicKey { path: PathBuf, source: jsonwebtoken::errors::Error },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::MissingDatabaseUrl => {
+                write!(f, "No database given, set `--database-url`, `DATABASE_URL` or `database_url` in the settings file")
+            }
+            ConfigError::Read { path, source } => write!(f, "Failed to read {}: {source}", path.display()),
+            ConfigError::Parse { path, source } => write!(f, "Invalid settings in {}: {source}", path.display()),
+            ConfigError::IncompleteTls => write!(f, "`tls_cert_file` and `tls_key_file` must be set together"),
+            ConfigError::ConflictingJwtKeys => {
+                write!(f, "Only one of `jwt_secret` and `jwt_public_key_file` may be set")
+            }
+            ConfigError::InvalidJwtPublicKey { path, source } => {
+                write!(f, "Invalid JWT public key in {}: {source}", path.display())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl Config {
+    /// Reads the settings of the command line, the environment and the settings file
+    pub fn load() -> Result<Self, ConfigError> {
+        let cli = Cli::parse();
+
+        let file = match cli.config {
+            Some(path) => read_file(path)?,
+            None => Settings::default(),
+        };
+
+        Config::from_settings(cli.settings.or(file))
+    }
+
+    fn from_settings(settings: Settings) -> Result<Self, ConfigError> {
+        let default_db = DbConfig::default();
+
+        let tls = match (settings.tls_cert_file, settings.tls_key_file) {
+            (Some(cert_file), Some(key_file)) => Some(TlsConfig { cert_file, key_file }),
+            (None, None) => None,
+            _ => return Err(ConfigError::IncompleteTls),
+        };
+
+        let jwt = match (settings.jwt_secret, settings.jwt_public_key_file) {
+            (Some(secret), None) => Some(Jwt::with_secret(secret.as_bytes())),
+            (None, Some(path)) => Some(read_public_key(path)?),
+            (None, None) => None,
+            (Some(_), Some(_)) => return Err(ConfigError::ConflictingJwtKeys),
+        };
+
+        Ok(Config {
+            database_url: settings.database_url.ok_or(ConfigError::MissingDatabaseUrl)?,
+            listen: settings.listen.unwrap_or(SocketAddr::from(([127, 0, 0, 1], 8000))),
+            db: DbConfig {
+                max_size: settings.pool_size.unwrap_or(default_db.max_size),
+                acquire_timeout: settings
+                    .acquire_timeout_ms
+                    .map_or(default_db.acquire_timeout, Duration::from_millis),
+            },
+            cors_origins: settings.cors_origins.unwrap_or_default(),
+            tls,
+            run_migrations: settings.run_migrations.unwrap_or(false),
+            graphiql: settings.graphiql.unwrap_or(true),
+            shutdown_timeout: Duration::from_secs(settings.shutdown_timeout_secs.unwrap_or(30)),
+            jwt,
+        })
+    }
+}
+
+fn read_file(path: PathBuf) -> Result<Settings, ConfigError> {
+    let toml = match fs::read_to_string(&path) {
+        Ok(toml) => toml,
+        Err(source) => return Err(ConfigError::Read { path, source }),
+    };
+
+    toml::from_str(&toml).map_err(|source| ConfigError::Parse { path, source })
+}
+
+fn read_public_key(path: PathBuf) -> Result<Jwt, ConfigError> {
+    let pem = match fs::read(&path) {
+        Ok(pem) => pem,
+        Err(source) => return Err(ConfigError::Read { path, source }),
+    };
+
+    Jwt::with_rsa_pem(&pem).map_err(|source| ConfigError::InvalidJwtPublicKey { path, source })
+}
@@ -0,0 +1,86 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use diesel::pg::PgConnection;
+use diesel::r2d2::ConnectionManager;
+use r2d2::Pool;
+use tokio::sync::Semaphore;
+
+use crate::error::{Error, Result};
+
+/// Settings of the database connection pool
+#[derive(Clone, Debug)]
+pub struct DbConfig {
+    /// Maximum number of connections, and so of queries running at once
+    pub max_size: u32,
+    /// How long to wait for a connection before failing with `Unavailable`
+    pub acquire_timeout: Duration,
+}
+
+impl Default for DbConfig {
+    fn default() -> Self {
+        DbConfig {
+            max_size: 10,
+            // Queued requests are served within a second at the rates of the concurrency bench, so
+            // waiting longer means the database is down or overloaded, and failing soon is kinder
+            acquire_timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+/// Runs diesel queries, which block, on tokio's blocking threads rather than its workers
+#[derive(Clone)]
+pub struct Db {
+    pool: Pool<ConnectionManager<PgConnection>>,
+    /// A permit per connection, so queries wait for a connection on the workers, where waiting
+    /// is cheap, rather than each on a blocking thread of its own
+    permits: Arc<Semaphore>,
+    acquire_timeout: Duration,
+}
+
+impl Db {
+    pub fn new(database_url: &str, config: &DbConfig) -> Result<Self> {
+        let pool = Pool::builder()
+            .max_size(config.max_size)
+            .connection_timeout(config.acquire_timeout)
+            .build(ConnectionManager::new(database_url))?;
+
+        Ok(Db {
+            pool,
+            permits: Arc::new(Semaphore::new(config.max_size as usize)),
+            acquire_timeout: config.acquire_timeout,
+        })
+    }
+
+    /// Runs a function with a connection from the pool, once one is available, failing with
+    /// `Unavailable` after waiting the acquire timeout
+    pub async fn run<T, F>(&self, f: F) -> Result<T>
+    where
+        F: FnOnce(&mut PgConnection) -> Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let permit = tokio::time::timeout(self.acquire_timeout, self.permits.clone().acquire_owned())
+            .await
+            .map_err(|_| Error::Unavailable("Timed out waiting for a database connection".to_string()))?
+            .map_err(|e| Error::Internal(format!("Connection permits closed: {e}")))?;
+
+        let pool = self.pool.clone();
+
+        tokio::task::spawn_blocking(move || {
+            let _permit = permit;
+            let mut conn = pool.get()?;
+
+            f(&mut conn)
+        })
+        .await
+        .map_err(|e| Error::Internal(format!("Database task failed: {e}")))?
+    }
+
+    /// Runs the migrations not yet run, returning their names
+    pub async fn migrate(&self) -> Result<Vec<String>> {
+        self.run(|conn| {
+            climb_db::migrate(conn).map_err(|e| Error::Internal(format!("Failed to run migrations: {e}")))
+        })
+        .await
+    }
+}
@@ -0,0 +1,138 @@
+use async_graphql::ErrorExtensions;
+use climb_db::geo::GeoPointError;
+use climb_db::grade::GradeError;
+use climb_db::repo;
+use diesel::result::Error as DieselError;
+
+/// Errors surfaced to API clients.
+///
+/// Each variant is reported with a machine readable `code` extension, so clients can tell a
+/// missing entity from an unreachable database.
+#[derive(Clone, Debug)]
+pub enum Error {
+    /// The entity, or an entity it refers to, does not exist
+    NotFound(String),
+    /// The change conflicts with existing data, e.g. removing an entity which is still referenced
+    Conflict {
+        message: String,
+        constraint: Option<String>,
+    },
+    /// The change would make an entity its own ancestor
+    CycleDetected(String),
+    /// The grade is not valid for its grade type
+    InvalidGrade(String),
+    /// The input violates a constraint, e.g. belonging to both an area and a formation
+    InvalidInput {
+        message: String,
+        constraint: Option<String>,
+    },
+    /// The request has no valid credentials, but the operation requires them
+    Unauthenticated(String),
+    /// The user making the request lacks the role the operation requires
+    Forbidden(String),
+    /// The database cannot be reached
+    Unavailable(String),
+    Internal(String),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Error::NotFound(_) => "NOT_FOUND",
+            Error::Conflict { .. } => "CONFLICT",
+            Error::CycleDetected(_) => "CYCLE_DETECTED",
+            Error::InvalidGrade(_) => "INVALID_GRADE",
+            Error::InvalidInput { .. } => "INVALID_INPUT",
+            Error::Unauthenticated(_) => "UNAUTHENTICATED",
+            Error::Forbidden(_) => "FORBIDDEN",
+            Error::Unavailable(_) => "UNAVAILABLE",
+            Error::Internal(_) => "INTERNAL",
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            Error::NotFound(message)
+            | Error::Conflict { message, .. }
+            | Error::CycleDetected(message)
+            | Error::InvalidGrade(message)
+            | Error::InvalidInput { message, .. }
+            | Error::Unauthenticated(message)
+            | Error::Forbidden(message)
+            | Error::Unavailable(message)
+            | Error::Internal(message) => message,
+        }
+    }
+
+    fn constraint(&self) -> Option<&str> {
+        match self {
+            Error::Conflict { constraint, .. } | Error::InvalidInput { constraint, .. } => {
+                constraint.as_deref()
+            }
+            _ => None,
+        }
+    }
+}
+
+impl ErrorExtensions for Error {
+    fn extend(&self) -> async_graphql::Error {
+        async_graphql::Error::new(self.message()).extend_with(|_, e| {
+            e.set("code", self.code());
+
+            if let Some(constraint) = self.constraint() {
+                e.set("constraint", constraint);
+            }
+        })
+    }
+}
+
+// NOTE `Error` intentionally does not implement `Display`, which would make this conversion
+// conflict with async-graphql's blanket conversion, which drops the extensions
+impl From<Error> for async_graphql::Error {
+    fn from(e: Error) -> Self {
+        e.extend()
+    }
+}
+
+impl From<repo::Error> for Error {
+    fn from(e: repo::Error) -> Self {
+        match e {
+            repo::Error::NotFound(message) => Error::NotFound(message),
+            repo::Error::Conflict { message, constraint } => Error::Conflict { message, constraint },
+            repo::Error::CycleDetected(message) => Error::CycleDetected(message),
+            repo::Error::InvalidGrade(message) => Error::InvalidGrade(message),
+            repo::Error::InvalidInput { message, constraint } => Error::InvalidInput { message, constraint },
+            repo::Error::Unavailable(message) => Error::Unavailable(message),
+            repo::Error::Database(e) => Error::Internal(e.to_string()),
+        }
+    }
+}
+
+impl From<DieselError> for Error {
+    fn from(e: DieselError) -> Self {
+        repo::Error::from(e).into()
+    }
+}
+
+impl From<GeoPointError> for Error {
+    fn from(e: GeoPointError) -> Self {
+        Error::InvalidInput {
+            message: e.to_string(),
+            constraint: None,
+        }
+    }
+}
+
+impl From<GradeError> for Error {
+    fn from(e: GradeError) -> Self {
+        Error::InvalidGrade(e.to_string())
+    }
+}
+
+impl From<r2d2::Error> for Error {
+    fn from(e: r2d2::Error) -> Self {
+        Error::Unavailable(e.to_string())
+    }
+}
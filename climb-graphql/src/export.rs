@@ -0,0 +1,26 @@
+use axum::extract::{Path, State};
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+
+use crate::db::Db;
+use crate::error::Error;
+
+/// Serves an area subtree as a GeoJSON `FeatureCollection`, for use as a web map source
+pub async fn area_geojson(State(db): State<Db>, Path(id): Path<i32>) -> Response {
+    let result = db
+        .run(move |conn| Ok(climb_db::export::area_feature_collection(conn, id)?))
+        .await;
+
+    match result {
+        Ok(geojson) => ([(header::CONTENT_TYPE, "application/geo+json")], geojson).into_response(),
+        Err(e) => {
+            let status = match e {
+                Error::NotFound(_) => StatusCode::NOT_FOUND,
+                Error::Unavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+
+            (status, e.message().to_string()).into_response()
+        }
+    }
+}
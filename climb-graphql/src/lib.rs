@@ -0,0 +1,26 @@
+pub mod auth;
+pub mod config;
+pub mod db;
+pub mod error;
+pub mod export;
+pub mod loaders;
+pub mod notifications;
+pub mod pagination;
+pub mod schema;
+
+use async_graphql::{dataloader::DataLoader, Schema};
+use db::Db;
+use loaders::DbLoader;
+use notifications::Changes;
+use schema::{MutationRoot, QueryRoot, SubscriptionRoot};
+
+pub type ClimbSchema = Schema<QueryRoot, MutationRoot, SubscriptionRoot>;
+
+/// Builds the schema, resolving fields with queries on `db` and subscriptions with `changes`
+pub fn build_schema(db: Db, changes: Changes) -> ClimbSchema {
+    Schema::build(QueryRoot, MutationRoot, SubscriptionRoot)
+        .data(DataLoader::new(DbLoader::new(db.clone()), tokio::spawn))
+        .data(changes)
+        .data(db)
+        .finish()
+}
@@ -0,0 +1,712 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use async_graphql::dataloader::Loader;
+use climb_db::grade::GradeSystem;
+use climb_db::models;
+use climb_db::repo::{AreaRepo, AscentRepo, ClimbRepo, ClimberRepo, DescriptionRepo, FormationRepo, Page, UserRepo};
+
+use crate::db::Db;
+use crate::error::Error;
+
+/// Batches the lookups of field resolvers so each level of a query runs one query per table.
+///
+/// Every key type below selects a different batch, e.g. loading `AreaId`s and `SubAreasOf`s
+/// within the same level results in one query on `areas` and one on `area_belongs_to`.
+pub struct DbLoader {
+    db: Db,
+}
+
+impl DbLoader {
+    pub fn new(db: Db) -> Self {
+        DbLoader { db }
+    }
+}
+
+/// Groups `(key, value)` rows into a map of every value per key
+fn group<K: Hash + Eq, V>(rows: Vec<(K, V)>) -> HashMap<K, Vec<V>> {
+    let mut map: HashMap<K, Vec<V>> = HashMap::new();
+
+    for (key, value) in rows {
+        map.entry(key).or_default().push(value);
+    }
+
+    map
+}
+
+/// Groups the parents of paged keys by page, so each page is loaded with one query
+fn by_page(keys: impl IntoIterator<Item = (i32, Page)>) -> HashMap<Page, Vec<i32>> {
+    group(keys.into_iter().map(|(id, page)| (page, id)).collect())
+}
+
+#[derive(Clone, Copy, Hash, Eq, PartialEq)]
+pub struct AreaId(pub i32);
+
+#[derive(Clone, Copy, Hash, Eq, PartialEq)]
+pub struct AreaBoundaryOf(pub i32);
+
+/// Areas and formations a place is within, nearest first
+#[derive(Clone, Copy, Hash, Eq, PartialEq)]
+pub struct AncestorsOf(pub climb_db::queries::Place);
+
+/// Names of a place, primary name first
+#[derive(Clone, Copy, Hash, Eq, PartialEq)]
+pub struct NamesOf(pub climb_db::queries::Place);
+
+/// Descriptions of a place by type name
+#[derive(Clone, Copy, Hash, Eq, PartialEq)]
+pub struct DescriptionsOf(pub climb_db::queries::Place);
+
+#[derive(Clone, Copy, Hash, Eq, PartialEq)]
+pub struct SuperAreaOf(pub i32);
+
+/// A page of the sub areas of an area
+#[derive(Clone, Copy, Hash, Eq, PartialEq)]
+pub struct SubAreasOf(pub i32, pub Page);
+
+/// A page of the formations directly within an area
+#[derive(Clone, Copy, Hash, Eq, PartialEq)]
+pub struct AreaFormationsOf(pub i32, pub Page);
+
+/// A page of the climbs directly within an area
+#[derive(Clone, Copy, Hash, Eq, PartialEq)]
+pub struct AreaClimbsOf(pub i32, pub Page);
+
+#[derive(Clone, Copy, Hash, Eq, PartialEq)]
+pub struct ClimbId(pub i32);
+
+#[derive(Clone, Copy, Hash, Eq, PartialEq)]
+pub struct ClimbParentOf(pub i32);
+
+#[derive(Clone, Copy, Hash, Eq, PartialEq)]
+pub struct ClimbGradesOf(pub i32);
+
+/// Grades of a climb converted into a grade type
+#[derive(Clone, Copy, Hash, Eq, PartialEq)]
+pub struct ClimbGradesConvertedTo(pub i32, pub GradeSystem);
+
+#[derive(Clone, Copy, Hash, Eq, PartialEq)]
+pub struct ClimbConsensusGradesOf(pub i32);
+
+#[derive(Clone, Copy, Hash, Eq, PartialEq)]
+pub struct ClimbGradeVotesOf(pub i32);
+
+#[derive(Clone, Copy, Hash, Eq, PartialEq)]
+pub struct ClimbAscentsOf(pub i32);
+
+#[derive(Clone, Copy, Hash, Eq, PartialEq)]
+pub struct ClimbVariationsOf(pub i32);
+
+#[derive(Clone, Copy, Hash, Eq, PartialEq)]
+pub struct ClimbVariationRootsOf(pub i32);
+
+#[derive(Clone, Copy, Hash, Eq, PartialEq)]
+pub struct FormationId(pub i32);
+
+#[derive(Clone, Copy, Hash, Eq, PartialEq)]
+pub struct FormationParentOf(pub i32);
+
+/// A page of the sub formations of a formation
+#[derive(Clone, Copy, Hash, Eq, PartialEq)]
+pub struct SubFormationsOf(pub i32, pub Page);
+
+/// A page of the climbs directly on a formation
+#[derive(Clone, Copy, Hash, Eq, PartialEq)]
+pub struct FormationClimbsOf(pub i32, pub Page);
+
+#[derive(Clone, Copy, Hash, Eq, PartialEq)]
+pub struct ClimberId(pub i32);
+
+#[derive(Clone, Copy, Hash, Eq, PartialEq)]
+pub struct ClimberAscentsOf(pub i32);
+
+#[derive(Clone, Copy, Hash, Eq, PartialEq)]
+pub struct AscentId(pub i32);
+
+#[derive(Clone, Copy, Hash, Eq, PartialEq)]
+pub struct AscentPartyOf(pub i32);
+
+#[derive(Clone, Copy, Hash, Eq, PartialEq)]
+pub struct AscentGradesOf(pub i32);
+
+#[derive(Clone, Copy, Hash, Eq, PartialEq)]
+pub struct UserId(pub i32);
+
+impl Loader<AreaId> for DbLoader {
+    type Value = models::Area;
+    type Error = Error;
+
+    async fn load(&self, keys: &[AreaId]) -> Result<HashMap<AreaId, Self::Value>, Self::Error> {
+        let ids: Vec<i32> = keys.iter().map(|key| key.0).collect();
+
+        self.db
+            .run(move |conn| {
+                let data = AreaRepo::new(conn).get_many(&ids)?;
+
+                Ok(data.into_iter().map(|area| (AreaId(area.id), area)).collect())
+            })
+            .await
+    }
+}
+
+impl Loader<AreaBoundaryOf> for DbLoader {
+    type Value = String;
+    type Error = Error;
+
+    async fn load(&self, keys: &[AreaBoundaryOf]) -> Result<HashMap<AreaBoundaryOf, Self::Value>, Self::Error> {
+        let ids: Vec<i32> = keys.iter().map(|key| key.0).collect();
+
+        self.db
+            .run(move |conn| {
+                let data = AreaRepo::new(conn).boundaries(&ids)?;
+
+                Ok(data.into_iter().map(|row| (AreaBoundaryOf(row.id), row.geojson)).collect())
+            })
+            .await
+    }
+}
+
+impl Loader<SuperAreaOf> for DbLoader {
+    type Value = i32;
+    type Error = Error;
+
+    async fn load(&self, keys: &[SuperAreaOf]) -> Result<HashMap<SuperAreaOf, Self::Value>, Self::Error> {
+        let ids: Vec<i32> = keys.iter().map(|key| key.0).collect();
+
+        self.db
+            .run(move |conn| {
+                let data = AreaRepo::new(conn).super_areas(&ids)?;
+
+                Ok(data.into_iter().map(|(id, super_id)| (SuperAreaOf(id), super_id)).collect())
+            })
+            .await
+    }
+}
+
+impl Loader<SubAreasOf> for DbLoader {
+    type Value = Vec<i32>;
+    type Error = Error;
+
+    async fn load(&self, keys: &[SubAreasOf]) -> Result<HashMap<SubAreasOf, Self::Value>, Self::Error> {
+        let pages = by_page(keys.iter().map(|key| (key.0, key.1)));
+
+        self.db
+            .run(move |conn| {
+                let mut data = Vec::new();
+
+                for (page, ids) in pages {
+                    let rows = AreaRepo::new(conn).sub_area_pages(&ids, page)?;
+
+                    data.extend(rows.into_iter().map(|(id, sub_id)| (SubAreasOf(id, page), sub_id)));
+                }
+
+                Ok(group(data))
+            })
+            .await
+    }
+}
+
+impl Loader<AreaFormationsOf> for DbLoader {
+    type Value = Vec<i32>;
+    type Error = Error;
+
+    async fn load(&self, keys: &[AreaFormationsOf]) -> Result<HashMap<AreaFormationsOf, Self::Value>, Self::Error> {
+        let pages = by_page(keys.iter().map(|key| (key.0, key.1)));
+
+        self.db
+            .run(move |conn| {
+                let mut data = Vec::new();
+
+                for (page, ids) in pages {
+                    let rows = AreaRepo::new(conn).formation_pages(&ids, page)?;
+
+                    data.extend(rows.into_iter().map(|(id, formation_id)| (AreaFormationsOf(id, page), formation_id)));
+                }
+
+                Ok(group(data))
+            })
+            .await
+    }
+}
+
+impl Loader<AreaClimbsOf> for DbLoader {
+    type Value = Vec<i32>;
+    type Error = Error;
+
+    async fn load(&self, keys: &[AreaClimbsOf]) -> Result<HashMap<AreaClimbsOf, Self::Value>, Self::Error> {
+        let pages = by_page(keys.iter().map(|key| (key.0, key.1)));
+
+        self.db
+            .run(move |conn| {
+                let mut data = Vec::new();
+
+                for (page, ids) in pages {
+                    let rows = AreaRepo::new(conn).climb_pages(&ids, page)?;
+
+                    data.extend(rows.into_iter().map(|(id, climb_id)| (AreaClimbsOf(id, page), climb_id)));
+                }
+
+                Ok(group(data))
+            })
+            .await
+    }
+}
+
+impl Loader<ClimbId> for DbLoader {
+    type Value = models::Climb;
+    type Error = Error;
+
+    async fn load(&self, keys: &[ClimbId]) -> Result<HashMap<ClimbId, Self::Value>, Self::Error> {
+        let ids: Vec<i32> = keys.iter().map(|key| key.0).collect();
+
+        self.db
+            .run(move |conn| {
+                let data = ClimbRepo::new(conn).get_many(&ids)?;
+
+                Ok(data.into_iter().map(|climb| (ClimbId(climb.id), climb)).collect())
+            })
+            .await
+    }
+}
+
+impl Loader<ClimbParentOf> for DbLoader {
+    type Value = models::ClimbBelongsTo;
+    type Error = Error;
+
+    async fn load(&self, keys: &[ClimbParentOf]) -> Result<HashMap<ClimbParentOf, Self::Value>, Self::Error> {
+        let ids: Vec<i32> = keys.iter().map(|key| key.0).collect();
+
+        self.db
+            .run(move |conn| {
+                let data = ClimbRepo::new(conn).parents(&ids)?;
+
+                Ok(data.into_iter().map(|relation| (ClimbParentOf(relation.climb_id), relation)).collect())
+            })
+            .await
+    }
+}
+
+impl Loader<ClimbGradesOf> for DbLoader {
+    type Value = Vec<(String, String)>;
+    type Error = Error;
+
+    async fn load(&self, keys: &[ClimbGradesOf]) -> Result<HashMap<ClimbGradesOf, Self::Value>, Self::Error> {
+        let ids: Vec<i32> = keys.iter().map(|key| key.0).collect();
+
+        self.db
+            .run(move |conn| {
+                let data = ClimbRepo::new(conn)
+                    .grades(&ids)?
+                    .into_iter()
+                    .map(|(id, grade)| (ClimbGradesOf(id), grade))
+                    .collect();
+
+                Ok(group(data))
+            })
+            .await
+    }
+}
+
+impl Loader<ClimbGradesConvertedTo> for DbLoader {
+    type Value = Vec<String>;
+    type Error = Error;
+
+    async fn load(
+        &self,
+        keys: &[ClimbGradesConvertedTo],
+    ) -> Result<HashMap<ClimbGradesConvertedTo, Self::Value>, Self::Error> {
+        let keys = keys.to_vec();
+
+        self.db
+            .run(move |conn| {
+                let mut repo = ClimbRepo::new(conn);
+
+                // One query per grade type, as rarely more than one is asked for at once
+                let mut data = Vec::new();
+
+                for system in GradeSystem::ALL {
+                    let ids: Vec<i32> = keys.iter().filter(|key| key.1 == system).map(|key| key.0).collect();
+
+                    if ids.is_empty() {
+                        continue;
+                    }
+
+                    data.extend(
+                        repo.converted_grades(&ids, system)?
+                            .into_iter()
+                            .map(|grade| (ClimbGradesConvertedTo(grade.climb_id, system), grade.value)),
+                    );
+                }
+
+                Ok(group(data))
+            })
+            .await
+    }
+}
+
+impl Loader<ClimbConsensusGradesOf> for DbLoader {
+    type Value = Vec<climb_db::queries::ConsensusGrade>;
+    type Error = Error;
+
+    async fn load(
+        &self,
+        keys: &[ClimbConsensusGradesOf],
+    ) -> Result<HashMap<ClimbConsensusGradesOf, Self::Value>, Self::Error> {
+        let ids: Vec<i32> = keys.iter().map(|key| key.0).collect();
+
+        self.db
+            .run(move |conn| {
+                let data = ClimbRepo::new(conn)
+                    .consensus_grades(&ids)?
+                    .into_iter()
+                    .map(|grade| (ClimbConsensusGradesOf(grade.climb_id), grade))
+                    .collect();
+
+                Ok(group(data))
+            })
+            .await
+    }
+}
+
+impl Loader<ClimbGradeVotesOf> for DbLoader {
+    type Value = Vec<climb_db::queries::GradeVotes>;
+    type Error = Error;
+
+    async fn load(&self, keys: &[ClimbGradeVotesOf]) -> Result<HashMap<ClimbGradeVotesOf, Self::Value>, Self::Error> {
+        let ids: Vec<i32> = keys.iter().map(|key| key.0).collect();
+
+        self.db
+            .run(move |conn| {
+                let data = ClimbRepo::new(conn)
+                    .grade_votes(&ids)?
+                    .into_iter()
+                    .map(|votes| (ClimbGradeVotesOf(votes.climb_id), votes))
+                    .collect();
+
+                Ok(group(data))
+            })
+            .await
+    }
+}
+
+impl Loader<AncestorsOf> for DbLoader {
+    type Value = Vec<climb_db::queries::Place>;
+    type Error = Error;
+
+    async fn load(&self, keys: &[AncestorsOf]) -> Result<HashMap<AncestorsOf, Self::Value>, Self::Error> {
+        let places: Vec<_> = keys.iter().map(|key| key.0).collect();
+
+        self.db
+            .run(move |conn| {
+                use climb_db::repo::ancestors;
+
+                let data = ancestors(conn, &places)?
+                    .into_iter()
+                    .map(|(place, ancestor)| (AncestorsOf(place), ancestor))
+                    .collect();
+
+                Ok(group(data))
+            })
+            .await
+    }
+}
+
+impl Loader<NamesOf> for DbLoader {
+    type Value = Vec<climb_db::names::Name>;
+    type Error = Error;
+
+    async fn load(&self, keys: &[NamesOf]) -> Result<HashMap<NamesOf, Self::Value>, Self::Error> {
+        let places: Vec<_> = keys.iter().map(|key| key.0).collect();
+
+        self.db
+            .run(move |conn| {
+                use climb_db::names::names;
+
+                let data = names(conn, &places)?
+                    .into_iter()
+                    .map(|(place, name)| (NamesOf(place), name))
+                    .collect();
+
+                Ok(group(data))
+            })
+            .await
+    }
+}
+
+impl Loader<DescriptionsOf> for DbLoader {
+    type Value = Vec<climb_db::descriptions::Description>;
+    type Error = Error;
+
+    async fn load(&self, keys: &[DescriptionsOf]) -> Result<HashMap<DescriptionsOf, Self::Value>, Self::Error> {
+        let places: Vec<_> = keys.iter().map(|key| key.0).collect();
+
+        self.db
+            .run(move |conn| {
+                let data = DescriptionRepo::new(conn)
+                    .get_many(&places)?
+                    .into_iter()
+                    .map(|(place, description)| (DescriptionsOf(place), description))
+                    .collect();
+
+                Ok(group(data))
+            })
+            .await
+    }
+}
+
+impl Loader<ClimbAscentsOf> for DbLoader {
+    type Value = Vec<i32>;
+    type Error = Error;
+
+    async fn load(&self, keys: &[ClimbAscentsOf]) -> Result<HashMap<ClimbAscentsOf, Self::Value>, Self::Error> {
+        let ids: Vec<i32> = keys.iter().map(|key| key.0).collect();
+
+        self.db
+            .run(move |conn| {
+                let data = ClimbRepo::new(conn)
+                    .ascents(&ids)?
+                    .into_iter()
+                    .map(|(id, ascent_id)| (ClimbAscentsOf(id), ascent_id))
+                    .collect();
+
+                Ok(group(data))
+            })
+            .await
+    }
+}
+
+impl Loader<ClimbVariationsOf> for DbLoader {
+    type Value = Vec<i32>;
+    type Error = Error;
+
+    async fn load(&self, keys: &[ClimbVariationsOf]) -> Result<HashMap<ClimbVariationsOf, Self::Value>, Self::Error> {
+        let ids: Vec<i32> = keys.iter().map(|key| key.0).collect();
+
+        self.db
+            .run(move |conn| {
+                let data = ClimbRepo::new(conn)
+                    .variations(&ids)?
+                    .into_iter()
+                    .map(|(id, variation_id)| (ClimbVariationsOf(id), variation_id))
+                    .collect();
+
+                Ok(group(data))
+            })
+            .await
+    }
+}
+
+impl Loader<ClimbVariationRootsOf> for DbLoader {
+    type Value = Vec<i32>;
+    type Error = Error;
+
+    async fn load(&self, keys: &[ClimbVariationRootsOf]) -> Result<HashMap<ClimbVariationRootsOf, Self::Value>, Self::Error> {
+        let ids: Vec<i32> = keys.iter().map(|key| key.0).collect();
+
+        self.db
+            .run(move |conn| {
+                let data = ClimbRepo::new(conn)
+                    .variation_roots(&ids)?
+                    .into_iter()
+                    .map(|(id, root_id)| (ClimbVariationRootsOf(id), root_id))
+                    .collect();
+
+                Ok(group(data))
+            })
+            .await
+    }
+}
+
+impl Loader<FormationId> for DbLoader {
+    type Value = models::Formation;
+    type Error = Error;
+
+    async fn load(&self, keys: &[FormationId]) -> Result<HashMap<FormationId, Self::Value>, Self::Error> {
+        let ids: Vec<i32> = keys.iter().map(|key| key.0).collect();
+
+        self.db
+            .run(move |conn| {
+                let data = FormationRepo::new(conn).get_many(&ids)?;
+
+                Ok(data.into_iter().map(|formation| (FormationId(formation.id), formation)).collect())
+            })
+            .await
+    }
+}
+
+impl Loader<FormationParentOf> for DbLoader {
+    type Value = models::FormationBelongsTo;
+    type Error = Error;
+
+    async fn load(&self, keys: &[FormationParentOf]) -> Result<HashMap<FormationParentOf, Self::Value>, Self::Error> {
+        let ids: Vec<i32> = keys.iter().map(|key| key.0).collect();
+
+        self.db
+            .run(move |conn| {
+                let data = FormationRepo::new(conn).parents(&ids)?;
+
+                Ok(data.into_iter().map(|relation| (FormationParentOf(relation.formation_id), relation)).collect())
+            })
+            .await
+    }
+}
+
+impl Loader<SubFormationsOf> for DbLoader {
+    type Value = Vec<i32>;
+    type Error = Error;
+
+    async fn load(&self, keys: &[SubFormationsOf]) -> Result<HashMap<SubFormationsOf, Self::Value>, Self::Error> {
+        let pages = by_page(keys.iter().map(|key| (key.0, key.1)));
+
+        self.db
+            .run(move |conn| {
+                let mut data = Vec::new();
+
+                for (page, ids) in pages {
+                    let rows = FormationRepo::new(conn).sub_formation_pages(&ids, page)?;
+
+                    data.extend(rows.into_iter().map(|(id, formation_id)| (SubFormationsOf(id, page), formation_id)));
+                }
+
+                Ok(group(data))
+            })
+            .await
+    }
+}
+
+impl Loader<FormationClimbsOf> for DbLoader {
+    type Value = Vec<i32>;
+    type Error = Error;
+
+    async fn load(&self, keys: &[FormationClimbsOf]) -> Result<HashMap<FormationClimbsOf, Self::Value>, Self::Error> {
+        let pages = by_page(keys.iter().map(|key| (key.0, key.1)));
+
+        self.db
+            .run(move |conn| {
+                let mut data = Vec::new();
+
+                for (page, ids) in pages {
+                    let rows = FormationRepo::new(conn).climb_pages(&ids, page)?;
+
+                    data.extend(rows.into_iter().map(|(id, climb_id)| (FormationClimbsOf(id, page), climb_id)));
+                }
+
+                Ok(group(data))
+            })
+            .await
+    }
+}
+
+impl Loader<ClimberId> for DbLoader {
+    type Value = models::Climber;
+    type Error = Error;
+
+    async fn load(&self, keys: &[ClimberId]) -> Result<HashMap<ClimberId, Self::Value>, Self::Error> {
+        let ids: Vec<i32> = keys.iter().map(|key| key.0).collect();
+
+        self.db
+            .run(move |conn| {
+                let data = ClimberRepo::new(conn).get_many(&ids)?;
+
+                Ok(data.into_iter().map(|climber| (ClimberId(climber.id), climber)).collect())
+            })
+            .await
+    }
+}
+
+impl Loader<ClimberAscentsOf> for DbLoader {
+    type Value = Vec<i32>;
+    type Error = Error;
+
+    async fn load(&self, keys: &[ClimberAscentsOf]) -> Result<HashMap<ClimberAscentsOf, Self::Value>, Self::Error> {
+        let ids: Vec<i32> = keys.iter().map(|key| key.0).collect();
+
+        self.db
+            .run(move |conn| {
+                let data = ClimberRepo::new(conn)
+                    .ascents(&ids)?
+                    .into_iter()
+                    .map(|(id, ascent_id)| (ClimberAscentsOf(id), ascent_id))
+                    .collect();
+
+                Ok(group(data))
+            })
+            .await
+    }
+}
+
+impl Loader<AscentId> for DbLoader {
+    type Value = models::Ascent;
+    type Error = Error;
+
+    async fn load(&self, keys: &[AscentId]) -> Result<HashMap<AscentId, Self::Value>, Self::Error> {
+        let ids: Vec<i32> = keys.iter().map(|key| key.0).collect();
+
+        self.db
+            .run(move |conn| {
+                let data = AscentRepo::new(conn).get_many(&ids)?;
+
+                Ok(data.into_iter().map(|ascent| (AscentId(ascent.id), ascent)).collect())
+            })
+            .await
+    }
+}
+
+impl Loader<AscentPartyOf> for DbLoader {
+    type Value = Vec<i32>;
+    type Error = Error;
+
+    async fn load(&self, keys: &[AscentPartyOf]) -> Result<HashMap<AscentPartyOf, Self::Value>, Self::Error> {
+        let ids: Vec<i32> = keys.iter().map(|key| key.0).collect();
+
+        self.db
+            .run(move |conn| {
+                let data = AscentRepo::new(conn)
+                    .parties(&ids)?
+                    .into_iter()
+                    .map(|(id, climber_id)| (AscentPartyOf(id), climber_id))
+                    .collect();
+
+                Ok(group(data))
+            })
+            .await
+    }
+}
+
+impl Loader<AscentGradesOf> for DbLoader {
+    type Value = Vec<(String, String)>;
+    type Error = Error;
+
+    async fn load(&self, keys: &[AscentGradesOf]) -> Result<HashMap<AscentGradesOf, Self::Value>, Self::Error> {
+        let ids: Vec<i32> = keys.iter().map(|key| key.0).collect();
+
+        self.db
+            .run(move |conn| {
+                let data = AscentRepo::new(conn)
+                    .grades(&ids)?
+                    .into_iter()
+                    .map(|(id, grade)| (AscentGradesOf(id), grade))
+                    .collect();
+
+                Ok(group(data))
+            })
+            .await
+    }
+}
+
+impl Loader<UserId> for DbLoader {
+    type Value = models::User;
+    type Error = Error;
+
+    async fn load(&self, keys: &[UserId]) -> Result<HashMap<UserId, Self::Value>, Self::Error> {
+        let ids: Vec<i32> = keys.iter().map(|key| key.0).collect();
+
+        self.db
+            .run(move |conn| {
+                let data = UserRepo::new(conn).get_many(&ids)?;
+
+                Ok(data.into_iter().map(|user| (UserId(user.id), user)).collect())
+            })
+            .await
+    }
+}
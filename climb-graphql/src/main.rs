@@ -1,43 +1,229 @@
-mod schema;
-mod queries;
-
-use async_graphql::{http::GraphiQLSource, EmptySubscription, Schema};
-use async_graphql_axum::GraphQL;
+use async_graphql::{http::GraphiQLSource, http::ALL_WEBSOCKET_PROTOCOLS, Data, ErrorExtensions, ServerError};
+use async_graphql_axum::{GraphQLProtocol, GraphQLRequest, GraphQLResponse, GraphQLWebSocket};
 use axum::{
+    extract::{State, WebSocketUpgrade},
+    http::{header, header::InvalidHeaderValue, HeaderMap, HeaderValue, Method, StatusCode},
     response::{self, IntoResponse},
-    routing::get,
-    Router,
+    routing::{get, post},
+    Extension, Router,
 };
-use schema::MutationRoot;
-use tokio::net::TcpListener;
-use crate::schema::QueryRoot;
+use axum_server::Handle;
+use climb_graphql::auth::Auth;
+use climb_graphql::config::Config;
+use climb_graphql::db::Db;
+use climb_graphql::notifications::Changes;
+use climb_graphql::{build_schema, export, ClimbSchema};
+use tower_http::cors::{AllowOrigin, CorsLayer};
 
-use diesel::pg::PgConnection;
-use diesel::r2d2::{self, ConnectionManager};
-use std::env;
+use std::process::ExitCode;
 
 async fn graphiql() -> impl IntoResponse {
-    response::Html(GraphiQLSource::build().endpoint("/graphql").finish())
+    response::Html(GraphiQLSource::build().endpoint("/graphql").subscription_endpoint("/ws").finish())
 }
 
-#[tokio::main]
-async fn main() {
-    let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
-    let manager = ConnectionManager::<PgConnection>::new(database_url);
+/// Executes a request as the user its bearer token identifies, if any
+async fn graphql(
+    State(db): State<Db>,
+    Extension(schema): Extension<ClimbSchema>,
+    Extension(auth): Extension<Auth>,
+    headers: HeaderMap,
+    request: GraphQLRequest,
+) -> GraphQLResponse {
+    let mut request = request.into_inner();
+
+    match auth.authenticate(&db, &headers).await {
+        Ok(Some(user)) => request = request.data(user),
+        Ok(None) => {}
+        Err(e) => {
+            let e = e.extend();
+            let mut error = ServerError::new(e.message, None);
+            error.extensions = e.extensions;
+
+            return async_graphql::Response::from_errors(vec![error]).into();
+        }
+    }
+
+    schema.execute(request).await.into()
+}
+
+/// Serves subscriptions over a WebSocket as the user its bearer token identifies, if any. As
+/// browsers cannot set headers of WebSockets, the token may instead be given as the
+/// `Authorization` of the `connection_init` payload.
+async fn subscriptions(
+    State(db): State<Db>,
+    Extension(schema): Extension<ClimbSchema>,
+    Extension(auth): Extension<Auth>,
+    headers: HeaderMap,
+    protocol: GraphQLProtocol,
+    upgrade: WebSocketUpgrade,
+) -> impl IntoResponse {
+    let user = match auth.authenticate(&db, &headers).await {
+        Ok(user) => user,
+        Err(e) => return (StatusCode::UNAUTHORIZED, e.message().to_string()).into_response(),
+    };
+
+    upgrade
+        .protocols(ALL_WEBSOCKET_PROTOCOLS)
+        .on_upgrade(move |stream| {
+            GraphQLWebSocket::new(stream, schema, protocol)
+                .on_connection_init(move |payload| async move {
+                    let mut data = Data::default();
+
+                    let authorization = payload
+                        .get("Authorization")
+                        .or_else(|| payload.get("authorization"))
+                        .and_then(serde_json::Value::as_str);
+
+                    match (user, authorization) {
+                        (Some(user), _) => data.insert(user),
+                        (None, Some(authorization)) => {
+                            data.insert(auth.authenticate_authorization(&db, authorization).await?)
+                        }
+                        (None, None) => {}
+                    }
+
+                    Ok(data)
+                })
+                .serve()
+        })
+        .into_response()
+}
+
+/// Allows cross-origin requests from origins, `*` allowing any, if any are given
+fn cors(origins: &[String]) -> Result<Option<CorsLayer>, InvalidHeaderValue> {
+    if origins.is_empty() {
+        return Ok(None);
+    }
+
+    let allow_origin = if origins.iter().any(|origin| origin == "*") {
+        AllowOrigin::any()
+    } else {
+        AllowOrigin::list(origins.iter().map(|origin| origin.parse()).collect::<Result<Vec<HeaderValue>, _>>()?)
+    };
+
+    Ok(Some(
+        CorsLayer::new()
+            .allow_origin(allow_origin)
+            .allow_methods([Method::GET, Method::POST])
+            .allow_headers([header::AUTHORIZATION, header::CONTENT_TYPE]),
+    ))
+}
+
+/// Resolves on SIGTERM or Ctrl-C
+async fn shutdown_signal() {
+    let interrupt = async {
+        tokio::signal::ctrl_c().await.expect("Failed to listen for Ctrl-C");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        signal(SignalKind::terminate()).expect("Failed to listen for SIGTERM").recv().await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
 
-    let pool = r2d2::Pool::builder()
-        .build(manager)
-        .expect("Failed to create pool.");
+    tokio::select! {
+        _ = interrupt => {}
+        _ = terminate => {}
+    }
+}
+
+async fn serve(config: Config) -> Result<(), Box<dyn std::error::Error>> {
+    #[cfg(feature = "tls")]
+    let rustls = match &config.tls {
+        Some(tls) => Some(
+            axum_server::tls_rustls::RustlsConfig::from_pem_file(&tls.cert_file, &tls.key_file)
+                .await
+                .map_err(|e| format!("Failed to load the TLS certificate or key: {e}"))?,
+        ),
+        None => None,
+    };
+
+    #[cfg(not(feature = "tls"))]
+    if config.tls.is_some() {
+        return Err("Serving over HTTPS requires the `tls` feature".into());
+    }
+
+    let db = Db::new(&config.database_url, &config.db)
+        .map_err(|e| format!("Failed to connect to the database: {}", e.message()))?;
+
+    if config.run_migrations {
+        let migrations = db.migrate().await.map_err(|e| e.message().to_string())?;
+
+        for migration in migrations {
+            println!("Ran migration {migration}");
+        }
+    }
+
+    let schema = build_schema(db.clone(), Changes::listen(config.database_url.clone()));
+
+    let graphql_route = if config.graphiql { get(graphiql).post(graphql) } else { post(graphql) };
+
+    let mut app = Router::new()
+        .route("/graphql", graphql_route)
+        .route("/ws", get(subscriptions))
+        .route("/areas/{id}/features.geojson", get(export::area_geojson))
+        .layer(Extension(schema))
+        .layer(Extension(Auth::with_jwt(config.jwt.clone())))
+        .with_state(db);
+
+    if let Some(cors) = cors(&config.cors_origins).map_err(|e| format!("Invalid CORS origin: {e}"))? {
+        app = app.layer(cors);
+    }
 
-    let schema = Schema::build(QueryRoot, MutationRoot, EmptySubscription)
-        .data(pool.clone())
-        .finish();
+    let (http, ws) = if config.tls.is_some() { ("https", "wss") } else { ("http", "ws") };
+    let listen = config.listen;
 
-    let app = Router::new().route("/graphql", get(graphiql).post_service(GraphQL::new(schema)));
+    if config.graphiql {
+        println!("GraphiQL IDE: {http}://{listen}/graphql");
+    }
+    println!("GraphQL subscriptions: {ws}://{listen}/ws");
+    println!("GeoJSON export: {http}://{listen}/areas/{{id}}/features.geojson");
 
-    println!("GraphiQL IDE: http://localhost:8000/graphql");
+    // Stops accepting connections on shutdown, then waits for in-flight requests to finish within
+    // the timeout. Subscriptions, being upgraded connections, end with the process.
+    let handle = Handle::new();
+    let shutdown_timeout = config.shutdown_timeout;
+
+    tokio::spawn({
+        let handle = handle.clone();
+
+        async move {
+            shutdown_signal().await;
+            println!("Shutting down, waiting up to {}s for in-flight requests", shutdown_timeout.as_secs());
+            handle.graceful_shutdown(Some(shutdown_timeout));
+        }
+    });
+
+    let app = app.into_make_service();
+
+    #[cfg(feature = "tls")]
+    if let Some(rustls) = rustls {
+        axum_server::bind_rustls(listen, rustls).handle(handle).serve(app).await?;
+
+        return Ok(());
+    }
+
+    axum_server::bind(listen).handle(handle).serve(app).await?;
+
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let result = match Config::load() {
+        Ok(config) => serve(config).await,
+        Err(e) => Err(e.into()),
+    };
 
-    axum::serve(TcpListener::bind("127.0.0.1:8000").await.unwrap(), app)
-        .await
-        .unwrap();
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+    }
 }
@@ -0,0 +1,71 @@
+use std::thread;
+use std::time::Duration;
+
+use async_graphql::futures_util::stream::{self, Stream};
+use climb_db::notify::ChangeNotification;
+use diesel::pg::PgConnection;
+use diesel::Connection;
+use tokio::sync::broadcast::{self, error::RecvError};
+
+/// How often the listening connection is checked for notifications
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How long to wait before listening again after losing the listening connection. Changes
+/// committed meanwhile are not notified.
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Changes notified by the database, whichever server instance or client made them
+#[derive(Clone)]
+pub struct Changes(broadcast::Sender<ChangeNotification>);
+
+impl Changes {
+    /// Listens for changes on a dedicated connection in a background thread
+    pub fn listen(database_url: String) -> Self {
+        let (sender, _) = broadcast::channel(1024);
+        let changes = Changes(sender.clone());
+
+        thread::spawn(move || loop {
+            if let Err(e) = forward(&database_url, &sender) {
+                eprintln!("Lost change notifications: {e}");
+            }
+
+            thread::sleep(RECONNECT_DELAY);
+        });
+
+        changes
+    }
+
+    /// Streams the changes notified from now on. Subscribers falling too far behind miss changes.
+    pub fn subscribe(&self) -> impl Stream<Item = ChangeNotification> {
+        stream::unfold(self.0.subscribe(), |mut receiver| async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(change) => return Some((change, receiver)),
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        })
+    }
+}
+
+/// Forwards notifications to subscribers until the listening connection fails
+fn forward(
+    database_url: &str,
+    sender: &broadcast::Sender<ChangeNotification>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut conn = PgConnection::establish(database_url)?;
+
+    use climb_db::notify::{listen, notifications};
+
+    listen(&mut conn)?;
+
+    loop {
+        for change in notifications(&mut conn)? {
+            // Sending fails only without subscribers
+            let _ = sender.send(change);
+        }
+
+        thread::sleep(POLL_INTERVAL);
+    }
+}
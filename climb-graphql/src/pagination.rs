@@ -0,0 +1,112 @@
+use std::future::Future;
+use std::hash::Hash;
+
+use async_graphql::connection::{self, Connection, Edge};
+use async_graphql::dataloader::{DataLoader, Loader};
+use async_graphql::{FieldResult, OutputType};
+use climb_db::repo::Page;
+use diesel::pg::PgConnection;
+
+use crate::db::Db;
+use crate::error::{Error, Result};
+use crate::loaders::DbLoader;
+
+/// Resolves a connection of ids, with ids as cursors, from a page of its arguments.
+///
+/// Pages given neither `first` nor `last` are the first `Page::DEFAULT_SIZE` ids, and `first` or
+/// `last` above `Page::MAX_SIZE` is `INVALID_INPUT`.
+pub async fn paginate<T, F, Fut>(
+    after: Option<String>,
+    before: Option<String>,
+    first: Option<i32>,
+    last: Option<i32>,
+    f: F,
+) -> FieldResult<Connection<i32, T>>
+where
+    T: OutputType,
+    F: FnOnce(Page) -> Fut,
+    Fut: Future<Output = Result<Connection<i32, T>>>,
+{
+    connection::query(after, before, first, last, |after, before, first, last| async move {
+        f(Page::new(after, before, first, last)?).await
+    })
+    .await
+}
+
+/// Resolves a connection of ids loaded a page at a time, e.g. by the `list` of a repository
+pub async fn paginate_query<T, F, N>(
+    db: &Db,
+    after: Option<String>,
+    before: Option<String>,
+    first: Option<i32>,
+    last: Option<i32>,
+    node: N,
+    load: F,
+) -> FieldResult<Connection<i32, T>>
+where
+    T: OutputType,
+    F: FnOnce(&mut PgConnection, Page) -> Result<Vec<i32>> + Send + 'static,
+    N: Fn(i32) -> T,
+{
+    paginate(after, before, first, last, |page| async move {
+        let ids = db.run(move |conn| load(conn, page)).await?;
+
+        Ok(connection_from_window(ids, page, node))
+    })
+    .await
+}
+
+/// Resolves a connection of ids loaded a page at a time through the loader, e.g. of the children
+/// of every parent at a level of a query
+pub async fn paginate_loader<T, K, F, N>(
+    loader: &DataLoader<DbLoader>,
+    after: Option<String>,
+    before: Option<String>,
+    first: Option<i32>,
+    last: Option<i32>,
+    node: N,
+    key: F,
+) -> FieldResult<Connection<i32, T>>
+where
+    T: OutputType,
+    K: Send + Sync + Hash + Eq + Clone + 'static,
+    DbLoader: Loader<K, Value = Vec<i32>, Error = Error>,
+    F: FnOnce(Page) -> K,
+    N: Fn(i32) -> T,
+{
+    paginate(after, before, first, last, |page| async move {
+        let ids = loader.load_one(key(page)).await?.unwrap_or_default();
+
+        Ok(connection_from_window(ids, page, node))
+    })
+    .await
+}
+
+/// Builds a connection, with ids as cursors, from a window of ids as described by `Page`
+pub fn connection_from_window<T, F>(mut ids: Vec<i32>, page: Page, node: F) -> Connection<i32, T>
+where
+    T: OutputType,
+    F: Fn(i32) -> T,
+{
+    let (has_previous_page, has_next_page) = match (page.first, page.last) {
+        (_, Some(last)) => {
+            let has_previous_page = ids.len() > last;
+            ids.truncate(last);
+            ids.reverse();
+            (has_previous_page, page.before.is_some())
+        }
+        (first, None) => {
+            let first = first.unwrap_or(Page::DEFAULT_SIZE);
+            let has_next_page = ids.len() > first;
+            ids.truncate(first);
+            (page.after.is_some(), has_next_page)
+        }
+    };
+
+    let mut connection = Connection::new(has_previous_page, has_next_page);
+    connection
+        .edges
+        .extend(ids.into_iter().map(|id| Edge::new(id, node(id))));
+
+    connection
+}
@@ -1,18 +1,70 @@
-use std::str::FromStr;
-
-use async_graphql::{Context, FieldResult, InputObject, Object, SimpleObject, Enum};
-use climbing_grades::verm;
-use r2d2::Pool;
+use std::future::ready;
+use std::ops::Bound;
+
+use async_graphql::{
+    Context, Enum, FieldResult, InputObject, InputValueError, InputValueResult, Json, MaybeUndefined, Object, Scalar,
+    ScalarType, SimpleObject, Subscription, Union, Value,
+};
+use async_graphql::futures_util::{stream, Stream, StreamExt};
+use async_graphql::connection;
+use async_graphql::dataloader::DataLoader;
+use chrono::{DateTime, NaiveDate, Utc};
 use diesel::pg::PgConnection;
-use diesel::prelude::*;
-use diesel::r2d2::ConnectionManager;
+use climb_db::descriptions::Description;
+use climb_db::geo::{GeoPoint, GeoPointError};
+use climb_db::grade::{GradeSystem, ParsedGrade};
 use climb_db::models;
+use climb_db::notify::ChangeNotification;
+use climb_db::queries::Place;
+use climb_db::repo::{
+    ancestors, AreaInput, AreaRepo, AscentInput, AscentRepo, AscentUpdate, ChangeRepo, ClimbInput, ClimbRepo,
+    ClimberRepo, DescriptionRepo, FormationInput, FormationRepo, GradeRepo, Page, Parent, SearchRepo, UserRepo,
+};
+use climb_db::users::Role;
+
+use crate::auth::{act, role, RoleGuard};
+use crate::db::Db;
+use crate::error::{Error, Result};
+use crate::loaders::*;
+use crate::notifications::Changes;
+use crate::pagination::{paginate_loader, paginate_query};
 
 pub struct Area(i32);
 
 #[derive(Enum, Copy, Clone, Eq, PartialEq)]
 pub enum GradeType {
     Vermin,
+    Yds,
+    French,
+    Uiaa,
+    Ewbank,
+    Font,
+}
+
+impl From<GradeType> for GradeSystem {
+    fn from(grade_type: GradeType) -> Self {
+        match grade_type {
+            GradeType::Vermin => GradeSystem::Vermin,
+            GradeType::Yds => GradeSystem::Yds,
+            GradeType::French => GradeSystem::French,
+            GradeType::Uiaa => GradeSystem::Uiaa,
+            GradeType::Ewbank => GradeSystem::Ewbank,
+            GradeType::Font => GradeSystem::Font,
+        }
+    }
+}
+
+impl From<GradeSystem> for GradeType {
+    fn from(system: GradeSystem) -> Self {
+        match system {
+            GradeSystem::Vermin => GradeType::Vermin,
+            GradeSystem::Yds => GradeType::Yds,
+            GradeSystem::French => GradeType::French,
+            GradeSystem::Uiaa => GradeType::Uiaa,
+            GradeSystem::Ewbank => GradeType::Ewbank,
+            GradeSystem::Font => GradeType::Font,
+        }
+    }
 }
 
 #[derive(SimpleObject, InputObject)]
@@ -23,6 +75,28 @@ pub struct Grade {
     pub value: String,
 }
 
+impl Grade {
+    /// Parses the value in its grade type, e.g. to get its canonical value
+    pub fn parse(&self) -> Result<ParsedGrade> {
+        Ok(GradeSystem::from(self.grade_type).parse(&self.value)?)
+    }
+
+    /// Converts a `(grade type name, value)` row, skipping unknown grade types
+    pub fn from_row((name, value): (String, String)) -> Option<Self> {
+        GradeSystem::from_name(&name).map(|system| Grade { grade_type: system.into(), value })
+    }
+}
+
+/// A grade of a climb
+#[derive(SimpleObject)]
+pub struct ClimbGrade {
+    #[graphql(name="type")]
+    pub grade_type: GradeType,
+    pub value: String,
+    /// Whether the grade was converted from a grade of another grade type
+    pub derived: bool,
+}
+
 #[derive(SimpleObject, InputObject)]
 #[graphql(input_name = "KVPairInput")]
 pub struct KVPair {
@@ -30,6 +104,12 @@ pub struct KVPair {
     pub value: String,
 }
 
+impl From<KVPair> for Description {
+    fn from(kv: KVPair) -> Self {
+        Description { key: kv.key, value: kv.value }
+    }
+}
+
 #[derive(SimpleObject, InputObject)]
 #[graphql(input_name = "CoordinateInput")]
 pub struct Coordinate {
@@ -37,1221 +117,2746 @@ pub struct Coordinate {
     pub longitude: f64,
 }
 
-#[Object]
-impl Area {
-    async fn id(&self) -> &i32 {
-        &self.0
+impl From<GeoPoint> for Coordinate {
+    fn from(point: GeoPoint) -> Self {
+        Coordinate {
+            latitude: point.latitude(),
+            longitude: point.longitude(),
+        }
     }
+}
 
-    async fn names<'a>(&self, ctx: &Context<'a>) -> Vec<String> {
-        let pool = ctx.data_unchecked::<Pool<ConnectionManager<PgConnection>>>();
-        let mut conn = match pool.get() {
-            Ok(connection) => connection,
-            Err(_) => return Vec::new(),
-        };
+impl TryFrom<Coordinate> for GeoPoint {
+    type Error = GeoPointError;
 
-        use climb_db::schema::areas;
+    fn try_from(coordinate: Coordinate) -> std::result::Result<Self, Self::Error> {
+        GeoPoint::new(coordinate.longitude, coordinate.latitude)
+    }
+}
 
-        let names = match areas::table
-            .filter(areas::id.eq(&self.0))
-            .select(areas::names)
-            .first::<Vec<Option<String>>>(&mut conn)
-        {
-            Ok(names) => names,
-            Err(_) => return Vec::new(),
-        };
+/// A GeoJSON object
+pub struct GeoJson(pub serde_json::Value);
 
-        names.into_iter().flatten().collect()
+#[Scalar(name = "GeoJSON")]
+impl ScalarType for GeoJson {
+    fn parse(value: Value) -> InputValueResult<Self> {
+        match value {
+            Value::Object(_) => Ok(GeoJson(value.into_json()?)),
+            _ => Err(InputValueError::expected_type(value)),
+        }
     }
 
-    async fn super_area<'a>(&self, ctx: &Context<'a>) -> Option<Area> {
-        let pool = ctx.data_unchecked::<Pool<ConnectionManager<PgConnection>>>();
-        let mut conn = pool.get().ok()?;
+    fn to_value(&self) -> Value {
+        Value::from_json(self.0.clone()).unwrap_or(Value::Null)
+    }
+}
 
-        use climb_db::schema::area_belongs_to;
+impl GeoJson {
+    fn geometry_type(&self) -> Option<&str> {
+        self.0.get("type").and_then(serde_json::Value::as_str)
+    }
+}
 
-        let data = area_belongs_to::table
-            .filter(area_belongs_to::area_id.eq(&self.0))
-            .select(area_belongs_to::super_area_id)
-            .first::<i32>(&mut conn)
-            .ok()?;
+/// A formation located outside of the boundary of the area it belongs to
+#[derive(SimpleObject)]
+pub struct FormationOutsideArea {
+    pub formation: Formation,
+    pub area: Area,
+}
 
-        Some(Area(data))
-    }
+/// A box bounded by two parallels and two meridians. A box whose west edge is east of its east
+/// edge crosses the antimeridian.
+#[derive(InputObject)]
+#[graphql(name = "BoundingBoxInput")]
+pub struct BoundingBox {
+    pub south: f64,
+    pub west: f64,
+    pub north: f64,
+    pub east: f64,
+}
 
-    async fn sub_areas<'a>(&self, ctx: &Context<'a>) -> Vec<Area> {
-        let pool = ctx.data_unchecked::<Pool<ConnectionManager<PgConnection>>>();
-        let mut conn = match pool.get() {
-            Ok(connection) => connection,
-            Err(_) => return Vec::new(),
-        };
+/// Grades of one grade type within an inclusive range, unbounded where omitted
+#[derive(InputObject)]
+#[graphql(name = "GradeRangeInput")]
+pub struct GradeRange {
+    #[graphql(name="type")]
+    pub grade_type: GradeType,
+    pub min: Option<String>,
+    pub max: Option<String>,
+}
 
-        use climb_db::schema::area_belongs_to;
+impl GradeRange {
+    fn parse(&self) -> Result<climb_db::filter::GradeRange> {
+        let system = GradeSystem::from(self.grade_type);
 
-        let data = match area_belongs_to::table
-            .filter(area_belongs_to::super_area_id.eq(&self.0))
-            .select(area_belongs_to::area_id)
-            .load::<i32>(&mut conn)
-        {
-            Ok(ids) => ids,
-            Err(_) => Vec::new(),
-        };
+        Ok(climb_db::filter::GradeRange {
+            system,
+            min: self.min.as_deref().map(|min| system.parse(min)).transpose()?,
+            max: self.max.as_deref().map(|max| system.parse(max)).transpose()?,
+        })
+    }
+}
 
-        data.into_iter().map(Area).collect()
+/// Criteria climbs must all meet, ignored where omitted
+#[derive(InputObject, Default)]
+pub struct ClimbFilter {
+    /// Climbs with a grade in any of the ranges
+    pub grades: Option<Vec<GradeRange>>,
+    /// Climbs anywhere within an area, including its sub areas and their formations
+    pub within_area: Option<i32>,
+    /// Climbs anywhere on a formation, including its sub formations
+    pub within_formation: Option<i32>,
+    /// Climbs on a formation, or sub formation of one, with a location
+    pub has_location: Option<bool>,
+    pub has_ascents: Option<bool>,
+    pub has_description: Option<bool>,
+    /// Climbs with a name containing the text, ignoring case
+    pub name_contains: Option<String>,
+}
+
+impl ClimbFilter {
+    fn parse(self) -> Result<climb_db::filter::ClimbFilter> {
+        Ok(climb_db::filter::ClimbFilter {
+            grades: self
+                .grades
+                .unwrap_or_default()
+                .iter()
+                .map(GradeRange::parse)
+                .collect::<Result<_>>()?,
+            within_area_id: self.within_area,
+            within_formation_id: self.within_formation,
+            has_location: self.has_location,
+            has_ascents: self.has_ascents,
+            has_description: self.has_description,
+            name_contains: self.name_contains,
+            ..Default::default()
+        })
     }
+}
 
-    async fn formations<'a>(&self, ctx: &Context<'a>) -> Vec<Formation> {
-        let pool = ctx.data_unchecked::<Pool<ConnectionManager<PgConnection>>>();
-        let mut conn = match pool.get() {
-            Ok(connection) => connection,
-            Err(_) => return Vec::new(),
-        };
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Default)]
+pub enum ClimbOrderField {
+    #[default]
+    Id,
+    /// The first name of the climb, ignoring case
+    Name,
+    /// The normalised difficulty of the climb
+    Difficulty,
+}
 
-        use climb_db::schema::formation_belongs_to;
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Default)]
+pub enum OrderDirection {
+    #[default]
+    Asc,
+    Desc,
+}
 
-        let data = match formation_belongs_to::table
-            .filter(formation_belongs_to::area_id.eq(&self.0))
-            .select(formation_belongs_to::formation_id)
-            .load::<i32>(&mut conn)
-        {
-            Ok(ids) => ids,
-            Err(_) => Vec::new(),
-        };
+/// Order of climbs, ties being broken by ascending id. Climbs without a name or difficulty come
+/// last in either direction.
+#[derive(InputObject)]
+pub struct ClimbOrder {
+    pub field: ClimbOrderField,
+    #[graphql(default)]
+    pub direction: OrderDirection,
+}
+
+impl From<ClimbOrder> for climb_db::filter::ClimbOrder {
+    fn from(order: ClimbOrder) -> Self {
+        use climb_db::filter::ClimbOrderField as Field;
 
-        data.into_iter().map(Formation).collect()
+        climb_db::filter::ClimbOrder {
+            field: match order.field {
+                ClimbOrderField::Id => Field::Id,
+                ClimbOrderField::Name => Field::Name,
+                ClimbOrderField::Difficulty => Field::Difficulty,
+            },
+            descending: order.direction == OrderDirection::Desc,
+        }
     }
+}
 
-    async fn climbs<'a>(&self, ctx: &Context<'a>) -> Vec<Climb> {
-        let pool = ctx.data_unchecked::<Pool<ConnectionManager<PgConnection>>>();
-        let mut conn = match pool.get() {
-            Ok(connection) => connection,
-            Err(_) => return Vec::new(),
-        };
+#[derive(Enum, Copy, Clone, Eq, PartialEq)]
+pub enum SearchKind {
+    Area,
+    Formation,
+    Climb,
+}
 
-        use climb_db::schema::climb_belongs_to;
+impl From<SearchKind> for climb_db::search::SearchKind {
+    fn from(kind: SearchKind) -> Self {
+        match kind {
+            SearchKind::Area => climb_db::search::SearchKind::Area,
+            SearchKind::Formation => climb_db::search::SearchKind::Formation,
+            SearchKind::Climb => climb_db::search::SearchKind::Climb,
+        }
+    }
+}
+
+#[derive(Union)]
+pub enum SearchNode {
+    Area(Area),
+    Formation(Formation),
+    Climb(Climb),
+}
+
+/// An area, formation or climb matching a search
+#[derive(SimpleObject)]
+pub struct SearchResult {
+    pub kind: SearchKind,
+    pub node: SearchNode,
+    /// The best matching name, or the primary name if only a description matched
+    pub name: String,
+    /// The matched name, or an excerpt of the matched description, as HTML escaped text with
+    /// matched words wrapped in `<mark>` tags
+    pub highlight: String,
+    /// Relevance of the match, higher being better
+    pub rank: f64,
+}
+
+impl SearchResult {
+    fn from_hit(hit: climb_db::search::SearchHit) -> Option<Self> {
+        use climb_db::search::SearchKind as Kind;
 
-        let data = match climb_belongs_to::table
-            .filter(climb_belongs_to::area_id.eq(&self.0))
-            .select(climb_belongs_to::climb_id)
-            .load::<i32>(&mut conn)
-        {
-            Ok(ids) => ids,
-            Err(_) => Vec::new(),
+        let (kind, node) = match Kind::from_name(&hit.kind)? {
+            Kind::Area => (SearchKind::Area, SearchNode::Area(Area(hit.id))),
+            Kind::Formation => (SearchKind::Formation, SearchNode::Formation(Formation(hit.id))),
+            Kind::Climb => (SearchKind::Climb, SearchNode::Climb(Climb(hit.id))),
         };
 
-        data.into_iter().map(Climb).collect()
+        Some(SearchResult { kind, node, name: hit.name, highlight: hit.highlight, rank: hit.rank })
     }
 }
 
-pub struct Climb(i32);
+#[derive(Enum, Copy, Clone, Eq, PartialEq)]
+pub enum NameKind {
+    /// The canonical name, of which there is at most one
+    Primary,
+    /// Another name in use
+    Alias,
+    /// A name no longer in use
+    Historic,
+    /// A name in another language
+    Translation,
+}
 
-#[Object]
-impl Climb {
-    async fn id(&self) -> &i32 {
-        &self.0
+impl From<NameKind> for climb_db::names::NameKind {
+    fn from(kind: NameKind) -> Self {
+        match kind {
+            NameKind::Primary => climb_db::names::NameKind::Primary,
+            NameKind::Alias => climb_db::names::NameKind::Alias,
+            NameKind::Historic => climb_db::names::NameKind::Historic,
+            NameKind::Translation => climb_db::names::NameKind::Translation,
+        }
     }
+}
 
-    async fn names<'a>(&self, ctx: &Context<'a>) -> Vec<String> {
-        let pool = ctx.data_unchecked::<Pool<ConnectionManager<PgConnection>>>();
-        let mut conn = match pool.get() {
-            Ok(connection) => connection,
-            Err(_) => return Vec::new(),
-        };
-
-        use climb_db::schema::climbs;
+impl From<climb_db::names::NameKind> for NameKind {
+    fn from(kind: climb_db::names::NameKind) -> Self {
+        match kind {
+            climb_db::names::NameKind::Primary => NameKind::Primary,
+            climb_db::names::NameKind::Alias => NameKind::Alias,
+            climb_db::names::NameKind::Historic => NameKind::Historic,
+            climb_db::names::NameKind::Translation => NameKind::Translation,
+        }
+    }
+}
 
-        let names = match climbs::table
-            .filter(climbs::id.eq(&self.0))
-            .select(climbs::names)
-            .first::<Vec<Option<String>>>(&mut conn)
-        {
-            Ok(names) => names,
-            Err(_) => return Vec::new(),
-        };
+/// A name of an area, formation or climb
+#[derive(SimpleObject)]
+pub struct Name {
+    pub value: String,
+    /// BCP 47 language tag, e.g. `en` or `ja-Latn`
+    pub language: Option<String>,
+    pub kind: NameKind,
+}
 
-        names.into_iter().flatten().collect()
-    }
+/// Kinds of changes of a row
+#[derive(Enum, Copy, Clone, Eq, PartialEq)]
+pub enum ChangeOperation {
+    Insert,
+    Update,
+    Delete,
+}
 
-    async fn descriptions<'a>(&self, ctx: &Context<'a>) -> Option<Vec<KVPair>> {
-        let pool = ctx.data_unchecked::<Pool<ConnectionManager<PgConnection>>>();
-        let mut conn = pool.get().ok()?;
+/// A recorded change of a row of an area, formation or climb
+#[derive(SimpleObject)]
+pub struct Change {
+    pub id: i64,
+    pub changed_at: DateTime<Utc>,
+    pub actor: Option<String>,
+    /// Table of the changed row
+    pub table: String,
+    pub operation: ChangeOperation,
+    /// The row before the change, unless inserted
+    pub before: Option<Json<serde_json::Value>>,
+    /// The row after the change, unless deleted
+    pub after: Option<Json<serde_json::Value>>,
+    /// Id of the change this change reverted
+    pub reverts: Option<i64>,
+}
 
-        use climb_db::schema::{climb_descriptions, climb_description_types};
+impl From<climb_db::audit::Change> for Change {
+    fn from(change: climb_db::audit::Change) -> Self {
+        let row = |json: Option<String>| json.and_then(|json| serde_json::from_str(&json).ok()).map(Json);
+
+        Change {
+            id: change.id,
+            changed_at: change.changed_at,
+            actor: change.actor,
+            table: change.table_name,
+            operation: match change.operation.as_str() {
+                "INSERT" => ChangeOperation::Insert,
+                "UPDATE" => ChangeOperation::Update,
+                _ => ChangeOperation::Delete,
+            },
+            before: row(change.before),
+            after: row(change.after),
+            reverts: change.reverts,
+        }
+    }
+}
 
-        // TODO This would be better if it was async
-        let data = climb_descriptions::table
-            .inner_join(
-                climb_description_types::table.on(
-                    climb_descriptions::climb_description_type_id.eq(climb_description_types::id)
-                    )
-                )
-            .filter(climb_descriptions::climb_id.eq(&self.0))
-            .select((climb_description_types::name, climb_descriptions::value))
-            .load::<(String, String)>(&mut conn)
-            .ok()?;
+/// Roles of users, each allowed everything the roles before it are
+#[derive(Enum, Copy, Clone, Eq, PartialEq)]
+pub enum UserRole {
+    Reader,
+    Contributor,
+    Moderator,
+    Admin,
+}
 
-        Some(data.into_iter().map(|(key, value)| KVPair { key, value }).collect())
+impl From<UserRole> for Role {
+    fn from(role: UserRole) -> Self {
+        match role {
+            UserRole::Reader => Role::Reader,
+            UserRole::Contributor => Role::Contributor,
+            UserRole::Moderator => Role::Moderator,
+            UserRole::Admin => Role::Admin,
+        }
     }
+}
 
-    async fn grades<'a>(&self, ctx: &Context<'a>) -> Option<Vec<Grade>> {
-        let pool = ctx.data_unchecked::<Pool<ConnectionManager<PgConnection>>>();
-        let mut conn = pool.get().ok()?;
+impl From<Role> for UserRole {
+    fn from(role: Role) -> Self {
+        match role {
+            Role::Reader => UserRole::Reader,
+            Role::Contributor => UserRole::Contributor,
+            Role::Moderator => UserRole::Moderator,
+            Role::Admin => UserRole::Admin,
+        }
+    }
+}
 
-        use climb_db::schema::climb_vermin_grades;
+/// A user of the API
+#[derive(SimpleObject)]
+pub struct User {
+    pub id: i32,
+    pub name: String,
+    pub role: UserRole,
+}
 
-        climb_vermin_grades::table
-            .filter(climb_vermin_grades::climb_id.eq(&self.0))
-            .select(climb_vermin_grades::value)
-            .load::<i32>(&mut conn)
-            .ok()?
-            .into_iter()
-            .map(|value| verm::Grade::new(value as u8))
-            .map(|grade| Some(Grade {
-                grade_type: GradeType::Vermin,
-                value: grade.to_string()
-            }))
-            .collect()
+impl From<models::User> for User {
+    fn from(user: models::User) -> Self {
+        User {
+            id: user.id,
+            role: role(&user).into(),
+            name: user.name,
+        }
     }
+}
 
-    async fn area<'a>(&self, ctx: &Context<'a>) -> Option<Area> {
-        let pool = ctx.data_unchecked::<Pool<ConnectionManager<PgConnection>>>();
-        let mut conn = pool.get().ok()?;
+/// A newly added API token, which cannot be shown again
+#[derive(SimpleObject)]
+pub struct ApiToken {
+    pub id: i32,
+    pub token: String,
+}
 
-        use climb_db::schema::climb_belongs_to;
+/// A formation along with its distance from a point
+#[derive(SimpleObject)]
+pub struct FormationDistance {
+    pub formation: Formation,
+    pub distance_meters: f64,
+}
 
-        climb_belongs_to::table
-            .filter(climb_belongs_to::climb_id.eq(&self.0))
-            .select(climb_belongs_to::area_id)
-            .first::<Option<i32>>(&mut conn)
-            .ok()?
-            .map(Area)
+impl From<climb_db::queries::FormationDistance> for FormationDistance {
+    fn from(row: climb_db::queries::FormationDistance) -> Self {
+        FormationDistance {
+            formation: Formation(row.id),
+            distance_meters: row.distance,
+        }
     }
+}
 
-    async fn formation<'a>(&self, ctx: &Context<'a>) -> Option<Formation> {
-        let pool = ctx.data_unchecked::<Pool<ConnectionManager<PgConnection>>>();
-        let mut conn = pool.get().ok()?;
-
-        use climb_db::schema::climb_belongs_to;
+/// An inclusive range of days, either end of which may be unknown
+#[derive(SimpleObject, InputObject)]
+#[graphql(input_name = "DateRangeInput")]
+pub struct DateRange {
+    pub start: Option<NaiveDate>,
+    pub end: Option<NaiveDate>,
+}
 
-        climb_belongs_to::table
-            .filter(climb_belongs_to::climb_id.eq(&self.0))
-            .select(climb_belongs_to::formation_id)
-            .first::<Option<i32>>(&mut conn)
-            .ok()?
-            .map(Formation)
+impl From<DateRange> for (Bound<NaiveDate>, Bound<NaiveDate>) {
+    fn from(range: DateRange) -> Self {
+        (
+            range.start.map_or(Bound::Unbounded, Bound::Included),
+            range.end.map_or(Bound::Unbounded, Bound::Included),
+        )
     }
 }
 
-pub struct Formation(i32);
+impl From<(Bound<NaiveDate>, Bound<NaiveDate>)> for DateRange {
+    fn from((start, end): (Bound<NaiveDate>, Bound<NaiveDate>)) -> Self {
+        // Postgres canonicalizes date ranges to `[start,end)`
+        DateRange {
+            start: match start {
+                Bound::Included(date) => Some(date),
+                Bound::Excluded(date) => date.succ_opt(),
+                Bound::Unbounded => None,
+            },
+            end: match end {
+                Bound::Included(date) => Some(date),
+                Bound::Excluded(date) => date.pred_opt(),
+                Bound::Unbounded => None,
+            },
+        }
+    }
+}
 
 #[Object]
-impl Formation {
+impl Area {
     async fn id(&self) -> &i32 {
         &self.0
     }
 
-    async fn names<'a>(&self, ctx: &Context<'a>) -> Vec<String> {
-        let pool = ctx.data_unchecked::<Pool<ConnectionManager<PgConnection>>>();
-        let mut conn = match pool.get() {
-            Ok(connection) => connection,
-            Err(_) => return Vec::new(),
-        };
-
-        use climb_db::schema::formations;
-
-        let names = match formations::table
-            .filter(formations::id.eq(&self.0))
-            .select(formations::names)
-            .first::<Vec<Option<String>>>(&mut conn)
-        {
-            Ok(names) => names,
-            Err(_) => return Vec::new(),
-        };
-
-        names.into_iter().flatten().collect()
+    /// The canonical name of the area
+    async fn primary_name<'a>(&self, ctx: &Context<'a>) -> Result<Option<String>> {
+        primary_name(ctx, Place::Area(self.0)).await
     }
 
-    async fn location<'a>(&self, ctx: &Context<'a>) -> Option<Coordinate> {
-        let pool = ctx.data_unchecked::<Pool<ConnectionManager<PgConnection>>>();
-        let mut conn = match pool.get() {
-            Ok(connection) => connection,
-            Err(_) => return None,
-        };
-
-        use climb_db::schema::formations;
-        use postgis_diesel::types::Point;
-
-        let location = match formations::table
-            .filter(formations::id.eq(&self.0))
-            .select(formations::location)
-            .first::<Option<Point>>(&mut conn)
-        {
-            Ok(location) => location,
-            Err(_) => return None,
-        };
+    /// Values of the names of the area, primary name first
+    #[graphql(deprecation = "Use `nameEntries`, which also gives the kind and language of each name")]
+    async fn names<'a>(&self, ctx: &Context<'a>) -> Result<Vec<String>> {
+        name_values(ctx, Place::Area(self.0)).await
+    }
 
-        location.map(|loc| Coordinate { latitude: loc.x, longitude: loc.y })
+    /// Names of the area, primary name first
+    async fn name_entries<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(
+            desc = "Only names of this kind"
+        )]
+        kind: Option<NameKind>,
+        #[graphql(
+            desc = "Only names in this language, including its subtags, e.g. `en` includes `en-GB`"
+        )]
+        lang: Option<String>,
+    ) -> Result<Vec<Name>> {
+        names(ctx, Place::Area(self.0), kind, lang).await
     }
 
-    async fn area<'a>(&self, ctx: &Context<'a>) -> Option<Area> {
-        let pool = ctx.data_unchecked::<Pool<ConnectionManager<PgConnection>>>();
-        let mut conn = pool.get().ok()?;
+    async fn descriptions<'a>(&self, ctx: &Context<'a>) -> Result<Vec<KVPair>> {
+        descriptions(ctx, Place::Area(self.0)).await
+    }
 
-        use climb_db::schema::formation_belongs_to;
+    async fn super_area<'a>(&self, ctx: &Context<'a>) -> Result<Option<Area>> {
+        let loader = ctx.data_unchecked::<DataLoader<DbLoader>>();
 
-        formation_belongs_to::table
-            .filter(formation_belongs_to::formation_id.eq(&self.0))
-            .select(formation_belongs_to::area_id)
-            .first::<Option<i32>>(&mut conn)
-            .ok()?
-            .map(Area)
+        Ok(loader.load_one(SuperAreaOf(self.0)).await?.map(Area))
     }
 
-    async fn super_formation<'a>(&self, ctx: &Context<'a>) -> Option<Formation> {
-        let pool = ctx.data_unchecked::<Pool<ConnectionManager<PgConnection>>>();
-        let mut conn = pool.get().ok()?;
+    async fn boundary<'a>(&self, ctx: &Context<'a>) -> Result<Option<GeoJson>> {
+        let loader = ctx.data_unchecked::<DataLoader<DbLoader>>();
 
-        use climb_db::schema::formation_belongs_to;
+        let Some(geojson) = loader.load_one(AreaBoundaryOf(self.0)).await? else {
+            return Ok(None);
+        };
+
+        let value = serde_json::from_str(&geojson).map_err(|e| Error::Internal(e.to_string()))?;
 
-        formation_belongs_to::table
-            .filter(formation_belongs_to::formation_id.eq(&self.0))
-            .select(formation_belongs_to::super_formation_id)
-            .first::<Option<i32>>(&mut conn)
-            .ok()?
-            .map(Formation)
+        Ok(Some(GeoJson(value)))
     }
 
-    async fn sub_formations<'a>(&self, ctx: &Context<'a>) -> Vec<Formation> {
-        let pool = ctx.data_unchecked::<Pool<ConnectionManager<PgConnection>>>();
-        let mut conn = match pool.get() {
-            Ok(connection) => connection,
-            Err(_) => return Vec::new(),
-        };
+    async fn feature_collection<'a>(&self, ctx: &Context<'a>) -> Result<GeoJson> {
+        let db = ctx.data_unchecked::<Db>();
+        let id = self.0;
 
-        use climb_db::schema::formation_belongs_to;
+        db.run(move |conn| {
+            use climb_db::export::area_feature_collection;
 
-        let data = match formation_belongs_to::table
-            .filter(formation_belongs_to::super_formation_id.eq(&self.0))
-            .select(formation_belongs_to::formation_id)
-            .load::<i32>(&mut conn)
-        {
-            Ok(ids) => ids,
-            Err(_) => Vec::new(),
-        };
+            let geojson = area_feature_collection(conn, id)?;
+            let value = serde_json::from_str(&geojson).map_err(|e| Error::Internal(e.to_string()))?;
 
-        data.into_iter().map(Formation).collect()
+            Ok(GeoJson(value))
+        })
+        .await
     }
 
-    async fn climbs<'a>(&self, ctx: &Context<'a>) -> Vec<Climb> {
-        let pool = ctx.data_unchecked::<Pool<ConnectionManager<PgConnection>>>();
-        let mut conn = match pool.get() {
-            Ok(connection) => connection,
-            Err(_) => return Vec::new(),
-        };
+    async fn sub_areas<'a>(
+        &self,
+        ctx: &Context<'a>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> FieldResult<connection::Connection<i32, Area>> {
+        let loader = ctx.data_unchecked::<DataLoader<DbLoader>>();
+        let id = self.0;
+
+        paginate_loader(loader, after, before, first, last, Area, move |page| SubAreasOf(id, page)).await
+    }
+
+    async fn formations<'a>(
+        &self,
+        ctx: &Context<'a>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> FieldResult<connection::Connection<i32, Formation>> {
+        let loader = ctx.data_unchecked::<DataLoader<DbLoader>>();
+        let id = self.0;
+
+        paginate_loader(loader, after, before, first, last, Formation, move |page| AreaFormationsOf(id, page)).await
+    }
 
-        use climb_db::schema::climb_belongs_to;
+    async fn climbs<'a>(
+        &self,
+        ctx: &Context<'a>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> FieldResult<connection::Connection<i32, Climb>> {
+        let loader = ctx.data_unchecked::<DataLoader<DbLoader>>();
+        let id = self.0;
+
+        paginate_loader(loader, after, before, first, last, Climb, move |page| AreaClimbsOf(id, page)).await
+    }
 
-        let data = match climb_belongs_to::table
-            .filter(climb_belongs_to::formation_id.eq(&self.0))
-            .select(climb_belongs_to::climb_id)
-            .load::<i32>(&mut conn)
-        {
-            Ok(ids) => ids,
-            Err(_) => Vec::new(),
+    /// Climbs within the area, its sub areas and all of their formations
+    async fn all_climbs<'a>(
+        &self,
+        ctx: &Context<'a>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> FieldResult<connection::Connection<i32, Climb>> {
+        let filter = climb_db::filter::ClimbFilter {
+            within_area_id: Some(self.0),
+            ..Default::default()
         };
 
-        data.into_iter().map(Climb).collect()
+        all_climbs(ctx, filter, after, before, first, last).await
     }
-}
-
-pub struct QueryRoot;
 
-#[Object]
-impl QueryRoot {
-    async fn areas<'a>(
+    /// Areas below the area, ordered by depth then id
+    async fn descendants<'a>(
         &self,
         ctx: &Context<'a>,
         #[graphql(
-            desc = "Parent area id"
+            desc = "Levels below the area to descend, 1 being its sub areas. Unlimited by default"
         )]
-        area_id: Option<i32>,
-    ) -> FieldResult<Vec<Area>> {
-        let pool = ctx.data_unchecked::<Pool<ConnectionManager<PgConnection>>>();
-        let mut conn = pool.get().map_err(|e| e.to_string())?;
+        depth: Option<i32>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> FieldResult<connection::Connection<i32, Area>> {
+        if depth.is_some_and(|depth| depth < 1) {
+            return Err(Error::InvalidInput {
+                message: "Depth must be at least 1".to_string(),
+                constraint: None,
+            }
+            .into());
+        }
 
-        use climb_db::schema::{areas,area_belongs_to};
+        let db = ctx.data_unchecked::<Db>();
+        let id = self.0;
 
-        let query = areas::table
-            .left_join(area_belongs_to::table.on(area_belongs_to::area_id.eq(areas::id)))
-            .into_boxed();
+        paginate_query(db, after, before, first, last, Area, move |conn, page| {
+            Ok(AreaRepo::new(conn).descendants(id, depth, page)?)
+        })
+        .await
+    }
 
-        let query = if let Some(id) = area_id {
-            query.filter(area_belongs_to::area_id.eq(id))
-        } else {
-            query
-        };
+    /// Areas the area is within, its super area first
+    async fn ancestors<'a>(&self, ctx: &Context<'a>) -> Result<Vec<Area>> {
+        let loader = ctx.data_unchecked::<DataLoader<DbLoader>>();
 
-        let result = query
-            .select(areas::id)
-            .load(&mut conn)
-            .map_err(|e| e.to_string())?;
+        let data = loader.load_one(AncestorsOf(Place::Area(self.0))).await?.unwrap_or_default();
 
-        let areas = result.into_iter().map(Area).collect();
+        Ok(data
+            .into_iter()
+            .filter_map(|ancestor| match ancestor {
+                Place::Area(id) => Some(Area(id)),
+                _ => None,
+            })
+            .collect())
+    }
 
-        Ok(areas)
+    /// Areas the area is within, its root area first
+    async fn breadcrumbs<'a>(&self, ctx: &Context<'a>) -> Result<Vec<Breadcrumb>> {
+        breadcrumbs(ctx, Place::Area(self.0)).await
     }
 
-    async fn area<'a>(
+    /// Changes of the area, its names, descriptions and links, newest first
+    async fn history<'a>(
         &self,
         ctx: &Context<'a>,
         #[graphql(
-            desc = "Returns the area with the given id"
+            desc = "Maximum number of changes, at most 100",
+            default = 50
         )]
-        id: i32,
-    ) -> FieldResult<Area> {
-        let pool = ctx.data_unchecked::<Pool<ConnectionManager<PgConnection>>>();
-        let mut conn = pool.get().map_err(|e| e.to_string())?;
+        limit: i32,
+    ) -> Result<Vec<Change>> {
+        history(ctx, Place::Area(self.0), limit).await
+    }
 
-        use climb_db::schema::areas;
+    /// The user who added the area, if known
+    async fn created_by<'a>(&self, ctx: &Context<'a>) -> Result<Option<User>> {
+        let loader = ctx.data_unchecked::<DataLoader<DbLoader>>();
 
-        let area_id = areas::table
-            .find(id)
-            .select(areas::id)
-            .first(&mut conn)
-            .map_err(|e| e.to_string())?;
+        let creator = loader.load_one(AreaId(self.0)).await?.and_then(|area| area.created_by);
 
-        Ok(Area(area_id))
+        user(ctx, creator).await
     }
+}
 
-    async fn climbs<'a>(
-        &self,
-        ctx: &Context<'a>,
-        #[graphql(
-            desc = "Parent area id"
-        )]
-        area_id: Option<i32>,
-        #[graphql(
-            desc = "Parent formation id"
-        )]
-        formation_id: Option<i32>
-    ) -> FieldResult<Vec<Climb>> {
-        let pool = ctx.data_unchecked::<Pool<ConnectionManager<PgConnection>>>();
-        let mut conn = pool.get().map_err(|e| e.to_string())?;
+/// An area or formation along the path from the root area to a place
+#[derive(Union)]
+pub enum Breadcrumb {
+    Area(Area),
+    Formation(Formation),
+}
+
+/// Whether a language tag is, or is a subtag of, another, e.g. `en-GB` is within `en`
+fn language_within(language: &str, lang: &str) -> bool {
+    language.eq_ignore_ascii_case(lang)
+        || language
+            .get(..lang.len() + 1)
+            .is_some_and(|prefix| prefix.eq_ignore_ascii_case(&format!("{lang}-")))
+}
 
-        use climb_db::schema::{climbs,climb_belongs_to};
+/// Loads the names of a place, primary name first, optionally only those of a kind or language
+async fn names(ctx: &Context<'_>, place: Place, kind: Option<NameKind>, lang: Option<String>) -> Result<Vec<Name>> {
+    let loader = ctx.data_unchecked::<DataLoader<DbLoader>>();
 
-        let query = climbs::table
-            .left_join(climb_belongs_to::table.on(climb_belongs_to::climb_id.eq(climbs::id)))
-            .into_boxed();
+    let data = loader.load_one(NamesOf(place)).await?.unwrap_or_default();
 
-        let query = if let Some(id) = area_id {
-            query.filter(climb_belongs_to::area_id.eq(id))
-        } else {
-            query
-        };
+    Ok(data
+        .into_iter()
+        .filter_map(|name| {
+            Some(Name {
+                kind: climb_db::names::NameKind::from_name(&name.kind)?.into(),
+                value: name.value,
+                language: name.language,
+            })
+        })
+        .filter(|name| kind.is_none_or(|kind| name.kind == kind))
+        .filter(|name| {
+            lang.as_deref().is_none_or(|lang| name.language.as_deref().is_some_and(|language| language_within(language, lang)))
+        })
+        .collect())
+}
 
-        let query = if let Some(id) = formation_id {
-            query.filter(climb_belongs_to::formation_id.eq(id))
-        } else {
-            query
-        };
+/// Loads the values of the names of a place, primary name first
+async fn name_values(ctx: &Context<'_>, place: Place) -> Result<Vec<String>> {
+    Ok(names(ctx, place, None, None).await?.into_iter().map(|name| name.value).collect())
+}
+
+/// Loads the primary name of a place
+async fn primary_name(ctx: &Context<'_>, place: Place) -> Result<Option<String>> {
+    let primary = names(ctx, place, Some(NameKind::Primary), None).await?;
+
+    Ok(primary.into_iter().next().map(|name| name.value))
+}
+
+/// Loads the descriptions of a place, ordered by key
+async fn descriptions(ctx: &Context<'_>, place: Place) -> Result<Vec<KVPair>> {
+    let loader = ctx.data_unchecked::<DataLoader<DbLoader>>();
+
+    let data = loader.load_one(DescriptionsOf(place)).await?.unwrap_or_default();
+
+    Ok(data
+        .into_iter()
+        .map(|description| KVPair { key: description.key, value: description.value })
+        .collect())
+}
+
+/// Loads the breadcrumbs of a place, root area first, excluding the place itself
+async fn breadcrumbs(ctx: &Context<'_>, place: Place) -> Result<Vec<Breadcrumb>> {
+    let loader = ctx.data_unchecked::<DataLoader<DbLoader>>();
+
+    let data = loader.load_one(AncestorsOf(place)).await?.unwrap_or_default();
 
-        let result = query
-            .select(climbs::id)
-            .load::<i32>(&mut conn)
-            .map_err(|e| e.to_string())?;
+    Ok(data
+        .into_iter()
+        .rev()
+        .filter_map(|ancestor| match ancestor {
+            Place::Area(id) => Some(Breadcrumb::Area(Area(id))),
+            Place::Formation(id) => Some(Breadcrumb::Formation(Formation(id))),
+            Place::Climb(_) => None,
+        })
+        .collect())
+}
+
+/// Loads a user
+async fn user(ctx: &Context<'_>, id: Option<i32>) -> Result<Option<User>> {
+    let Some(id) = id else {
+        return Ok(None);
+    };
+
+    let loader = ctx.data_unchecked::<DataLoader<DbLoader>>();
 
-        let climbs = result.into_iter().map(Climb).collect();
+    Ok(loader.load_one(UserId(id)).await?.map(User::from))
+}
+
+/// Most changes of a place loaded at once
+const MAX_HISTORY: i32 = 100;
 
-        Ok(climbs)
+/// Loads the latest changes of a place, newest first
+async fn history(ctx: &Context<'_>, place: Place, limit: i32) -> Result<Vec<Change>> {
+    if !(0..=MAX_HISTORY).contains(&limit) {
+        return Err(Error::InvalidInput {
+            message: format!("Limit must be between 0 and {MAX_HISTORY}"),
+            constraint: None,
+        });
     }
 
-    async fn climb<'a>(
-        &self,
-        ctx: &Context<'a>,
-        #[graphql(
-            desc = "Returns climb with given id"
-        )]
-        id: i32,
-    ) -> FieldResult<Climb> {
-        let pool = ctx.data_unchecked::<Pool<ConnectionManager<PgConnection>>>();
-        let mut conn = pool.get().map_err(|e| e.to_string())?;
+    let db = ctx.data_unchecked::<Db>();
+
+    db.run(move |conn| {
+        let changes = ChangeRepo::new(conn).history(place, limit.into())?;
+
+        Ok(changes.into_iter().map(Change::from).collect())
+    })
+    .await
+}
 
-        use climb_db::schema::climbs;
+/// Resolves a connection of the climbs within an area or on a formation, including those of its
+/// descendants
+async fn all_climbs(
+    ctx: &Context<'_>,
+    filter: climb_db::filter::ClimbFilter,
+    after: Option<String>,
+    before: Option<String>,
+    first: Option<i32>,
+    last: Option<i32>,
+) -> FieldResult<connection::Connection<i32, Climb>> {
+    let db = ctx.data_unchecked::<Db>();
+
+    paginate_query(db, after, before, first, last, Climb, move |conn, page| {
+        Ok(ClimbRepo::new(conn).list(&filter, page)?)
+    })
+    .await
+}
 
-        let climb_id = climbs::table
-            .find(id)
-            .select(climbs::id)
-            .first::<i32>(&mut conn)
-            .map_err(|e| e.to_string())?;
+pub struct Climb(i32);
 
-        Ok(Climb(climb_id))
+#[Object]
+impl Climb {
+    async fn id(&self) -> &i32 {
+        &self.0
     }
 
-    async fn formations<'a>(
+    /// The canonical name of the climb
+    async fn primary_name<'a>(&self, ctx: &Context<'a>) -> Result<Option<String>> {
+        primary_name(ctx, Place::Climb(self.0)).await
+    }
+
+    /// Values of the names of the climb, primary name first
+    #[graphql(deprecation = "Use `nameEntries`, which also gives the kind and language of each name")]
+    async fn names<'a>(&self, ctx: &Context<'a>) -> Result<Vec<String>> {
+        name_values(ctx, Place::Climb(self.0)).await
+    }
+
+    /// Names of the climb, primary name first
+    async fn name_entries<'a>(
         &self,
         ctx: &Context<'a>,
         #[graphql(
-            desc = "Parent area id"
+            desc = "Only names of this kind"
         )]
-        area_id: Option<i32>,
+        kind: Option<NameKind>,
         #[graphql(
-            desc = "Parent formation id"
+            desc = "Only names in this language, including its subtags, e.g. `en` includes `en-GB`"
         )]
-        formation_id: Option<i32>
-    ) -> FieldResult<Vec<Formation>> {
-        let pool = ctx.data_unchecked::<Pool<ConnectionManager<PgConnection>>>();
-        let mut conn = pool.get().map_err(|e| e.to_string())?;
-
-        use climb_db::schema::{formations,formation_belongs_to};
+        lang: Option<String>,
+    ) -> Result<Vec<Name>> {
+        names(ctx, Place::Climb(self.0), kind, lang).await
+    }
 
-        let query = formations::table
-            .left_join(formation_belongs_to::table.on(formation_belongs_to::formation_id.eq(formations::id)))
-            .into_boxed();
+    async fn descriptions<'a>(&self, ctx: &Context<'a>) -> Result<Option<Vec<KVPair>>> {
+        Ok(Some(descriptions(ctx, Place::Climb(self.0)).await?))
+    }
 
-        let query = if let Some(id) = area_id {
-            query.filter(formation_belongs_to::area_id.eq(id))
-        } else {
-            query
-        };
+    async fn grades<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(
+            desc = "Grade type of the grades. Climbs without a grade of this type have their grades of other types converted"
+        )]
+        system: Option<GradeType>,
+    ) -> Result<Option<Vec<ClimbGrade>>> {
+        let loader = ctx.data_unchecked::<DataLoader<DbLoader>>();
 
-        let query = if let Some(id) = formation_id {
-            query.filter(formation_belongs_to::formation_id.eq(id))
-        } else {
-            query
-        };
+        let data = loader.load_one(ClimbGradesOf(self.0)).await?.unwrap_or_default();
 
-        let result = query
-            .select(formations::id)
-            .load::<i32>(&mut conn)
-            .map_err(|e| e.to_string())?;
+        let grades: Vec<ClimbGrade> = data
+            .into_iter()
+            .filter_map(Grade::from_row)
+            .filter(|grade| system.is_none_or(|system| grade.grade_type == system))
+            .map(|grade| ClimbGrade { grade_type: grade.grade_type, value: grade.value, derived: false })
+            .collect();
 
-        let formations = result.into_iter().map(Formation).collect();
+        let Some(system) = system.filter(|_| grades.is_empty()) else {
+            return Ok(Some(grades));
+        };
 
-        Ok(formations)
+        let converted = loader
+            .load_one(ClimbGradesConvertedTo(self.0, system.into()))
+            .await?
+            .unwrap_or_default();
+
+        Ok(Some(
+            converted
+                .into_iter()
+                .map(|value| ClimbGrade { grade_type: system, value, derived: true })
+                .collect(),
+        ))
     }
 
-    async fn formation<'a>(
+    /// Consensus of the grades proposed by the climb's ascents
+    async fn consensus_grade<'a>(
         &self,
         ctx: &Context<'a>,
         #[graphql(
-            desc = "Returns the formation with given id"
+            desc = "Grade type of the consensus, defaults to the grade type with the most votes"
         )]
-        id: i32,
-    ) -> FieldResult<Formation> {
-        let pool = ctx.data_unchecked::<Pool<ConnectionManager<PgConnection>>>();
-        let mut conn = pool.get().map_err(|e| e.to_string())?;
+        system: Option<GradeType>,
+    ) -> Result<Option<ConsensusGrade>> {
+        let loader = ctx.data_unchecked::<DataLoader<DbLoader>>();
 
-        use climb_db::schema::formations;
+        let data = loader.load_one(ClimbConsensusGradesOf(self.0)).await?.unwrap_or_default();
 
-        let formation_id = formations::table
-            .find(id)
-            .select(formations::id)
-            .first::<i32>(&mut conn)
-            .map_err(|e| e.to_string())?;
-
-        Ok(Formation(formation_id))
+        // Consensus grades come ordered by most votes
+        Ok(data
+            .into_iter()
+            .filter_map(|row| {
+                let system = GradeSystem::from_name(&row.grade_type)?;
+                Some(ConsensusGrade { grade_type: system.into(), row })
+            })
+            .find(|consensus| system.is_none_or(|system| consensus.grade_type == system)))
     }
-}
 
-pub struct MutationRoot;
+    /// Mean difficulty of the climb's grades on a scale common to every grade type, following
+    /// Ewbank. Climbs without grades, or only grades without conversions, have none.
+    async fn difficulty<'a>(&self, ctx: &Context<'a>) -> Result<Option<f64>> {
+        let loader = ctx.data_unchecked::<DataLoader<DbLoader>>();
+
+        Ok(loader.load_one(ClimbId(self.0)).await?.and_then(|climb| climb.difficulty))
+    }
+
+    async fn area<'a>(&self, ctx: &Context<'a>) -> Result<Option<Area>> {
+        let loader = ctx.data_unchecked::<DataLoader<DbLoader>>();
+
+        Ok(loader.load_one(ClimbParentOf(self.0)).await?.and_then(|row| row.area_id).map(Area))
+    }
+
+    async fn formation<'a>(&self, ctx: &Context<'a>) -> Result<Option<Formation>> {
+        let loader = ctx.data_unchecked::<DataLoader<DbLoader>>();
+
+        Ok(loader.load_one(ClimbParentOf(self.0)).await?.and_then(|row| row.formation_id).map(Formation))
+    }
+
+    /// Areas and formations the climb is within, its root area first
+    async fn breadcrumbs<'a>(&self, ctx: &Context<'a>) -> Result<Vec<Breadcrumb>> {
+        breadcrumbs(ctx, Place::Climb(self.0)).await
+    }
+
+    async fn ascents<'a>(&self, ctx: &Context<'a>) -> Result<Vec<Ascent>> {
+        let loader = ctx.data_unchecked::<DataLoader<DbLoader>>();
+
+        let data = loader.load_one(ClimbAscentsOf(self.0)).await?.unwrap_or_default();
+
+        Ok(data.into_iter().map(Ascent).collect())
+    }
+
+    async fn variations<'a>(&self, ctx: &Context<'a>) -> Result<Vec<Climb>> {
+        let loader = ctx.data_unchecked::<DataLoader<DbLoader>>();
+
+        let data = loader.load_one(ClimbVariationsOf(self.0)).await?.unwrap_or_default();
+
+        Ok(data.into_iter().map(Climb).collect())
+    }
+
+    async fn variation_of<'a>(&self, ctx: &Context<'a>) -> Result<Vec<Climb>> {
+        let loader = ctx.data_unchecked::<DataLoader<DbLoader>>();
+
+        let data = loader.load_one(ClimbVariationRootsOf(self.0)).await?.unwrap_or_default();
+
+        Ok(data.into_iter().map(Climb).collect())
+    }
+
+    async fn variation_family<'a>(&self, ctx: &Context<'a>) -> Result<VariationFamily> {
+        let db = ctx.data_unchecked::<Db>();
+        let id = self.0;
+
+        db.run(move |conn| {
+            let mut repo = ClimbRepo::new(conn);
+
+            let members = repo.variation_family(id)?;
+            let ids: Vec<i32> = members.iter().map(|member| member.id).collect();
+            let edges = repo.variations(&ids)?;
+
+            Ok(VariationFamily {
+                roots: members.iter().filter(|member| member.is_root).map(|member| Climb(member.id)).collect(),
+                climbs: ids.into_iter().map(Climb).collect(),
+                edges: edges
+                    .into_iter()
+                    .map(|(root_id, variation_id)| ClimbVariation {
+                        root: Climb(root_id),
+                        variation: Climb(variation_id),
+                    })
+                    .collect(),
+            })
+        })
+        .await
+    }
+
+    /// Changes of the climb, its names, descriptions, grades and links, newest first
+    async fn history<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(
+            desc = "Maximum number of changes, at most 100",
+            default = 50
+        )]
+        limit: i32,
+    ) -> Result<Vec<Change>> {
+        history(ctx, Place::Climb(self.0), limit).await
+    }
+
+    /// The user who added the climb, if known
+    async fn created_by<'a>(&self, ctx: &Context<'a>) -> Result<Option<User>> {
+        let loader = ctx.data_unchecked::<DataLoader<DbLoader>>();
+
+        let creator = loader.load_one(ClimbId(self.0)).await?.and_then(|climb| climb.created_by);
+
+        user(ctx, creator).await
+    }
+}
+
+/// Consensus of the grades proposed by the ascents of a climb in one grade type
+pub struct ConsensusGrade {
+    grade_type: GradeType,
+    row: climb_db::queries::ConsensusGrade,
+}
+
+#[Object]
+impl ConsensusGrade {
+    #[graphql(name="type")]
+    async fn grade_type(&self) -> GradeType {
+        self.grade_type
+    }
+
+    /// The lower median when there is an even number of votes
+    async fn median(&self) -> Grade {
+        Grade { grade_type: self.grade_type, value: self.row.median.clone() }
+    }
+
+    /// The easiest of the most voted grades
+    async fn mode(&self) -> Grade {
+        Grade { grade_type: self.grade_type, value: self.row.mode.clone() }
+    }
+
+    async fn votes(&self) -> i32 {
+        self.row.votes
+    }
+
+    /// Difference in difficulty between the hardest and easiest votes. Grade types without
+    /// conversions have none.
+    async fn spread(&self) -> Option<f64> {
+        self.row.spread
+    }
+
+    /// Votes for each proposed grade, easiest first
+    async fn distribution<'a>(&self, ctx: &Context<'a>) -> Result<Vec<GradeVoteCount>> {
+        let loader = ctx.data_unchecked::<DataLoader<DbLoader>>();
+
+        let data = loader.load_one(ClimbGradeVotesOf(self.row.climb_id)).await?.unwrap_or_default();
+
+        Ok(data
+            .into_iter()
+            .filter(|votes| votes.grade_type == self.row.grade_type)
+            .map(|votes| GradeVoteCount {
+                grade: Grade { grade_type: self.grade_type, value: votes.value },
+                votes: votes.votes as i32,
+            })
+            .collect())
+    }
+}
+
+/// Number of ascents proposing a grade
+#[derive(SimpleObject)]
+pub struct GradeVoteCount {
+    pub grade: Grade,
+    pub votes: i32,
+}
+
+#[derive(SimpleObject)]
+pub struct ClimbVariation {
+    pub root: Climb,
+    pub variation: Climb,
+}
+
+/// A climb's variation graph
+#[derive(SimpleObject)]
+pub struct VariationFamily {
+    /// Climbs which are not a variation of any other climb
+    pub roots: Vec<Climb>,
+    /// Every climb in the family, including the roots
+    pub climbs: Vec<Climb>,
+    pub edges: Vec<ClimbVariation>,
+}
+
+pub struct Formation(i32);
+
+#[Object]
+impl Formation {
+    async fn id(&self) -> &i32 {
+        &self.0
+    }
+
+    /// The canonical name of the formation
+    async fn primary_name<'a>(&self, ctx: &Context<'a>) -> Result<Option<String>> {
+        primary_name(ctx, Place::Formation(self.0)).await
+    }
+
+    /// Values of the names of the formation, primary name first
+    #[graphql(deprecation = "Use `nameEntries`, which also gives the kind and language of each name")]
+    async fn names<'a>(&self, ctx: &Context<'a>) -> Result<Vec<String>> {
+        name_values(ctx, Place::Formation(self.0)).await
+    }
+
+    /// Names of the formation, primary name first
+    async fn name_entries<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(
+            desc = "Only names of this kind"
+        )]
+        kind: Option<NameKind>,
+        #[graphql(
+            desc = "Only names in this language, including its subtags, e.g. `en` includes `en-GB`"
+        )]
+        lang: Option<String>,
+    ) -> Result<Vec<Name>> {
+        names(ctx, Place::Formation(self.0), kind, lang).await
+    }
+
+    async fn descriptions<'a>(&self, ctx: &Context<'a>) -> Result<Vec<KVPair>> {
+        descriptions(ctx, Place::Formation(self.0)).await
+    }
+
+    async fn location<'a>(&self, ctx: &Context<'a>) -> Result<Option<Coordinate>> {
+        let loader = ctx.data_unchecked::<DataLoader<DbLoader>>();
+
+        let location = loader.load_one(FormationId(self.0)).await?.and_then(|formation| formation.location);
+
+        Ok(location.map(Coordinate::from))
+    }
+
+    async fn area<'a>(&self, ctx: &Context<'a>) -> Result<Option<Area>> {
+        let loader = ctx.data_unchecked::<DataLoader<DbLoader>>();
+
+        Ok(loader.load_one(FormationParentOf(self.0)).await?.and_then(|row| row.area_id).map(Area))
+    }
+
+    async fn super_formation<'a>(&self, ctx: &Context<'a>) -> Result<Option<Formation>> {
+        let loader = ctx.data_unchecked::<DataLoader<DbLoader>>();
+
+        Ok(loader.load_one(FormationParentOf(self.0)).await?.and_then(|row| row.super_formation_id).map(Formation))
+    }
+
+    async fn sub_formations<'a>(
+        &self,
+        ctx: &Context<'a>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> FieldResult<connection::Connection<i32, Formation>> {
+        let loader = ctx.data_unchecked::<DataLoader<DbLoader>>();
+        let id = self.0;
+
+        paginate_loader(loader, after, before, first, last, Formation, move |page| SubFormationsOf(id, page)).await
+    }
+
+    async fn climbs<'a>(
+        &self,
+        ctx: &Context<'a>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> FieldResult<connection::Connection<i32, Climb>> {
+        let loader = ctx.data_unchecked::<DataLoader<DbLoader>>();
+        let id = self.0;
+
+        paginate_loader(loader, after, before, first, last, Climb, move |page| FormationClimbsOf(id, page)).await
+    }
+
+    /// Climbs on the formation and its sub formations
+    async fn all_climbs<'a>(
+        &self,
+        ctx: &Context<'a>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> FieldResult<connection::Connection<i32, Climb>> {
+        let filter = climb_db::filter::ClimbFilter {
+            within_formation_id: Some(self.0),
+            ..Default::default()
+        };
+
+        all_climbs(ctx, filter, after, before, first, last).await
+    }
+
+    /// Areas and formations the formation is within, its root area first
+    async fn breadcrumbs<'a>(&self, ctx: &Context<'a>) -> Result<Vec<Breadcrumb>> {
+        breadcrumbs(ctx, Place::Formation(self.0)).await
+    }
+
+    /// Changes of the formation, its names, descriptions and links, newest first
+    async fn history<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(
+            desc = "Maximum number of changes, at most 100",
+            default = 50
+        )]
+        limit: i32,
+    ) -> Result<Vec<Change>> {
+        history(ctx, Place::Formation(self.0), limit).await
+    }
+
+    /// The user who added the formation, if known
+    async fn created_by<'a>(&self, ctx: &Context<'a>) -> Result<Option<User>> {
+        let loader = ctx.data_unchecked::<DataLoader<DbLoader>>();
+
+        let creator = loader.load_one(FormationId(self.0)).await?.and_then(|formation| formation.created_by);
+
+        user(ctx, creator).await
+    }
+}
+
+pub struct Climber(i32);
+
+#[Object]
+impl Climber {
+    async fn id(&self) -> &i32 {
+        &self.0
+    }
+
+    async fn first_name<'a>(&self, ctx: &Context<'a>) -> Result<Option<String>> {
+        let loader = ctx.data_unchecked::<DataLoader<DbLoader>>();
+
+        Ok(loader.load_one(ClimberId(self.0)).await?.map(|climber| climber.first_name))
+    }
+
+    async fn last_name<'a>(&self, ctx: &Context<'a>) -> Result<Option<String>> {
+        let loader = ctx.data_unchecked::<DataLoader<DbLoader>>();
+
+        Ok(loader.load_one(ClimberId(self.0)).await?.map(|climber| climber.last_name))
+    }
+
+    async fn ascents<'a>(&self, ctx: &Context<'a>) -> Result<Vec<Ascent>> {
+        let loader = ctx.data_unchecked::<DataLoader<DbLoader>>();
+
+        let data = loader.load_one(ClimberAscentsOf(self.0)).await?.unwrap_or_default();
+
+        Ok(data.into_iter().map(Ascent).collect())
+    }
+
+    /// The user who added the climber, if known
+    async fn created_by<'a>(&self, ctx: &Context<'a>) -> Result<Option<User>> {
+        let loader = ctx.data_unchecked::<DataLoader<DbLoader>>();
+
+        let creator = loader.load_one(ClimberId(self.0)).await?.and_then(|climber| climber.created_by);
+
+        user(ctx, creator).await
+    }
+}
+
+pub struct Ascent(i32);
+
+#[Object]
+impl Ascent {
+    async fn id(&self) -> &i32 {
+        &self.0
+    }
+
+    async fn climb<'a>(&self, ctx: &Context<'a>) -> Result<Option<Climb>> {
+        let loader = ctx.data_unchecked::<DataLoader<DbLoader>>();
+
+        Ok(loader.load_one(AscentId(self.0)).await?.map(|ascent| Climb(ascent.climb_id)))
+    }
+
+    async fn date<'a>(&self, ctx: &Context<'a>) -> Result<Option<DateRange>> {
+        let loader = ctx.data_unchecked::<DataLoader<DbLoader>>();
+
+        Ok(loader.load_one(AscentId(self.0)).await?.and_then(|row| row.ascent_date).map(DateRange::from))
+    }
+
+    async fn party<'a>(&self, ctx: &Context<'a>) -> Result<Vec<Climber>> {
+        let loader = ctx.data_unchecked::<DataLoader<DbLoader>>();
+
+        let data = loader.load_one(AscentPartyOf(self.0)).await?.unwrap_or_default();
+
+        Ok(data.into_iter().map(Climber).collect())
+    }
+
+    async fn grades<'a>(&self, ctx: &Context<'a>) -> Result<Vec<Grade>> {
+        let loader = ctx.data_unchecked::<DataLoader<DbLoader>>();
+
+        let data = loader.load_one(AscentGradesOf(self.0)).await?.unwrap_or_default();
+
+        Ok(data.into_iter().filter_map(Grade::from_row).collect())
+    }
+
+    /// The user who added the ascent, if known
+    async fn created_by<'a>(&self, ctx: &Context<'a>) -> Result<Option<User>> {
+        let loader = ctx.data_unchecked::<DataLoader<DbLoader>>();
+
+        let creator = loader.load_one(AscentId(self.0)).await?.and_then(|ascent| ascent.created_by);
+
+        user(ctx, creator).await
+    }
+}
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    async fn areas<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(
+            desc = "Parent area id"
+        )]
+        area_id: Option<i32>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> FieldResult<connection::Connection<i32, Area>> {
+        let db = ctx.data_unchecked::<Db>();
+
+        paginate_query(db, after, before, first, last, Area, move |conn, page| {
+            Ok(AreaRepo::new(conn).list(area_id, page)?)
+        })
+        .await
+    }
+
+    async fn area<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(
+            desc = "Returns the area with the given id"
+        )]
+        id: i32,
+    ) -> Result<Area> {
+        let db = ctx.data_unchecked::<Db>();
+
+        db.run(move |conn| Ok(Area(AreaRepo::new(conn).get(id)?.id))).await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn climbs<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(
+            desc = "Parent area id"
+        )]
+        area_id: Option<i32>,
+        #[graphql(
+            desc = "Parent formation id"
+        )]
+        formation_id: Option<i32>,
+        #[graphql(
+            desc = "Criteria the climbs must all meet"
+        )]
+        filter: Option<ClimbFilter>,
+        #[graphql(
+            desc = "Order of the climbs, by ascending id by default"
+        )]
+        order: Option<ClimbOrder>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> FieldResult<connection::Connection<i32, Climb>> {
+        let db = ctx.data_unchecked::<Db>();
+
+        let filter = climb_db::filter::ClimbFilter {
+            area_id,
+            formation_id,
+            ..filter.unwrap_or_default().parse()?
+        };
+
+        let order = order.map(climb_db::filter::ClimbOrder::from).unwrap_or_default();
+
+        paginate_query(db, after, before, first, last, Climb, move |conn, page| {
+            Ok(ClimbRepo::new(conn).list_ordered(&filter, order, page)?)
+        })
+        .await
+    }
+
+    async fn climb<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(
+            desc = "Returns climb with given id"
+        )]
+        id: i32,
+    ) -> Result<Climb> {
+        let db = ctx.data_unchecked::<Db>();
+
+        db.run(move |conn| Ok(Climb(ClimbRepo::new(conn).get(id)?.id))).await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn formations<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(
+            desc = "Parent area id"
+        )]
+        area_id: Option<i32>,
+        #[graphql(
+            desc = "Parent formation id"
+        )]
+        formation_id: Option<i32>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> FieldResult<connection::Connection<i32, Formation>> {
+        let db = ctx.data_unchecked::<Db>();
+        let parent = Parent::from_ids(area_id, formation_id).map_err(Error::from)?;
+
+        paginate_query(db, after, before, first, last, Formation, move |conn, page| {
+            Ok(FormationRepo::new(conn).list(parent, page)?)
+        })
+        .await
+    }
+
+    async fn formation<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(
+            desc = "Returns the formation with given id"
+        )]
+        id: i32,
+    ) -> Result<Formation> {
+        let db = ctx.data_unchecked::<Db>();
+
+        db.run(move |conn| Ok(Formation(FormationRepo::new(conn).get(id)?.id))).await
+    }
+
+    /// The user making the request, if authenticated
+    async fn me<'a>(&self, ctx: &Context<'a>) -> Option<User> {
+        ctx.data_opt::<models::User>().cloned().map(User::from)
+    }
+
+    /// Keys of the types of description areas, formations and climbs may have
+    async fn description_types<'a>(&self, ctx: &Context<'a>) -> Result<Vec<String>> {
+        let db = ctx.data_unchecked::<Db>();
+
+        db.run(move |conn| Ok(DescriptionRepo::new(conn).types()?)).await
+    }
+
+    async fn search<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(
+            desc = "Words to search for, supporting quoted phrases, `or` and `-` exclusions"
+        )]
+        query: String,
+        #[graphql(
+            desc = "Kinds of results to search for, every kind by default"
+        )]
+        kinds: Option<Vec<SearchKind>>,
+        #[graphql(
+            desc = "Maximum number of results, at most 100",
+            default = 20
+        )]
+        limit: i32,
+    ) -> Result<Vec<SearchResult>> {
+        let kinds: Vec<climb_db::search::SearchKind> = match kinds {
+            Some(kinds) => kinds.into_iter().map(Into::into).collect(),
+            None => climb_db::search::SearchKind::ALL.to_vec(),
+        };
+
+        let db = ctx.data_unchecked::<Db>();
+
+        db.run(move |conn| {
+            let hits = SearchRepo::new(conn).search(&query, &kinds, limit.into())?;
+
+            Ok(hits.into_iter().filter_map(SearchResult::from_hit).collect())
+        })
+        .await
+    }
+
+    async fn formations_within<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(
+            desc = "Bounding box to find formations within"
+        )]
+        bbox: BoundingBox,
+    ) -> Result<Vec<Formation>> {
+        if bbox.south > bbox.north {
+            return Err(Error::InvalidInput {
+                message: "The south edge must not be north of the north edge".to_string(),
+                constraint: None,
+            });
+        }
+
+        let db = ctx.data_unchecked::<Db>();
+
+        db.run(move |conn| {
+            let south_west = GeoPoint::new(bbox.west, bbox.south)?;
+            let north_east = GeoPoint::new(bbox.east, bbox.north)?;
+
+            let ids = FormationRepo::new(conn).within(&south_west, &north_east)?;
+
+            Ok(ids.into_iter().map(Formation).collect())
+        })
+        .await
+    }
+
+    async fn formations_near<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(
+            desc = "Point to find formations near"
+        )]
+        point: Coordinate,
+        #[graphql(
+            desc = "Distance from the point, in meters"
+        )]
+        radius_meters: f64,
+    ) -> Result<Vec<FormationDistance>> {
+        if radius_meters < 0.0 {
+            return Err(Error::InvalidInput {
+                message: "Radius must not be negative".to_string(),
+                constraint: None,
+            });
+        }
+
+        let db = ctx.data_unchecked::<Db>();
+
+        db.run(move |conn| {
+            let rows = FormationRepo::new(conn).near(&GeoPoint::try_from(point)?, radius_meters)?;
+
+            Ok(rows.into_iter().map(FormationDistance::from).collect())
+        })
+        .await
+    }
+
+    async fn nearest_formations<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(
+            desc = "Point to find formations nearest to"
+        )]
+        point: Coordinate,
+        #[graphql(
+            desc = "Maximum number of formations to return",
+            default = 10
+        )]
+        limit: i32,
+    ) -> Result<Vec<FormationDistance>> {
+        if limit < 0 {
+            return Err(Error::InvalidInput {
+                message: "Limit must not be negative".to_string(),
+                constraint: None,
+            });
+        }
+
+        let db = ctx.data_unchecked::<Db>();
+
+        db.run(move |conn| {
+            let rows = FormationRepo::new(conn).nearest(&GeoPoint::try_from(point)?, limit.into())?;
+
+            Ok(rows.into_iter().map(FormationDistance::from).collect())
+        })
+        .await
+    }
+
+    async fn areas_containing<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(
+            desc = "Point to find the areas of, innermost first"
+        )]
+        point: Coordinate,
+    ) -> Result<Vec<Area>> {
+        let db = ctx.data_unchecked::<Db>();
+
+        db.run(move |conn| {
+            let ids = AreaRepo::new(conn).containing(&GeoPoint::try_from(point)?)?;
+
+            Ok(ids.into_iter().map(Area).collect())
+        })
+        .await
+    }
+
+    async fn formations_outside_areas<'a>(&self, ctx: &Context<'a>) -> Result<Vec<FormationOutsideArea>> {
+        let db = ctx.data_unchecked::<Db>();
+
+        db.run(move |conn| {
+            let rows = FormationRepo::new(conn).outside_areas()?;
+
+            Ok(rows
+                .into_iter()
+                .map(|row| FormationOutsideArea {
+                    formation: Formation(row.formation_id),
+                    area: Area(row.area_id),
+                })
+                .collect())
+        })
+        .await
+    }
+
+    async fn climbers<'a>(
+        &self,
+        ctx: &Context<'a>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> FieldResult<connection::Connection<i32, Climber>> {
+        let db = ctx.data_unchecked::<Db>();
+
+        paginate_query(db, after, before, first, last, Climber, move |conn, page| {
+            Ok(ClimberRepo::new(conn).list(page)?)
+        })
+        .await
+    }
+
+    async fn climber<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(
+            desc = "Returns the climber with given id"
+        )]
+        id: i32,
+    ) -> Result<Climber> {
+        let db = ctx.data_unchecked::<Db>();
+
+        db.run(move |conn| Ok(Climber(ClimberRepo::new(conn).get(id)?.id))).await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn ascents<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(
+            desc = "Climb id"
+        )]
+        climb_id: Option<i32>,
+        #[graphql(
+            desc = "Id of a climber in the party"
+        )]
+        climber_id: Option<i32>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> FieldResult<connection::Connection<i32, Ascent>> {
+        let db = ctx.data_unchecked::<Db>();
+
+        paginate_query(db, after, before, first, last, Ascent, move |conn, page| {
+            Ok(AscentRepo::new(conn).list(climb_id, climber_id, page)?)
+        })
+        .await
+    }
+
+    async fn ascent<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(
+            desc = "Returns the ascent with given id"
+        )]
+        id: i32,
+    ) -> Result<Ascent> {
+        let db = ctx.data_unchecked::<Db>();
+
+        db.run(move |conn| Ok(Ascent(AscentRepo::new(conn).get(id)?.id))).await
+    }
+}
+
+pub struct MutationRoot;
 
 #[Object]
 impl MutationRoot {
+    #[graphql(guard = "RoleGuard(Role::Contributor)")]
     async fn add_area<'a>(
         &self,
         ctx: &Context<'a>,
-        names: Option<Vec<String>>,
-        super_area_id: Option<i32>,
-    ) -> FieldResult<Area> {
-        let pool = ctx.data_unchecked::<Pool<ConnectionManager<PgConnection>>>();
+        names: Option<Vec<String>>,
+        descriptions: Option<Vec<KVPair>>,
+        super_area_id: Option<i32>,
+    ) -> Result<Area> {
+        act(ctx, move |conn| {
+            let input = AreaInput {
+                names: names.unwrap_or_default(),
+                descriptions: descriptions.into_iter().flatten().map(Description::from).collect(),
+                super_area_id,
+            };
+
+            Ok(Area(AreaRepo::new(conn).create(input)?))
+        })
+        .await
+    }
+
+    #[graphql(guard = "RoleGuard(Role::Contributor)")]
+    async fn add_area_name<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(
+            desc = "Area id to add name to"
+        )]
+        id: i32,
+        #[graphql(
+            desc = "Name which to add"
+        )]
+        name: String,
+        #[graphql(
+            desc = "Kind of the name, by default primary if the area has no primary name and alias otherwise"
+        )]
+        kind: Option<NameKind>,
+        #[graphql(
+            desc = "BCP 47 language tag of the name, required for translations"
+        )]
+        language: Option<String>,
+    ) -> Result<Area> {
+        act(ctx, move |conn| {
+            AreaRepo::new(conn).add_name(id, &name, kind.map(Into::into), language.as_deref())?;
+
+            Ok(Area(id))
+        })
+        .await
+    }
+
+    #[graphql(guard = "RoleGuard(Role::Contributor)")]
+    async fn remove_area_name<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(
+            desc = "Area id to remove name from"
+        )]
+        id: i32,
+        #[graphql(
+            desc = "Name which to remove"
+        )]
+        name: String
+    ) -> Result<Area> {
+        act(ctx, move |conn| {
+            AreaRepo::new(conn).remove_name(id, &name)?;
+
+            Ok(Area(id))
+        })
+        .await
+    }
+
+    #[graphql(guard = "RoleGuard(Role::Contributor)")]
+    async fn set_area_description<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(
+            desc = "Area id to describe"
+        )]
+        id: i32,
+        #[graphql(
+            desc = "Description type, replacing any description of this type"
+        )]
+        key: String,
+        #[graphql(
+            desc = "Description"
+        )]
+        value: String,
+    ) -> Result<Area> {
+        act(ctx, move |conn| {
+            AreaRepo::new(conn).set_description(id, Description { key, value })?;
+
+            Ok(Area(id))
+        })
+        .await
+    }
+
+    #[graphql(guard = "RoleGuard(Role::Contributor)")]
+    async fn remove_area_description<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(
+            desc = "Area id to remove description from"
+        )]
+        id: i32,
+        #[graphql(
+            desc = "Description type to remove"
+        )]
+        key: String,
+    ) -> Result<Area> {
+        act(ctx, move |conn| {
+            AreaRepo::new(conn).remove_description(id, &key)?;
+
+            Ok(Area(id))
+        })
+        .await
+    }
+
+    #[graphql(guard = "RoleGuard(Role::Contributor)")]
+    async fn set_super_area<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(
+            desc = "Area id to set 'super area' of"
+        )]
+        id: i32,
+        #[graphql(
+            desc = "Super area id"
+        )]
+        super_area_id: i32
+    ) -> Result<Area> {
+        act(ctx, move |conn| {
+            AreaRepo::new(conn).move_to(id, Some(super_area_id))?;
+
+            Ok(Area(id))
+        })
+        .await
+    }
+    #[graphql(guard = "RoleGuard(Role::Contributor)")]
+    async fn clear_super_area<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(
+            desc = "Area id to clear 'super area' of"
+        )]
+        id: i32,
+    ) -> Result<Area> {
+        act(ctx, move |conn| {
+            AreaRepo::new(conn).move_to(id, None)?;
 
-        let mut conn = pool.get().map_err(|e| e.to_string())?;
+            Ok(Area(id))
+        })
+        .await
+    }
 
-        conn.transaction(|conn| {
-            use climb_db::models::NewArea;
-            use climb_db::schema::areas;
+    #[graphql(guard = "RoleGuard(Role::Contributor)")]
+    async fn set_area_boundary<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(
+            desc = "Area id to set boundary of"
+        )]
+        id: i32,
+        #[graphql(
+            desc = "GeoJSON Polygon or MultiPolygon, in WGS 84 longitude/latitude"
+        )]
+        boundary: GeoJson,
+    ) -> Result<Area> {
+        if !matches!(boundary.geometry_type(), Some("Polygon" | "MultiPolygon")) {
+            return Err(Error::InvalidInput {
+                message: "Boundary must be a GeoJSON Polygon or MultiPolygon".to_string(),
+                constraint: None,
+            });
+        }
 
-            let new_area = NewArea { names: vec!() };
-            let area_id = diesel::insert_into(areas::table)
-                .values(&new_area)
-                .returning(areas::id)
-                .get_result::<i32>(conn)
-                .map_err(|e| e.to_string())?;
+        act(ctx, move |conn| {
+            AreaRepo::new(conn).set_boundary(id, Some(&boundary.0.to_string()))?;
 
-            if let Some(names) = names {
-                use crate::queries::set_area_names;
-                set_area_names(conn, area_id, names)?;
-            }
+            Ok(Area(id))
+        })
+        .await
+    }
 
-            if let Some(super_area_id) = super_area_id {
-                use crate::queries::set_area_super_area_id;
-                set_area_super_area_id(conn, area_id, super_area_id)?;
-            }
+    #[graphql(guard = "RoleGuard(Role::Contributor)")]
+    async fn clear_area_boundary<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(
+            desc = "Area id to clear boundary of"
+        )]
+        id: i32,
+    ) -> Result<Area> {
+        act(ctx, move |conn| {
+            AreaRepo::new(conn).set_boundary(id, None)?;
 
-            Ok(Area(area_id))
+            Ok(Area(id))
         })
+        .await
     }
 
-    async fn add_area_name<'a>(
+    #[graphql(guard = "RoleGuard(Role::Moderator)")]
+    async fn remove_area<'a>(
         &self,
         ctx: &Context<'a>,
         #[graphql(
-            desc = "Area id to add name to"
+            desc = "Removes area with given id"
+        )]
+        id: i32,
+    ) -> Result<Area> {
+        act(ctx, move |conn| {
+            AreaRepo::new(conn).delete(id)?;
+
+            Ok(Area(id))
+        })
+        .await
+    }
+
+    #[graphql(guard = "RoleGuard(Role::Contributor)")]
+    async fn add_climb<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(
+            desc = "Names to associate with the climb"
+        )]
+        names: Option<Vec<String>>,
+        #[graphql(
+            desc = "Descriptions to associate with the climb"
+        )]
+        descriptions: Option<Vec<KVPair>>,
+        #[graphql(
+            desc = "Grades to associate with the climb"
+        )]
+        grades: Option<Vec<Grade>>,
+        #[graphql(
+            desc = "Parent area id of the climb"
+        )]
+        area_id: Option<i32>,
+        #[graphql(
+            desc = "Parent formation id of the climb"
+        )]
+        formation_id: Option<i32>,
+    ) -> Result<Climb> {
+        act(ctx, move |conn| {
+            let input = ClimbInput {
+                names: names.unwrap_or_default(),
+                descriptions: descriptions.into_iter().flatten().map(Description::from).collect(),
+                grades: grades.iter().flatten().map(Grade::parse).collect::<Result<_>>()?,
+                parent: Parent::from_ids(area_id, formation_id)?,
+            };
+
+            Ok(Climb(ClimbRepo::new(conn).create(input)?))
+        })
+        .await
+    }
+
+    #[graphql(guard = "RoleGuard(Role::Contributor)")]
+    async fn add_climb_name<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(
+            desc = "Climb id to add name to"
         )]
         id: i32,
         #[graphql(
             desc = "Name which to add"
         )]
+        name: String,
+        #[graphql(
+            desc = "Kind of the name, by default primary if the climb has no primary name and alias otherwise"
+        )]
+        kind: Option<NameKind>,
+        #[graphql(
+            desc = "BCP 47 language tag of the name, required for translations"
+        )]
+        language: Option<String>,
+    ) -> Result<Climb> {
+        act(ctx, move |conn| {
+            ClimbRepo::new(conn).add_name(id, &name, kind.map(Into::into), language.as_deref())?;
+
+            Ok(Climb(id))
+        })
+        .await
+    }
+
+    #[graphql(guard = "RoleGuard(Role::Contributor)")]
+    async fn remove_climb_name<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(
+            desc = "Climb id to remove name from"
+        )]
+        id: i32,
+        #[graphql(
+            desc = "Name which to remove"
+        )]
         name: String
-    ) -> FieldResult<Area> {
-        let pool = ctx.data_unchecked::<Pool<ConnectionManager<PgConnection>>>();
-        let mut conn = pool.get().map_err(|e| e.to_string())?;
+    ) -> Result<Climb> {
+        act(ctx, move |conn| {
+            ClimbRepo::new(conn).remove_name(id, &name)?;
+
+            Ok(Climb(id))
+        })
+        .await
+    }
+
+    #[graphql(guard = "RoleGuard(Role::Contributor)")]
+    async fn set_climb_description<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(
+            desc = "Climb id to describe"
+        )]
+        id: i32,
+        #[graphql(
+            desc = "Description type, replacing any description of this type"
+        )]
+        key: String,
+        #[graphql(
+            desc = "Description"
+        )]
+        value: String,
+    ) -> Result<Climb> {
+        act(ctx, move |conn| {
+            ClimbRepo::new(conn).set_description(id, Description { key, value })?;
+
+            Ok(Climb(id))
+        })
+        .await
+    }
+
+    #[graphql(guard = "RoleGuard(Role::Contributor)")]
+    async fn remove_climb_description<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(
+            desc = "Climb id to remove description from"
+        )]
+        id: i32,
+        #[graphql(
+            desc = "Description type to remove"
+        )]
+        key: String,
+    ) -> Result<Climb> {
+        act(ctx, move |conn| {
+            ClimbRepo::new(conn).remove_description(id, &key)?;
+
+            Ok(Climb(id))
+        })
+        .await
+    }
+
+    #[graphql(guard = "RoleGuard(Role::Contributor)")]
+    async fn add_climb_grade<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(
+            desc = "Climb id to add grade to"
+        )]
+        id: i32,
+        #[graphql(
+            desc = "Grade which to add"
+        )]
+        grade: Grade
+    ) -> Result<Climb> {
+        act(ctx, move |conn| {
+            ClimbRepo::new(conn).add_grade(id, &grade.parse()?)?;
+
+            Ok(Climb(id))
+        })
+        .await
+    }
+
+    #[graphql(guard = "RoleGuard(Role::Contributor)")]
+    async fn remove_climb_grade<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(desc = "Climb id to remove grade from")] id: i32,
+        #[graphql(desc = "Grade to remove")] grade: Grade,
+    ) -> Result<Climb> {
+        act(ctx, move |conn| {
+            ClimbRepo::new(conn).remove_grade(id, &grade.parse()?)?;
+
+            Ok(Climb(id))
+        })
+        .await
+    }
+
+    #[graphql(guard = "RoleGuard(Role::Moderator)")]
+    async fn set_grade_conversion<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(desc = "Grade to convert")] grade: Grade,
+        #[graphql(desc = "Difficulty of the grade on the common scale, following Ewbank")] difficulty: f64,
+    ) -> Result<Grade> {
+        act(ctx, move |conn| {
+            let parsed = grade.parse()?;
+
+            GradeRepo::new(conn).set_conversion(&parsed, difficulty)?;
+
+            Ok(Grade { grade_type: grade.grade_type, value: parsed.value })
+        })
+        .await
+    }
+
+    #[graphql(guard = "RoleGuard(Role::Moderator)")]
+    async fn remove_grade_conversion<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(desc = "Grade whose conversion to remove")] grade: Grade,
+    ) -> Result<Grade> {
+        act(ctx, move |conn| {
+            let parsed = grade.parse()?;
+
+            GradeRepo::new(conn).remove_conversion(&parsed)?;
+
+            Ok(Grade { grade_type: grade.grade_type, value: parsed.value })
+        })
+        .await
+    }
+
+    /// Adds a type of description areas, formations and climbs may have, returning its key
+    #[graphql(guard = "RoleGuard(Role::Moderator)")]
+    async fn add_description_type<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(
+            desc = "Key of the description type, e.g. `approach`"
+        )]
+        key: String,
+    ) -> Result<String> {
+        act(ctx, move |conn| {
+            DescriptionRepo::new(conn).add_type(&key)?;
+
+            Ok(key)
+        })
+        .await
+    }
+
+    /// Reverts a change along with the other changes made with it, returning the changes made by
+    /// reverting. Fails if a changed row has changed again since.
+    #[graphql(guard = "RoleGuard(Role::Moderator)")]
+    async fn revert_change<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(
+            desc = "Id of the change to revert"
+        )]
+        id: i64,
+    ) -> Result<Vec<Change>> {
+        act(ctx, move |conn| {
+            let changes = ChangeRepo::new(conn).revert(id)?;
+
+            Ok(changes.into_iter().map(Change::from).collect())
+        })
+        .await
+    }
+
+    #[graphql(guard = "RoleGuard(Role::Moderator)")]
+    async fn remove_climb<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(
+            desc = "Removes climb with given id"
+        )]
+        id: i32,
+    ) -> Result<Climb> {
+        act(ctx, move |conn| {
+            ClimbRepo::new(conn).delete(id)?;
+
+            Ok(Climb(id))
+        })
+        .await
+    }
+
+    #[graphql(guard = "RoleGuard(Role::Contributor)")]
+    async fn add_climb_variation<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(
+            desc = "Climb id of the root climb"
+        )]
+        root_id: i32,
+        #[graphql(
+            desc = "Climb id of the variation"
+        )]
+        variation_id: i32,
+    ) -> Result<Climb> {
+        act(ctx, move |conn| {
+            ClimbRepo::new(conn).add_variation(root_id, variation_id)?;
+
+            Ok(Climb(root_id))
+        })
+        .await
+    }
+
+    #[graphql(guard = "RoleGuard(Role::Contributor)")]
+    async fn remove_climb_variation<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(
+            desc = "Climb id of the root climb"
+        )]
+        root_id: i32,
+        #[graphql(
+            desc = "Climb id of the variation"
+        )]
+        variation_id: i32,
+    ) -> Result<Climb> {
+        act(ctx, move |conn| {
+            ClimbRepo::new(conn).remove_variation(root_id, variation_id)?;
+
+            Ok(Climb(root_id))
+        })
+        .await
+    }
+
+    #[graphql(guard = "RoleGuard(Role::Contributor)")]
+    async fn add_formation<'a>(
+        &self,
+        ctx: &Context<'a>,
+        names: Option<Vec<String>>,
+        descriptions: Option<Vec<KVPair>>,
+        area_id: Option<i32>,
+        super_formation_id: Option<i32>,
+        location: Option<Coordinate>,
+    ) -> Result<Formation> {
+        act(ctx, move |conn| {
+            let input = FormationInput {
+                names: names.unwrap_or_default(),
+                descriptions: descriptions.into_iter().flatten().map(Description::from).collect(),
+                location: location.map(GeoPoint::try_from).transpose()?,
+                parent: Parent::from_ids(area_id, super_formation_id)?,
+            };
+
+            Ok(Formation(FormationRepo::new(conn).create(input)?))
+        })
+        .await
+    }
+
+    #[graphql(guard = "RoleGuard(Role::Contributor)")]
+    async fn add_formation_name<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(
+            desc = "Formation id to add name to"
+        )]
+        id: i32,
+        #[graphql(
+            desc = "Name which to add"
+        )]
+        name: String,
+        #[graphql(
+            desc = "Kind of the name, by default primary if the formation has no primary name and alias otherwise"
+        )]
+        kind: Option<NameKind>,
+        #[graphql(
+            desc = "BCP 47 language tag of the name, required for translations"
+        )]
+        language: Option<String>,
+    ) -> Result<Formation> {
+        act(ctx, move |conn| {
+            FormationRepo::new(conn).add_name(id, &name, kind.map(Into::into), language.as_deref())?;
 
-        use climb_db::schema::areas;
-        use diesel::dsl::sql;
-        use diesel::sql_types::{Array,Nullable,Text};
+            Ok(Formation(id))
+        })
+        .await
+    }
+
+    #[graphql(guard = "RoleGuard(Role::Contributor)")]
+    async fn remove_formation_name<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(
+            desc = "Formation id to remove name from"
+        )]
+        id: i32,
+        #[graphql(
+            desc = "Name which to remove"
+        )]
+        name: String
+    ) -> Result<Formation> {
+        act(ctx, move |conn| {
+            FormationRepo::new(conn).remove_name(id, &name)?;
 
-        diesel::update(areas::table)
-            .filter(areas::id.eq(id))
-            .set(areas::names.eq(sql::<Array<Nullable<Text>>>(
-                &format!("array_append(names, '{}')", name)
-            )))
-            .execute(&mut conn)
-            .map_err(|e| e.to_string())?;
+            Ok(Formation(id))
+        })
+        .await
+    }
 
-        let area_id = areas::table
-            .find(id)
-            .select(areas::id)
-            .first(&mut conn)
-            .map_err(|e| e.to_string())?;
+    #[graphql(guard = "RoleGuard(Role::Contributor)")]
+    async fn set_formation_description<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(
+            desc = "Formation id to describe"
+        )]
+        id: i32,
+        #[graphql(
+            desc = "Description type, replacing any description of this type"
+        )]
+        key: String,
+        #[graphql(
+            desc = "Description"
+        )]
+        value: String,
+    ) -> Result<Formation> {
+        act(ctx, move |conn| {
+            FormationRepo::new(conn).set_description(id, Description { key, value })?;
 
-        Ok(Area(area_id))
+            Ok(Formation(id))
+        })
+        .await
     }
 
-    async fn remove_area_name<'a>(
+    #[graphql(guard = "RoleGuard(Role::Contributor)")]
+    async fn remove_formation_description<'a>(
         &self,
         ctx: &Context<'a>,
         #[graphql(
-            desc = "Area id to remove name from"
+            desc = "Formation id to remove description from"
         )]
         id: i32,
         #[graphql(
-            desc = "Name which to remove"
+            desc = "Description type to remove"
         )]
-        name: String
-    ) -> FieldResult<Area> {
-        let pool = ctx.data_unchecked::<Pool<ConnectionManager<PgConnection>>>();
-        let mut conn = pool.get().map_err(|e| e.to_string())?;
-
-        use climb_db::schema::areas;
-        use diesel::dsl::sql;
-        use diesel::sql_types::{Array,Nullable,Text};
-
-        let area_id = diesel::update(areas::table)
-            .filter(areas::id.eq(id))
-            .set(areas::names.eq(sql::<Array<Nullable<Text>>>(
-                &format!("array_remove(names, '{}')", name)
-            )))
-            .returning(areas::id)
-            .get_result(&mut conn)
-            .map_err(|e| e.to_string())?;
+        key: String,
+    ) -> Result<Formation> {
+        act(ctx, move |conn| {
+            FormationRepo::new(conn).remove_description(id, &key)?;
 
-        Ok(Area(area_id))
+            Ok(Formation(id))
+        })
+        .await
     }
 
-    async fn set_super_area<'a>(
+    #[graphql(guard = "RoleGuard(Role::Contributor)")]
+    async fn set_formation_location<'a>(
         &self,
         ctx: &Context<'a>,
         #[graphql(
-            desc = "Area id to set 'super area' of"
+            desc = "Formation id to set location of"
         )]
         id: i32,
         #[graphql(
-            desc = "Super area id"
+            desc = "Location of the formation"
         )]
-        super_area_id: i32
-    ) -> FieldResult<Area> {
-        let pool = ctx.data_unchecked::<Pool<ConnectionManager<PgConnection>>>();
-        let mut conn = pool.get().map_err(|e| e.to_string())?;
-
-        use climb_db::schema::area_belongs_to;
-        use diesel::upsert::excluded;
-
-        let new_area_belongs_to = models::NewAreaBelongsTo {
-            area_id: id,
-            super_area_id
-        };
-
-        diesel::insert_into(area_belongs_to::table)
-            .values(new_area_belongs_to)
-            .on_conflict(area_belongs_to::area_id)
-            .do_update()
-            .set(area_belongs_to::super_area_id.eq(excluded(area_belongs_to::super_area_id)))
-            .execute(&mut conn)
-            .map_err(|e| e.to_string())?;
-
-        use climb_db::schema::areas;
-
-        let area_id = areas::table
-            .find(id)
-            .select(areas::id)
-            .first(&mut conn)
-            .map_err(|e| e.to_string())?;
+        location: Coordinate
+    ) -> Result<Formation> {
+        act(ctx, move |conn| {
+            FormationRepo::new(conn).set_location(id, Some(GeoPoint::try_from(location)?))?;
 
-        Ok(Area(area_id))
+            Ok(Formation(id))
+        })
+        .await
     }
-    async fn clear_super_area<'a>(
+
+    #[graphql(guard = "RoleGuard(Role::Contributor)")]
+    async fn clear_formation_location<'a>(
         &self,
         ctx: &Context<'a>,
         #[graphql(
-            desc = "Area id to clear 'super area' of"
+            desc = "Formation id to set location of"
         )]
         id: i32,
-    ) -> FieldResult<Area> {
-        let pool = ctx.data_unchecked::<Pool<ConnectionManager<PgConnection>>>();
-        let mut conn = pool.get().map_err(|e| e.to_string())?;
-
-        use climb_db::schema::area_belongs_to;
+    ) -> Result<Formation> {
+        act(ctx, move |conn| {
+            FormationRepo::new(conn).set_location(id, None)?;
 
-        let area_id = diesel::delete(
-            area_belongs_to::table
-                .filter(area_belongs_to::area_id.eq(id)))
-            .returning(area_belongs_to::area_id)
-            .get_result(&mut conn)
-            .map_err(|e| e.to_string())?;
-
-        Ok(Area(area_id))
+            Ok(Formation(id))
+        })
+        .await
     }
 
-    async fn remove_area<'a>(
+    #[graphql(guard = "RoleGuard(Role::Contributor)")]
+    async fn set_formation_area<'a>(
         &self,
         ctx: &Context<'a>,
         #[graphql(
-            desc = "Removes area with given id"
+            desc = "Formation id to area of"
         )]
         id: i32,
-    ) -> FieldResult<Area> {
-        let pool = ctx.data_unchecked::<Pool<ConnectionManager<PgConnection>>>();
-        let mut conn = pool.get().map_err(|e| e.to_string())?;
-
-        use climb_db::schema::areas;
-
-        let area_id = diesel::delete(areas::table.filter(areas::id.eq(id)))
-            .returning(areas::id)
-            .get_result(&mut conn)
-            .map_err(|e| e.to_string())?;
+        #[graphql(
+            desc = "Area id"
+        )]
+        area_id: i32,
+    ) -> Result<Formation> {
+        act(ctx, move |conn| {
+            FormationRepo::new(conn).move_to(id, Some(Parent::Area(area_id)))?;
 
-        Ok(Area(area_id))
+            Ok(Formation(id))
+        })
+        .await
     }
 
-    async fn add_climb<'a>(
+    #[graphql(guard = "RoleGuard(Role::Contributor)")]
+    async fn set_formation_super_formation<'a>(
         &self,
         ctx: &Context<'a>,
         #[graphql(
-            desc = "Names to associate with the climb"
-        )]
-        names: Option<Vec<String>>,
-        #[graphql(
-            desc = "Descriptions to associate with the climb"
-        )]
-        descriptions: Option<Vec<KVPair>>,
-        #[graphql(
-            desc = "Grades to associate with the climb"
-        )]
-        grades: Option<Vec<Grade>>,
-        #[graphql(
-            desc = "Parent area id of the climb"
+            desc = "Formation id to super-formation of"
         )]
-        area_id: Option<i32>,
+        id: i32,
         #[graphql(
-            desc = "Parent formation id of the climb"
+            desc = "Super formation id"
         )]
-        formation_id: Option<i32>,
-    ) -> FieldResult<Climb> {
-        let pool = ctx.data_unchecked::<Pool<ConnectionManager<PgConnection>>>();
-
-        let mut conn = pool.get().map_err(|e| e.to_string())?;
-
-        conn.transaction(|conn| {
-            use climb_db::models::NewClimb;
-            use climb_db::schema::climbs;
-
-            let new_climb = NewClimb {
-                names: names.map_or_else(std::vec::Vec::new, |vec| vec.into_iter().map(Some).collect()),
-            };
-
-            let climb_id = diesel::insert_into(climbs::table)
-                .values(&new_climb)
-                .returning(climbs::id)
-                .get_result::<i32>(conn)?;
-
-            if let Some(descriptions) = descriptions {
-                use crate::queries::set_climb_descriptions;
-                set_climb_descriptions(conn, climb_id, descriptions)?;
-            }
-
-            if let Some(grades) = grades {
-                use crate::queries::set_climb_grades;
-                set_climb_grades(conn, climb_id, grades)?;
-            }
-
-            if let Some(area_id) = area_id {
-                use crate::queries::set_climb_area_id;
-                set_climb_area_id(conn, climb_id, area_id)?;
-            }
-
-            if let Some(formation_id) = formation_id {
-                use crate::queries::set_climb_formation_id;
-                set_climb_formation_id(conn, climb_id, formation_id)?;
-            }
+        super_formation_id: i32,
+    ) -> Result<Formation> {
+        act(ctx, move |conn| {
+            FormationRepo::new(conn).move_to(id, Some(Parent::Formation(super_formation_id)))?;
 
-            Ok(Climb(climb_id))
+            Ok(Formation(id))
         })
+        .await
     }
 
-    async fn add_climb_name<'a>(
+    #[graphql(guard = "RoleGuard(Role::Contributor)")]
+    async fn clear_formation_area<'a>(
         &self,
         ctx: &Context<'a>,
         #[graphql(
-            desc = "Climb id to add name to"
+            desc = "Formation id to area of"
         )]
         id: i32,
-        #[graphql(
-            desc = "Name which to add"
-        )]
-        name: String
-    ) -> FieldResult<Climb> {
-        let pool = ctx.data_unchecked::<Pool<ConnectionManager<PgConnection>>>();
-        let mut conn = pool.get().map_err(|e| e.to_string())?;
-
-        use climb_db::schema::climbs;
-        use diesel::dsl::sql;
-        use diesel::sql_types::{Array,Nullable,Text};
-
-        let _ = diesel::update(climbs::table)
-            .filter(climbs::id.eq(id))
-            .set(climbs::names.eq(sql::<Array<Nullable<Text>>>(
-                &format!("array_append(names, '{}')", name)
-            )))
-            .execute(&mut conn)
-            .map_err(|e| e.to_string())?;
+    ) -> Result<Formation> {
+        act(ctx, move |conn| {
+            FormationRepo::new(conn).move_to(id, None)?;
 
-        Ok(Climb(id))
+            Ok(Formation(id))
+        })
+        .await
     }
 
-    async fn remove_climb_name<'a>(
+    // TODO This same thing as `clear_formation_area`. Is there a common name I can use to avoid
+    // this duplication? Or from an outside view, does it make sense to keep them separate?
+    #[graphql(guard = "RoleGuard(Role::Contributor)")]
+    async fn clear_formation_super_formation<'a>(
         &self,
         ctx: &Context<'a>,
         #[graphql(
-            desc = "Climb id to remove name from"
+            desc = "Formation id to super-formation of"
         )]
         id: i32,
-        #[graphql(
-            desc = "Name which to remove"
-        )]
-        name: String
-    ) -> FieldResult<Climb> {
-        let pool = ctx.data_unchecked::<Pool<ConnectionManager<PgConnection>>>();
-        let mut conn = pool.get().map_err(|e| e.to_string())?;
-
-        use climb_db::schema::climbs;
-        use diesel::dsl::sql;
-        use diesel::sql_types::{Array,Nullable,Text};
+    ) -> Result<Formation> {
+        act(ctx, move |conn| {
+            FormationRepo::new(conn).move_to(id, None)?;
 
-        let _ = diesel::update(climbs::table)
-            .filter(climbs::id.eq(id))
-            .set(climbs::names.eq(sql::<Array<Nullable<Text>>>(
-                &format!("array_remove(names, '{}')", name)
-            )))
-            .execute(&mut conn)
-            .map_err(|e| e.to_string())?;
-
-        Ok(Climb(id))
+            Ok(Formation(id))
+        })
+        .await
     }
 
-    async fn add_climb_grade<'a>(
+    #[graphql(guard = "RoleGuard(Role::Moderator)")]
+    async fn remove_formation<'a>(
         &self,
         ctx: &Context<'a>,
         #[graphql(
-            desc = "Climb id to add grade to"
+            desc = "Removes formation with given id"
         )]
         id: i32,
-        #[graphql(
-            desc = "Grade which to add"
-        )]
-        grade: Grade
-    ) -> FieldResult<Climb> {
-        let pool = ctx.data_unchecked::<Pool<ConnectionManager<PgConnection>>>();
-        let mut conn = pool.get().map_err(|e| e.to_string())?;
-
-        use climb_db::schema::climb_vermin_grades;
-
-        match grade.grade_type {
-            GradeType::Vermin => {
-                use climb_db::models::NewClimbVerminGrade;
+    ) -> Result<Formation> {
+        act(ctx, move |conn| {
+            FormationRepo::new(conn).delete(id)?;
 
-                let grade = verm::Grade::from_str(grade.value.as_str()).map_err(|_|"Failed to parse grade")?;
-                let db_grade = NewClimbVerminGrade { climb_id: id, value: grade.value() as i32 };
-
-                let _ = diesel::insert_into(climb_vermin_grades::table)
-                    .values(db_grade)
-                    .execute(&mut conn)?;
-            },
-        };
-
-        Ok(Climb(id))
+            Ok(Formation(id))
+        })
+        .await
     }
 
-    async fn remove_climb_grade<'a>(
+    #[graphql(guard = "RoleGuard(Role::Contributor)")]
+    async fn add_climber<'a>(
         &self,
         ctx: &Context<'a>,
-        #[graphql(desc = "Climb id to remove grade from")] id: i32,
-        #[graphql(desc = "Grade to remove")] grade: Grade,
-    ) -> FieldResult<Climb> {
-        let pool = ctx.data_unchecked::<Pool<ConnectionManager<PgConnection>>>();
-        let mut conn = pool.get().map_err(|e| e.to_string())?;
-
-        use climb_db::schema::climb_vermin_grades::dsl::*;
-
-        match grade.grade_type {
-            GradeType::Vermin => {
-                let parsed_grade = verm::Grade::from_str(grade.value.as_str())
-                    .map_err(|_| "Failed to parse grade")?;
-
-                let num_deleted = diesel::delete(
-                    climb_vermin_grades
-                        .filter(climb_id.eq(id).and(value.eq(parsed_grade.value() as i32))),
-                )
-                .execute(&mut conn)?;
-
-                if num_deleted == 0 {
-                    return Err("Grade not found for the specified climb".into());
-                }
-            }
-        }
-
-        Ok(Climb(id))
+        #[graphql(
+            desc = "First name of the climber"
+        )]
+        first_name: String,
+        #[graphql(
+            desc = "Last name of the climber"
+        )]
+        last_name: String,
+    ) -> Result<Climber> {
+        act(ctx, move |conn| Ok(Climber(ClimberRepo::new(conn).create(&first_name, &last_name)?))).await
     }
 
-    async fn remove_climb<'a>(
+    #[graphql(guard = "RoleGuard(Role::Contributor)")]
+    async fn update_climber<'a>(
         &self,
         ctx: &Context<'a>,
         #[graphql(
-            desc = "Removes climb with given id"
+            desc = "Climber id to rename"
         )]
         id: i32,
-    ) -> FieldResult<Climb> {
-        let pool = ctx.data_unchecked::<Pool<ConnectionManager<PgConnection>>>();
-        let mut conn = pool.get().map_err(|e| e.to_string())?;
-
-        use climb_db::schema::climbs;
-
-        let _ = diesel::delete(climbs::table.filter(climbs::id.eq(id)))
-            .execute(&mut conn)
-            .map_err(|e| e.to_string())?;
+        #[graphql(
+            desc = "First name of the climber"
+        )]
+        first_name: String,
+        #[graphql(
+            desc = "Last name of the climber"
+        )]
+        last_name: String,
+    ) -> Result<Climber> {
+        act(ctx, move |conn| {
+            ClimberRepo::new(conn).update(id, &first_name, &last_name)?;
 
-        Ok(Climb(id))
+            Ok(Climber(id))
+        })
+        .await
     }
 
-    async fn add_formation<'a>(
+    /// Removes a climber, along with them from the parties of their ascents
+    #[graphql(guard = "RoleGuard(Role::Moderator)")]
+    async fn remove_climber<'a>(
         &self,
         ctx: &Context<'a>,
-        names: Option<Vec<String>>,
-        area_id: Option<i32>,
-        super_formation_id: Option<i32>,
-        location: Option<Coordinate>,
-    ) -> FieldResult<Formation> {
-        let pool = ctx.data_unchecked::<Pool<ConnectionManager<PgConnection>>>();
-
-        let mut conn = pool.get().map_err(|e| e.to_string())?;
-
-        conn.transaction(|conn| {
-            use climb_db::models::NewFormation;
-            use climb_db::schema::formations;
-            use postgis_diesel::types::Point;
-
-            let new_formation = NewFormation {
-                names: names.map_or_else(std::vec::Vec::new, |vec| vec.into_iter().map(Some).collect()),
-                location: location.map(|loc| Point { x: loc.latitude, y: loc.longitude, srid: None }),
-            };
-
-            let formation_id = diesel::insert_into(formations::table)
-                .values(&new_formation)
-                .returning(formations::id)
-                .get_result::<i32>(conn)
-                .map_err(|e| e.to_string())?;
-
-            if let Some(area_id) = area_id {
-                use crate::queries::set_formation_area_id;
-                set_formation_area_id(conn, formation_id, area_id)?;
-            }
-
-            if let Some(super_formation_id) = super_formation_id {
-                use crate::queries::set_formation_super_formation_id;
-                set_formation_super_formation_id(conn, formation_id, super_formation_id)?;
-            }
+        #[graphql(
+            desc = "Climber id to remove"
+        )]
+        id: i32,
+    ) -> Result<Climber> {
+        act(ctx, move |conn| {
+            ClimberRepo::new(conn).delete(id)?;
 
-            Ok(Formation(formation_id))
+            Ok(Climber(id))
         })
+        .await
     }
 
-    async fn add_formation_name<'a>(
+    #[graphql(guard = "RoleGuard(Role::Contributor)")]
+    async fn add_ascent<'a>(
         &self,
         ctx: &Context<'a>,
         #[graphql(
-            desc = "Formation id to add name to"
+            desc = "Climb id which was ascended"
         )]
-        id: i32,
+        climb_id: i32,
         #[graphql(
-            desc = "Name which to add"
+            desc = "Days within which the ascent happened"
         )]
-        name: String
-    ) -> FieldResult<Formation> {
-        let pool = ctx.data_unchecked::<Pool<ConnectionManager<PgConnection>>>();
-        let mut conn = pool.get().map_err(|e| e.to_string())?;
-
-        use climb_db::schema::formations;
-        use diesel::dsl::sql;
-        use diesel::sql_types::{Array,Nullable,Text};
-
-        let _ = diesel::update(formations::table)
-            .filter(formations::id.eq(id))
-            .set(formations::names.eq(sql::<Array<Nullable<Text>>>(
-                &format!("array_append(names, '{}')", name)
-            )))
-            .execute(&mut conn)
-            .map_err(|e| e.to_string())?;
+        date: Option<DateRange>,
+        #[graphql(
+            desc = "Climber ids of the ascent party"
+        )]
+        party: Option<Vec<i32>>,
+        #[graphql(
+            desc = "Grade proposed by the ascent party"
+        )]
+        grade: Option<Grade>,
+    ) -> Result<Ascent> {
+        act(ctx, move |conn| {
+            let input = AscentInput {
+                climb_id,
+                date: date.map(DateRange::into),
+                party: party.unwrap_or_default(),
+                grade: grade.as_ref().map(Grade::parse).transpose()?,
+            };
 
-        Ok(Formation(id))
+            Ok(Ascent(AscentRepo::new(conn).create(input)?))
+        })
+        .await
     }
 
-    async fn remove_formation_name<'a>(
+    /// Changes the date, party or proposed grade of an ascent, leaving those not given unchanged
+    #[graphql(guard = "RoleGuard(Role::Contributor)")]
+    async fn update_ascent<'a>(
         &self,
         ctx: &Context<'a>,
         #[graphql(
-            desc = "Formation id to remove name from"
+            desc = "Ascent id to change"
         )]
         id: i32,
         #[graphql(
-            desc = "Name which to remove"
+            desc = "Days within which the ascent happened, cleared if null"
         )]
-        name: String
-    ) -> FieldResult<Formation> {
-        let pool = ctx.data_unchecked::<Pool<ConnectionManager<PgConnection>>>();
-        let mut conn = pool.get().map_err(|e| e.to_string())?;
-
-        use climb_db::schema::formations;
-        use diesel::dsl::sql;
-        use diesel::sql_types::{Array,Nullable,Text};
+        date: MaybeUndefined<DateRange>,
+        #[graphql(
+            desc = "Climber ids replacing the ascent party"
+        )]
+        party: Option<Vec<i32>>,
+        #[graphql(
+            desc = "Grade proposed by the ascent party, cleared if null"
+        )]
+        grade: MaybeUndefined<Grade>,
+    ) -> Result<Ascent> {
+        act(ctx, move |conn| {
+            let update = AscentUpdate {
+                date: date.map_value(DateRange::into).into(),
+                party,
+                grade: grade.map_value(|grade| grade.parse()).transpose()?.into(),
+            };
 
-        let _ = diesel::update(formations::table)
-            .filter(formations::id.eq(id))
-            .set(formations::names.eq(sql::<Array<Nullable<Text>>>(
-                &format!("array_remove(names, '{}')", name)
-            )))
-            .execute(&mut conn)
-            .map_err(|e| e.to_string())?;
+            AscentRepo::new(conn).update(id, update)?;
 
-        Ok(Formation(id))
+            Ok(Ascent(id))
+        })
+        .await
     }
 
-    async fn set_formation_location<'a>(
+    #[graphql(guard = "RoleGuard(Role::Moderator)")]
+    async fn remove_ascent<'a>(
         &self,
         ctx: &Context<'a>,
         #[graphql(
-            desc = "Formation id to set location of"
+            desc = "Removes ascent with given id"
         )]
         id: i32,
-        #[graphql(
-            desc = "Location of the formation"
-        )]
-        location: Coordinate
-    ) -> FieldResult<Formation> {
-        let pool = ctx.data_unchecked::<Pool<ConnectionManager<PgConnection>>>();
-        let mut conn = pool.get().map_err(|e| e.to_string())?;
+    ) -> Result<Ascent> {
+        act(ctx, move |conn| {
+            AscentRepo::new(conn).delete(id)?;
 
-        use climb_db::schema::formations;
-        use postgis_diesel::types::Point;
-
-        let _ = diesel::update(formations::table)
-            .filter(formations::id.eq(id))
-            .set(formations::location.eq(Point {
-                x: location.latitude,
-                y: location.longitude,
-                srid: None,
-            }))
-            .execute(&mut conn)
-            .map_err(|e| e.to_string())?;
-
-        Ok(Formation(id))
+            Ok(Ascent(id))
+        })
+        .await
     }
 
-    async fn clear_formation_location<'a>(
+    /// Adds a user
+    #[graphql(guard = "RoleGuard(Role::Admin)")]
+    async fn add_user<'a>(
         &self,
         ctx: &Context<'a>,
         #[graphql(
-            desc = "Formation id to set location of"
+            desc = "Unique name of the user"
         )]
-        id: i32,
-    ) -> FieldResult<Formation> {
-        let pool = ctx.data_unchecked::<Pool<ConnectionManager<PgConnection>>>();
-        let mut conn = pool.get().map_err(|e| e.to_string())?;
-
-        use climb_db::schema::formations;
-        use postgis_diesel::types::Point;
-
-        let _ = diesel::update(formations::table)
-            .filter(formations::id.eq(id))
-            .set(formations::location.eq(None::<Point>))
-            .execute(&mut conn)
-            .map_err(|e| e.to_string())?;
-
-        Ok(Formation(id))
+        name: String,
+        #[graphql(
+            desc = "Role of the user",
+            default_with = "UserRole::Reader"
+        )]
+        role: UserRole,
+    ) -> Result<User> {
+        act(ctx, move |conn| Ok(UserRepo::new(conn).create(&name, role.into())?.into())).await
     }
 
-    async fn set_formation_area<'a>(
+    /// Changes the role of a user
+    #[graphql(guard = "RoleGuard(Role::Admin)")]
+    async fn set_user_role<'a>(
         &self,
         ctx: &Context<'a>,
         #[graphql(
-            desc = "Formation id to area of"
+            desc = "Id of the user"
         )]
         id: i32,
         #[graphql(
-            desc = "Area id"
+            desc = "New role of the user"
         )]
-        area_id: i32,
-    ) -> FieldResult<Formation> {
-        let pool = ctx.data_unchecked::<Pool<ConnectionManager<PgConnection>>>();
-        let mut conn = pool.get().map_err(|e| e.to_string())?;
-
-        use climb_db::schema::formation_belongs_to;
-        use diesel::upsert::excluded;
-
-        let new_formation_belongs_to = models::NewFormationBelongsTo {
-            formation_id: id,
-            area_id: Some(area_id),
-            super_formation_id: None,
-        };
-
-        diesel::insert_into(formation_belongs_to::table)
-            .values(new_formation_belongs_to)
-            .on_conflict(formation_belongs_to::formation_id)
-            .do_update()
-            .set((
-                formation_belongs_to::area_id.eq(excluded(formation_belongs_to::area_id)),
-                formation_belongs_to::super_formation_id.eq(None::<i32>),
-            ))
-            .execute(&mut conn)
-            .map_err(|e| e.to_string())?;
-
-        Ok(Formation(id))
+        role: UserRole,
+    ) -> Result<User> {
+        act(ctx, move |conn| Ok(UserRepo::new(conn).set_role(id, role.into())?.into())).await
     }
 
-    async fn set_formation_super_formation<'a>(
+    /// Adds an API token, which is sent as `Authorization: Bearer <token>`
+    #[graphql(guard = "RoleGuard(Role::Reader)")]
+    async fn add_api_token<'a>(
         &self,
         ctx: &Context<'a>,
         #[graphql(
-            desc = "Formation id to super-formation of"
+            desc = "Id of the user the token identifies, by default the user making the request. Adding tokens for other users requires the admin role."
         )]
-        id: i32,
+        user_id: Option<i32>,
         #[graphql(
-            desc = "Super formation id"
+            desc = "Label to tell the token apart from other tokens of the user"
         )]
-        super_formation_id: i32,
-    ) -> FieldResult<Formation> {
-        let pool = ctx.data_unchecked::<Pool<ConnectionManager<PgConnection>>>();
-        let mut conn = pool.get().map_err(|e| e.to_string())?;
-
-        use climb_db::schema::formation_belongs_to;
-        use diesel::upsert::excluded;
+        label: Option<String>,
+        #[graphql(
+            desc = "Time after which the token is no longer valid"
+        )]
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<ApiToken> {
+        let me = ctx.data_unchecked::<models::User>();
+        let user_id = user_id.unwrap_or(me.id);
 
-        let new_formation_belongs_to = models::NewFormationBelongsTo {
-            formation_id: id,
-            area_id: None,
-            super_formation_id: Some(super_formation_id),
-        };
+        if user_id != me.id && role(me) < Role::Admin {
+            return Err(Error::Forbidden("Requires the admin role".to_string()));
+        }
 
-        diesel::insert_into(formation_belongs_to::table)
-            .values(new_formation_belongs_to)
-            .on_conflict(formation_belongs_to::formation_id)
-            .do_update()
-            .set((
-                formation_belongs_to::area_id.eq(excluded(formation_belongs_to::area_id)),
-                formation_belongs_to::super_formation_id.eq(None::<i32>),
-            ))
-            .execute(&mut conn)
-            .map_err(|e| e.to_string())?;
+        act(ctx, move |conn| {
+            let (id, token) = UserRepo::new(conn).add_api_token(user_id, label.as_deref(), expires_at)?;
 
-        Ok(Formation(id))
+            Ok(ApiToken { id, token })
+        })
+        .await
     }
 
-    async fn clear_formation_area<'a>(
+    /// Revokes an API token of the user making the request, returning its id
+    #[graphql(guard = "RoleGuard(Role::Reader)")]
+    async fn revoke_api_token<'a>(
         &self,
         ctx: &Context<'a>,
         #[graphql(
-            desc = "Formation id to area of"
+            desc = "Id of the token"
         )]
         id: i32,
-    ) -> FieldResult<Formation> {
-        let pool = ctx.data_unchecked::<Pool<ConnectionManager<PgConnection>>>();
-        let mut conn = pool.get().map_err(|e| e.to_string())?;
-
-        use climb_db::schema::formation_belongs_to;
+    ) -> Result<i32> {
+        let user_id = ctx.data_unchecked::<models::User>().id;
 
-        let formation_id = diesel::delete(
-            formation_belongs_to::table
-                .filter(formation_belongs_to::formation_id.eq(id)))
-            .returning(formation_belongs_to::formation_id)
-            .get_result(&mut conn)
-            .map_err(|e| e.to_string())?;
+        act(ctx, move |conn| {
+            UserRepo::new(conn).revoke_api_token(user_id, id)?;
 
-        Ok(Formation(formation_id))
+            Ok(id)
+        })
+        .await
     }
+}
 
-    // TODO This same thing as `clear_formation_area`. Is there a common name I can use to avoid
-    // this duplication? Or from an outside view, does it make sense to keep them separate?
-    async fn clear_formation_super_formation<'a>(
+pub struct SubscriptionRoot;
+
+#[Subscription]
+impl SubscriptionRoot {
+    /// Notifies changes of an area, its names, descriptions or links, once per transaction
+    async fn area_changed<'a>(
         &self,
         ctx: &Context<'a>,
         #[graphql(
-            desc = "Formation id to super-formation of"
+            desc = "Area id"
         )]
         id: i32,
-    ) -> FieldResult<Formation> {
-        let pool = ctx.data_unchecked::<Pool<ConnectionManager<PgConnection>>>();
-        let mut conn = pool.get().map_err(|e| e.to_string())?;
-
-        use climb_db::schema::formation_belongs_to;
-
-        let formation_id = diesel::delete(
-            formation_belongs_to::table
-                .filter(formation_belongs_to::formation_id.eq(id)))
-            .returning(formation_belongs_to::formation_id)
-            .get_result(&mut conn)
-            .map_err(|e| e.to_string())?;
+    ) -> impl Stream<Item = Area> {
+        let changes = ctx.data_unchecked::<Changes>().subscribe();
 
-        Ok(Formation(formation_id))
+        once_per_transaction(changes.filter(move |change| ready(change.place == Place::Area(id)))).map(move |_| Area(id))
     }
 
-    async fn remove_formation<'a>(
+    /// Notifies climbs added to an area, its sub areas or their formations, including climbs
+    /// moved there from elsewhere, directly or along with their formation
+    async fn climb_added<'a>(
         &self,
         ctx: &Context<'a>,
         #[graphql(
-            desc = "Removes formation with given id"
+            desc = "Area id"
         )]
-        id: i32,
-    ) -> FieldResult<Formation> {
-        let pool = ctx.data_unchecked::<Pool<ConnectionManager<PgConnection>>>();
-        let mut conn = pool.get().map_err(|e| e.to_string())?;
+        under_area_id: i32,
+    ) -> impl Stream<Item = Result<Climb>> {
+        let db = ctx.data_unchecked::<Db>().clone();
+        let changes = ctx.data_unchecked::<Changes>().subscribe().filter(|change| {
+            ready(
+                (change.table_name == "climb_belongs_to" || change.table_name == "formation_belongs_to")
+                    && change.operation != "DELETE",
+            )
+        });
+
+        once_per_transaction(changes)
+            .then(move |change| {
+                let db = db.clone();
+
+                async move { db.run(move |conn| added_under(conn, &change, under_area_id)).await }
+            })
+            .flat_map(|added| {
+                stream::iter(match added {
+                    Ok(ids) => ids.into_iter().map(|id| Ok(Climb(id))).collect(),
+                    Err(e) => vec![Err(e)],
+                })
+            })
+    }
+
+    /// Notifies formations moved to another location, area or super formation
+    async fn formation_moved<'a>(&self, ctx: &Context<'a>) -> impl Stream<Item = Formation> {
+        let changes = ctx.data_unchecked::<Changes>().subscribe().filter(|change| {
+            ready(
+                change.table_name == "formation_belongs_to"
+                    || (change.table_name == "formations" && change.operation == "UPDATE"),
+            )
+        });
+
+        once_per_transaction(changes).filter_map(|change| {
+            ready(match change.place {
+                Place::Formation(id) => Some(Formation(id)),
+                _ => None,
+            })
+        })
+    }
+}
+
+/// Passes on only the first change of each place within a transaction
+fn once_per_transaction(changes: impl Stream<Item = ChangeNotification>) -> impl Stream<Item = ChangeNotification> {
+    changes
+        .scan(None, |last, change| {
+            let first = *last != Some((change.transaction_id, change.place));
+            *last = Some((change.transaction_id, change.place));
+
+            ready(Some(first.then_some(change)))
+        })
+        .filter_map(ready)
+}
+
+/// Whether a place is an area or within it
+fn within(conn: &mut PgConnection, place: Place, area_id: i32) -> Result<bool> {
+    Ok(place == Place::Area(area_id)
+        || ancestors(conn, &[place])?.into_iter().any(|(_, ancestor)| ancestor == Place::Area(area_id)))
+}
+
+/// The climbs a change of the parent of a climb or formation placed within an area they were not
+/// within before
+fn added_under(conn: &mut PgConnection, change: &ChangeNotification, area_id: i32) -> Result<Vec<i32>> {
+    if !moved_under(conn, change, area_id)? {
+        return Ok(vec![]);
+    }
 
-        use climb_db::schema::formations;
+    match change.place {
+        Place::Climb(id) => Ok(vec![id]),
+        Place::Formation(id) => {
+            let filter = climb_db::filter::ClimbFilter {
+                within_formation_id: Some(id),
+                ..Default::default()
+            };
+            let mut repo = ClimbRepo::new(conn);
+            let mut ids = vec![];
+
+            loop {
+                let page = Page {
+                    after: ids.last().copied(),
+                    first: Some(Page::MAX_SIZE),
+                    ..Default::default()
+                };
+                let more = repo.list(&filter, page)?;
+                let done = more.len() <= Page::MAX_SIZE;
+
+                ids.extend(more.into_iter().take(Page::MAX_SIZE));
+
+                if done {
+                    return Ok(ids);
+                }
+            }
+        }
+        Place::Area(_) => Ok(vec![]),
+    }
+}
 
-        let formation_id = diesel::delete(formations::table.filter(formations::id.eq(id)))
-            .returning(formations::id)
-            .get_result(&mut conn)
-            .map_err(|e| e.to_string())?;
+/// Whether a change of the parent of a climb or formation placed it within an area it was not
+/// within before
+fn moved_under(conn: &mut PgConnection, change: &ChangeNotification, area_id: i32) -> Result<bool> {
+    if !within(conn, change.place, area_id)? {
+        return Ok(false);
+    }
 
-        Ok(Formation(formation_id))
+    let before = ChangeRepo::new(conn)
+        .get(change.change_id)?
+        .before
+        .and_then(|before| serde_json::from_str::<serde_json::Value>(&before).ok());
+
+    // The parent in the `climb_belongs_to` or `formation_belongs_to` row before the change, if
+    // updated
+    let formation_column = match change.place {
+        Place::Formation(_) => "super_formation_id",
+        _ => "formation_id",
+    };
+    let parent = before.and_then(|before| {
+        let id = |column: &str| before.get(column).and_then(serde_json::Value::as_i64).and_then(|id| i32::try_from(id).ok());
+
+        id(formation_column).map(Place::Formation).or_else(|| id("area_id").map(Place::Area))
+    });
+
+    match parent {
+        Some(parent) => Ok(!within(conn, parent, area_id)?),
+        None => Ok(true),
     }
 }